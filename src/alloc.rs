@@ -0,0 +1,628 @@
+//! Allocation interface used by the collections that own their memory.
+//!
+//! A stable stand-in for the nightly `allocator_api`: [`Vector`],
+//! [`DequeueList`], [`LinkedList`] and [`BTree`] take an allocator type
+//! parameter that defaults to [`Global`], and their `new_in` constructors
+//! accept any [`Allocator`]. [`Counting`] keeps track of what goes through
+//! it, which makes leaks visible in tests, and [`Bump`] hands out memory
+//! from a fixed arena.
+//!
+//! [`Vector`]: crate::Vector
+//! [`DequeueList`]: crate::DequeueList
+//! [`LinkedList`]: crate::LinkedList
+//! [`BTree`]: crate::BTree
+
+use std::{
+    alloc::{self, Layout},
+    cell::Cell,
+    fmt::{self, Debug},
+    ptr::{self, NonNull},
+    rc::Rc,
+};
+
+/// The allocator couldn't satisfy a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// Source of memory for the collections.
+///
+/// # Safety
+///
+/// Blocks returned by [`allocate`], [`grow`] and [`shrink`] must be valid
+/// for reads and writes of `layout.size()` bytes, aligned to
+/// `layout.align()` and not overlap any other live block, until they are
+/// passed to [`deallocate`], [`grow`] or [`shrink`].
+///
+/// A block may also be freed or grown through a clone of the allocator that
+/// returned it, or through any allocator [`is_same`] returns `true` for, and
+/// has to stay valid as long as one of them is alive.
+///
+/// [`allocate`]: Allocator::allocate
+/// [`grow`]: Allocator::grow
+/// [`shrink`]: Allocator::shrink
+/// [`deallocate`]: Allocator::deallocate
+/// [`is_same`]: Allocator::is_same
+pub unsafe trait Allocator {
+    /// Whether `self` and `other` can free each other's blocks, which is
+    /// what lets collections relink nodes between each other instead of
+    /// moving the values. Defaults to `false`.
+    fn is_same(&self, other: &Self) -> bool {
+        let _ = other;
+        false
+    }
+
+    /// Allocates a block for `layout`, which has a non-zero size.
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// Frees a block.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` has to be a live block of this allocator, allocated with
+    /// `layout`.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Moves a block to a larger one, keeping its content. By default
+    /// allocates a new block and copies the old one into it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` has to be a live block of this allocator, allocated with
+    /// `old`, and `new` can't be smaller than `old`. On success `ptr` is no
+    /// longer valid.
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new_ptr = self.allocate(new)?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old.size());
+            self.deallocate(ptr, old);
+        }
+
+        Ok(new_ptr)
+    }
+
+    /// Moves a block to a smaller one, keeping the start of its content. By
+    /// default allocates a new block and copies into it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` has to be a live block of this allocator, allocated with
+    /// `old`, and `new` has a non-zero size no larger than `old`. On success
+    /// `ptr` is no longer valid.
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new_ptr = self.allocate(new)?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), new.size());
+            self.deallocate(ptr, old);
+        }
+
+        Ok(new_ptr)
+    }
+}
+
+/// The global allocator, what `Box` and `Vec` use.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn is_same(&self, _other: &Self) -> bool {
+        true
+    }
+
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        NonNull::new(unsafe { alloc::alloc(layout) }).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { alloc::dealloc(ptr.as_ptr(), layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        if old.align() != new.align() {
+            return Err(AllocError);
+        }
+
+        NonNull::new(unsafe { alloc::realloc(ptr.as_ptr(), old, new.size()) }).ok_or(AllocError)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        if old.align() != new.align() {
+            return Err(AllocError);
+        }
+
+        NonNull::new(unsafe { alloc::realloc(ptr.as_ptr(), old, new.size()) }).ok_or(AllocError)
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    fn is_same(&self, other: &Self) -> bool {
+        (**self).is_same(*other)
+    }
+
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { (**self).deallocate(ptr, layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { (**self).grow(ptr, old, new) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        unsafe { (**self).shrink(ptr, old, new) }
+    }
+}
+
+/// Moves `value` into a block from `alloc`, like `Box::new`.
+pub(crate) fn allocate_value<T, A: Allocator + ?Sized>(alloc: &A, value: T) -> NonNull<T> {
+    let layout = Layout::new::<T>();
+
+    let ptr: NonNull<T> = if layout.size() == 0 {
+        NonNull::dangling()
+    } else {
+        match alloc.allocate(layout) {
+            Ok(ptr) => ptr.cast(),
+            Err(_) => alloc::handle_alloc_error(layout),
+        }
+    };
+
+    unsafe { ptr.as_ptr().write(value) };
+    ptr
+}
+
+/// Moves the value out of a block from [`allocate_value`] and frees it.
+///
+/// # Safety
+///
+/// `ptr` has to come from [`allocate_value`] with the same allocator, and
+/// can't be used afterwards.
+pub(crate) unsafe fn take_value<T, A: Allocator + ?Sized>(alloc: &A, ptr: NonNull<T>) -> T {
+    let layout = Layout::new::<T>();
+    let value = unsafe { ptr.as_ptr().read() };
+
+    if layout.size() != 0 {
+        unsafe { alloc.deallocate(ptr.cast(), layout) };
+    }
+
+    value
+}
+
+/// Allocator that counts what goes through it.
+///
+/// Clones share their counters, so do references:
+///
+/// ```
+/// use ds_rust::{Vector, alloc::Counting};
+///
+/// let counting = Counting::new();
+/// let mut v = Vector::new_in(&counting);
+/// v.push(1u64);
+/// v.push(2);
+/// assert_eq!(counting.bytes_in_use(), 16);
+///
+/// drop(v);
+/// assert_eq!(counting.live_allocations(), 0);
+/// ```
+pub struct Counting<A: Allocator = Global> {
+    inner: A,
+    counters: Rc<Counters>,
+}
+
+#[derive(Default)]
+struct Counters {
+    allocations: Cell<usize>,
+    deallocations: Cell<usize>,
+    bytes_in_use: Cell<usize>,
+    peak_bytes: Cell<usize>,
+}
+
+impl Counting {
+    /// Creates a Counting allocator over [`Global`].
+    pub fn new() -> Self {
+        Self::wrap(Global)
+    }
+}
+
+impl<A: Allocator> Counting<A> {
+    /// Counts the allocations made through `inner`.
+    pub fn wrap(inner: A) -> Self {
+        Counting {
+            inner,
+            counters: Rc::default(),
+        }
+    }
+
+    /// Number of blocks allocated so far, growing a block counts as one.
+    pub fn allocations(&self) -> usize {
+        self.counters.allocations.get()
+    }
+
+    /// Number of blocks freed so far, including the ones replaced by growing.
+    pub fn deallocations(&self) -> usize {
+        self.counters.deallocations.get()
+    }
+
+    /// Blocks that were allocated and not freed yet.
+    pub fn live_allocations(&self) -> usize {
+        self.allocations() - self.deallocations()
+    }
+
+    /// Bytes currently allocated.
+    pub fn bytes_in_use(&self) -> usize {
+        self.counters.bytes_in_use.get()
+    }
+
+    /// Most bytes allocated at the same time.
+    pub fn peak_bytes(&self) -> usize {
+        self.counters.peak_bytes.get()
+    }
+
+    fn add_bytes(&self, bytes: usize) {
+        let in_use = self.counters.bytes_in_use.get() + bytes;
+        self.counters.bytes_in_use.set(in_use);
+        self.counters.peak_bytes.set(self.counters.peak_bytes.get().max(in_use));
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Counting<A> {
+    fn is_same(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.counters, &other.counters) && self.inner.is_same(&other.inner)
+    }
+
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.inner.allocate(layout)?;
+        self.counters.allocations.set(self.counters.allocations.get() + 1);
+        self.add_bytes(layout.size());
+
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { self.inner.deallocate(ptr, layout) };
+        self.counters.deallocations.set(self.counters.deallocations.get() + 1);
+        self.counters.bytes_in_use.set(self.counters.bytes_in_use.get() - layout.size());
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = unsafe { self.inner.grow(ptr, old, new)? };
+        self.counters.allocations.set(self.counters.allocations.get() + 1);
+        self.counters.deallocations.set(self.counters.deallocations.get() + 1);
+        self.counters.bytes_in_use.set(self.counters.bytes_in_use.get() - old.size());
+        self.add_bytes(new.size());
+
+        Ok(ptr)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = unsafe { self.inner.shrink(ptr, old, new)? };
+        self.counters.allocations.set(self.counters.allocations.get() + 1);
+        self.counters.deallocations.set(self.counters.deallocations.get() + 1);
+        self.counters.bytes_in_use.set(self.counters.bytes_in_use.get() - old.size());
+        self.add_bytes(new.size());
+
+        Ok(ptr)
+    }
+}
+
+impl<A: Allocator + Clone> Clone for Counting<A> {
+    fn clone(&self) -> Self {
+        Counting {
+            inner: self.inner.clone(),
+            counters: Rc::clone(&self.counters),
+        }
+    }
+}
+
+impl Default for Counting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Allocator> Debug for Counting<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Counting")
+            .field("allocations", &self.allocations())
+            .field("deallocations", &self.deallocations())
+            .field("bytes_in_use", &self.bytes_in_use())
+            .field("peak_bytes", &self.peak_bytes())
+            .finish()
+    }
+}
+
+/// Arena allocator that carves blocks out of one fixed chunk.
+///
+/// Allocating only moves a pointer forward, and freeing does nothing except
+/// for the last block, which can also grow and shrink in place. The whole
+/// chunk is released when the arena is dropped, which is why only `&Bump`
+/// is an [`Allocator`]: the borrow checker makes sure collections using it
+/// are gone by then.
+pub struct Bump {
+    chunk: NonNull<u8>,
+    capacity: usize,
+    /// Offset of the first free byte.
+    used: Cell<usize>,
+    /// Offset of the last block, the only one that can be freed or grown.
+    last: Cell<Option<usize>>,
+}
+
+// The chunk is owned, `Cell` already keeps it from being shared.
+unsafe impl Send for Bump {}
+
+impl Bump {
+    /// Alignment of the chunk, enough for any primitive type.
+    const ALIGN: usize = 16;
+
+    /// Creates an arena of `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let chunk = if capacity == 0 {
+            NonNull::dangling()
+        } else {
+            let layout = Self::layout(capacity);
+            match NonNull::new(unsafe { alloc::alloc(layout) }) {
+                Some(chunk) => chunk,
+                None => alloc::handle_alloc_error(layout),
+            }
+        };
+
+        Bump {
+            chunk,
+            capacity,
+            used: Cell::new(0),
+            last: Cell::new(None),
+        }
+    }
+
+    fn layout(capacity: usize) -> Layout {
+        Layout::from_size_align(capacity, Self::ALIGN).unwrap()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Bytes handed out so far, including padding.
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    fn offset_of(&self, ptr: NonNull<u8>) -> usize {
+        ptr.as_ptr() as usize - self.chunk.as_ptr() as usize
+    }
+}
+
+unsafe impl Allocator for &Bump {
+    fn is_same(&self, other: &Self) -> bool {
+        ptr::eq(*self, *other)
+    }
+
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.align() > Bump::ALIGN {
+            return Err(AllocError);
+        }
+
+        let start = self.used.get().next_multiple_of(layout.align());
+        let end = start.checked_add(layout.size()).ok_or(AllocError)?;
+        if end > self.capacity {
+            return Err(AllocError);
+        }
+
+        self.used.set(end);
+        self.last.set(Some(start));
+
+        Ok(unsafe { self.chunk.add(start) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        let offset = self.offset_of(ptr);
+
+        if self.last.get() == Some(offset) {
+            self.used.set(offset);
+            self.last.set(None);
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let offset = self.offset_of(ptr);
+
+        if self.last.get() == Some(offset)
+            && new.align() <= Bump::ALIGN
+            && offset.is_multiple_of(new.align())
+        {
+            let end = offset.checked_add(new.size()).ok_or(AllocError)?;
+            if end <= self.capacity {
+                self.used.set(end);
+                return Ok(ptr);
+            }
+        }
+
+        let new_ptr = self.allocate(new)?;
+        unsafe { ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old.size()) };
+
+        Ok(new_ptr)
+    }
+
+    /// Gives the end of the last block back, any other block stays where it
+    /// is since it can't be freed anyway.
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        _old: Layout,
+        new: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let offset = self.offset_of(ptr);
+
+        if !offset.is_multiple_of(new.align()) {
+            return Err(AllocError);
+        }
+        if self.last.get() == Some(offset) {
+            self.used.set(offset + new.size());
+        }
+
+        Ok(ptr)
+    }
+}
+
+impl Drop for Bump {
+    fn drop(&mut self) {
+        if self.capacity != 0 {
+            unsafe { alloc::dealloc(self.chunk.as_ptr(), Self::layout(self.capacity)) };
+        }
+    }
+}
+
+impl Debug for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bump")
+            .field("used", &self.used())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Allocator, Bump, Counting, Global};
+    use crate::{BTree, DequeueList, LinkedList, Vector};
+    use std::alloc::Layout;
+
+    #[test]
+    fn test_counting_collections() {
+        let counting = Counting::new();
+
+        {
+            let mut v = Vector::new_in(&counting);
+            for i in 0..100u32 {
+                v.push(i);
+            }
+            // 1, 2, 4, ... 128 elements, each grow replaces a block.
+            assert_eq!(counting.allocations(), 8);
+            assert_eq!(counting.live_allocations(), 1);
+            assert_eq!(counting.bytes_in_use(), 128 * 4);
+
+            let mut list = DequeueList::new_in(&counting);
+            list.extend(0..10);
+            list.pop_front();
+            assert_eq!(counting.live_allocations(), 1 + 9);
+
+            let mut linked = LinkedList::new_in(&counting);
+            linked.add('a');
+            linked.add('b');
+            linked.remove('a');
+
+            let mut tree = BTree::new_in(&counting);
+            for n in [5, 3, 8, 1, 4] {
+                tree.insert(n);
+            }
+            tree.remove(&3);
+            assert_eq!(counting.live_allocations(), 1 + 9 + 1 + 4);
+        }
+
+        assert_eq!(counting.live_allocations(), 0);
+        assert_eq!(counting.bytes_in_use(), 0);
+        assert!(counting.peak_bytes() >= 128 * 4);
+    }
+
+    #[test]
+    fn test_is_same() {
+        assert!(Global.is_same(&Global));
+
+        let counting = Counting::new();
+        let clone = counting.clone();
+        assert!(counting.is_same(&clone) && Allocator::is_same(&&counting, &&clone));
+        assert!(!counting.is_same(&Counting::new()));
+
+        let layout = Layout::new::<u32>();
+        let ptr = clone.allocate(layout).unwrap();
+        assert_eq!(counting.bytes_in_use(), 4);
+        unsafe { counting.deallocate(ptr, layout) };
+        assert_eq!((clone.live_allocations(), clone.bytes_in_use()), (0, 0));
+
+        let (one, two) = (Bump::with_capacity(8), Bump::with_capacity(8));
+        assert!((&one).is_same(&&one));
+        assert!(!(&one).is_same(&&two));
+    }
+
+    #[test]
+    fn test_bump() {
+        let bump = Bump::with_capacity(1024);
+
+        {
+            let mut v = Vector::new_in(&bump);
+            for i in 0..64u64 {
+                v.push(i);
+            }
+            // Only the last block, so every grow was in place.
+            assert_eq!(bump.used(), 64 * 8);
+            assert!(v.iter().copied().eq(0..64));
+
+            let mut list = DequeueList::new_in(&bump);
+            list.push_back(1u8);
+            assert!(bump.used() > 64 * 8);
+        }
+
+        let small = &Bump::with_capacity(8);
+        let layout = Layout::new::<u64>();
+        let ptr = small.allocate(layout).unwrap();
+        assert!(small.allocate(layout).is_err());
+        unsafe { small.deallocate(ptr, layout) };
+        assert!(small.allocate(layout).is_ok());
+    }
+}
@@ -0,0 +1,101 @@
+//! Containers of fixed capacity `N` whose storage is an inline array, so
+//! they never touch the heap. Pushing to a full one panics, the `try_*`
+//! methods give the value back in a [`CapacityError`](crate::error::CapacityError)
+//! instead.
+
+use std::{fmt::Debug, mem::MaybeUninit, ptr};
+
+mod deque;
+mod stack;
+mod vector;
+
+pub use deque::ArrayDeque;
+pub use stack::ArrayStack;
+pub use vector::ArrayVector;
+
+/// Empty storage for `N` values.
+const fn uninit_array<T, const N: usize>() -> [MaybeUninit<T>; N] {
+    [const { MaybeUninit::uninit() }; N]
+}
+
+/// Maps the logical position `index` of a ring starting at `head` to its
+/// slot. `head + index` can't reach `2 * N`.
+fn wrap<const N: usize>(head: usize, index: usize) -> usize {
+    if index >= N - head {
+        index - (N - head)
+    } else {
+        head + index
+    }
+}
+
+/// Owning iterator of the array containers, taking the values out of the
+/// storage they leave behind.
+///
+/// The values are `len` consecutive slots of a ring starting at `head`, so
+/// [`ArrayVector`] and [`ArrayStack`] (always starting at slot 0) share it
+/// with [`ArrayDeque`].
+pub struct RawArrayIter<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RawArrayIter<T, N> {
+    /// # Safety
+    ///
+    /// The `len` slots of `buf` starting at `head` (wrapping around the end)
+    /// have to be initialized, and `head < N` unless `len == 0`.
+    unsafe fn new(buf: [MaybeUninit<T>; N], head: usize, len: usize) -> Self {
+        RawArrayIter { buf, head, len }
+    }
+}
+
+impl<T, const N: usize> Iterator for RawArrayIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let slot = self.head;
+        self.head = wrap::<N>(self.head, 1);
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.buf[slot].as_ptr())) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for RawArrayIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        let slot = wrap::<N>(self.head, self.len);
+
+        unsafe { Some(ptr::read(self.buf[slot].as_ptr())) }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for RawArrayIter<T, N> {}
+
+impl<T, const N: usize> Drop for RawArrayIter<T, N> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for RawArrayIter<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values =
+            (0..self.len).map(|index| unsafe { &*self.buf[wrap::<N>(self.head, index)].as_ptr() });
+
+        f.debug_list().entries(values).finish()
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    fmt::Debug,
+    mem::{self, MaybeUninit},
+    ops::{Index, IndexMut},
+    ptr, slice,
+};
+
+use super::{RawArrayIter, uninit_array, wrap};
+use crate::error::{CapacityError, capacity_exceeded, out_of_bounds};
+use crate::traits::MemoryUsage;
+
+/// [`RingDeque`](crate::RingDeque) of up to `N` values stored inline.
+///
+/// Unlike [`CircularBuffer`](crate::CircularBuffer) it never overwrites, and
+/// both ends can be pushed and popped.
+pub struct ArrayDeque<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    head: usize,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<T, const N: usize> ArrayDeque<T, N> {
+    pub const fn new() -> Self {
+        ArrayDeque {
+            buf: uninit_array(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    fn ptr(&self) -> *const T {
+        self.buf.as_ptr().cast()
+    }
+
+    fn ptr_mut(&mut self) -> *mut T {
+        self.buf.as_mut_ptr().cast()
+    }
+
+    /// # Panics
+    ///
+    /// If the deque is full.
+    pub fn push_back(&mut self, value: T) {
+        if self.try_push_back(value).is_err() {
+            capacity_exceeded(N);
+        }
+    }
+
+    pub fn try_push_back(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.buf[wrap::<N>(self.head, self.len)].write(value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// # Panics
+    ///
+    /// If the deque is full.
+    pub fn push_front(&mut self, value: T) {
+        if self.try_push_front(value).is_err() {
+            capacity_exceeded(N);
+        }
+    }
+
+    pub fn try_push_front(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.head = wrap::<N>(self.head, N - 1);
+        self.buf[self.head].write(value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let old_head = self.head;
+        self.head = wrap::<N>(self.head, 1);
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr().add(old_head))) }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        let slot = wrap::<N>(self.head, self.len);
+
+        unsafe { Some(ptr::read(self.ptr().add(slot))) }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(&*self.ptr().add(wrap::<N>(self.head, index))) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = wrap::<N>(self.head, index);
+        unsafe { Some(&mut *self.ptr_mut().add(slot)) }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the contents front to back as two slices, the second one
+    /// being the part that wrapped around the end of the storage.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe {
+            if self.head <= N - self.len {
+                (
+                    slice::from_raw_parts(self.ptr().add(self.head), self.len),
+                    &[],
+                )
+            } else {
+                let head_len = N - self.head;
+                (
+                    slice::from_raw_parts(self.ptr().add(self.head), head_len),
+                    slice::from_raw_parts(self.ptr(), self.len - head_len),
+                )
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayDeque<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for ArrayDeque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayDeque<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const N: usize> Index<usize> for ArrayDeque<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index)
+            .unwrap_or_else(|| out_of_bounds(index, self.len))
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for ArrayDeque<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| out_of_bounds(index, len))
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for ArrayDeque<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayDeque<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayDeque<T, N> {}
+
+impl<T, const N: usize> Extend<T> for ArrayDeque<T, N> {
+    /// Pushes every value to the back.
+    ///
+    /// # Panics
+    ///
+    /// If there are more than the free space.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayDeque<T, N> {
+    /// # Panics
+    ///
+    /// If there are more than `N` values.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayDeque<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T, const N: usize> IntoIterator for ArrayDeque<T, N> {
+    type Item = T;
+    type IntoIter = RawArrayIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = mem::take(&mut self.len);
+        let buf = mem::replace(&mut self.buf, uninit_array());
+
+        unsafe { RawArrayIter::new(buf, self.head, len) }
+    }
+}
+
+impl<T, const N: usize> MemoryUsage for ArrayDeque<T, N> {
+    fn heap_bytes(&self) -> usize {
+        // The elements live inline.
+        0
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArrayDeque;
+
+    /// `[1, 2, 3]` wrapped around the end of the storage.
+    fn wrapped() -> ArrayDeque<i32, 4> {
+        let mut deque = ArrayDeque::new();
+        deque.push_back(2);
+        deque.push_back(3);
+        deque.push_front(1);
+        deque
+    }
+
+    #[test]
+    fn test_basics() {
+        let mut deque = wrapped();
+        assert_eq!(deque.as_slices(), (&[1][..], &[2, 3][..]));
+        assert_eq!((deque[0], deque[2]), (1, 3));
+
+        deque.push_back(4);
+        assert!(deque.is_full());
+        assert_eq!(deque.try_push_front(0).unwrap_err().into_inner(), 0);
+        assert_eq!(deque.try_push_back(5).unwrap_err().into_inner(), 5);
+
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_back(), Some(4));
+        deque[1] = 30;
+        assert_eq!((deque.front(), deque.back()), (Some(&2), Some(&30)));
+        assert_eq!(format!("{deque:?}"), "[2, 30]");
+        assert_eq!(deque.clone(), deque);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let deque = wrapped();
+        assert_eq!(deque.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(wrapped().into_iter().rev().collect::<Vec<_>>(), [3, 2, 1]);
+
+        let mut strings: ArrayDeque<String, 3> = ArrayDeque::new();
+        strings.push_back("b".into());
+        strings.push_front("a".into());
+        let mut iter = strings.into_iter();
+        assert_eq!(iter.next().as_deref(), Some("a"));
+        // The rest is dropped with the iterator.
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient capacity")]
+    fn test_push_full() {
+        let mut deque = wrapped();
+        deque.extend([4, 5]);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut deque: ArrayDeque<i32, 0> = ArrayDeque::new();
+        assert!(deque.try_push_front(1).is_err());
+        assert!(deque.try_push_back(1).is_err());
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.iter().next(), None);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{fmt::Debug, iter::Rev, slice};
+
+use super::{ArrayVector, RawArrayIter};
+use crate::error::CapacityError;
+use crate::traits::MemoryUsage;
+
+/// [`Stack`](crate::Stack) of up to `N` values stored inline, the top of the
+/// stack being the end of an [`ArrayVector`].
+pub struct ArrayStack<T, const N: usize> {
+    vec: ArrayVector<T, N>,
+}
+
+impl<T, const N: usize> ArrayStack<T, N> {
+    pub const fn new() -> Self {
+        ArrayStack {
+            vec: ArrayVector::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.vec.is_full()
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Puts `value` on top of the stack.
+    ///
+    /// # Panics
+    ///
+    /// If the stack is full.
+    pub fn push(&mut self, value: T) {
+        self.vec.push(value);
+    }
+
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        self.vec.try_push(value)
+    }
+
+    /// Removes the top of the stack.
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+
+    /// Returns the top of the stack without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.vec.last()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.vec.last_mut()
+    }
+
+    /// Iterates from the top of the stack to the bottom.
+    pub fn iter(&self) -> Rev<slice::Iter<'_, T>> {
+        self.vec.iter().rev()
+    }
+
+    pub fn iter_mut(&mut self) -> Rev<slice::IterMut<'_, T>> {
+        self.vec.iter_mut().rev()
+    }
+}
+
+impl<T, const N: usize> Default for ArrayStack<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayStack<T, N> {
+    fn clone(&self) -> Self {
+        ArrayStack {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for ArrayStack<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayStack<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vec == other.vec
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayStack<T, N> {}
+
+impl<T, const N: usize> Extend<T> for ArrayStack<T, N> {
+    /// Pushes every item in order, so the last one ends up on top.
+    ///
+    /// # Panics
+    ///
+    /// If there are more than the free space.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.vec.extend(iter);
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayStack<T, N> {
+    /// # Panics
+    ///
+    /// If there are more than `N` values.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        ArrayStack {
+            vec: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayStack<T, N> {
+    type Item = T;
+    type IntoIter = Rev<RawArrayIter<T, N>>;
+
+    /// Pops the values from the top of the stack to the bottom.
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.vec.take_raw().rev()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayStack<T, N> {
+    type Item = &'a T;
+    type IntoIter = Rev<slice::Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayStack<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = Rev<slice::IterMut<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> MemoryUsage for ArrayStack<T, N> {
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArrayStack;
+
+    #[test]
+    fn test_basics() {
+        let mut stack: ArrayStack<i32, 3> = [1, 2].into_iter().collect();
+        stack.push(3);
+        assert!(stack.is_full());
+        assert_eq!(stack.try_push(4).unwrap_err().into_inner(), 4);
+
+        *stack.peek_mut().unwrap() = 30;
+        assert_eq!(format!("{stack:?}"), "[30, 2, 1]");
+        assert_eq!(stack.pop(), Some(30));
+        assert_eq!(stack.peek(), Some(&2));
+        assert_eq!(stack.clone(), stack);
+
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), [2, 1]);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    fmt::Debug,
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr, slice,
+};
+
+use super::{RawArrayIter, uninit_array};
+use crate::error::{CapacityError, IndexError, capacity_exceeded};
+use crate::traits::MemoryUsage;
+
+/// [`Vector`](crate::Vector) of up to `N` values stored inline.
+pub struct ArrayVector<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVector<T, N> {
+    pub const fn new() -> Self {
+        ArrayVector {
+            buf: uninit_array(),
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn ptr(&self) -> *const T {
+        self.buf.as_ptr().cast()
+    }
+
+    fn ptr_mut(&mut self) -> *mut T {
+        self.buf.as_mut_ptr().cast()
+    }
+
+    /// # Panics
+    ///
+    /// If the vector is full.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            capacity_exceeded(N);
+        }
+    }
+
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.buf[self.len].write(value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        unsafe { Some(ptr::read(self.ptr().add(self.len))) }
+    }
+
+    /// # Panics
+    ///
+    /// If `index > len` or the vector is full.
+    pub fn insert(&mut self, index: usize, value: T) {
+        if self.try_insert(index, value).is_err() {
+            capacity_exceeded(N);
+        }
+    }
+
+    /// Like [`ArrayVector::insert`], but gives `value` back if the vector is
+    /// full. Still panics if `index > len`.
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), CapacityError<T>> {
+        assert!(index <= self.len, "Index out of bounds");
+
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+
+        unsafe {
+            let slot = self.ptr_mut().add(index);
+            ptr::copy(slot, slot.add(1), self.len - index);
+            ptr::write(slot, value);
+        }
+        self.len += 1;
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Index out of bounds");
+
+        self.len -= 1;
+        unsafe {
+            let slot = self.ptr_mut().add(index);
+            let value = ptr::read(slot);
+            ptr::copy(slot.add(1), slot, self.len - index);
+            value
+        }
+    }
+
+    pub fn try_remove(&mut self, index: usize) -> Result<T, IndexError> {
+        if index >= self.len {
+            return Err(IndexError {
+                index,
+                len: self.len,
+            });
+        }
+
+        Ok(self.remove(index))
+    }
+
+    /// Drops the values past the first `len`.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr_mut(), self.len) }
+    }
+
+    /// Takes the storage, leaving the vector empty.
+    pub(super) fn take_raw(&mut self) -> RawArrayIter<T, N> {
+        let len = mem::take(&mut self.len);
+        let buf = mem::replace(&mut self.buf, uninit_array());
+
+        unsafe { RawArrayIter::new(buf, 0, len) }
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVector<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVector<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVector<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVector<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVector<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for ArrayVector<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayVector<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayVector<T, N> {}
+
+impl<T, const N: usize> Extend<T> for ArrayVector<T, N> {
+    /// # Panics
+    ///
+    /// If there are more than the free space.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayVector<T, N> {
+    /// # Panics
+    ///
+    /// If there are more than `N` values.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVector<T, N> {
+    type Item = T;
+    type IntoIter = RawArrayIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.take_raw()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVector<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVector<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> MemoryUsage for ArrayVector<T, N> {
+    fn heap_bytes(&self) -> usize {
+        // The elements live inline.
+        0
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArrayVector;
+    use crate::error::IndexError;
+
+    #[test]
+    fn test_basics() {
+        let mut vec: ArrayVector<i32, 4> = ArrayVector::new();
+        vec.push(1);
+        vec.push(3);
+        vec.insert(1, 2);
+        assert_eq!(*vec, [1, 2, 3]);
+
+        vec.push(4);
+        assert!(vec.is_full());
+        assert_eq!(vec.try_push(5).unwrap_err().into_inner(), 5);
+        assert_eq!(vec.try_insert(0, 0).unwrap_err().into_inner(), 0);
+
+        assert_eq!(vec.remove(0), 1);
+        assert_eq!(vec.try_remove(3), Err(IndexError { index: 3, len: 3 }));
+        assert_eq!(vec.pop(), Some(4));
+
+        vec[0] = 20;
+        assert_eq!(format!("{vec:?}"), "[20, 3]");
+        assert_eq!(vec.clone(), vec);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let vec: ArrayVector<String, 4> = ["a", "b", "c"].map(String::from).into_iter().collect();
+
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back().as_deref(), Some("c"));
+        assert_eq!(format!("{iter:?}"), r#"["a", "b"]"#);
+        // The rest is dropped with the iterator.
+    }
+
+    #[test]
+    #[should_panic(expected = "insufficient capacity")]
+    fn test_push_full() {
+        let mut vec: ArrayVector<i32, 1> = ArrayVector::new();
+        vec.extend([1, 2]);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut vec: ArrayVector<i32, 0> = ArrayVector::new();
+        assert!(vec.is_full());
+        assert!(vec.try_push(1).is_err());
+        assert_eq!(vec.into_iter().next(), None);
+    }
+}
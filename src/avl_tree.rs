@@ -0,0 +1,651 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    mem,
+    ops::ControlFlow,
+};
+
+use crate::alloc::Global;
+use crate::display_tree::{DisplayTree, TreeNode, display_tree};
+use crate::raw::NodePtr;
+use crate::stats::TreeStats;
+use crate::traits::MemoryUsage;
+use crate::traverse::{Order, Traverse, Visit, walk_binary};
+
+/// AvlTree node.
+struct Node<T> {
+    left: Link<T>,
+    right: Link<T>,
+    /// Height of the subtree rooted at this node, a leaf has height 1.
+    height: usize,
+    elem: T,
+}
+
+type Link<T> = Option<NodePtr<Node<T>>>;
+
+/// Height-balanced binary search tree.
+///
+/// After every insert and remove the heights of the two subtrees of any node
+/// differ by at most one, restored with rotations on the way back up, so the
+/// tree never degenerates like a plain [`BTree`](crate::BTree) can.
+pub struct AvlTree<T> {
+    root: Link<T>,
+    size: usize,
+    stats: TreeStats,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for AvlTree<T> {}
+unsafe impl<T: Sync> Sync for AvlTree<T> {}
+
+/// In-order iterator, see [`AvlTree::iter`].
+pub struct Iter<'a, T> {
+    stack: Vec<NodePtr<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+impl<T> Node<T> {
+    /// Create new leaf node.
+    fn new(elem: T) -> NodePtr<Node<T>> {
+        NodePtr::alloc(
+            &Global,
+            Node {
+                left: None,
+                right: None,
+                height: 1,
+                elem,
+            },
+        )
+    }
+}
+
+unsafe fn height<T>(link: Link<T>) -> usize {
+    unsafe { link.map_or(0, |node| node.as_ref().height) }
+}
+
+/// Recomputes the height of `node` from its children.
+unsafe fn update_height<T>(node: NodePtr<Node<T>>) {
+    unsafe {
+        let left = height(node.as_ref().left);
+        let right = height(node.as_ref().right);
+        node.as_mut().height = left.max(right) + 1;
+    }
+}
+
+/// Height of the left subtree minus height of the right one.
+unsafe fn balance_factor<T>(node: NodePtr<Node<T>>) -> isize {
+    unsafe { height(node.as_ref().left) as isize - height(node.as_ref().right) as isize }
+}
+
+/// ```text
+///     x              y
+///    / \            / \
+///   a   y    =>    x   c
+///      / \        / \
+///     b   c      a   b
+/// ```
+unsafe fn rotate_left<T>(x: NodePtr<Node<T>>) -> NodePtr<Node<T>> {
+    unsafe {
+        let y = x.as_ref().right.unwrap();
+        x.as_mut().right = y.as_ref().left;
+        y.as_mut().left = Some(x);
+        update_height(x);
+        update_height(y);
+        y
+    }
+}
+
+/// Mirror image of [`rotate_left`].
+unsafe fn rotate_right<T>(x: NodePtr<Node<T>>) -> NodePtr<Node<T>> {
+    unsafe {
+        let y = x.as_ref().left.unwrap();
+        x.as_mut().left = y.as_ref().right;
+        y.as_mut().right = Some(x);
+        update_height(x);
+        update_height(y);
+        y
+    }
+}
+
+/// Fixes the height of `node` and rotates if its subtrees are more than one
+/// level apart. Returns the new root of the subtree.
+unsafe fn rebalance<T>(node: NodePtr<Node<T>>, stats: &mut TreeStats) -> NodePtr<Node<T>> {
+    unsafe {
+        update_height(node);
+
+        match balance_factor(node) {
+            2.. => {
+                let left = node.as_ref().left.unwrap();
+                if balance_factor(left) < 0 {
+                    node.as_mut().left = Some(rotate_left(left));
+                    stats.record_rotation();
+                }
+                stats.record_rotation();
+                rotate_right(node)
+            }
+            ..=-2 => {
+                let right = node.as_ref().right.unwrap();
+                if balance_factor(right) > 0 {
+                    node.as_mut().right = Some(rotate_right(right));
+                    stats.record_rotation();
+                }
+                stats.record_rotation();
+                rotate_left(node)
+            }
+            _ => node,
+        }
+    }
+}
+
+/// Detaches the minimum of the subtree rooted at `node`. Returns the new
+/// root of the subtree and the detached node.
+unsafe fn remove_min<T>(
+    node: NodePtr<Node<T>>,
+    stats: &mut TreeStats,
+) -> (Link<T>, NodePtr<Node<T>>) {
+    unsafe {
+        match node.as_ref().left {
+            None => (node.as_ref().right, node),
+            Some(left) => {
+                let (new_left, min) = remove_min(left, stats);
+                node.as_mut().left = new_left;
+                (Some(rebalance(node, stats)), min)
+            }
+        }
+    }
+}
+
+impl<T> AvlTree<T> {
+    /// Creates a new AvlTree with no elements.
+    pub fn new() -> Self {
+        AvlTree {
+            root: None,
+            size: 0,
+            stats: TreeStats::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns AvlTree size.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether the AvlTree has no values.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the height of the tree, `0` when empty.
+    pub fn height(&self) -> usize {
+        unsafe { height(self.root) }
+    }
+
+    /// Counters of the rebalancing done so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TreeStats {
+        self.stats
+    }
+
+    /// Returns the smallest element.
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root?;
+
+        unsafe {
+            while let Some(left) = node.as_ref().left {
+                node = left;
+            }
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    /// Returns the largest element.
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root?;
+
+        unsafe {
+            while let Some(right) = node.as_ref().right {
+                node = right;
+            }
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::with_capacity(self.height()),
+            len: self.size,
+            marker: PhantomData,
+        };
+
+        iter.push_left(self.root);
+
+        iter
+    }
+
+    /// Removes every element from the tree.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePtr<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            unsafe {
+                let node = node.dealloc(&Global);
+                stack.extend(node.left);
+                stack.extend(node.right);
+            }
+        }
+        self.size = 0;
+    }
+}
+
+impl<T: Ord> AvlTree<T> {
+    /// Insert a new node. Does nothing if `elem` is already in the tree.
+    pub fn insert(&mut self, elem: T) {
+        unsafe {
+            self.root = Some(self.insert_recursive(self.root, elem, 1));
+        }
+    }
+
+    /// Recursive function to insert a new node at `depth`, returns the new
+    /// root of the subtree.
+    unsafe fn insert_recursive(
+        &mut self,
+        current: Link<T>,
+        elem: T,
+        depth: usize,
+    ) -> NodePtr<Node<T>> {
+        let Some(node) = current else {
+            self.size += 1;
+            self.stats.record_depth(depth);
+            return Node::new(elem);
+        };
+
+        unsafe {
+            match elem.cmp(&node.as_ref().elem) {
+                Ordering::Less => {
+                    let left = self.insert_recursive(node.as_ref().left, elem, depth + 1);
+                    node.as_mut().left = Some(left);
+                }
+                Ordering::Greater => {
+                    let right = self.insert_recursive(node.as_ref().right, elem, depth + 1);
+                    node.as_mut().right = Some(right);
+                }
+                Ordering::Equal => return node,
+            }
+
+            rebalance(node, &mut self.stats)
+        }
+    }
+
+    fn find(&self, elem: &T) -> Link<T> {
+        let mut current = self.root;
+
+        unsafe {
+            while let Some(node) = current {
+                current = match elem.cmp(&node.as_ref().elem) {
+                    Ordering::Less => node.as_ref().left,
+                    Ordering::Greater => node.as_ref().right,
+                    Ordering::Equal => return current,
+                };
+            }
+        }
+
+        None
+    }
+
+    /// Returns `true` if `elem` is in the tree.
+    pub fn contains(&self, elem: &T) -> bool {
+        self.find(elem).is_some()
+    }
+
+    /// Returns the element in the tree that is equal to `elem`.
+    pub fn get(&self, elem: &T) -> Option<&T> {
+        unsafe { Some(&self.find(elem)?.as_ref().elem) }
+    }
+
+    /// Removes `elem` from the AvlTree.
+    pub fn remove(&mut self, elem: &T) {
+        self.take(elem);
+    }
+
+    /// Removes `elem` from the AvlTree and returns it.
+    pub fn take(&mut self, elem: &T) -> Option<T> {
+        let mut removed = None;
+
+        unsafe {
+            self.root = self.remove_recursive(self.root, elem, &mut removed);
+        }
+
+        removed
+    }
+
+    /// AvlTree remove algorithm, returns the new root of the subtree.
+    unsafe fn remove_recursive(
+        &mut self,
+        current: Link<T>,
+        elem: &T,
+        removed: &mut Option<T>,
+    ) -> Link<T> {
+        let node = current?;
+
+        unsafe {
+            match elem.cmp(&node.as_ref().elem) {
+                Ordering::Less => {
+                    let left = node.as_ref().left;
+                    node.as_mut().left = self.remove_recursive(left, elem, removed);
+                }
+                Ordering::Greater => {
+                    let right = node.as_ref().right;
+                    node.as_mut().right = self.remove_recursive(right, elem, removed);
+                }
+                Ordering::Equal => {
+                    let node = node.dealloc(&Global);
+                    self.size -= 1;
+                    *removed = Some(node.elem);
+
+                    // The in-order successor replaces the removed node.
+                    return match (node.left, node.right) {
+                        (None, child) | (child, None) => child,
+                        (Some(left), Some(right)) => {
+                            let (new_right, successor) = remove_min(right, &mut self.stats);
+                            successor.as_mut().left = Some(left);
+                            successor.as_mut().right = new_right;
+                            Some(rebalance(successor, &mut self.stats))
+                        }
+                    };
+                }
+            }
+
+            Some(rebalance(node, &mut self.stats))
+        }
+    }
+}
+
+impl<T> Drop for AvlTree<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for AvlTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// Pushes `link` and all its left descendants.
+    fn push_left(&mut self, mut link: Link<T>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = unsafe { node.as_ref().left };
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        unsafe {
+            self.push_left(node.as_ref().right);
+            self.len -= 1;
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a AvlTree<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> Extend<T> for AvlTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for AvlTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<T: Debug> Debug for AvlTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T: Display> AvlTree<T> {
+    /// Draws the shape of the tree with the height of every node, see
+    /// [`display_tree`].
+    pub fn display_tree(&self) -> DisplayTree<impl TreeNode + '_> {
+        display_tree(self.root.map(|root| unsafe { root.as_ref() }))
+    }
+}
+
+impl<T: Display> TreeNode for &Node<T> {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (height {})", self.elem, self.height)
+    }
+
+    fn children(&self) -> Vec<Option<Self>> {
+        unsafe {
+            vec![
+                self.left.map(|n| n.as_ref()),
+                self.right.map(|n| n.as_ref()),
+            ]
+        }
+    }
+}
+
+impl<T> Traverse for AvlTree<T> {
+    type Elem = T;
+
+    fn traverse<'a, B>(
+        &'a self,
+        order: Order,
+        visit: impl FnMut(Visit<'a, T>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let split = |node: NodePtr<Node<T>>| unsafe {
+            let node = node.as_ref();
+            (&node.elem, node.left, node.right)
+        };
+
+        walk_binary(self.root, order, split, visit)
+    }
+}
+
+impl<T> MemoryUsage for AvlTree<T> {
+    fn heap_bytes(&self) -> usize {
+        self.size * mem::size_of::<Node<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AvlTree, Link};
+    use crate::traverse::{Order, Traverse};
+
+    fn tree_values() -> Vec<i32> {
+        vec![40, 20, 60, 10, 30, 25, 35, 50, 45, 70, 80, 75]
+    }
+
+    /// Checks the search tree order, the stored heights and the balance of
+    /// every node.
+    fn check_balanced<T: Ord>(tree: &AvlTree<T>) {
+        /// Returns the height and size of the subtree.
+        fn check<T: Ord>(link: Link<T>) -> (usize, usize) {
+            let Some(node) = link else {
+                return (0, 0);
+            };
+
+            unsafe {
+                let node = node.as_ref();
+
+                if let Some(left) = node.left {
+                    assert!(left.as_ref().elem < node.elem, "left child out of order");
+                }
+                if let Some(right) = node.right {
+                    assert!(right.as_ref().elem > node.elem, "right child out of order");
+                }
+
+                let (left_height, left_size) = check(node.left);
+                let (right_height, right_size) = check(node.right);
+                assert!(left_height.abs_diff(right_height) <= 1, "unbalanced node");
+                assert_eq!(
+                    node.height,
+                    left_height.max(right_height) + 1,
+                    "stale height"
+                );
+
+                (node.height, left_size + right_size + 1)
+            }
+        }
+
+        assert_eq!(check(tree.root).1, tree.size());
+    }
+
+    #[test]
+    fn test_insert() {
+        let numbers = tree_values();
+        let mut tree = AvlTree::new();
+
+        for n in &numbers {
+            tree.insert(*n);
+            check_balanced(&tree);
+        }
+
+        tree.insert(40);
+        assert_eq!(tree.size(), numbers.len());
+
+        for n in &numbers {
+            assert!(tree.contains(n));
+        }
+
+        assert_eq!(tree.get(&35), Some(&35));
+        assert_eq!(tree.min(), Some(&10));
+        assert_eq!(tree.max(), Some(&80));
+    }
+
+    #[test]
+    fn test_sequential_insert() {
+        let tree: AvlTree<i32> = (0..1023).collect();
+        check_balanced(&tree);
+
+        // A perfectly balanced tree of 1023 nodes has height 10, AVL trees
+        // are at most ~1.44 times taller.
+        assert!(tree.height() <= 14);
+    }
+
+    #[test]
+    fn test_remove() {
+        let numbers = tree_values();
+        let mut tree: AvlTree<i32> = numbers.iter().copied().collect();
+
+        // Leaf, one child, two children and root.
+        for n in [75, 70, 50, 20, 40] {
+            tree.remove(&n);
+            assert!(!tree.contains(&n));
+            check_balanced(&tree);
+        }
+
+        assert_eq!(tree.take(&35), Some(35));
+        assert_eq!(tree.take(&35), None);
+        assert_eq!(tree.size(), numbers.len() - 6);
+
+        let mut tree: AvlTree<u32> = (0..200).collect();
+        for i in (0..200).map(|i| (i * 31) % 200) {
+            tree.remove(&i);
+            check_balanced(&tree);
+        }
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut values = tree_values();
+        let tree: AvlTree<i32> = values.iter().copied().collect();
+
+        values.sort();
+
+        assert!(tree.iter().eq(values.iter()));
+        assert_eq!(tree.iter().len(), values.len());
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<AvlTree<String>>();
+        assert_send_sync::<super::Iter<'_, String>>();
+    }
+
+    #[test]
+    fn test_display_tree() {
+        let mut tree = AvlTree::new();
+        for value in 1..=5 {
+            tree.insert(value);
+        }
+
+        assert_eq!(
+            tree.display_tree().to_string(),
+            "2 (height 3)\n├── 1 (height 1)\n└── 4 (height 2)\n    ├── 3 (height 1)\n    └── 5 (height 1)"
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut tree = AvlTree::new();
+        for i in 1..=7 {
+            tree.insert(i);
+        }
+
+        // Ascending inserts rotate at 1, 3, 2 and 5.
+        let stats = tree.stats();
+        assert_eq!(stats.rotations, 4);
+        assert_eq!(stats.max_depth, 4);
+    }
+
+    #[test]
+    fn test_traverse() {
+        let mut tree = AvlTree::new();
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        assert_eq!(Traverse::height(&tree), tree.height());
+        assert!(tree.elems(Order::In).into_iter().eq(tree.iter()));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::{HashMap, hash_map::RandomState},
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+};
+
+/// Pairs displaced by [`BiMap::insert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    /// Neither value was in the map.
+    Neither,
+    /// The left value was paired with something else, that pair is returned.
+    Left(L, R),
+    /// The right value was paired with something else, that pair is returned.
+    Right(L, R),
+    /// The exact same pair was in the map.
+    Pair(L, R),
+    /// Both values were paired with something else, first the pair of the
+    /// left value then the pair of the right one.
+    Both((L, R), (L, R)),
+}
+
+/// One-to-one map that can be looked up from either side.
+///
+/// Keeps a forward and a reverse hash map in sync, so both values of every
+/// pair are stored twice and have to be `Clone`. Every left value is paired
+/// with exactly one right value and the other way around: inserting a pair
+/// removes any pair that shared one of its values.
+///
+/// Both maps hash with a clone of the same `S`, see [`hash`](crate::hash)
+/// for the alternatives to `RandomState`.
+pub struct BiMap<L, R, S = RandomState> {
+    left_to_right: HashMap<L, R, S>,
+    right_to_left: HashMap<R, L, S>,
+}
+
+impl<L, R> BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    /// Creates a new BiMap with no pairs.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<L, R, S> BiMap<L, R, S>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    S: BuildHasher + Clone,
+{
+    /// Creates a new BiMap that hashes both sides with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        BiMap {
+            left_to_right: HashMap::with_hasher(hash_builder.clone()),
+            right_to_left: HashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Number of pairs.
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left_to_right.is_empty()
+    }
+
+    /// Pairs `left` with `right`, removing the pairs either of them was part
+    /// of before.
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let by_left = self.remove_by_left(&left);
+        let by_right = self.remove_by_right(&right);
+
+        // The pair itself was removed through its left value.
+        let overwritten = match (by_left, by_right) {
+            (None, None) => Overwritten::Neither,
+            (Some((l, r)), None) if r == right => Overwritten::Pair(l, r),
+            (Some((l, r)), None) => Overwritten::Left(l, r),
+            (None, Some((l, r))) => Overwritten::Right(l, r),
+            (Some(by_left), Some(by_right)) => Overwritten::Both(by_left, by_right),
+        };
+
+        self.left_to_right.insert(left.clone(), right.clone());
+        self.right_to_left.insert(right, left);
+
+        overwritten
+    }
+
+    /// Pairs `left` with `right` only if neither is in the map yet,
+    /// otherwise gives the pair back.
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<(), (L, R)> {
+        if self.contains_left(&left) || self.contains_right(&right) {
+            return Err((left, right));
+        }
+
+        self.left_to_right.insert(left.clone(), right.clone());
+        self.right_to_left.insert(right, left);
+
+        Ok(())
+    }
+
+    /// Returns the right value paired with `left`.
+    pub fn get_by_left(&self, left: &L) -> Option<&R> {
+        self.left_to_right.get(left)
+    }
+
+    /// Returns the left value paired with `right`.
+    pub fn get_by_right(&self, right: &R) -> Option<&L> {
+        self.right_to_left.get(right)
+    }
+
+    pub fn contains_left(&self, left: &L) -> bool {
+        self.left_to_right.contains_key(left)
+    }
+
+    pub fn contains_right(&self, right: &R) -> bool {
+        self.right_to_left.contains_key(right)
+    }
+
+    /// Removes the pair with `left` from both sides.
+    pub fn remove_by_left(&mut self, left: &L) -> Option<(L, R)> {
+        let (left, right) = self.left_to_right.remove_entry(left)?;
+        self.right_to_left.remove(&right);
+
+        Some((left, right))
+    }
+
+    /// Removes the pair with `right` from both sides.
+    pub fn remove_by_right(&mut self, right: &R) -> Option<(L, R)> {
+        let (right, left) = self.right_to_left.remove_entry(right)?;
+        self.left_to_right.remove(&left);
+
+        Some((left, right))
+    }
+
+    pub fn clear(&mut self) {
+        self.left_to_right.clear();
+        self.right_to_left.clear();
+    }
+
+    /// Iterates over the pairs in arbitrary order.
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, L, R> {
+        self.left_to_right.iter()
+    }
+
+    pub fn left_values(&self) -> std::collections::hash_map::Keys<'_, L, R> {
+        self.left_to_right.keys()
+    }
+
+    pub fn right_values(&self) -> std::collections::hash_map::Keys<'_, R, L> {
+        self.right_to_left.keys()
+    }
+}
+
+impl<L, R, S> Default for BiMap<L, R, S>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    S: BuildHasher + Clone + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<L, R, S> Extend<(L, R)> for BiMap<L, R, S>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    S: BuildHasher + Clone,
+{
+    fn extend<I: IntoIterator<Item = (L, R)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+impl<L, R, S> FromIterator<(L, R)> for BiMap<L, R, S>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    S: BuildHasher + Clone + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (L, R)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<L, R, S> IntoIterator for BiMap<L, R, S> {
+    type IntoIter = std::collections::hash_map::IntoIter<L, R>;
+    type Item = (L, R);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.left_to_right.into_iter()
+    }
+}
+
+impl<L: Eq + Hash, R: PartialEq, S: BuildHasher> PartialEq for BiMap<L, R, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.left_to_right == other.left_to_right
+    }
+}
+
+impl<L: Eq + Hash, R: Eq, S: BuildHasher> Eq for BiMap<L, R, S> {}
+
+impl<L: Debug, R: Debug, S> Debug for BiMap<L, R, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.left_to_right.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BiMap, Overwritten};
+
+    #[test]
+    fn test_lookup() {
+        let mut map = BiMap::new();
+        assert_eq!(map.insert("one", 1), Overwritten::Neither);
+        assert_eq!(map.insert("two", 2), Overwritten::Neither);
+
+        assert_eq!(map.get_by_left(&"one"), Some(&1));
+        assert_eq!(map.get_by_right(&2), Some(&"two"));
+        assert_eq!(map.get_by_right(&3), None);
+
+        assert_eq!(map.remove_by_right(&1), Some(("one", 1)));
+        assert!(!map.contains_left(&"one"));
+        assert_eq!(map.remove_by_left(&"two"), Some(("two", 2)));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let mut map: BiMap<char, i32> = [('a', 1), ('b', 2), ('c', 3)].into_iter().collect();
+
+        assert_eq!(map.insert('a', 1), Overwritten::Pair('a', 1));
+        assert_eq!(map.insert('a', 10), Overwritten::Left('a', 1));
+        assert_eq!(map.insert('d', 2), Overwritten::Right('b', 2));
+        assert_eq!(map.insert('c', 10), Overwritten::Both(('c', 3), ('a', 10)));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get_by_left(&'c'), Some(&10));
+        assert_eq!(map.get_by_right(&2), Some(&'d'));
+        assert!(!map.contains_right(&3));
+        assert!(!map.contains_left(&'a'));
+
+        assert_eq!(map.insert_no_overwrite('e', 2), Err(('e', 2)));
+        assert_eq!(map.insert_no_overwrite('e', 5), Ok(()));
+
+        let mut rights: Vec<i32> = map.right_values().copied().collect();
+        rights.sort();
+        assert_eq!(rights, [2, 5, 10]);
+    }
+
+    #[test]
+    fn test_hasher() {
+        use crate::hash::FnvBuildHasher;
+
+        let mut map: BiMap<String, u32, FnvBuildHasher> = BiMap::default();
+        map.insert("one".into(), 1);
+        assert_eq!(map.insert("uno".into(), 1), Overwritten::Right("one".into(), 1));
+        assert_eq!(map.get_by_right(&1).map(String::as_str), Some("uno"));
+    }
+}
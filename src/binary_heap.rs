@@ -0,0 +1,235 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use crate::Vector;
+use crate::compare::{Comparator, NaturalOrder};
+use crate::traits::MemoryUsage;
+
+/// Max-heap stored as an implicit binary tree in a [`Vector`].
+///
+/// The children of `i` are `2i + 1` and `2i + 2` and every element is at
+/// least as large as its children, so the maximum is always at the front.
+/// "Large" is up to the [`Comparator`] `C`, build it with
+/// [`compare::Reverse`](crate::compare::Reverse) for a min-heap.
+pub struct BinaryHeap<T, C = NaturalOrder> {
+    data: Vector<T>,
+    cmp: C,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Creates a new BinaryHeap with no elements.
+    pub fn new() -> Self {
+        Self::with_comparator(NaturalOrder)
+    }
+}
+
+impl<T, C: Comparator<T>> BinaryHeap<T, C> {
+    /// Creates an empty heap ordered by `cmp`.
+    pub fn with_comparator(cmp: C) -> Self {
+        BinaryHeap {
+            data: Vector::new(),
+            cmp,
+        }
+    }
+
+    /// Adds `elem` to the heap in O(log n).
+    pub fn push(&mut self, elem: T) {
+        self.data.push(elem);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Removes and returns the largest element in O(log n).
+    pub fn pop(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let elem = self.data.swap_remove(0);
+        self.sift_down(0);
+
+        Some(elem)
+    }
+
+    /// Moves the element at `i` up until its parent is not smaller.
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+
+            if self.cmp.compare(&self.data[i], &self.data[parent]) != Ordering::Greater {
+                break;
+            }
+
+            self.data.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    /// Moves the element at `i` down until no child is larger.
+    fn sift_down(&mut self, mut i: usize) {
+        let len = self.data.len();
+
+        loop {
+            let mut largest = i;
+
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < len
+                    && self.cmp.compare(&self.data[child], &self.data[largest]) == Ordering::Greater
+                {
+                    largest = child;
+                }
+            }
+
+            if largest == i {
+                break;
+            }
+
+            self.data.swap(i, largest);
+            i = largest;
+        }
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    pub fn into_sorted_vector(mut self) -> Vector<T> {
+        let mut sorted = Vector::new();
+        while let Some(elem) = self.pop() {
+            sorted.push(elem);
+        }
+        sorted.reverse();
+
+        sorted
+    }
+}
+
+impl<T, C> BinaryHeap<T, C> {
+    /// Returns the largest element.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        while self.data.pop().is_some() {}
+    }
+
+    /// Iterates over the elements in no particular order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Consumes the heap and returns its elements in heap order.
+    pub fn into_vector(self) -> Vector<T> {
+        self.data
+    }
+}
+
+impl<T, C: Comparator<T> + Default> Default for BinaryHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Comparator<T>> Extend<T> for BinaryHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T, C: Comparator<T> + Default> FromIterator<T> for BinaryHeap<T, C> {
+    /// Builds the heap bottom-up in O(n).
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::default();
+        for elem in iter {
+            heap.data.push(elem);
+        }
+
+        for i in (0..heap.len() / 2).rev() {
+            heap.sift_down(i);
+        }
+
+        heap
+    }
+}
+
+impl<T: Debug, C> Debug for BinaryHeap<T, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, C> MemoryUsage for BinaryHeap<T, C> {
+    fn heap_bytes(&self) -> usize {
+        self.data.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryHeap;
+    use crate::compare::NaturalOrder;
+    use std::cmp::Reverse;
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = BinaryHeap::new();
+        assert_eq!(heap.pop(), None);
+
+        for n in [5, 1, 8, 3, 9, 2, 8] {
+            heap.push(n);
+        }
+
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some(&9));
+
+        let mut popped = Vec::new();
+        while let Some(n) = heap.pop() {
+            popped.push(n);
+        }
+        assert_eq!(popped, [9, 8, 8, 5, 3, 2, 1]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let values: Vec<i32> = (0..100).map(|i| (i * 37) % 101).collect();
+        let heap: BinaryHeap<i32> = values.iter().copied().collect();
+
+        let mut sorted = values.clone();
+        sorted.sort();
+        assert_eq!(*heap.into_sorted_vector(), sorted);
+
+        let mut min_heap: BinaryHeap<Reverse<i32>> = values.into_iter().map(Reverse).collect();
+        assert_eq!(min_heap.pop(), Some(Reverse(0)));
+        assert_eq!(min_heap.pop(), Some(Reverse(1)));
+    }
+
+    #[test]
+    fn test_comparator() {
+        use crate::compare::{self, ByKey};
+
+        let mut min_heap = BinaryHeap::with_comparator(compare::Reverse(NaturalOrder));
+        min_heap.extend([5, 1, 8, 3]);
+        assert_eq!(min_heap.pop(), Some(1));
+        assert_eq!(min_heap.peek(), Some(&3));
+
+        let mut by_len = BinaryHeap::with_comparator(ByKey(|s: &&str| s.len()));
+        by_len.extend(["fig", "banana", "kiwi"]);
+        assert_eq!(by_len.pop(), Some("banana"));
+
+        let last_digit = |a: &i32, b: &i32| (a % 10).cmp(&(b % 10));
+        let mut by_closure = BinaryHeap::with_comparator(last_digit);
+        by_closure.extend([19, 21, 35]);
+        assert_eq!(by_closure.pop(), Some(19));
+    }
+}
@@ -1,4 +1,13 @@
-use std::{ marker::PhantomData, ptr::NonNull, ptr };
+use std::{cmp::Ordering, fmt, marker::PhantomData, mem, ops::ControlFlow};
+
+use crate::alloc::{Allocator, Global};
+use crate::compare::{Comparator, NaturalOrder};
+use crate::display_tree::{DisplayTree, TreeNode, display_tree};
+use crate::extract::{Drain, ExtractIf, Sealed, Source};
+use crate::frozen::Frozen;
+use crate::raw::NodePtr;
+use crate::traits::MemoryUsage;
+use crate::traverse::{Order, Traverse, Visit, walk_binary};
 
 /// BTree node.
 struct Node<T> {
@@ -8,15 +17,20 @@ struct Node<T> {
 }
 
 /// Rusty pointers to nodes.
-type Link<T> = Option<NonNull<Node<T>>>;
+type Link<T> = Option<NodePtr<Node<T>>>;
 
-/// BTree struct
-pub struct BTree<T> {
+/// BTree struct, ordered by the [`Comparator`] `C`.
+pub struct BTree<T, A: Allocator = Global, C = NaturalOrder> {
     root: Link<T>,
     size: usize,
+    alloc: A,
+    cmp: C,
     _marker: PhantomData<T>,
 }
 
+unsafe impl<T: Send, A: Allocator + Send, C: Send> Send for BTree<T, A, C> {}
+unsafe impl<T: Sync, A: Allocator + Sync, C: Sync> Sync for BTree<T, A, C> {}
+
 pub struct Iter<'a, T> {
     elems: Vec<&'a T>,
     current_idx: usize,
@@ -24,19 +38,41 @@ pub struct Iter<'a, T> {
 
 impl<T> Node<T> {
     /// Create new node.
-    fn new(left: Link<T>, right: Link<T>, elem: T) -> NonNull<Node<T>> {
-        unsafe {
-            NonNull::new_unchecked(Box::into_raw(Box::new(Node { left, right, elem })))
-        }
+    fn new<A: Allocator>(alloc: &A, left: Link<T>, right: Link<T>, elem: T) -> NodePtr<Node<T>> {
+        NodePtr::alloc(alloc, Node { left, right, elem })
     }
 }
 
 impl<T: Ord> BTree<T> {
     /// Creates a new BinaryTree struct with no elements.
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T: Ord, A: Allocator> BTree<T, A> {
+    /// Creates a new BinaryTree whose nodes are allocated from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_comparator_in(NaturalOrder, alloc)
+    }
+}
+
+impl<T, C: Comparator<T>> BTree<T, Global, C> {
+    /// Creates a new BinaryTree ordered by `cmp`.
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_comparator_in(cmp, Global)
+    }
+}
+
+impl<T, A: Allocator, C: Comparator<T>> BTree<T, A, C> {
+    /// Creates a new BinaryTree ordered by `cmp`, its nodes allocated from
+    /// `alloc`.
+    pub fn with_comparator_in(cmp: C, alloc: A) -> Self {
         BTree {
             root: None,
             size: 0,
+            alloc,
+            cmp,
             _marker: PhantomData,
         }
     }
@@ -61,14 +97,18 @@ impl<T: Ord> BTree<T> {
     unsafe fn insert_recursive(&mut self, mut current: Link<T>, elem: T) -> Link<T> {
         if let Some(node) = current {
             unsafe {
-                if elem < (*node.as_ptr()).elem {
-                    (*node.as_ptr()).left = self.insert_recursive((*node.as_ptr()).left, elem);
-                } else if elem > (*node.as_ptr()).elem {
-                    (*node.as_ptr()).right = self.insert_recursive((*node.as_ptr()).right, elem);
+                match self.cmp.compare(&elem, &node.as_ref().elem) {
+                    Ordering::Less => {
+                        node.as_mut().left = self.insert_recursive(node.as_ref().left, elem);
+                    }
+                    Ordering::Greater => {
+                        node.as_mut().right = self.insert_recursive(node.as_ref().right, elem);
+                    }
+                    Ordering::Equal => {}
                 }
             }
         } else {
-            let new_node = Some(Node::new(None, None, elem));
+            let new_node = Some(Node::new(&self.alloc, None, None, elem));
             current = new_node;
             self.size += 1
         }
@@ -90,14 +130,12 @@ impl<T: Ord> BTree<T> {
 
             Some(node) => {
                 unsafe {
-                    // You could also take a reference of &(*node.as_ptr()).elem
+                    // You could also take a reference of &node.as_ref().elem
                     // and compare it with elem which is a &T.
-                    if *elem < (*node.as_ptr()).elem {
-                        self.search_recursive((*node.as_ptr()).left, elem)
-                    } else if *elem > (*node.as_ptr()).elem {
-                        self.search_recursive((*node.as_ptr()).right, elem)
-                    } else {
-                        true
+                    match self.cmp.compare(elem, &node.as_ref().elem) {
+                        Ordering::Less => self.search_recursive(node.as_ref().left, elem),
+                        Ordering::Greater => self.search_recursive(node.as_ref().right, elem),
+                        Ordering::Equal => true,
                     }
                 }
             }
@@ -107,12 +145,12 @@ impl<T: Ord> BTree<T> {
 
     // Returns a pointer to the parent node of the node that contains the
     /// minimum value in the given subtree. Used for searching inorder successors.
-    unsafe fn min_value_parent_node(&self, node: NonNull<Node<T>>) -> Link<T> {
+    unsafe fn min_value_parent_node(&self, node: NodePtr<Node<T>>) -> Link<T> {
         unsafe {
-            match (*node.as_ptr()).left {
+            match node.as_ref().left {
                 None => None,
                 
-                Some(node_left) => match (*node_left.as_ptr()).left {
+                Some(node_left) => match node_left.as_ref().left {
                     None => Some(node),
                     Some(_) => self.min_value_parent_node(node_left),
                 }
@@ -130,19 +168,20 @@ impl<T: Ord> BTree<T> {
     /// BTree remove algorithm
     unsafe fn remove_recursive(&mut self, current: Link<T>, elem: &T) -> Link<T> {
         // Node not found
-        if current.is_none() { return None; }
+        let node = current?;
 
         // Search
-        let node = current.unwrap();
         unsafe {
-            if *elem < (*node.as_ptr()).elem {
-                (*node.as_ptr()).left = self.remove_recursive((*node.as_ptr()).left, elem);
-                return current;
-            }
-            
-            if *elem > (*node.as_ptr()).elem {
-                (*node.as_ptr()).right = self.remove_recursive((*node.as_ptr()).right, elem);
-                return current;
+            match self.cmp.compare(elem, &node.as_ref().elem) {
+                Ordering::Less => {
+                    node.as_mut().left = self.remove_recursive(node.as_ref().left, elem);
+                    return current;
+                }
+                Ordering::Greater => {
+                    node.as_mut().right = self.remove_recursive(node.as_ref().right, elem);
+                    return current;
+                }
+                Ordering::Equal => {}
             }
 
 
@@ -151,50 +190,64 @@ impl<T: Ord> BTree<T> {
 
             // Case 1: Node has only one child or None
             let mut replacement = None;
-            if (*node.as_ptr()).left.is_none() {
-                replacement = Some((*node.as_ptr()).right);
-            } else if (*node.as_ptr()).right.is_none() {
-                replacement = Some((*node.as_ptr()).left);
+            if node.as_ref().left.is_none() {
+                replacement = Some(node.as_ref().right);
+            } else if node.as_ref().right.is_none() {
+                replacement = Some(node.as_ref().left);
             }
 
-            if replacement.is_some() {
-                drop(Box::from_raw(node.as_ptr()));
-                return replacement.unwrap();
+            if let Some(replacement) = replacement {
+                drop(node.dealloc(&self.alloc));
+                return replacement;
             }
 
-            // Case 2: Node has two children
-            let node_to_drop;
-
-            if let Some(parent) = self.min_value_parent_node((*node.as_ptr()).right.unwrap()) {
-                node_to_drop = (*parent.as_ptr()).left.unwrap();
-                let left = ptr::read(node_to_drop.as_ptr());
-                (*node.as_ptr()).elem = left.elem;
-                (*parent.as_ptr()).left = left.right
+            // Case 2: Node has two children. The successor's element is
+            // moved into `node` and its memory freed without dropping it.
+            if let Some(parent) = self.min_value_parent_node(node.as_ref().right.unwrap()) {
+                let left = parent.as_ref().left.unwrap().dealloc(&self.alloc);
+                node.as_mut().elem = left.elem;
+                parent.as_mut().left = left.right
             } else {
-                node_to_drop = (*node.as_ptr()).right.unwrap();
-                let right = ptr::read(node_to_drop.as_ptr());
-                (*node.as_ptr()).elem = right.elem;
-                (*node.as_ptr()).right = right.right;
+                let right = node.as_ref().right.unwrap().dealloc(&self.alloc);
+                node.as_mut().elem = right.elem;
+                node.as_mut().right = right.right;
             }
-            drop(Box::from_raw(node_to_drop.as_ptr()));
         }
         current 
     }
 }
 
-impl<T> BTree<T> {
+impl<T, C: Comparator<T> + Default> Default for BTree<T, Global, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T: std::fmt::Debug, A: Allocator, C> std::fmt::Debug for BTree<T, A, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, A: Allocator, C> Drop for BTree<T, A, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, A: Allocator, C> BTree<T, A, C> {
     unsafe fn push_inorder(&self, current: Link<T>, elems: &mut Vec<&T>) {
         unsafe {
             if let Some(node) = current {
-                self.push_inorder((*node.as_ptr()).left, elems);
-                elems.push(&(*node.as_ptr()).elem);
-                self.push_inorder((*node.as_ptr()).right, elems);
+                self.push_inorder(node.as_ref().left, elems);
+                elems.push(&node.as_ref().elem);
+                self.push_inorder(node.as_ref().right, elems);
                 
             }
         }
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         let mut elems = Vec::with_capacity(self.size);
 
         unsafe {
@@ -206,6 +259,156 @@ impl<T> BTree<T> {
             current_idx: 0,
         }
     }
+
+    /// Returns the smallest element.
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root?;
+
+        unsafe {
+            while let Some(left) = node.as_ref().left {
+                node = left;
+            }
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    /// Returns the largest element.
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root?;
+
+        unsafe {
+            while let Some(right) = node.as_ref().right {
+                node = right;
+            }
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    /// Removes every element from the tree.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePtr<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            let node = unsafe { node.dealloc(&self.alloc) };
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+        self.size = 0;
+    }
+
+    /// Removes and yields, in order, the elements for which `pred` returns
+    /// `true`, as the iterator is advanced. See [`extract`](crate::extract)
+    /// for what happens when it's dropped early or something panics.
+    ///
+    /// The tree is rebuilt balanced from the nodes left once the iterator is
+    /// dropped, and is empty until then if the iterator is leaked.
+    pub fn extract_if<F: FnMut(&T) -> bool>(
+        &mut self,
+        mut pred: F,
+    ) -> ExtractIf<ExtractSource<'_, T, A, C>, impl FnMut(&mut T) -> bool> {
+        // The elements are keys, `pred` can't be allowed to reorder them.
+        ExtractIf::new(self.extract_source(), move |elem: &mut T| pred(elem))
+    }
+
+    /// Removes and yields every element in order, the tree is empty once the
+    /// iterator is dropped.
+    pub fn drain(&mut self) -> Drain<ExtractSource<'_, T, A, C>> {
+        Drain::new(self.extract_source())
+    }
+
+    /// Turns the tree into a read-only [`Frozen`] snapshot that can be
+    /// cloned and shared between threads without copying the nodes.
+    pub fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self)
+    }
+
+    fn extract_source(&mut self) -> ExtractSource<'_, T, A, C> {
+        let mut nodes = Vec::with_capacity(self.size);
+        let mut stack = Vec::new();
+        let mut node = self.root.take();
+
+        while node.is_some() || !stack.is_empty() {
+            while let Some(current) = node {
+                stack.push(current);
+                node = unsafe { current.as_ref().left };
+            }
+
+            let current = stack.pop().unwrap();
+            nodes.push(current);
+            node = unsafe { current.as_ref().right };
+        }
+        self.size = 0;
+
+        ExtractSource {
+            tree: self,
+            nodes,
+            index: 0,
+            kept: 0,
+        }
+    }
+}
+
+/// [`Source`] of [`BTree::extract_if`] and [`BTree::drain`]. It detaches
+/// the nodes in order, packs the kept ones at the front and links them back
+/// into a balanced tree when it finishes.
+pub struct ExtractSource<'a, T, A: Allocator = Global, C = NaturalOrder> {
+    tree: &'a mut BTree<T, A, C>,
+    nodes: Vec<NodePtr<Node<T>>>,
+    /// Next node to visit.
+    index: usize,
+    /// Nodes kept so far, at the front of `nodes`.
+    kept: usize,
+}
+
+impl<T, A: Allocator, C> Sealed for ExtractSource<'_, T, A, C> {}
+
+impl<T, A: Allocator, C> Source for ExtractSource<'_, T, A, C> {
+    type Item = T;
+
+    fn current(&mut self) -> Option<&mut T> {
+        let node = *self.nodes.get(self.index)?;
+        unsafe { Some(&mut node.as_mut().elem) }
+    }
+
+    fn keep(&mut self) {
+        self.nodes[self.kept] = self.nodes[self.index];
+        self.kept += 1;
+        self.index += 1;
+    }
+
+    fn remove(&mut self) -> T {
+        let node = self.nodes[self.index];
+        self.index += 1;
+
+        unsafe { node.dealloc(&self.tree.alloc).elem }
+    }
+
+    fn finish(&mut self) {
+        /// Links the sorted `nodes` into a balanced tree.
+        fn link<T>(nodes: &[NodePtr<Node<T>>]) -> Link<T> {
+            let (left, rest) = nodes.split_at(nodes.len() / 2);
+            let (&node, right) = rest.split_first()?;
+
+            unsafe {
+                node.as_mut().left = link(left);
+                node.as_mut().right = link(right);
+            }
+            Some(node)
+        }
+
+        self.nodes.drain(self.kept..self.index);
+        self.kept = 0;
+        self.index = 0;
+
+        self.tree.root = link(&self.nodes);
+        self.tree.size = self.nodes.len();
+    }
+
+    fn remaining(&self) -> usize {
+        self.nodes.len() - self.index
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -230,9 +433,245 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
+impl<T: fmt::Display, A: Allocator, C> BTree<T, A, C> {
+    /// Draws the shape of the tree, see [`display_tree`].
+    pub fn display_tree(&self) -> DisplayTree<impl TreeNode + '_> {
+        display_tree(self.root.map(|root| unsafe { root.as_ref() }))
+    }
+}
+
+impl<T: fmt::Display> TreeNode for &Node<T> {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.elem.fmt(f)
+    }
+
+    fn children(&self) -> Vec<Option<Self>> {
+        unsafe { vec![self.left.map(|n| n.as_ref()), self.right.map(|n| n.as_ref())] }
+    }
+}
+
+impl<T, A: Allocator, C> Traverse for BTree<T, A, C> {
+    type Elem = T;
+
+    fn traverse<'a, B>(
+        &'a self,
+        order: Order,
+        visit: impl FnMut(Visit<'a, T>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let split = |node: NodePtr<Node<T>>| unsafe {
+            let node = node.as_ref();
+            (&node.elem, node.left, node.right)
+        };
+
+        walk_binary(self.root, order, split, visit)
+    }
+}
+
+impl<T, A: Allocator, C> MemoryUsage for BTree<T, A, C> {
+    fn heap_bytes(&self) -> usize {
+        self.size * mem::size_of::<Node<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Ord> BTree<T> {
+    /// Builds a balanced tree from sorted, distinct elements.
+    fn from_sorted(elems: Vec<T>) -> Self {
+        fn build<T>(elems: &mut std::vec::IntoIter<T>, len: usize) -> Link<T> {
+            if len == 0 {
+                return None;
+            }
+
+            let left = build(elems, len / 2);
+            let elem = elems.next().unwrap();
+            let right = build(elems, len - len / 2 - 1);
+
+            Some(Node::new(&Global, left, right, elem))
+        }
+
+        let size = elems.len();
+        let root = build(&mut elems.into_iter(), size);
+
+        BTree {
+            root,
+            size,
+            alloc: Global,
+            cmp: NaturalOrder,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: Allocator, C> serde::Serialize for BTree<T, A, C> {
+    /// Serializes the elements in ascending order.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de> + Ord> serde::Deserialize<'de> for BTree<T> {
+    /// Rebuilds a balanced tree in O(n) from sorted input, or O(n log n)
+    /// otherwise. Inserting the elements one by one would degenerate into a
+    /// list for sorted input.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = crate::serde_impls::SeqVisitor::new(Vec::new(), Vec::push);
+        let mut elems = deserializer.deserialize_seq(visitor)?;
+        elems.sort();
+        elems.dedup();
+
+        Ok(BTree::from_sorted(elems))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a, A: Allocator, C> rayon::iter::IntoParallelIterator for &'a BTree<T, A, C> {
+    type Item = &'a T;
+    type Iter = ParIter<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter {
+            root: self.root,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Parallel in-order iterator, work is split along the subtrees.
+#[cfg(feature = "rayon")]
+pub struct ParIter<'a, T> {
+    root: Link<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+#[cfg(feature = "rayon")]
+unsafe impl<'a, T: Sync> Send for ParIter<'a, T> {}
+#[cfg(feature = "rayon")]
+unsafe impl<'a, T: Sync> Sync for ParIter<'a, T> {}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a> rayon::iter::ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: rayon::iter::plumbing::UnindexedConsumer<&'a T>,
+    {
+        let producer = SubtreeProducer {
+            first: None,
+            root: self.root,
+            _marker: PhantomData,
+        };
+
+        rayon::iter::plumbing::bridge_unindexed(producer, consumer)
+    }
+}
+
+/// Yields `first` followed by the subtree under `root`. Splitting hands the
+/// left subtree to one half and the root plus its right subtree to the other.
+#[cfg(feature = "rayon")]
+struct SubtreeProducer<'a, T> {
+    first: Option<&'a T>,
+    root: Link<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+#[cfg(feature = "rayon")]
+unsafe impl<'a, T: Sync> Send for SubtreeProducer<'a, T> {}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a> rayon::iter::plumbing::UnindexedProducer for SubtreeProducer<'a, T> {
+    type Item = &'a T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        let Some(node) = self.root else {
+            return (self, None);
+        };
+        let node = unsafe { node.as_ref() };
+
+        if node.left.is_none() && node.right.is_none() {
+            return (self, None);
+        }
+
+        let left = SubtreeProducer {
+            first: self.first,
+            root: node.left,
+            _marker: PhantomData,
+        };
+        let right = SubtreeProducer {
+            first: Some(&node.elem),
+            root: node.right,
+            _marker: PhantomData,
+        };
+
+        (left, Some(right))
+    }
+
+    fn fold_with<F: rayon::iter::plumbing::Folder<&'a T>>(self, mut folder: F) -> F {
+        if let Some(first) = self.first {
+            folder = folder.consume(first);
+        }
+
+        let mut stack: Vec<&'a Node<T>> = Vec::new();
+        let mut current = self.root;
+
+        while !folder.full() {
+            while let Some(node) = current {
+                let node = unsafe { node.as_ref() };
+                stack.push(node);
+                current = node.left;
+            }
+
+            let Some(node) = stack.pop() else {
+                break;
+            };
+
+            folder = folder.consume(&node.elem);
+            current = node.right;
+        }
+
+        folder
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a> + Ord> arbitrary::Arbitrary<'a> for BTree<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.try_fold(BTree::new(), |mut tree, elem| {
+            tree.insert(elem?);
+            Ok(tree)
+        })
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.try_fold(BTree::new(), |mut tree, elem| {
+            tree.insert(elem?);
+            Ok(tree)
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + Ord> proptest::arbitrary::Arbitrary for BTree<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = crate::strategy::BTreeStrategy<T::Strategy>;
+
+    fn arbitrary_with((size, params): Self::Parameters) -> Self::Strategy {
+        crate::strategy::btree(proptest::arbitrary::any_with::<T>(params), size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::ops::ControlFlow;
+
     use super::BTree;
+    use crate::traverse::{Order, Traverse};
 
     fn tree_values() -> Vec<i32> {
         vec![40, 20, 60, 10, 30, 25, 35, 50, 45, 70, 80, 75]
@@ -332,4 +771,283 @@ mod tests {
 
         assert!(iter.next().is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_drop() {
+        use std::rc::Rc;
+
+        let values: Vec<Rc<i32>> = tree_values().into_iter().map(Rc::new).collect();
+        let mut tree = BTree::new();
+        for value in &values {
+            tree.insert(value.clone());
+        }
+
+        // 40 and 20 have two children.
+        tree.remove(&Rc::new(40));
+        tree.remove(&Rc::new(20));
+        assert_eq!(Rc::strong_count(&values[0]), 1);
+        assert_eq!(Rc::strong_count(&values[1]), 1);
+        assert_eq!(Rc::strong_count(&values[2]), 2);
+
+        drop(tree);
+        assert!(values.iter().all(|value| Rc::strong_count(value) == 1));
+    }
+
+    #[test]
+    fn test_extract_if() {
+        use crate::alloc::Counting;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let alloc = Counting::new();
+        let mut tree = BTree::new_in(&alloc);
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        let mut tens = tree.extract_if(|value| value % 10 == 0);
+        assert_eq!(tens.next(), Some(10));
+        assert_eq!(tens.next(), Some(20));
+        drop(tens);
+        assert_eq!(format!("{tree:?}"), "{25, 30, 35, 40, 45, 50, 60, 70, 75, 80}");
+        assert_eq!(alloc.live_allocations(), 10);
+        // Rebuilt balanced from the rest.
+        assert_eq!(tree.display_tree().to_string().lines().next(), Some("50"));
+        assert!(tree.contains(&25) && !tree.contains(&20));
+
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            tree.extract_if(|value| {
+                calls += 1;
+                assert!(calls < 4);
+                *value < 40
+            })
+            .for_each(drop)
+        }));
+        assert!(result.is_err());
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), [40, 45, 50, 60, 70, 75, 80]);
+        assert_eq!(alloc.live_allocations(), 7);
+
+        let mut drain = tree.drain();
+        assert_eq!(drain.len(), 7);
+        assert_eq!(drain.next(), Some(40));
+        drop(drain);
+        assert!(tree.is_empty());
+        assert_eq!(alloc.live_allocations(), 0);
+
+        tree.insert(1);
+        std::mem::forget(tree.drain());
+        assert!(tree.is_empty());
+        tree.insert(2);
+        assert_eq!(tree.size(), 1);
+    }
+
+    #[test]
+    fn test_panic_safety() {
+        use crate::alloc::Counting;
+        use std::cmp::Ordering;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        /// Comparing with 99 panics.
+        #[derive(PartialEq, Eq)]
+        struct Key(i32);
+
+        impl PartialOrd for Key {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Key {
+            fn cmp(&self, other: &Self) -> Ordering {
+                assert!(self.0 != 99 && other.0 != 99);
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let alloc = Counting::new();
+        let mut tree = BTree::new_in(&alloc);
+        for value in tree_values() {
+            tree.insert(Key(value));
+        }
+        let size = tree.size();
+
+        let result = catch_unwind(AssertUnwindSafe(|| tree.insert(Key(99))));
+        assert!(result.is_err());
+        let result = catch_unwind(AssertUnwindSafe(|| tree.remove(&Key(99))));
+        assert!(result.is_err());
+
+        assert_eq!(tree.size(), size);
+        assert_eq!(alloc.live_allocations(), size);
+        assert!(tree_values().into_iter().all(|value| tree.contains(&Key(value))));
+
+        drop(tree);
+        assert_eq!(alloc.live_allocations(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut tree = BTree::new();
+        for n in tree_values() {
+            tree.insert(n);
+        }
+
+        use serde::{Deserialize, de::value::{Error, SeqDeserializer}};
+        use serde_test::{Token, assert_ser_tokens};
+
+        let mut tokens = vec![Token::Seq { len: Some(12) }];
+        tokens.extend([10, 20, 25, 30, 35, 40, 45, 50, 60, 70, 75, 80].map(Token::I32));
+        tokens.push(Token::SeqEnd);
+        assert_ser_tokens(&tree, &tokens);
+
+        let input = SeqDeserializer::<_, Error>::new([3, 1, 2, 3, 0].into_iter());
+        let mut back = BTree::<i32>::deserialize(input).unwrap();
+        assert_eq!(back.size(), 4);
+        assert!(back.iter().copied().eq(0..4));
+        back.remove(&2);
+        assert!(!back.contains(&2));
+        assert!(back.contains(&3));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_rayon() {
+        use rayon::prelude::*;
+
+        let mut tree = BTree::new();
+        for value in tree_values() {
+            tree.insert(value);
+        }
+
+        let mut sorted = tree_values();
+        sorted.sort_unstable();
+
+        let values: Vec<i32> = tree.par_iter().copied().collect();
+        assert_eq!(values, sorted);
+        assert_eq!(tree.par_iter().sum::<i32>(), sorted.iter().sum());
+        assert_eq!(tree.par_iter().find_any(|x| **x == 45), Some(&45));
+        assert_eq!(BTree::<i32>::new().par_iter().count(), 0);
+
+        let mut big = BTree::new();
+        for i in 0..1000 {
+            big.insert((i * 7919) % 1000);
+        }
+        assert!(big.par_iter().copied().collect::<Vec<_>>().into_iter().eq(0..1000));
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn test_degenerate_strategy() {
+        use proptest::{prelude::*, strategy::ValueTree, test_runner::TestRunner};
+
+        let strategy = crate::strategy::degenerate_btree(any::<u8>(), 0..50);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..32 {
+            let tree = strategy.new_tree(&mut runner).unwrap().current();
+
+            // Every node has at most one child.
+            let mut node = tree.root;
+            while let Some(current) = node {
+                let current = unsafe { current.as_ref() };
+                assert!(current.left.is_none() || current.right.is_none());
+                node = current.left.or(current.right);
+            }
+        }
+    }
+
+    #[test]
+    fn test_send_sync() {
+        use std::sync::Arc;
+        use std::thread;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BTree<String>>();
+
+        let tree = thread::spawn(|| {
+            let mut tree = BTree::new();
+            for value in tree_values() {
+                tree.insert(value);
+            }
+            tree
+        })
+        .join()
+        .unwrap();
+
+        let tree = Arc::new(tree);
+        let handles: Vec<_> = tree_values()
+            .into_iter()
+            .map(|value| {
+                let tree = Arc::clone(&tree);
+                thread::spawn(move || tree.contains(&value) && !tree.contains(&(value + 1)))
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_display_tree() {
+        let mut tree = BTree::new();
+        for value in [40, 20, 60, 10, 30, 70] {
+            tree.insert(value);
+        }
+
+        assert_eq!(
+            tree.display_tree().to_string(),
+            "40\n├── 20\n│   ├── 10\n│   └── 30\n└── 60\n    ├── ·\n    └── 70"
+        );
+        assert_eq!(BTree::<i32>::new().display_tree().to_string(), "(empty)");
+    }
+
+    #[test]
+    fn test_traverse() {
+        let mut tree = BTree::new();
+        for value in [40, 20, 60, 10, 30, 70] {
+            tree.insert(value);
+        }
+
+        assert_eq!(tree.elems(Order::Pre), [&40, &20, &10, &30, &60, &70]);
+        assert_eq!(tree.elems(Order::In), [&10, &20, &30, &40, &60, &70]);
+        assert_eq!(tree.elems(Order::Post), [&10, &30, &20, &70, &60, &40]);
+        assert_eq!(Traverse::height(&tree), 3);
+
+        // Breaks at the first element over 25, in order.
+        let found = tree.traverse(Order::In, |visit| {
+            if *visit.elem > 25 {
+                ControlFlow::Break((*visit.elem, visit.depth, visit.parent))
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(found, ControlFlow::Break((30, 2, Some(1))));
+
+        assert_eq!(
+            tree.to_dot(),
+            "digraph {\n    0 [label=\"40\"];\n    1 [label=\"20\"];\n    0 -> 1;\n    \
+             2 [label=\"10\"];\n    1 -> 2;\n    3 [label=\"30\"];\n    1 -> 3;\n    \
+             4 [label=\"60\"];\n    0 -> 4;\n    5 [label=\"70\"];\n    4 -> 5;\n}"
+        );
+        assert_eq!(BTree::<i32>::new().to_dot(), "digraph {\n}");
+    }
+
+    #[test]
+    fn test_comparator() {
+        use crate::compare::{ByKey, Reverse};
+
+        let mut tree = BTree::with_comparator(Reverse(ByKey(|s: &&str| s.len())));
+        for word in ["fig", "banana", "kiwi", "pear", "apple"] {
+            tree.insert(word);
+        }
+
+        // "pear" has the length of "kiwi", so it counts as already there.
+        assert_eq!(tree.size(), 4);
+        assert!(tree.contains(&"plum") && !tree.contains(&"cherries"));
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), ["banana", "apple", "kiwi", "fig"]);
+
+        tree.remove(&"bean");
+        assert_eq!(tree.min(), Some(&"banana"));
+        assert_eq!(tree.max(), Some(&"fig"));
+    }
+}
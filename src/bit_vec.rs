@@ -0,0 +1,614 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{BitAndAssign, BitOrAssign, BitXorAssign, Not},
+};
+
+use crate::Vector;
+use crate::traits::MemoryUsage;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Growable vector of bits packed into `u64` words.
+///
+/// Bit `i` is bit `i % 64` of word `i / 64`, and the bits of the last word
+/// past `len` are always zero so whole words can be compared and counted.
+/// The bitwise operators work a word at a time; when the lengths differ the
+/// shorter operand counts as padded with zeros.
+pub struct BitVec {
+    words: Vector<u64>,
+    len: usize,
+}
+
+/// Iterator over the bits, see [`BitVec::iter`].
+pub struct Iter<'a> {
+    vec: &'a BitVec,
+    front: usize,
+    back: usize,
+}
+
+/// Iterator over the positions of set bits, see [`BitVec::iter_ones`].
+pub struct IterOnes<'a> {
+    words: std::slice::Iter<'a, u64>,
+    /// Bits of the current word not returned yet.
+    current: u64,
+    /// Index of the first bit of the current word.
+    base: usize,
+}
+
+impl BitVec {
+    /// Creates a new BitVec with no bits.
+    pub fn new() -> Self {
+        BitVec {
+            words: Vector::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates a BitVec of `len` bits all set to `value`.
+    pub fn repeat(value: bool, len: usize) -> Self {
+        let mut vec = Self::new();
+        vec.resize(len, value);
+
+        vec
+    }
+
+    /// Builds a BitVec from bytes, bit `i` being bit `i % 8` of byte `i / 8`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut vec = Self::new();
+
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            vec.words.push(u64::from_le_bytes(word));
+        }
+        vec.len = bytes.len() * 8;
+
+        vec
+    }
+
+    /// Packs the bits into bytes, the inverse of [`BitVec::from_bytes`]. The
+    /// last byte is padded with zeros.
+    pub fn to_bytes(&self) -> Vector<u8> {
+        let mut bytes = Vector::new();
+
+        for byte in self.words.iter().flat_map(|word| word.to_le_bytes()) {
+            if bytes.len() == self.len.div_ceil(8) {
+                break;
+            }
+            bytes.push(byte);
+        }
+
+        bytes
+    }
+
+    /// Number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the bits of the last word past `len`.
+    fn clear_tail(&mut self) {
+        let used = self.len % WORD_BITS;
+
+        if used != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << used) - 1;
+        }
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.push(0);
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    pub fn pop(&mut self) -> Option<bool> {
+        let value = self.get(self.len.checked_sub(1)?)?;
+
+        self.set(self.len - 1, false);
+        self.len -= 1;
+
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.pop();
+        }
+
+        Some(value)
+    }
+
+    /// Grows or shrinks to `len` bits, new bits are set to `value`.
+    pub fn resize(&mut self, len: usize, value: bool) {
+        if len <= self.len {
+            while self.words.len() > len.div_ceil(WORD_BITS) {
+                self.words.pop();
+            }
+            self.len = len;
+            self.clear_tail();
+            return;
+        }
+
+        // Fill up the last word, then whole words at a time.
+        while !self.len.is_multiple_of(WORD_BITS) && self.len < len {
+            self.push(value);
+        }
+
+        let fill = if value { u64::MAX } else { 0 };
+        while self.words.len() < len.div_ceil(WORD_BITS) {
+            self.words.push(fill);
+        }
+
+        self.len = len;
+        self.clear_tail();
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// Sets bit `index` to `value`. Panics if `index >= len`.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "Index out of bounds");
+
+        let mask = 1 << (index % WORD_BITS);
+        if value {
+            self.words[index / WORD_BITS] |= mask;
+        } else {
+            self.words[index / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// Flips bit `index` and returns its new value. Panics if
+    /// `index >= len`.
+    pub fn flip(&mut self, index: usize) -> bool {
+        assert!(index < self.len, "Index out of bounds");
+
+        self.words[index / WORD_BITS] ^= 1 << (index % WORD_BITS);
+        self.get(index).unwrap()
+    }
+
+    /// Number of bits set to `1`.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Number of bits set to `0`.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// The packed words, bits of the last word past `len` are zero.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Sets every bit to `0`, keeping the length.
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            vec: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    /// Iterates over the positions of the set bits in ascending order,
+    /// skipping whole words of zeros.
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        IterOnes {
+            words: self.words.iter(),
+            current: 0,
+            base: 0usize.wrapping_sub(WORD_BITS),
+        }
+    }
+
+    /// Applies `op` to every pair of words, growing `self` to the length of
+    /// `other` first.
+    fn zip_words(&mut self, other: &BitVec, op: impl Fn(u64, u64) -> u64) {
+        if other.len > self.len {
+            self.resize(other.len, false);
+        }
+
+        for (i, word) in self.words.iter_mut().enumerate() {
+            *word = op(*word, other.words.get(i).copied().unwrap_or(0));
+        }
+    }
+}
+
+impl Default for BitVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for BitVec {
+    fn clone(&self) -> Self {
+        let mut words = Vector::new();
+        for &word in self.words.iter() {
+            words.push(word);
+        }
+
+        BitVec {
+            words,
+            len: self.len,
+        }
+    }
+}
+
+impl BitAndAssign<&BitVec> for BitVec {
+    fn bitand_assign(&mut self, other: &BitVec) {
+        self.zip_words(other, |a, b| a & b);
+    }
+}
+
+impl BitOrAssign<&BitVec> for BitVec {
+    fn bitor_assign(&mut self, other: &BitVec) {
+        self.zip_words(other, |a, b| a | b);
+    }
+}
+
+impl BitXorAssign<&BitVec> for BitVec {
+    fn bitxor_assign(&mut self, other: &BitVec) {
+        self.zip_words(other, |a, b| a ^ b);
+    }
+}
+
+impl Not for BitVec {
+    type Output = BitVec;
+
+    fn not(mut self) -> Self::Output {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+        self.clear_tail();
+
+        self
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        self.vec.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        self.vec.get(self.back)
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl Iterator for IterOnes<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.current = *self.words.next()?;
+            self.base = self.base.wrapping_add(WORD_BITS);
+        }
+
+        let bit = self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit.
+        self.current &= self.current - 1;
+
+        Some(self.base + bit)
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type IntoIter = Iter<'a>;
+    type Item = bool;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+
+        vec
+    }
+}
+
+impl From<&[u8]> for BitVec {
+    fn from(bytes: &[u8]) -> Self {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl PartialEq for BitVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && *self.words == *other.words
+    }
+}
+
+impl Eq for BitVec {}
+
+impl Hash for BitVec {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        (*self.words).hash(state);
+    }
+}
+
+impl Debug for BitVec {
+    /// Formats the bits as a string of `0`s and `1`s, first bit first.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bits: String = self.iter().map(|b| if b { '1' } else { '0' }).collect();
+        write!(f, "BitVec({bits})")
+    }
+}
+
+/// Set of small non-negative integers, stored as a [`BitVec`] where bit `i`
+/// tells whether `i` is in the set.
+#[derive(Clone, Default)]
+pub struct BitSet {
+    bits: BitVec,
+}
+
+impl BitSet {
+    /// Creates a new BitSet with no values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.words.iter().all(|&word| word == 0)
+    }
+
+    /// Adds `value`, returns `false` if it was already present.
+    pub fn insert(&mut self, value: usize) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+
+        if value >= self.bits.len() {
+            self.bits.resize(value + 1, false);
+        }
+
+        self.bits.set(value, true);
+        true
+    }
+
+    /// Removes `value`, returns `false` if it wasn't present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+
+        self.bits.set(value, false);
+        true
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.bits.get(value).unwrap_or(false)
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear_all();
+    }
+
+    /// Adds every value of `other`.
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.bits |= &other.bits;
+    }
+
+    /// Keeps only the values also in `other`.
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.bits &= &other.bits;
+    }
+
+    /// Removes every value of `other`.
+    pub fn difference_with(&mut self, other: &BitSet) {
+        self.bits.zip_words(&other.bits, |a, b| a & !b);
+    }
+
+    /// Keeps the values in exactly one of the two sets.
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.bits ^= &other.bits;
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.iter().all(|value| other.contains(value))
+    }
+
+    /// Iterates over the values in ascending order.
+    pub fn iter(&self) -> IterOnes<'_> {
+        self.bits.iter_ones()
+    }
+
+    /// The underlying bits.
+    pub fn as_bit_vec(&self) -> &BitVec {
+        &self.bits
+    }
+}
+
+impl PartialEq for BitSet {
+    /// Sets are equal if they hold the same values, regardless of how many
+    /// bits each one has allocated.
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for BitSet {}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type IntoIter = IterOnes<'a>;
+    type Item = usize;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+
+        set
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl MemoryUsage for BitVec {
+    fn heap_bytes(&self) -> usize {
+        self.words.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitSet, BitVec};
+
+    #[test]
+    fn test_bits() {
+        let mut vec = BitVec::repeat(false, 70);
+        vec.set(0, true);
+        vec.set(64, true);
+        assert!(vec.flip(69));
+        assert!(!vec.flip(0));
+
+        assert_eq!(vec.get(64), Some(true));
+        assert_eq!(vec.get(70), None);
+        assert_eq!(vec.count_ones(), 2);
+        assert_eq!(vec.count_zeros(), 68);
+        assert!(vec.iter_ones().eq([64, 69]));
+
+        vec.push(true);
+        assert_eq!(vec.pop(), Some(true));
+        assert_eq!(vec.pop(), Some(true));
+        assert_eq!(vec.len(), 69);
+
+        let inverted = !vec.clone();
+        assert_eq!(inverted.count_ones(), 68);
+        assert_eq!(inverted.get(64), Some(false));
+
+        vec.resize(130, true);
+        assert_eq!(vec.count_ones(), 62);
+        vec.resize(3, true);
+        assert_eq!(format!("{:?}", vec), "BitVec(000)");
+    }
+
+    #[test]
+    fn test_ops_and_bytes() {
+        let a: BitVec = [true, true, false, false].into_iter().collect();
+        let b: BitVec = [true, false, true, false, true].into_iter().collect();
+
+        let mut and = a.clone();
+        and &= &b;
+        assert_eq!(format!("{:?}", and), "BitVec(10000)");
+
+        let mut or = a.clone();
+        or |= &b;
+        assert_eq!(format!("{:?}", or), "BitVec(11101)");
+
+        let mut xor = a;
+        xor ^= &b;
+        assert_eq!(format!("{:?}", xor), "BitVec(01101)");
+
+        let bytes = [0b1010_0001, 0xff, 0x01];
+        let vec = BitVec::from(&bytes[..]);
+        assert_eq!(vec.len(), 24);
+        assert!(vec.iter_ones().take(3).eq([0, 5, 7]));
+        assert_eq!(*vec.to_bytes(), bytes);
+
+        let short: BitVec = [true, false, true].into_iter().collect();
+        assert_eq!(*short.to_bytes(), [0b101]);
+    }
+
+    #[test]
+    fn test_bit_set() {
+        let mut a: BitSet = [1, 3, 5, 200].into_iter().collect();
+        let b: BitSet = [3, 4, 5].into_iter().collect();
+
+        assert!(a.insert(7));
+        assert!(!a.insert(7));
+        assert!(a.remove(200));
+        assert!(!a.remove(200));
+        assert_eq!(a, [1, 3, 5, 7].into_iter().collect());
+        assert_eq!(a.len(), 4);
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert!(union.iter().eq([1, 3, 4, 5, 7]));
+
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert!(intersection.iter().eq([3, 5]));
+        assert!(intersection.is_subset(&a));
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert!(difference.iter().eq([1, 7]));
+
+        a.symmetric_difference_with(&b);
+        assert_eq!(format!("{:?}", a), "{1, 4, 7}");
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    fmt::Debug,
+    io,
+    mem::MaybeUninit,
+    ops::{Index, IndexMut},
+    ptr, slice,
+};
+
+use crate::error::{CapacityError, out_of_bounds};
+use crate::traits::MemoryUsage;
+
+/// What [`CircularBuffer::push`] does when the buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Give the new value back to the caller.
+    Reject,
+    /// Drop the oldest value to make room for the new one.
+    Overwrite,
+}
+
+/// Bounded FIFO buffer that stores up to `N` values inline.
+///
+/// Nothing is allocated on the heap, the storage is part of the struct, so
+/// it's meant for things like keeping the last `N` samples of a signal.
+pub struct CircularBuffer<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    head: usize,
+    len: usize,
+    mode: Mode,
+}
+
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+pub struct IntoIter<T, const N: usize>(CircularBuffer<T, N>);
+
+impl<T, const N: usize> CircularBuffer<T, N> {
+    /// Creates an empty buffer that behaves according to `mode` when full.
+    pub const fn new(mode: Mode) -> Self {
+        CircularBuffer {
+            buf: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+            mode,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    fn ptr(&self) -> *const T {
+        self.buf.as_ptr() as *const T
+    }
+
+    fn ptr_mut(&mut self) -> *mut T {
+        self.buf.as_mut_ptr() as *mut T
+    }
+
+    /// Maps the logical index `index` to its slot in `buf`.
+    fn wrap_add(&self, index: usize) -> usize {
+        if index >= N - self.head {
+            index - (N - self.head)
+        } else {
+            self.head + index
+        }
+    }
+
+    /// Appends `value` as the newest element.
+    ///
+    /// When the buffer is full, [`Mode::Reject`] returns `Err(value)` and
+    /// leaves the buffer untouched, while [`Mode::Overwrite`] drops the oldest
+    /// element and always succeeds.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if N == 0 {
+            return Err(value);
+        }
+
+        if self.is_full() {
+            match self.mode {
+                Mode::Reject => return Err(value),
+                Mode::Overwrite => drop(self.pop()),
+            }
+        }
+
+        let slot = self.wrap_add(self.len);
+        unsafe {
+            ptr::write(self.ptr_mut().add(slot), value);
+        }
+
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Appends `value` only if there's room, whatever the [`Mode`], so
+    /// nothing is ever overwritten.
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.push(value).map_err(CapacityError::new)
+    }
+
+    /// Removes and returns the oldest element.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let old_head = self.head;
+        self.head = self.wrap_add(1);
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr().add(old_head))) }
+    }
+
+    /// Removes and returns the newest element.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        let slot = self.wrap_add(self.len);
+
+        unsafe { Some(ptr::read(self.ptr().add(slot))) }
+    }
+
+    /// Returns the element at logical position `index`, `0` being the oldest.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(&*self.ptr().add(self.wrap_add(index))) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = self.wrap_add(index);
+        unsafe { Some(&mut *self.ptr_mut().add(slot)) }
+    }
+
+    /// Returns the oldest element.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the newest element.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the contents oldest first as two slices, the second one being
+    /// the part that wrapped around the end of the storage.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe {
+            if self.head <= N - self.len {
+                (slice::from_raw_parts(self.ptr().add(self.head), self.len), &[])
+            } else {
+                let head_len = N - self.head;
+                (
+                    slice::from_raw_parts(self.ptr().add(self.head), head_len),
+                    slice::from_raw_parts(self.ptr(), self.len - head_len),
+                )
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for CircularBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for CircularBuffer<T, N> {
+    fn default() -> Self {
+        Self::new(Mode::Reject)
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for CircularBuffer<T, N> {
+    fn clone(&self) -> Self {
+        let mut new_buffer = Self::new(self.mode);
+
+        for value in self {
+            // Can't fail, `new_buffer` has the same capacity.
+            let _ = new_buffer.push(value.clone());
+        }
+
+        new_buffer
+    }
+}
+
+impl<T, const N: usize> Index<usize> for CircularBuffer<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).unwrap_or_else(|| out_of_bounds(index, self.len))
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for CircularBuffer<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index).unwrap_or_else(|| out_of_bounds(index, len))
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for CircularBuffer<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for CircularBuffer<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for CircularBuffer<T, N> {}
+
+impl<'a, T, const N: usize> IntoIterator for &'a CircularBuffer<T, N> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T, const N: usize> IntoIterator for CircularBuffer<T, N> {
+    type IntoIter = IntoIter<T, N>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+/// Reads the oldest bytes, so the buffer works as a bounded pipe.
+impl<const N: usize> io::Read for CircularBuffer<u8, N> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (mut front, mut back) = self.as_slices();
+        let mut read = front.read(buf)?;
+        read += back.read(&mut buf[read..])?;
+
+        // Bytes need no dropping, skipping them is enough.
+        self.head = self.wrap_add(read);
+        self.len -= read;
+
+        Ok(read)
+    }
+}
+
+/// Writes as many bytes as [`CircularBuffer::push`] accepts: up to the free
+/// space with [`Mode::Reject`], all of them with [`Mode::Overwrite`].
+impl<const N: usize> io::Write for CircularBuffer<u8, N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+
+        for &byte in buf {
+            if self.push(byte).is_err() {
+                break;
+            }
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> MemoryUsage for CircularBuffer<T, N> {
+    fn heap_bytes(&self) -> usize {
+        // The elements live inline.
+        0
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircularBuffer, Mode};
+
+    #[test]
+    fn test_reject() {
+        let mut buf: CircularBuffer<i32, 3> = CircularBuffer::new(Mode::Reject);
+        assert_eq!(buf.pop(), None);
+
+        assert_eq!(buf.push(1), Ok(()));
+        assert_eq!(buf.push(2), Ok(()));
+        assert_eq!(buf.push(3), Ok(()));
+        assert!(buf.is_full());
+        assert_eq!(buf.push(4), Err(4));
+
+        assert_eq!(buf.pop(), Some(1));
+        assert_eq!(buf.push(4), Ok(()));
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(buf.front(), Some(&2));
+        assert_eq!(buf.back(), Some(&4));
+        assert_eq!(buf.pop_back(), Some(4));
+        assert_eq!(buf.len(), 2);
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let mut buf: CircularBuffer<i32, 4> = CircularBuffer::new(Mode::Overwrite);
+
+        for i in 0..10 {
+            assert_eq!(buf.push(i), Ok(()));
+        }
+
+        assert_eq!(buf.len(), 4);
+        assert_eq!(buf.as_slices(), (&[6, 7][..], &[8, 9][..]));
+        assert_eq!(format!("{:?}", buf), "[6, 7, 8, 9]");
+        assert_eq!(buf.clone(), buf);
+        assert_eq!(buf.into_iter().rev().collect::<Vec<_>>(), [9, 8, 7, 6]);
+    }
+
+    #[test]
+    fn test_try_push() {
+        let mut buf: CircularBuffer<i32, 2> = CircularBuffer::new(Mode::Overwrite);
+        assert!(buf.try_push(1).is_ok());
+        assert!(buf.try_push(2).is_ok());
+
+        // Never overwrites, even in `Mode::Overwrite`.
+        assert_eq!(buf.try_push(3).unwrap_err().into_inner(), 3);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn test_io() {
+        use std::io::{Read, Write};
+
+        let mut pipe: CircularBuffer<u8, 8> = CircularBuffer::new(Mode::Reject);
+        assert_eq!(pipe.write(b"hello world").unwrap(), 8);
+        assert!(pipe.write_all(b"!").is_err());
+
+        let mut word = [0; 6];
+        pipe.read_exact(&mut word).unwrap();
+        assert_eq!(&word, b"hello ");
+
+        // Wraps around the end of the storage.
+        pipe.write_all(b"wor").unwrap();
+        let mut rest = Vec::new();
+        pipe.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"wowor");
+        assert!(pipe.is_empty());
+
+        let mut last: CircularBuffer<u8, 4> = CircularBuffer::new(Mode::Overwrite);
+        write!(last, "{}", 1234567).unwrap();
+        assert_eq!(last.iter().copied().collect::<Vec<_>>(), b"4567");
+    }
+
+    #[test]
+    fn test_index() {
+        let mut buf: CircularBuffer<i32, 3> = CircularBuffer::new(Mode::Overwrite);
+        for i in 0..5 {
+            buf.push(i).unwrap();
+        }
+
+        buf[0] *= 10;
+        assert_eq!((buf[0], buf[1], buf[2]), (20, 3, 4));
+        assert_eq!(buf.get_mut(3), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 out of bounds for length 3")]
+    fn test_index_out_of_bounds() {
+        let mut buf: CircularBuffer<i32, 3> = CircularBuffer::new(Mode::Reject);
+        for i in 0..3 {
+            buf.push(i).unwrap();
+        }
+
+        let _ = buf[3];
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut buf: CircularBuffer<i32, 0> = CircularBuffer::new(Mode::Overwrite);
+        assert_eq!(buf.push(1), Err(1));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_drop() {
+        use std::rc::Rc;
+
+        let elem = Rc::new(0);
+        let mut buf: CircularBuffer<Rc<i32>, 2> = CircularBuffer::new(Mode::Overwrite);
+
+        for _ in 0..5 {
+            buf.push(Rc::clone(&elem)).unwrap();
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 3);
+        drop(buf);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+}
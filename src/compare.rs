@@ -0,0 +1,83 @@
+//! Orderings as values, for the structures that keep their elements sorted.
+//!
+//! [`BTree`](crate::BTree) and [`BinaryHeap`](crate::BinaryHeap) take a
+//! [`Comparator`] as a type parameter defaulting to [`NaturalOrder`], and
+//! [`Algorithm::sort_by_comparator`](crate::sort::Algorithm::sort_by_comparator)
+//! sorts a slice with one. Any `Fn(&T, &T) -> Ordering` is a comparator, the
+//! adapters below cover the usual cases without naming a closure type:
+//!
+//! ```
+//! use ds_rust::compare::{ByKey, Comparator, NaturalOrder, Reverse};
+//! use std::cmp::Ordering;
+//!
+//! assert_eq!(NaturalOrder.compare(&1, &2), Ordering::Less);
+//! assert_eq!(Reverse(NaturalOrder).compare(&1, &2), Ordering::Greater);
+//! assert_eq!(ByKey(|s: &&str| s.len()).compare(&"ab", &"c"), Ordering::Greater);
+//! ```
+
+use std::cmp::Ordering;
+
+/// A total order over `T`.
+pub trait Comparator<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+/// The order of [`Ord`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NaturalOrder;
+
+/// The order of `C` turned around, [`Reverse(NaturalOrder)`](Reverse) makes
+/// a min-heap out of [`BinaryHeap`](crate::BinaryHeap).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reverse<C = NaturalOrder>(pub C);
+
+/// Orders by the [`Ord`] key `F` extracts, like `slice::sort_by_key`.
+#[derive(Debug, Clone, Copy)]
+pub struct ByKey<F>(pub F);
+
+impl<T: Ord + ?Sized> Comparator<T> for NaturalOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T: ?Sized, C: Comparator<T>> Comparator<T> for Reverse<C> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+impl<T: ?Sized, K: Ord, F: Fn(&T) -> K> Comparator<T> for ByKey<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Comparator<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByKey, Comparator, NaturalOrder, Reverse};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_adapters() {
+        assert_eq!(NaturalOrder.compare("a", "b"), Ordering::Less);
+        assert_eq!(Reverse(NaturalOrder).compare(&1, &1), Ordering::Equal);
+        assert_eq!(
+            Reverse(Reverse(NaturalOrder)).compare(&1, &2),
+            Ordering::Less
+        );
+
+        let by_abs = ByKey(|n: &i32| n.abs());
+        assert_eq!(by_abs.compare(&-3, &2), Ordering::Greater);
+        assert_eq!(Reverse(by_abs).compare(&-3, &2), Ordering::Less);
+
+        let closure = |a: &i32, b: &i32| (a % 10).cmp(&(b % 10));
+        assert_eq!(closure.compare(&19, &21), Ordering::Greater);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, hash_map::RandomState},
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+use crate::Vector;
+
+/// Multiset that counts how many times each item was added.
+///
+/// Items with a count of zero are never stored, so [`len`](Counter::len) is
+/// the number of distinct items present. Items are hashed with `S`, see
+/// [`hash`](crate::hash) for the alternatives to `RandomState`.
+pub struct Counter<T, S = RandomState> {
+    counts: HashMap<T, usize, S>,
+    total: usize,
+}
+
+impl<T: Hash + Eq> Counter<T> {
+    /// Creates a new Counter with no items.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Counter<T, S> {
+    /// Creates a new Counter that hashes items with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Counter {
+            counts: HashMap::with_hasher(hash_builder),
+            total: 0,
+        }
+    }
+
+    /// Number of distinct items.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Sum of all the counts.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Adds one `item`, returns its new count.
+    pub fn add(&mut self, item: T) -> usize {
+        self.add_n(item, 1)
+    }
+
+    /// Adds `n` copies of `item`, returns its new count.
+    pub fn add_n(&mut self, item: T, n: usize) -> usize {
+        if n == 0 {
+            return self.count(&item);
+        }
+
+        let count = self.counts.entry(item).or_insert(0);
+        *count += n;
+        self.total += n;
+
+        *count
+    }
+
+    /// Removes one `item`, returns its new count.
+    pub fn remove(&mut self, item: &T) -> usize {
+        self.remove_n(item, 1)
+    }
+
+    /// Removes up to `n` copies of `item`, returns its new count.
+    pub fn remove_n(&mut self, item: &T, n: usize) -> usize {
+        let Some(count) = self.counts.get_mut(item) else {
+            return 0;
+        };
+
+        let removed = n.min(*count);
+        *count -= removed;
+        self.total -= removed;
+
+        if *count == 0 {
+            self.counts.remove(item);
+            return 0;
+        }
+
+        *count
+    }
+
+    /// Number of times `item` was added, `0` if it is not present.
+    pub fn count(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.counts.contains_key(item)
+    }
+
+    /// The `n` items with the highest counts, most common first. Items with
+    /// the same count come in arbitrary order.
+    pub fn most_common(&self, n: usize) -> Vector<(&T, usize)> {
+        let mut items: Vec<(&T, usize)> = self.iter().collect();
+        items.sort_unstable_by_key(|&(_, count)| Reverse(count));
+
+        let mut most_common = Vector::new();
+        for item in items.into_iter().take(n) {
+            most_common.push(item);
+        }
+
+        most_common
+    }
+
+    /// Removes all the items.
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.total = 0;
+    }
+
+    /// Iterates over `(item, count)` in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(item, &count)| (item, count))
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> Default for Counter<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Extend<T> for Counter<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for Counter<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::default();
+        counter.extend(iter);
+
+        counter
+    }
+}
+
+impl<T, S> IntoIterator for Counter<T, S> {
+    type IntoIter = std::collections::hash_map::IntoIter<T, usize>;
+    type Item = (T, usize);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> AddAssign for Counter<T, S> {
+    /// Adds the counts of `other`.
+    fn add_assign(&mut self, other: Self) {
+        for (item, count) in other {
+            self.add_n(item, count);
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Add for Counter<T, S> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self::Output {
+        self += other;
+        self
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> SubAssign for Counter<T, S> {
+    /// Subtracts the counts of `other`, dropping items that reach zero.
+    fn sub_assign(&mut self, other: Self) {
+        for (item, count) in other {
+            self.remove_n(&item, count);
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Sub for Counter<T, S> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self::Output {
+        self -= other;
+        self
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for Counter<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.counts == other.counts
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Eq for Counter<T, S> {}
+
+impl<T: Debug, S> Debug for Counter<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.counts.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Counter;
+
+    #[test]
+    fn test_counts() {
+        let mut counter: Counter<char> = "abracadabra".chars().collect();
+
+        assert_eq!(counter.count(&'a'), 5);
+        assert_eq!(counter.count(&'z'), 0);
+        assert_eq!(counter.len(), 5);
+        assert_eq!(counter.total(), 11);
+
+        {
+            let most_common = counter.most_common(3);
+            assert_eq!(most_common[0], (&'a', 5));
+            assert_eq!(most_common[1].1, 2);
+            assert_eq!(most_common[2].1, 2);
+            assert_eq!(counter.most_common(10).len(), 5);
+        }
+
+        assert_eq!(counter.remove(&'c'), 0);
+        assert!(!counter.contains(&'c'));
+        assert_eq!(counter.remove_n(&'a', 3), 2);
+        assert_eq!(counter.remove_n(&'b', 10), 0);
+        assert_eq!(counter.add_n('z', 0), 0);
+        assert_eq!(counter.total(), 5);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a: Counter<&str> = ["x", "x", "y"].into_iter().collect();
+        let b: Counter<&str> = ["x", "y", "y", "z"].into_iter().collect();
+
+        let sum = a + b;
+        assert_eq!(sum.count(&"x"), 3);
+        assert_eq!(sum.count(&"y"), 3);
+        assert_eq!(sum.total(), 7);
+
+        let difference = sum - ["y", "y", "y", "y", "z"].into_iter().collect();
+        assert_eq!(difference, ["x", "x", "x"].into_iter().collect());
+        assert_eq!(difference.len(), 1);
+    }
+
+    #[test]
+    fn test_hasher() {
+        use crate::hash::{FxBuildHasher, SeededState};
+
+        let mut counter: Counter<&str, FxBuildHasher> = ["x", "y", "x"].into_iter().collect();
+        counter += ["y"].into_iter().collect();
+        assert_eq!((counter.count(&"x"), counter.count(&"y")), (2, 2));
+
+        // Fixed keys, so the order is the same every run.
+        let order = || {
+            let mut counter = Counter::with_hasher(SeededState::new(7, 7));
+            counter.extend(0..20);
+            counter.iter().map(|(item, _)| *item).collect::<Vec<_>>()
+        };
+        assert_eq!(order(), order());
+    }
+}
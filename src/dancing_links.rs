@@ -0,0 +1,378 @@
+use std::fmt::Debug;
+
+/// Root of the column header list.
+const ROOT: usize = 0;
+
+/// Exact cover problem solved with Knuth's Dancing Links.
+///
+/// The 1s of the matrix are nodes of a torus of circular doubly linked
+/// lists: every row is linked left-right and every column up-down through
+/// a header node. Covering a column unlinks it and every row that uses it,
+/// and since unlinked nodes still point at their old neighbours, uncovering
+/// in reverse order relinks them in O(1) per node without any allocation.
+///
+/// Nodes are stored in parallel vectors and linked by index. Index `0` is the
+/// root, `1..=columns` are the column headers and the rest are the 1s.
+///
+/// Primary columns must be covered exactly once, secondary columns at most
+/// once.
+pub struct DancingLinks {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of each node.
+    column: Vec<usize>,
+    /// Row of each node, unused for headers.
+    row: Vec<usize>,
+    /// Nodes in each column, indexed by header.
+    size: Vec<usize>,
+    columns: usize,
+    rows: usize,
+}
+
+/// Iterator over the solutions of a [`DancingLinks`], see
+/// [`DancingLinks::solutions`].
+///
+/// Dropping it before the end restores the matrix.
+pub struct Solutions<'a> {
+    links: &'a mut DancingLinks,
+    /// Row node chosen at every level of the search.
+    chosen: Vec<usize>,
+    /// Whether the search has yielded and has to move past the last choice.
+    resume: bool,
+}
+
+impl DancingLinks {
+    /// Creates a matrix with `columns` primary columns and no rows.
+    pub fn new(columns: usize) -> Self {
+        Self::with_secondary(columns, 0)
+    }
+
+    /// Creates a matrix with `primary` columns that must be covered, followed
+    /// by `secondary` columns that may be left uncovered.
+    pub fn with_secondary(primary: usize, secondary: usize) -> Self {
+        let columns = primary + secondary;
+        let headers = columns + 1;
+
+        let mut links = DancingLinks {
+            left: Vec::with_capacity(headers),
+            right: Vec::with_capacity(headers),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            row: vec![usize::MAX; headers],
+            size: vec![0; headers],
+            columns,
+            rows: 0,
+        };
+
+        // Only the primary headers are linked to the root, secondary ones
+        // are lists of their own and are never picked by the search.
+        for i in 0..headers {
+            if i <= primary {
+                links.left.push(if i == 0 { primary } else { i - 1 });
+                links.right.push(if i == primary { 0 } else { i + 1 });
+            } else {
+                links.left.push(i);
+                links.right.push(i);
+            }
+        }
+
+        links
+    }
+
+    /// Number of columns, primary and secondary.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Number of rows added.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Adds a row with 1s in `columns` and returns its index.
+    /// Panics if a column is out of bounds or repeated.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        let row = self.rows;
+
+        for (i, &col) in columns.iter().enumerate() {
+            assert!(col < self.columns, "Column out of bounds");
+            assert!(!columns[..i].contains(&col), "Repeated column");
+        }
+
+        let first = self.left.len();
+        for (i, &col) in columns.iter().enumerate() {
+            let header = col + 1;
+            let node = first + i;
+
+            // Row list, the last node wraps back to the first.
+            self.left.push(if i == 0 {
+                first + columns.len() - 1
+            } else {
+                node - 1
+            });
+            self.right.push(if i == columns.len() - 1 {
+                first
+            } else {
+                node + 1
+            });
+
+            // Column list, inserted above the header so at the bottom.
+            let last = self.up[header];
+            self.up.push(last);
+            self.down.push(header);
+            self.down[last] = node;
+            self.up[header] = node;
+
+            self.column.push(header);
+            self.row.push(row);
+            self.size[header] += 1;
+        }
+
+        self.rows += 1;
+        row
+    }
+
+    /// Unlinks `header` from the header list and every row using it from
+    /// the other columns.
+    fn cover(&mut self, header: usize) {
+        let (l, r) = (self.left[header], self.right[header]);
+        self.right[l] = r;
+        self.left[r] = l;
+
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = d;
+                self.up[d] = u;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    /// Exact inverse of [`cover`], which has to be the last column covered.
+    ///
+    /// [`cover`]: DancingLinks::cover
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = j;
+                self.up[d] = j;
+                self.size[self.column[j]] += 1;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        let (l, r) = (self.left[header], self.right[header]);
+        self.right[l] = header;
+        self.left[r] = header;
+    }
+
+    /// Covers the other columns of the row of `node`.
+    fn select(&mut self, node: usize) {
+        let mut j = self.right[node];
+        while j != node {
+            self.cover(self.column[j]);
+            j = self.right[j];
+        }
+    }
+
+    /// Inverse of [`select`](DancingLinks::select).
+    fn unselect(&mut self, node: usize) {
+        let mut j = self.left[node];
+        while j != node {
+            self.uncover(self.column[j]);
+            j = self.left[j];
+        }
+    }
+
+    /// Uncovered primary column with the fewest rows, `None` if all of them
+    /// are covered.
+    fn choose_column(&self) -> Option<usize> {
+        let mut best = None;
+        let mut header = self.right[ROOT];
+
+        while header != ROOT {
+            if best.is_none_or(|best| self.size[header] < self.size[best]) {
+                best = Some(header);
+            }
+            header = self.right[header];
+        }
+
+        best
+    }
+
+    /// Lazily searches for every set of rows that covers each primary column
+    /// exactly once and each secondary column at most once.
+    pub fn solutions(&mut self) -> Solutions<'_> {
+        Solutions {
+            links: self,
+            chosen: Vec::new(),
+            resume: false,
+        }
+    }
+
+    /// Returns the first solution found, if any.
+    pub fn solve(&mut self) -> Option<Vec<usize>> {
+        self.solutions().next()
+    }
+}
+
+impl<'a> Solutions<'a> {
+    /// Moves to the next row of the deepest choice, backtracking out of
+    /// exhausted columns. Returns `false` when the search is over.
+    fn advance(&mut self) -> bool {
+        while let Some(node) = self.chosen.pop() {
+            self.links.unselect(node);
+
+            let next = self.links.down[node];
+            let header = self.links.column[node];
+
+            if next != header {
+                self.links.select(next);
+                self.chosen.push(next);
+                return true;
+            }
+
+            self.links.uncover(header);
+        }
+
+        false
+    }
+}
+
+impl<'a> Iterator for Solutions<'a> {
+    /// Indices of the chosen rows, in ascending order.
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.resume && !self.advance() {
+            return None;
+        }
+        self.resume = true;
+
+        loop {
+            let Some(header) = self.links.choose_column() else {
+                let mut rows: Vec<usize> = self.chosen.iter().map(|&n| self.links.row[n]).collect();
+                rows.sort_unstable();
+
+                return Some(rows);
+            };
+
+            self.links.cover(header);
+
+            let first = self.links.down[header];
+            if first == header {
+                self.links.uncover(header);
+                if !self.advance() {
+                    return None;
+                }
+            } else {
+                self.links.select(first);
+                self.chosen.push(first);
+            }
+        }
+    }
+}
+
+impl<'a> Drop for Solutions<'a> {
+    fn drop(&mut self) {
+        while let Some(node) = self.chosen.pop() {
+            self.links.unselect(node);
+            self.links.uncover(self.links.column[node]);
+        }
+    }
+}
+
+impl Debug for DancingLinks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DancingLinks")
+            .field("columns", &self.columns)
+            .field("rows", &self.rows)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DancingLinks;
+
+    #[test]
+    fn test_knuth_example() {
+        let mut links = DancingLinks::new(7);
+        for row in [
+            &[2, 4, 5][..],
+            &[0, 3, 6],
+            &[1, 2, 5],
+            &[0, 3],
+            &[1, 6],
+            &[3, 4, 6],
+        ] {
+            links.add_row(row);
+        }
+
+        let solutions: Vec<_> = links.solutions().collect();
+        assert_eq!(solutions, [vec![0, 3, 4]]);
+        assert_eq!(links.solve(), Some(vec![0, 3, 4]));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let mut links = DancingLinks::new(3);
+        links.add_row(&[0, 1]);
+        links.add_row(&[1, 2]);
+
+        assert_eq!(links.solve(), None);
+
+        // Nothing to cover has the empty solution.
+        assert_eq!(
+            DancingLinks::new(0).solutions().collect::<Vec<_>>(),
+            [vec![]]
+        );
+    }
+
+    fn queens(n: usize) -> DancingLinks {
+        // Ranks and files are primary, diagonals secondary.
+        let mut links = DancingLinks::with_secondary(2 * n, 2 * (2 * n - 1));
+        for r in 0..n {
+            for c in 0..n {
+                let diagonal = 2 * n + r + c;
+                let anti_diagonal = 2 * n + (2 * n - 1) + (r + n - 1 - c);
+                links.add_row(&[r, n + c, diagonal, anti_diagonal]);
+            }
+        }
+
+        links
+    }
+
+    #[test]
+    fn test_queens() {
+        assert_eq!(queens(6).solutions().count(), 4);
+
+        let mut links = queens(8);
+        assert_eq!(links.solutions().count(), 92);
+        assert!(links.solutions().all(|rows| rows.len() == 8));
+    }
+
+    #[test]
+    fn test_drop_restores() {
+        let mut links = queens(6);
+        let mut solutions = links.solutions();
+        let first = solutions.next().unwrap();
+        solutions.next();
+        drop(solutions);
+
+        assert_eq!(links.solve(), Some(first));
+        assert_eq!(links.solutions().count(), 4);
+    }
+}
@@ -1,4 +1,21 @@
-use std::{fmt::Debug, hash::Hash, marker::PhantomData, ptr::NonNull};
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    mem,
+    ops::{Index, IndexMut},
+};
+
+use crate::alloc::{Allocator, Global};
+use crate::error::out_of_bounds;
+use crate::extract::{Drain, ExtractIf, Sealed, Source};
+use crate::frozen::Frozen;
+use crate::guard::Guard;
+use crate::raw::{self, DoublyLinked, NodePtr};
+use crate::sort::{self, ChainNode};
+use crate::stats::ListStats;
+use crate::traits::MemoryUsage;
 
 struct Node<T> {
     next: Link<T>,
@@ -6,55 +23,131 @@ struct Node<T> {
     elem: T,
 }
 
-type Link<T> = Option<NonNull<Node<T>>>;
+type Link<T> = Option<NodePtr<Node<T>>>;
+/// First and last node of a chain, and its length.
+type Chain<T> = (NodePtr<Node<T>>, NodePtr<Node<T>>, usize);
 
-pub struct DequeueList<T> {
+impl<T> DoublyLinked for Node<T> {
+    fn prev_mut(&mut self) -> &mut Link<T> {
+        &mut self.prev
+    }
+
+    fn next_mut(&mut self) -> &mut Link<T> {
+        &mut self.next
+    }
+}
+
+impl<T> ChainNode for Node<T> {
+    type Elem = T;
+
+    fn elem(&self) -> &T {
+        &self.elem
+    }
+
+    fn next_link(&mut self) -> &mut Link<T> {
+        &mut self.next
+    }
+}
+
+pub struct DequeueList<T, A: Allocator = Global> {
     head: Link<T>,
     tail: Link<T>,
     len: usize,
+    alloc: A,
+    stats: ListStats,
     marker: PhantomData<T>
 }
 
+unsafe impl<T: Send, A: Allocator + Send> Send for DequeueList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for DequeueList<T, A> {}
+
 pub struct Iter<'a, T> {
     head: Link<T>,
     tail: Link<T>,
     len: usize,
-    marker: PhantomData<&'a mut T>
+    marker: PhantomData<&'a T>,
 }
 
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+/// Iterator over mutable references to the elements of a [`DequeueList`].
+///
+/// Like `&'a mut T` it's invariant in `T`, otherwise a short-lived value
+/// could be written into a list of longer-lived ones:
+///
+/// ```compile_fail
+/// use ds_rust::dequeue::IterMut;
+///
+/// fn shorten<'a, 'b: 'a>(iter: IterMut<'a, &'b str>) -> IterMut<'a, &'a str> {
+///     iter
+/// }
+/// ```
 pub struct IterMut<'a, T> {
     head: Link<T>,
     tail: Link<T>,
     len: usize,
-    marker: PhantomData<&'a T>,
+    marker: PhantomData<&'a mut T>,
 }
 
-pub struct IntoIter<T>(DequeueList<T>);
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+pub struct IntoIter<T, A: Allocator = Global>(DequeueList<T, A>);
 
-pub struct CursorMut<'a, T> {
+pub struct CursorMut<'a, T, A: Allocator = Global> {
     current: Link<T>,
-    list: &'a mut DequeueList<T>,
+    list: &'a mut DequeueList<T, A>,
     index: Option<usize>,
 }
 
-impl<T> Node<T> {
-    fn new(next: Link<T>, prev: Link<T>, elem: T) -> NonNull<Node<T>> {
-        unsafe {
-            NonNull::new_unchecked(Box::into_raw(Box::new(Node { next, prev, elem })))
-        }
-    }
+unsafe impl<'a, T: Send, A: Allocator + Send> Send for CursorMut<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: Allocator + Sync> Sync for CursorMut<'a, T, A> {}
+
+/// Read-only counterpart of [`CursorMut`].
+pub struct Cursor<'a, T, A: Allocator = Global> {
+    current: Link<T>,
+    list: &'a DequeueList<T, A>,
+    index: Option<usize>,
 }
 
+unsafe impl<'a, T: Sync, A: Allocator + Sync> Send for Cursor<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: Allocator + Sync> Sync for Cursor<'a, T, A> {}
+
 impl<T> DequeueList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> DequeueList<T, A> {
+    /// Creates an empty list whose nodes are allocated from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
         DequeueList {
             head: None,
             tail: None,
             len: 0,
+            alloc,
+            stats: ListStats::default(),
             marker: PhantomData,
         }
     }
 
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Counters of the nodes allocated so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> ListStats {
+        self.stats
+    }
+
+    fn new_node(&mut self, elem: T) -> NodePtr<Node<T>> {
+        self.stats.record_node_allocation();
+        NodePtr::alloc(&self.alloc, Node { next: None, prev: None, elem })
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
@@ -63,97 +156,194 @@ impl<T> DequeueList<T> {
         self.len == 0
     }
 
-    pub fn clear(&mut self) -> () {
+    pub fn clear(&mut self) {
         while self.pop_front().is_some() {}
     }
 
     pub fn push_front(&mut self, elem: T) {
-        unsafe {
-            let new_node = Node::new(None, None, elem);
+        let new_node = self.new_node(elem);
+        let next = self.head;
 
-            if let Some(old_head) = self.head {
-                (*old_head.as_ptr()).prev = Some(new_node);
-                (*new_node.as_ptr()).next = Some(old_head);
-            } else {
-                self.tail = Some(new_node);
-            }
-
-            self.head = Some(new_node);
-            self.len += 1;
-        }
+        unsafe { raw::link(&mut self.head, &mut self.tail, None, next, new_node) }
+        self.len += 1;
     }
 
     pub fn push_back(&mut self, elem: T) {
-        unsafe {
-            let new_node = Node::new(None, None, elem);
+        let new_node = self.new_node(elem);
+        let prev = self.tail;
 
-            if let Some(old_tail) = self.tail {
-                (*old_tail.as_ptr()).next = Some(new_node);
-                (*new_node.as_ptr()).prev = Some(old_tail); 
-            } else {
-                self.head = Some(new_node);
-            }
-
-            self.tail = Some(new_node);
-            self.len += 1;
-        }
+        unsafe { raw::link(&mut self.head, &mut self.tail, prev, None, new_node) }
+        self.len += 1;
     }
 
-    pub fn pop_front(&mut self) -> Option<T> {
-        self.head.map(|node| unsafe {
-            let current_head = Box::from_raw(node.as_ptr());
-            let elem = current_head.elem;
+    /// Takes the nodes of `other` as a chain to link into this list, with
+    /// its length. If the allocators can't free each other's nodes the
+    /// values are moved into new nodes instead.
+    fn adopt_chain(&mut self, other: &mut Self) -> Option<Chain<T>> {
+        if self.alloc.is_same(&other.alloc) {
+            let (first, last) = (other.head.take()?, other.tail.take()?);
+            return Some((first, last, mem::take(&mut other.len)));
+        }
 
-            self.head = current_head.next;
+        let (mut first, mut last) = (None, None);
+        let len = other.len;
+        while let Some(elem) = other.pop_front() {
+            let node = self.new_node(elem);
+            let prev = last;
+            unsafe { raw::link(&mut first, &mut last, prev, None, node) }
+        }
 
-            if let Some(new_head) = self.head {
-                (*new_head.as_ptr()).prev = None
-            } else {
-                self.tail = None
-            }
+        Some((first?, last?, len))
+    }
 
+    /// Unlinks `node` from the list and frees it.
+    ///
+    /// # Safety
+    ///
+    /// `node` has to belong to this list.
+    unsafe fn remove_node(&mut self, node: NodePtr<Node<T>>) -> T {
+        unsafe {
+            raw::unlink(&mut self.head, &mut self.tail, node);
             self.len -= 1;
 
-            elem
-        }) 
+            node.dealloc(&self.alloc).elem
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|node| unsafe { self.remove_node(node) })
     }
 
     pub fn pop_back(&mut self) -> Option<T> {
-        self.tail.map(|node| unsafe {
-            let current_tail = Box::from_raw(node.as_ptr());
-            let elem = current_tail.elem;
+        self.tail.map(|node| unsafe { self.remove_node(node) })
+    }
 
-            self.tail = current_tail.prev;
+    /// Moves all the elements of `other` to the back, leaving it empty.
+    ///
+    /// Relinks the nodes in O(1) if both lists use the same allocator, see
+    /// [`Allocator::is_same`]. Otherwise every value is moved into a new node.
+    pub fn append(&mut self, other: &mut Self) {
+        let Some((first, last, len)) = self.adopt_chain(other) else {
+            return;
+        };
+
+        let prev = self.tail;
+        unsafe { raw::link_chain(&mut self.head, &mut self.tail, prev, None, first, last) }
+        self.len += len;
+    }
+
+    /// Splits the list in two at `at`, returning the elements from `at` on.
+    /// Finds the split walking from the nearer end, like [`get`](Self::get),
+    /// and gives the new list a clone of the allocator.
+    ///
+    /// # Panics
+    ///
+    /// If `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        A: Clone,
+    {
+        if at > self.len {
+            out_of_bounds(at, self.len);
+        }
 
-            if let Some(new_tail) = self.tail {
-                (*new_tail.as_ptr()).next = None;
-            } else {
+        let mut output = DequeueList::new_in(self.alloc.clone());
+        let Some(node) = self.node_at(at) else {
+            return output;
+        };
+
+        unsafe {
+            let prev = raw::cut_before(node);
+            if prev.is_none() {
                 self.head = None;
             }
 
-            self.len -= 1;
+            output.head = Some(node);
+            output.tail = mem::replace(&mut self.tail, prev);
+            output.len = self.len - at;
+            self.len = at;
+        }
 
-            elem
-        })
+        output
     }
 
     pub fn front(&self) -> Option<&T> {
-        unsafe { Some(&(*self.head?.as_ptr()).elem) }
+        unsafe { Some(&self.head?.as_ref().elem) }
     }
 
     pub fn front_mut(&mut self) -> Option<&mut T> {
-        unsafe { Some(&mut (*self.head?.as_ptr()).elem) }
+        unsafe { Some(&mut self.head?.as_mut().elem) }
     }
 
     pub fn back(&self) -> Option<&T> {
-        unsafe { Some(&(*self.tail?.as_ptr()).elem) }
+        unsafe { Some(&self.tail?.as_ref().elem) }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { Some(&mut self.tail?.as_mut().elem) }
+    }
+
+    /// Node at `index`, walking from whichever end is nearer.
+    fn node_at(&self, index: usize) -> Link<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.head?;
+                for _ in 0..index {
+                    node = node.as_ref().next?;
+                }
+                Some(node)
+            } else {
+                let mut node = self.tail?;
+                for _ in index + 1..self.len {
+                    node = node.as_ref().prev?;
+                }
+                Some(node)
+            }
+        }
+    }
+
+    /// Element at `index`, in `O(min(index, len - index))`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        unsafe { Some(&self.node_at(index)?.as_ref().elem) }
     }
 
-    pub fn back_mut(&self) -> Option<&mut T> {
-        unsafe { Some(&mut (*self.tail?.as_ptr()).elem) }
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        unsafe { Some(&mut self.node_at(index)?.as_mut().elem) }
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    /// Inserts `elem` at `index`, shifting the elements after it back.
+    ///
+    /// # Panics
+    ///
+    /// If `index > len`.
+    pub fn insert(&mut self, index: usize, elem: T) {
+        if index > self.len {
+            out_of_bounds(index, self.len);
+        }
+
+        let Some(next) = self.node_at(index) else {
+            return self.push_back(elem);
+        };
+
+        let new_node = self.new_node(elem);
+        unsafe {
+            let prev = next.as_ref().prev;
+            raw::link(&mut self.head, &mut self.tail, prev, Some(next), new_node);
+        }
+        self.len += 1;
+    }
+
+    /// Removes the element at `index`, or returns `None` if out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let node = self.node_at(index)?;
+        unsafe { Some(self.remove_node(node)) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             head: self.head,
             tail: self.tail,
@@ -162,7 +352,7 @@ impl<T> DequeueList<T> {
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             head: self.head,
             tail: self.tail,
@@ -171,26 +361,168 @@ impl<T> DequeueList<T> {
         }
     }
 
-    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, A> {
         CursorMut { 
             current: None, 
             list: self, 
             index: None 
         }
     }
+
+    /// Cursor on the first element, or on the ghost if the list is empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, A> {
+        let index = self.head.map(|_| 0);
+        CursorMut { current: self.head, list: self, index }
+    }
+
+    /// Cursor on the last element, or on the ghost if the list is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, A> {
+        let index = self.len.checked_sub(1);
+        CursorMut { current: self.tail, list: self, index }
+    }
+
+    /// Read-only cursor on the first element, or on the ghost if the list is
+    /// empty.
+    pub fn cursor_front(&self) -> Cursor<'_, T, A> {
+        Cursor { current: self.head, list: self, index: self.head.map(|_| 0) }
+    }
+
+    /// Read-only cursor on the last element, or on the ghost if the list is
+    /// empty.
+    pub fn cursor_back(&self) -> Cursor<'_, T, A> {
+        Cursor { current: self.tail, list: self, index: self.len.checked_sub(1) }
+    }
+
+    /// Keeps only the elements for which `keep` returns `true`, freeing the
+    /// other nodes as it goes.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        self.retain_mut(|elem| keep(elem));
+    }
+
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut keep: F) {
+        self.extract_if(|elem| !keep(elem)).for_each(drop);
+    }
+
+    /// Removes and yields the elements for which `pred` returns `true`, as
+    /// the iterator is advanced. See [`extract`](crate::extract) for what
+    /// happens when it's dropped early or something panics.
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> ExtractIf<ExtractSource<'_, T, A>, F> {
+        ExtractIf::new(self.extract_source(), pred)
+    }
+
+    /// Removes and yields every element front to back, the list is empty
+    /// once the iterator is dropped.
+    pub fn drain(&mut self) -> Drain<ExtractSource<'_, T, A>> {
+        Drain::new(self.extract_source())
+    }
+
+    /// Turns the list into a read-only [`Frozen`] snapshot that can be
+    /// cloned and shared between threads without copying the nodes.
+    pub fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self)
+    }
+
+    fn extract_source(&mut self) -> ExtractSource<'_, T, A> {
+        ExtractSource {
+            current: self.head,
+            remaining: self.len,
+            list: self,
+        }
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Stable merge sort that relinks the nodes instead of moving elements.
+    /// If `compare` panics the list keeps all its elements, in no particular
+    /// order.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let len = self.len;
+
+        // Sorting only follows `next`, the back links are rebuilt when it's
+        // done or unwinding.
+        let mut list = Guard::new(self, |list| unsafe { list.relink_back() });
+        unsafe { sort::merge_sort_chain(&mut list.head, len, &mut compare) }
+    }
+
+    /// Rebuilds the `prev` links and the tail from the `next` links.
+    ///
+    /// # Safety
+    ///
+    /// `head` has to start a chain of `len` live nodes.
+    unsafe fn relink_back(&mut self) {
+        let mut prev = None;
+        let mut node = self.head;
+
+        while let Some(current) = node {
+            unsafe {
+                current.as_mut().prev = prev;
+                node = current.as_ref().next;
+            }
+            prev = Some(current);
+        }
+
+        self.tail = prev;
+    }
+}
+
+
+/// [`Source`] of [`DequeueList::extract_if`] and [`DequeueList::drain`].
+/// Nodes are unlinked one at a time, so the list is whole in between.
+pub struct ExtractSource<'a, T, A: Allocator = Global> {
+    list: &'a mut DequeueList<T, A>,
+    current: Link<T>,
+    remaining: usize,
 }
 
+impl<T, A: Allocator> Sealed for ExtractSource<'_, T, A> {}
+
+impl<T, A: Allocator> Source for ExtractSource<'_, T, A> {
+    type Item = T;
+
+    fn current(&mut self) -> Option<&mut T> {
+        unsafe { Some(&mut self.current?.as_mut().elem) }
+    }
+
+    fn keep(&mut self) {
+        if let Some(node) = self.current {
+            self.current = unsafe { node.as_ref().next };
+            self.remaining -= 1;
+        }
+    }
+
+    fn remove(&mut self) -> T {
+        let node = self.current.expect("no current element");
+        self.current = unsafe { node.as_ref().next };
+        self.remaining -= 1;
+
+        unsafe { self.list.remove_node(node) }
+    }
+
+    fn finish(&mut self) {}
+
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
 
-impl<T> Drop for DequeueList<T> {
+impl<T, A: Allocator> Drop for DequeueList<T, A> {
     
     /// See [`DequeueList::clear`] for a different implementation of this loop.
     fn drop(&mut self) {
         // Pop elements until we have to stop.
-        while let Some(_) = self.pop_front() { }
+        while self.pop_front().is_some() { }
     }
 }
 
-impl<'a, T> IntoIterator for &'a DequeueList<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a DequeueList<T, A> {
     type IntoIter = Iter<'a, T>;
     type Item = &'a T;
 
@@ -209,8 +541,9 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
         self.head.map(|node| unsafe {
             self.len -= 1;
-            self.head = (*node.as_ptr()).next;
-            &(*node.as_ptr()).elem
+            let node = node.as_ref();
+            self.head = node.next;
+            &node.elem
         })
     }
 
@@ -227,8 +560,9 @@ impl <'a, T> DoubleEndedIterator for Iter<'a, T> {
 
         self.tail.map(|node| unsafe {
             self.len -= 1;
-            self.tail = (*node.as_ptr()).prev;
-            &(*node.as_ptr()).elem
+            let node = node.as_ref();
+            self.tail = node.prev;
+            &node.elem
         })
     }
 }
@@ -239,7 +573,7 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut DequeueList<T> {
+impl<'a, T, A: Allocator> IntoIterator for &'a mut DequeueList<T, A> {
     type IntoIter = IterMut<'a, T>;
     type Item = &'a mut T;
 
@@ -258,8 +592,9 @@ impl<'a, T> Iterator for IterMut<'a, T> {
 
         self.head.map(|node| unsafe {
             self.len -= 1;
-            self.head = (*node.as_ptr()).next;
-            &mut (*node.as_ptr()).elem
+            let node = node.as_mut();
+            self.head = node.next;
+            &mut node.elem
         })
     }
 
@@ -276,8 +611,9 @@ impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
 
         self.tail.map(|node| unsafe {
             self.len -= 1;
-            self.tail = (*node.as_ptr()).prev;
-            &mut (*node.as_ptr()).elem
+            let node = node.as_mut();
+            self.tail = node.prev;
+            &mut node.elem
         })
     }
 }
@@ -288,8 +624,8 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
-impl<T> IntoIterator for DequeueList<T> {
-    type IntoIter = IntoIter<T>;
+impl<T, A: Allocator> IntoIterator for DequeueList<T, A> {
+    type IntoIter = IntoIter<T, A>;
     type Item = T;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -297,7 +633,7 @@ impl<T> IntoIterator for DequeueList<T> {
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -309,13 +645,13 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.0.pop_back()
     }
 }
 
-impl<T> ExactSizeIterator for IntoIter<T> {
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {
     fn len(&self) -> usize {
         self.0.len
     }
@@ -327,9 +663,9 @@ impl<T> Default for DequeueList<T> {
     }
 }
 
-impl<T: Clone> Clone for DequeueList<T> {
+impl<T: Clone, A: Allocator + Clone> Clone for DequeueList<T, A> {
     fn clone(&self) -> Self {
-        let mut new_dequeue = Self::new();
+        let mut new_dequeue = Self::new_in(self.alloc.clone());
 
         for value in self {
             new_dequeue.push_back(value.clone())
@@ -339,7 +675,7 @@ impl<T: Clone> Clone for DequeueList<T> {
     }
 }
 
-impl<T> Extend<T> for DequeueList<T> {
+impl<T, A: Allocator> Extend<T> for DequeueList<T, A> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
             self.push_back(item);
@@ -356,37 +692,48 @@ impl<T> FromIterator<T> for DequeueList<T> {
     }
 }
 
-impl<T: Debug> Debug for DequeueList<T> {
+impl<T, A: Allocator> Index<usize> for DequeueList<T, A> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).unwrap_or_else(|| out_of_bounds(index, self.len))
+    }
+}
+
+impl<T, A: Allocator> IndexMut<usize> for DequeueList<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index).unwrap_or_else(|| out_of_bounds(index, len))
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for DequeueList<T, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for DequeueList<T> {
+impl<T: PartialEq, A: Allocator> PartialEq for DequeueList<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.len() != other.len() || self.iter().ne(other)
-    }
 }
 
-impl<T: Eq> Eq for DequeueList<T> { }
+impl<T: Eq, A: Allocator> Eq for DequeueList<T, A> { }
 
-impl<T: PartialOrd> PartialOrd for DequeueList<T> {
+impl<T: PartialOrd, A: Allocator> PartialOrd for DequeueList<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         self.iter().partial_cmp(other)
     }
 }
  
-impl<T: Ord> Ord for DequeueList<T> {
+impl<T: Ord, A: Allocator> Ord for DequeueList<T, A> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.iter().cmp(other)
     }
 }
 
-impl<T: Hash> Hash for DequeueList<T> {
+impl<T: Hash, A: Allocator> Hash for DequeueList<T, A> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -410,7 +757,7 @@ impl<T: Hash> Hash for DequeueList<T> {
 // Calling prev again will yield the tail.
 
 
-impl<'a, T> CursorMut<'a, T> {
+impl<'a, T, A: Allocator> Cursor<'a, T, A> {
     pub fn index(&self) -> Option<usize> {
         self.index
     }
@@ -418,7 +765,75 @@ impl<'a, T> CursorMut<'a, T> {
     pub fn move_next(&mut self) {
         if let Some(current) = self.current {
             unsafe {
-                self.current = (*current.as_ptr()).next;
+                self.current = current.as_ref().next;
+                if self.current.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.current = self.list.head;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(current) = self.current {
+            unsafe {
+                self.current = current.as_ref().prev;
+                if self.current.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.current = self.list.tail;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.current.map(|node| &node.as_ref().elem) }
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let next = match self.current {
+                Some(current) => current.as_ref().next,
+                None => self.list.head,
+            };
+            next.map(|node| &node.as_ref().elem)
+        }
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let prev = match self.current {
+                Some(current) => current.as_ref().prev,
+                None => self.list.tail,
+            };
+            prev.map(|node| &node.as_ref().elem)
+        }
+    }
+}
+
+impl<T, A: Allocator> Clone for Cursor<'_, T, A> {
+    fn clone(&self) -> Self {
+        Cursor { current: self.current, list: self.list, index: self.index }
+    }
+}
+
+impl<'a, T, A: Allocator> CursorMut<'a, T, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(current) = self.current {
+            unsafe {
+                self.current = current.as_ref().next;
                 if self.current.is_some() {
                     *self.index.as_mut().unwrap() += 1;
                 } else {
@@ -436,7 +851,7 @@ impl<'a, T> CursorMut<'a, T> {
     pub fn move_prev(&mut self) {
         if let Some(current) = self.current {
             unsafe {
-                self.current = (*current.as_ptr()).prev;
+                self.current = current.as_ref().prev;
                 if self.current.is_some() {
                     *self.index.as_mut().unwrap() -= 1;
                 } else {
@@ -452,36 +867,127 @@ impl<'a, T> CursorMut<'a, T> {
     }
 
     pub fn current(&mut self) -> Option<&mut T> {
-        unsafe { self.current.map(|node| &mut (*node.as_ptr()).elem) }
+        unsafe { self.current.map(|node| &mut node.as_mut().elem) }
     }
 
     pub fn peek_next(&mut self) -> Option<&mut T> {
         unsafe {
             let next = if let Some(current) = self.current {
-                (*current.as_ptr()).next
+                current.as_ref().next
             } else {
                 self.list.head
             };
 
-            next.map(|node| &mut (*node.as_ptr()).elem)
+            next.map(|node| &mut node.as_mut().elem)
         }
     }
 
     pub fn peek_prev(&mut self) -> Option<&mut T> {
         unsafe {
             let prev = if let Some(current) = self.current {
-                (*current.as_ptr()).prev
+                current.as_ref().prev
             } else {
                 self.list.tail
             };
 
-            prev.map(|node| &mut (*node.as_ptr()).elem)
+            prev.map(|node| &mut node.as_mut().elem)
+        }
+    }
+
+    pub fn splice_before(&mut self, mut input: DequeueList<T, A>) {
+        let Some((input_head, input_tail, input_len)) = self.list.adopt_chain(&mut input) else {
+            return;
+        };
+
+        unsafe {
+            // The ghost sits between the tail and the head.
+            let (prev, next) = match self.current {
+                Some(current) => (current.as_ref().prev, Some(current)),
+                None => (self.list.tail, None),
+            };
+
+            raw::link_chain(&mut self.list.head, &mut self.list.tail, prev, next, input_head, input_tail);
+        }
+
+        self.list.len += input_len;
+    }
+
+    pub fn splice_after(&mut self, mut input: DequeueList<T, A>) {
+        let Some((input_head, input_tail, input_len)) = self.list.adopt_chain(&mut input) else {
+            return;
+        };
+
+        unsafe {
+            let (prev, next) = match self.current {
+                Some(current) => (Some(current), current.as_ref().next),
+                None => (None, self.list.head),
+            };
+
+            raw::link_chain(&mut self.list.head, &mut self.list.tail, prev, next, input_head, input_tail);
+        }
+
+        self.list.len += input_len;
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let current = self.current?;
+
+        unsafe {
+            self.current = current.as_ref().next;
+            if self.current.is_none() {
+                self.index = None;
+            }
+
+            Some(self.list.remove_node(current))
         }
     }
 
-    pub fn split_before(&mut self) -> DequeueList<T> {
+    /// Pushes to the front of the list, the cursor stays on its element.
+    pub fn push_front(&mut self, elem: T) {
+        self.list.push_front(elem);
+        if let Some(index) = &mut self.index {
+            *index += 1;
+        }
+    }
+
+    /// Pushes to the back of the list, the cursor stays on its element.
+    pub fn push_back(&mut self, elem: T) {
+        self.list.push_back(elem);
+    }
+
+    /// Pops the front of the list. If the cursor is on it, it moves to the
+    /// next element like with [`remove_current`](Self::remove_current).
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.list.head?;
+
+        if self.current == Some(head) {
+            self.current = unsafe { head.as_ref().next };
+            self.index = self.current.map(|_| 0);
+        } else if let Some(index) = &mut self.index {
+            *index -= 1;
+        }
+
+        self.list.pop_front()
+    }
+
+    /// Pops the back of the list. If the cursor is on it, it moves to the
+    /// ghost like with [`remove_current`](Self::remove_current).
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.current.is_some() && self.current == self.list.tail {
+            self.current = None;
+            self.index = None;
+        }
+
+        self.list.pop_back()
+    }
+}
+
+/// Splitting needs a copy of the allocator for the new list.
+impl<'a, T, A: Allocator + Clone> CursorMut<'a, T, A> {
+    pub fn split_before(&mut self) -> DequeueList<T, A> {
         if self.current.is_none() {
-            return std::mem::replace(self.list, DequeueList::new());
+            let empty = DequeueList::new_in(self.list.alloc.clone());
+            return std::mem::replace(self.list, empty);
         }
 
         unsafe {
@@ -489,7 +995,6 @@ impl<'a, T> CursorMut<'a, T> {
 
             let old_len = self.list.len;
             let old_idx = self.index.unwrap();
-            let prev = (*current.as_ptr()).prev;
 
             let new_len = old_len - old_idx;
             let new_head = self.current;
@@ -498,12 +1003,7 @@ impl<'a, T> CursorMut<'a, T> {
 
             let output_len = old_len - new_len;
             let output_head = self.list.head;
-            let output_tail = prev;
-
-            if let Some(prev) = prev {
-                (*current.as_ptr()).prev = None;
-                (*prev.as_ptr()).next = None;
-            }
+            let output_tail = raw::cut_before(current);
 
             self.list.len = new_len;
             self.list.head = new_head;
@@ -514,14 +1014,17 @@ impl<'a, T> CursorMut<'a, T> {
                 head: output_head,
                 tail: output_tail,
                 len: output_len,
+                alloc: self.list.alloc.clone(),
+                stats: ListStats::default(),
                 marker: PhantomData,
             }
         }
     }
 
-    pub fn split_after(&mut self) -> DequeueList<T> {
+    pub fn split_after(&mut self) -> DequeueList<T, A> {
         if self.current.is_none() {
-            return std::mem::replace(self.list, DequeueList::new());
+            let empty = DequeueList::new_in(self.list.alloc.clone());
+            return std::mem::replace(self.list, empty);
         }
 
         unsafe {
@@ -529,7 +1032,7 @@ impl<'a, T> CursorMut<'a, T> {
 
             let old_len = self.list.len;
             let old_idx = self.index.unwrap();
-            let next = (*current.as_ptr()).next;
+            let next = current.as_ref().next;
 
             let new_len = old_idx + 1;
             let new_head = self.list.head;
@@ -541,8 +1044,7 @@ impl<'a, T> CursorMut<'a, T> {
             let output_tail = self.list.tail;
 
             if let Some(next) = next {
-                (*current.as_ptr()).next = None;
-                (*next.as_ptr()).prev = None;
+                raw::cut_before(next);
             }
 
             self.list.len = new_len;
@@ -554,124 +1056,101 @@ impl<'a, T> CursorMut<'a, T> {
                 tail: output_tail,
                 head: output_head,
                 len: output_len,
+                alloc: self.list.alloc.clone(),
+                stats: ListStats::default(),
                 marker: PhantomData,
             }
         }
     }
+}
 
-    pub fn splice_before(&mut self, mut input: DequeueList<T>) {
-        if input.is_empty() {
-            return;
-        }
-
-        if self.list.is_empty() {
-            *self.list = input;
-            return;
-        }
+impl<T, A: Allocator> MemoryUsage for DequeueList<T, A> {
+    fn heap_bytes(&self) -> usize {
+        self.len * mem::size_of::<Node<T>>()
+    }
 
-        let input_head = input.head.take().unwrap();
-        let input_tail = input.tail.take().unwrap();
+    fn node_count(&self) -> usize {
+        self.len
+    }
+}
 
-        unsafe {
-            if let Some(current) = self.current {
-                if let Some(prev) = (*current.as_ptr()).prev {
-                    (*prev.as_ptr()).next = Some(input_head);
-                    (*input_head.as_ptr()).prev = Some(prev);
-                    (*current.as_ptr()).prev = Some(input_tail);
-                    (*input_tail.as_ptr()).next = Some(current);
-                } else{
-                    (*current.as_ptr()).prev = Some(input_tail);
-                    (*input_tail.as_ptr()).next = Some(current);
-                    self.list.head = Some(input_head);
-                }
-            } else {
-                (*self.list.tail.unwrap().as_ptr()).next = Some(input_head);
-                (*input_head.as_ptr()).prev = self.list.tail;
-                self.list.tail = Some(input_tail);
-            }
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: Allocator> serde::Serialize for DequeueList<T, A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
 
-            self.list.len += input.len;
-            input.len = 0;
-        }
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for DequeueList<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = crate::serde_impls::SeqVisitor::new(DequeueList::new(), DequeueList::push_back);
+        deserializer.deserialize_seq(visitor)
     }
+}
 
-    pub fn splice_after(&mut self, mut input: DequeueList<T>) {
-        if input.is_empty() {
-            return;
-        }
+// The links can't be split without walking them, so the parallel iterators
+// collect the elements first and let rayon split the collected buffer.
 
-        if self.list.is_empty() {
-            *self.list = input;
-            return;
-        }
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a, A: Allocator> rayon::iter::IntoParallelIterator for &'a DequeueList<T, A> {
+    type Item = &'a T;
+    type Iter = rayon::vec::IntoIter<&'a T>;
 
-        let input_head = input.head.take().unwrap();
-        let input_tail = input.tail.take().unwrap();
+    fn into_par_iter(self) -> Self::Iter {
+        self.iter().collect::<Vec<_>>().into_par_iter()
+    }
+}
 
-        unsafe {
-            if let Some(current) = self.current {
-                if let Some(next) = (*current.as_ptr()).next {
-                    (*next.as_ptr()).prev = Some(input_tail);
-                    (*input_tail.as_ptr()).next = Some(next);
-                    (*current.as_ptr()).next = Some(input_head);
-                    (*input_head.as_ptr()).prev = Some(current);
-                } else {
-                    (*current.as_ptr()).next = Some(input_head);
-                    (*input_head.as_ptr()).prev = Some(current);
-                    self.list.tail = Some(input_tail);
-                }
-            } else {
-                (*self.list.head.unwrap().as_ptr()).prev = Some(input_tail);
-                (*input_tail.as_ptr()).next = self.list.head;
-                self.list.head = Some(input_head);
-            }
+#[cfg(feature = "rayon")]
+impl<'a, T: Send + 'a, A: Allocator> rayon::iter::IntoParallelIterator for &'a mut DequeueList<T, A> {
+    type Item = &'a mut T;
+    type Iter = rayon::vec::IntoIter<&'a mut T>;
 
-            self.list.len += input.len;
-            input.len = 0;
-        }
+    fn into_par_iter(self) -> Self::Iter {
+        self.iter_mut().collect::<Vec<_>>().into_par_iter()
     }
+}
 
-    pub fn remove_current(&mut self) -> Option<T> {
-        if self.list.is_empty() {
-            return None;
-        }
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator> rayon::iter::IntoParallelIterator for DequeueList<T, A> {
+    type Item = T;
+    type Iter = crate::vec::ParIntoIter<T>;
 
-        if self.current.is_none() {
-            return None;
+    fn into_par_iter(self) -> Self::Iter {
+        let mut values = crate::Vector::new();
+        for value in self {
+            values.push(value);
         }
 
-        unsafe {
-            let mut current = Box::from_raw(self.current.unwrap().as_ptr());
+        values.into_par_iter()
+    }
+}
 
-            let value = current.elem;
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for DequeueList<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.try_fold(DequeueList::new(), |mut list, elem| {
+            list.push_back(elem?);
+            Ok(list)
+        })
+    }
 
-            if let Some(next) = current.next {
-                if let Some(prev) = current.prev {
-                    (*prev.as_ptr()).next = Some(next);
-                    (*next.as_ptr()).prev = Some(prev);
-                } else {
-                    self.list.head = Some(next);
-                    (*next.as_ptr()).prev = None;
-                }
-                self.current = Some(next);
-            } else {
-                if let Some(prev) = current.prev {
-                    self.list.tail = Some(prev);
-                    (*prev.as_ptr()).next = None;
-                } else {
-                    self.list.tail = None;
-                    self.list.head = None;
-                }
-                self.current = None;
-            }
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.try_fold(DequeueList::new(), |mut list, elem| {
+            list.push_back(elem?);
+            Ok(list)
+        })
+    }
+}
 
-            current.next = None;
-            current.prev = None;
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for DequeueList<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = crate::strategy::DequeueListStrategy<T::Strategy>;
 
-            self.list.len -= 1;
-            
-            Some(value)
-        }
+    fn arbitrary_with((size, params): Self::Parameters) -> Self::Strategy {
+        crate::strategy::dequeue_list(proptest::arbitrary::any_with::<T>(params), size)
     }
 }
 
@@ -809,7 +1288,7 @@ mod tests {
             assert_eq!(6 - i as i32, *elt);
         }
         let mut n = DequeueList::new();
-        assert_eq!(n.iter().rev().next(), None);
+        assert_eq!(n.iter().next_back(), None);
         n.push_front(4);
         let mut it = n.iter().rev();
         assert_eq!(it.size_hint(), (1, Some(1)));
@@ -883,8 +1362,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
     fn test_ord_nan() {
-        let nan = 0.0f64 / 0.0;
+        let nan = f64::NAN;
         let n = list_from(&[nan]);
         let m = list_from(&[nan]);
         assert!(!(n < m));
@@ -919,7 +1399,7 @@ mod tests {
         let list: DequeueList<i32> = (0..10).collect();
         assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
 
-        let list: DequeueList<&str> = vec!["just", "one", "test", "more"]
+        let list: DequeueList<&str> = ["just", "one", "test", "more"]
             .iter()
             .copied()
             .collect();
@@ -1075,11 +1555,453 @@ mod tests {
         );
     }
 
-    fn check_links<T: Eq + std::fmt::Debug>(list: &DequeueList<T>) {
+    #[test]
+    fn test_index() {
+        let mut list = generate_test();
+        for i in 0..list.len() {
+            assert_eq!(list[i], i as i32);
+        }
+
+        list[1] = 10;
+        list[5] = 50;
+        assert_eq!(list.get(1), Some(&10));
+        assert_eq!(list.get(5), Some(&50));
+        assert_eq!(list.get(7), None);
+        assert_eq!(list.get_mut(7), None);
+        assert_eq!(DequeueList::<i32>::new().get(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 7 out of bounds for length 7")]
+    fn test_index_out_of_bounds() {
+        let list = generate_test();
+        let _ = list[7];
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut list: DequeueList<_> = [5, 1, 4, 1, 3, 9, 2, 6].into_iter().collect();
+        list.sort();
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 1, 2, 3, 4, 5, 6, 9]);
+
+        // Stable: equal keys keep their order.
+        let mut list: DequeueList<_> = (0..50).map(|i| (i % 3, i)).collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        check_links(&list);
+        let mut expected: Vec<_> = (0..50).map(|i| (i % 3, i)).collect();
+        expected.sort_by_key(|&(key, _)| key);
+        assert!(list.iter().copied().eq(expected));
+
+        list.push_back((-1, 0));
+        assert_eq!(list.back(), Some(&(-1, 0)));
+
+        let mut empty = DequeueList::<i32>::new();
+        empty.sort();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_append() {
+        let mut list = list_from(&[1, 2]);
+        let mut other = list_from(&[3, 4, 5]);
+        list.append(&mut other);
+        check_links(&list);
+        assert!(other.is_empty() && other.front().is_none());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+        list.append(&mut other);
+        assert_eq!(list.len(), 5);
+
+        let mut empty = DequeueList::new();
+        empty.append(&mut list);
+        check_links(&empty);
+        assert_eq!(empty.back(), Some(&5));
+        other.push_back(6);
+        empty.append(&mut other);
+        assert_eq!(empty.pop_back(), Some(6));
+        assert_eq!(empty.len(), 5);
+    }
+
+    #[test]
+    fn test_append_across_allocators() {
+        use crate::alloc::{Bump, Counting};
+
+        let (ours, theirs) = (Counting::new(), Counting::new());
+        let mut list = DequeueList::new_in(&ours);
+        list.extend([1, 2]);
+        let mut other = DequeueList::new_in(&theirs);
+        other.extend([3, 4, 5]);
+
+        list.append(&mut other);
+        check_links(&list);
+        assert!(other.is_empty() && list.iter().copied().eq(1..6));
+        assert_eq!((ours.live_allocations(), theirs.live_allocations()), (5, 0));
+        drop(list);
+        assert_eq!((ours.bytes_in_use(), theirs.bytes_in_use()), (0, 0));
+
+        // The same arena relinks, another one moves the values.
+        let (arena, other_arena) = (Bump::with_capacity(256), Bump::with_capacity(256));
+        let mut list = DequeueList::new_in(&arena);
+        list.push_back(1u8);
+        let mut same = DequeueList::new_in(&arena);
+        same.push_back(2);
+        let used = arena.used();
+        list.append(&mut same);
+        assert_eq!(arena.used(), used);
+
+        let mut other = DequeueList::new_in(&other_arena);
+        other.push_back(3);
+        list.append(&mut other);
+        assert!(arena.used() > used);
+        assert!(list.iter().copied().eq(1..4));
+    }
+
+    #[test]
+    fn test_split_off() {
+        for at in 0..=7 {
+            let mut list = generate_test();
+            let tail = list.split_off(at);
+            check_links(&list);
+            check_links(&tail);
+            assert!(list.iter().copied().eq(0..at as i32));
+            assert!(tail.iter().copied().eq(at as i32..7));
+        }
+
+        let mut list = generate_test();
+        let mut tail = list.split_off(2);
+        tail.push_front(-1);
+        list.push_back(-2);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, -2]);
+        assert_eq!(tail.len(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 8 out of bounds for length 7")]
+    fn test_split_off_out_of_bounds() {
+        generate_test().split_off(8);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut list = generate_test();
+        list.insert(0, -1);
+        list.insert(8, 7);
+        list.insert(2, 10);
+        list.insert(6, 20);
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [-1, 0, 10, 1, 2, 3, 20, 4, 5, 6, 7]);
+
+        assert_eq!(list.remove(11), None);
+        assert_eq!(list.remove(2), Some(10));
+        assert_eq!(list.remove(5), Some(20));
+        assert_eq!(list.remove(0), Some(-1));
+        assert_eq!(list.remove(7), Some(7));
+        check_links(&list);
+        assert!(list.iter().copied().eq(0..7));
+
+        let mut list = DequeueList::new();
+        list.insert(0, 1);
+        assert_eq!(list.remove(0), Some(1));
+        check_links(&list);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 8 out of bounds for length 7")]
+    fn test_insert_out_of_bounds() {
+        generate_test().insert(8, 0);
+    }
+
+    #[test]
+    fn test_cursor_front_back() {
+        let mut list = generate_test();
+        let mut cursor = list.cursor_front_mut();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&mut 0)));
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!((cursor.index(), cursor.current()), (Some(6), Some(&mut 6)));
+        *cursor.peek_prev().unwrap() = 50;
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+
+        let mut cursor = list.cursor_back();
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(5), Some(&50)));
+        let ahead = cursor.clone();
+        cursor.move_prev();
+        assert_eq!(ahead.peek_prev(), cursor.current());
+        assert_eq!(cursor.peek_next(), Some(&50));
+
+        let mut cursor = list.cursor_front();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&0)));
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.peek_next()), (None, Some(&0)));
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(6), Some(&6)));
+
+        let mut empty = DequeueList::<i32>::new();
+        assert_eq!(empty.cursor_front().current(), None);
+        assert_eq!(empty.cursor_back().index(), None);
+        assert_eq!(empty.cursor_front_mut().index(), None);
+        assert_eq!(empty.cursor_back_mut().current(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut_push_pop() {
+        let mut list = generate_test();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.push_front(-1);
+        cursor.push_back(7);
+        assert_eq!((cursor.index(), cursor.current()), (Some(3), Some(&mut 2)));
+
+        assert_eq!(cursor.pop_front(), Some(-1));
+        assert_eq!(cursor.pop_back(), Some(7));
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 2)));
+
+        // Popping the cursor's own element moves it on.
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.pop_front(), Some(0));
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&mut 1)));
+
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.pop_back(), Some(6));
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+
+        // The ghost stays put.
+        cursor.push_front(0);
+        cursor.push_back(6);
+        assert_eq!(cursor.pop_front(), Some(0));
+        assert_eq!((cursor.index(), cursor.peek_next()), (None, Some(&mut 1)));
+        check_links(&list);
+        assert!(list.iter().copied().eq(1..7));
+
+        let mut list = list_from(&[1]);
+        let mut cursor = list.cursor_front_mut();
+        assert_eq!(cursor.pop_front(), Some(1));
+        assert_eq!((cursor.index(), cursor.pop_front(), cursor.pop_back()), (None, None, None));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut list: DequeueList<_> = (1..=8).collect();
+        list.retain(|elem| elem % 2 == 0);
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [2, 4, 6, 8]);
+
+        list.retain_mut(|elem| {
+            *elem += 1;
+            *elem != 9
+        });
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 5, 7]);
+        assert_eq!((list.front(), list.back(), list.len()), (Some(&3), Some(&7), 3));
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut list: DequeueList<_> = (1..=8).collect();
+        let mut evens = list.extract_if(|elem| *elem % 2 == 0);
+        assert_eq!(evens.next(), Some(2));
+        assert_eq!(evens.next(), Some(4));
+        drop(evens);
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 3, 5, 6, 7, 8]);
+
+        let big: Vec<_> = list.extract_if(|elem| *elem > 5).collect();
+        assert_eq!(big, [6, 7, 8]);
+        check_links(&list);
+        assert_eq!((list.back(), list.len()), (Some(&5), 3));
+
+        let mut drain = list.drain();
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some(1));
+        // The rest is removed with the iterator.
+        drop(drain);
+        assert!(list.is_empty() && list.front().is_none() && list.back().is_none());
+
+        list.extend([1, 2]);
+        std::mem::forget(list.drain());
+        check_links(&list);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_splice_across_allocators() {
+        use crate::alloc::Counting;
+
+        let (ours, theirs) = (Counting::new(), Counting::new());
+        let mut list = DequeueList::new_in(&ours);
+        list.extend([1, 4]);
+
+        let mut cursor = list.cursor_front_mut();
+        let mut input = DequeueList::new_in(&theirs);
+        input.extend([2, 3]);
+        cursor.splice_after(input);
+        cursor.move_prev();
+        let mut input = DequeueList::new_in(&theirs);
+        input.push_back(0);
+        cursor.splice_after(input);
+
+        check_links(&list);
+        assert!(list.iter().copied().eq(0..5));
+        assert_eq!((ours.live_allocations(), theirs.live_allocations()), (5, 0));
+        drop(list);
+        assert_eq!((ours.bytes_in_use(), theirs.bytes_in_use()), (0, 0));
+
+        // Clones free each other's nodes, so those are relinked as they are.
+        let mut list = DequeueList::new_in(ours.clone());
+        list.push_back(1);
+        let mut input = DequeueList::new_in(ours.clone());
+        input.push_back(2);
+        list.cursor_mut().splice_before(input);
+        assert_eq!((ours.allocations(), ours.live_allocations()), (7, 2));
+        assert!(list.iter().copied().eq(1..3));
+    }
+
+    #[test]
+    fn test_panic_safety() {
+        use crate::alloc::Counting;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let alloc = Counting::new();
+        let mut list = DequeueList::new_in(&alloc);
+        list.extend([8, 3, 9, 1, 7, 2, 6, 4, 5, 0]);
+
+        // Panics in the middle of merging, the list keeps every node.
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.sort_by(|a: &i32, b| {
+                calls += 1;
+                assert!(calls < 12);
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.len(), 10);
+        assert_eq!(alloc.live_allocations(), 10);
+        let mut elems: Vec<_> = list.iter().copied().collect();
+        let mut back: Vec<_> = list.iter().rev().copied().collect();
+        back.reverse();
+        assert_eq!(elems, back);
+        elems.sort();
+        assert!(elems.into_iter().eq(0..10));
+
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.retain(|elem| {
+                calls += 1;
+                assert!(calls < 6);
+                elem % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.len(), alloc.live_allocations());
+        assert_eq!(list.iter().count(), list.len());
+
+        list.sort();
+        assert!(list.iter().is_sorted());
+        drop(list);
+        assert_eq!(alloc.live_allocations(), 0);
+    }
+
+    fn check_links<T: Eq + std::fmt::Debug, A: crate::alloc::Allocator>(list: &DequeueList<T, A>) {
         let from_front: Vec<_> = list.iter().collect();
         let from_back: Vec<_> = list.iter().rev().collect();
         let re_reved: Vec<_> = from_back.into_iter().rev().collect();
 
         assert_eq!(from_front, re_reved);
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::{Deserialize, de::value::{Error, SeqDeserializer}};
+        use serde_test::{Token, assert_tokens};
+
+        let list = list_from(&[1, 2]);
+        assert_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(2) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::SeqEnd,
+            ],
+        );
+
+        let input = SeqDeserializer::<_, Error>::new(0..7);
+        let back = DequeueList::<i32>::deserialize(input).unwrap();
+        assert_eq!(back, generate_test());
+        assert_eq!(back.back(), Some(&6));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_rayon() {
+        use rayon::prelude::*;
+
+        let mut list: DequeueList<i32> = (0..1000).collect();
+
+        assert_eq!(list.par_iter().sum::<i32>(), 499500);
+        list.par_iter_mut().for_each(|x| *x += 1);
+        assert_eq!(list.front(), Some(&1));
+
+        let values: Vec<i32> = list.into_par_iter().map(|x| x - 1).collect();
+        assert_eq!(values, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_send_sync() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DequeueList<String>>();
+        assert_send_sync::<super::Iter<'_, String>>();
+        assert_send_sync::<super::IterMut<'_, String>>();
+        assert_send_sync::<super::CursorMut<'_, String>>();
+
+        let list = Arc::new(Mutex::new(DequeueList::new()));
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    for j in 0..25 {
+                        list.lock().unwrap().push_back(i * 25 + j);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let list = Arc::try_unwrap(list).unwrap().into_inner().unwrap();
+        let mut values: Vec<_> = thread::spawn(move || list.into_iter().collect())
+            .join()
+            .unwrap();
+        values.sort_unstable();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut list = generate_test();
+        list.pop_front();
+        list.push_front(0);
+        assert_eq!(list.stats().node_allocations, 8);
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        let split = cursor.split_after();
+        assert_eq!(split.stats().node_allocations, 0);
+        assert_eq!(list.clone().stats().node_allocations, 1);
+    }
+}
@@ -0,0 +1,172 @@
+//! Pretty-printer for tree-shaped structures.
+//!
+//! Anything that can hand out its nodes through [`TreeNode`] gets drawn by
+//! [`display_tree`], one node per line:
+//!
+//! ```text
+//! 40
+//! ├── 20
+//! │   ├── 10
+//! │   └── 30
+//! └── 60
+//!     ├── ·
+//!     └── 70
+//! ```
+//!
+//! `·` marks a missing child, so it's clear on which side of a binary node
+//! a lone child is.
+
+use std::fmt;
+
+/// Node of a tree that [`display_tree`] can draw, usually a reference into
+/// the tree.
+pub trait TreeNode: Sized {
+    /// Writes what the node holds, without its children.
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// The children from left to right, `None` for a missing one. Nodes
+    /// whose children are all missing are drawn as leaves.
+    fn children(&self) -> Vec<Option<Self>>;
+}
+
+/// Draws the tree under `root`, see the [module docs](self).
+pub fn display_tree<N: TreeNode>(root: Option<N>) -> DisplayTree<N> {
+    DisplayTree {
+        root,
+        style: Style::Unicode,
+        depths: false,
+    }
+}
+
+/// `Display` of a tree, made by [`display_tree`].
+pub struct DisplayTree<N> {
+    root: Option<N>,
+    style: Style,
+    depths: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Unicode,
+    Ascii,
+}
+
+impl Style {
+    /// Branch in front of a node and what goes below it for its children.
+    fn branch(self, last: bool) -> (&'static str, &'static str) {
+        match (self, last) {
+            (Style::Unicode, false) => ("├── ", "│   "),
+            (Style::Unicode, true) => ("└── ", "    "),
+            (Style::Ascii, false) => ("|-- ", "|   "),
+            (Style::Ascii, true) => ("`-- ", "    "),
+        }
+    }
+
+    fn missing(self) -> &'static str {
+        match self {
+            Style::Unicode => "·",
+            Style::Ascii => ".",
+        }
+    }
+}
+
+impl<N: TreeNode> DisplayTree<N> {
+    /// Draws with plain ASCII instead of box-drawing characters.
+    pub fn ascii(mut self) -> Self {
+        self.style = Style::Ascii;
+        self
+    }
+
+    /// Adds the depth of every node after its label, the root being 0.
+    pub fn with_depths(mut self) -> Self {
+        self.depths = true;
+        self
+    }
+
+    fn fmt_node(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        node: &N,
+        prefix: &mut String,
+        depth: usize,
+    ) -> fmt::Result {
+        node.fmt_label(f)?;
+        if self.depths {
+            write!(f, " [{depth}]")?;
+        }
+
+        let children = node.children();
+        if children.iter().all(Option::is_none) {
+            return Ok(());
+        }
+
+        let count = children.len();
+        for (i, child) in children.into_iter().enumerate() {
+            let (branch, indent) = self.style.branch(i + 1 == count);
+            write!(f, "\n{prefix}{branch}")?;
+
+            match child {
+                Some(child) => {
+                    let len = prefix.len();
+                    prefix.push_str(indent);
+                    self.fmt_node(f, &child, prefix, depth + 1)?;
+                    prefix.truncate(len);
+                }
+                None => f.write_str(self.style.missing())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<N: TreeNode> fmt::Display for DisplayTree<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.root {
+            Some(root) => self.fmt_node(f, root, &mut String::new(), 0),
+            None => f.write_str("(empty)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Directory-like tree with any number of children.
+    struct Dir(&'static str, Vec<Dir>);
+
+    impl TreeNode for &Dir {
+        fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.0)
+        }
+
+        fn children(&self) -> Vec<Option<Self>> {
+            self.1.iter().map(Some).collect()
+        }
+    }
+
+    #[test]
+    fn test_display_tree() {
+        let root = Dir(
+            "src",
+            vec![
+                Dir(
+                    "graph",
+                    vec![Dir("algo.rs", vec![]), Dir("matrix.rs", vec![])],
+                ),
+                Dir("lib.rs", vec![]),
+            ],
+        );
+
+        assert_eq!(
+            display_tree(Some(&root)).to_string(),
+            "src\n├── graph\n│   ├── algo.rs\n│   └── matrix.rs\n└── lib.rs"
+        );
+        assert_eq!(
+            display_tree(Some(&root)).ascii().with_depths().to_string(),
+            "src [0]\n|-- graph [1]\n|   |-- algo.rs [2]\n|   `-- matrix.rs [2]\n`-- lib.rs [1]"
+        );
+        assert_eq!(display_tree(None::<&Dir>).to_string(), "(empty)");
+    }
+}
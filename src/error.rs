@@ -0,0 +1,158 @@
+//! Errors of the fallible `try_*` methods, for callers that would rather
+//! recover from a full container or a failed allocation than panic.
+
+use std::{
+    alloc::Layout,
+    error::Error,
+    fmt,
+    ops::{Bound, Range, RangeBounds},
+};
+
+/// Reserving capacity failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The new capacity doesn't fit in `usize`, or its size in bytes
+    /// exceeds `isize::MAX`.
+    CapacityOverflow,
+    /// The allocator couldn't provide a block for `layout`.
+    AllocError { layout: Layout },
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryReserveError::AllocError { layout } => {
+                write!(f, "memory allocation of {} bytes failed", layout.size())
+            }
+        }
+    }
+}
+
+impl Error for TryReserveError {}
+
+/// Turns a failed reservation into the panic (or abort) of the infallible
+/// methods.
+pub(crate) fn handle_reserve(result: Result<(), TryReserveError>) {
+    match result {
+        Ok(()) => {}
+        Err(TryReserveError::CapacityOverflow) => panic!("Capacity overflow"),
+        Err(TryReserveError::AllocError { layout }) => std::alloc::handle_alloc_error(layout),
+    }
+}
+
+/// There was no room for a value, either because the container is full or
+/// because growing it failed. The value is handed back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T> {
+    value: T,
+}
+
+impl<T> CapacityError<T> {
+    pub fn new(value: T) -> Self {
+        CapacityError { value }
+    }
+
+    /// The value that didn't fit.
+    pub fn element(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+// Without `T: Debug`, so it works as an error for any element.
+impl<T> fmt::Debug for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CapacityError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+impl<T> Error for CapacityError<T> {}
+
+/// `index` was out of bounds for a container of length `len`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexError {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index {} out of bounds for length {}",
+            self.index, self.len
+        )
+    }
+}
+
+impl Error for IndexError {}
+
+/// Panic of the `Index` impls, with the same message as [`IndexError`].
+#[track_caller]
+pub(crate) fn out_of_bounds(index: usize, len: usize) -> ! {
+    panic!("{}", IndexError { index, len })
+}
+
+/// Resolves `range` against a container of length `len`, panicking if it's
+/// out of bounds or decreasing.
+#[track_caller]
+pub(crate) fn check_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).expect("Range out of bounds"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("Range out of bounds"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(start <= end && end <= len, "Range out of bounds");
+
+    start..end
+}
+
+/// Panic of the fixed-capacity containers when pushing to a full one.
+#[track_caller]
+pub(crate) fn capacity_exceeded(capacity: usize) -> ! {
+    panic!("insufficient capacity, the container holds at most {capacity} elements")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let layout = Layout::array::<u64>(4).unwrap();
+        assert_eq!(
+            TryReserveError::CapacityOverflow.to_string(),
+            "capacity overflow"
+        );
+        assert_eq!(
+            TryReserveError::AllocError { layout }.to_string(),
+            "memory allocation of 32 bytes failed"
+        );
+
+        let err = CapacityError::new(vec![1]);
+        assert_eq!(
+            format!("{err} {err:?}"),
+            "insufficient capacity CapacityError { .. }"
+        );
+        assert_eq!(err.into_inner(), [1]);
+
+        let err: Box<dyn Error> = Box::new(IndexError { index: 3, len: 2 });
+        assert_eq!(err.to_string(), "index 3 out of bounds for length 2");
+    }
+}
@@ -0,0 +1,128 @@
+//! Lazy removal iterators shared by the containers.
+//!
+//! A container hands out a [`Source`]: a cursor walking its elements front
+//! to back that can keep or remove the current one. [`ExtractIf`] removes
+//! the elements matching a predicate and [`Drain`] removes all of them, and
+//! both finish the source when dropped. So whatever the container:
+//!
+//! - elements are only removed while the iterator is advanced, the ones
+//!   [`ExtractIf`] didn't get to stay in the container;
+//! - [`Drain`] removes (and drops) the elements not yet yielded when it's
+//!   dropped;
+//! - a panic in the predicate or in a `Drop` leaves the container valid,
+//!   with the elements not yet removed;
+//! - leaking the iterator with `mem::forget` may leak elements, but never
+//!   leaves the container in an invalid state.
+
+use crate::guard::Guard;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+pub(crate) use sealed::Sealed;
+
+/// Cursor over the elements of a container that can take the current one
+/// out. Implemented by the containers of the crate only.
+pub trait Source: Sealed {
+    type Item;
+
+    /// The current element, `None` once every element was visited.
+    #[doc(hidden)]
+    fn current(&mut self) -> Option<&mut Self::Item>;
+
+    /// Leaves the current element in the container and moves to the next.
+    #[doc(hidden)]
+    fn keep(&mut self);
+
+    /// Takes the current element out and moves to the next.
+    #[doc(hidden)]
+    fn remove(&mut self) -> Self::Item;
+
+    /// Puts the container back in shape, runs once when the iterator is
+    /// dropped, even while unwinding.
+    #[doc(hidden)]
+    fn finish(&mut self);
+
+    /// Number of elements not visited yet.
+    #[doc(hidden)]
+    fn remaining(&self) -> usize;
+}
+
+/// Iterator removing the elements for which the predicate returns `true`,
+/// see the `extract_if` methods of the containers.
+pub struct ExtractIf<S: Source, F> {
+    source: S,
+    pred: F,
+}
+
+impl<S: Source, F> ExtractIf<S, F> {
+    pub(crate) fn new(source: S, pred: F) -> Self {
+        ExtractIf { source, pred }
+    }
+}
+
+impl<S, F> Iterator for ExtractIf<S, F>
+where
+    S: Source,
+    F: FnMut(&mut S::Item) -> bool,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<S::Item> {
+        while let Some(elem) = self.source.current() {
+            if (self.pred)(elem) {
+                return Some(self.source.remove());
+            }
+            self.source.keep();
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.source.remaining()))
+    }
+}
+
+impl<S: Source, F> Drop for ExtractIf<S, F> {
+    fn drop(&mut self) {
+        self.source.finish();
+    }
+}
+
+/// Iterator removing every element, see the `drain` methods of the
+/// containers.
+pub struct Drain<S: Source> {
+    source: S,
+}
+
+impl<S: Source> Drain<S> {
+    pub(crate) fn new(source: S) -> Self {
+        Drain { source }
+    }
+}
+
+impl<S: Source> Iterator for Drain<S> {
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<S::Item> {
+        self.source.current()?;
+        Some(self.source.remove())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.source.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<S: Source> ExactSizeIterator for Drain<S> {}
+
+impl<S: Source> Drop for Drain<S> {
+    fn drop(&mut self) {
+        // Finishes the source even if dropping an element panics.
+        let mut drain = Guard::new(self, |drain| drain.source.finish());
+        for _ in &mut **drain {}
+    }
+}
@@ -0,0 +1,110 @@
+//! Immutable, thread-shareable snapshots of a collection, for handing
+//! finished data to many readers without copying it.
+
+use std::sync::Arc;
+
+use crate::shared::ref_counted;
+use crate::{BTree, DequeueList, Vector};
+
+/// Collection behind an `Arc` that is only lent out as `&C`, built by the
+/// `freeze` methods of [`Vector`], [`DequeueList`] and [`BTree`].
+///
+/// Reads go through `Deref`: `frozen.iter()`, `frozen.contains(..)`,
+/// `frozen[i]`.
+pub struct Frozen<C>(Arc<C>);
+
+pub type FrozenVector<T> = Frozen<Vector<T>>;
+
+pub type FrozenDequeueList<T> = Frozen<DequeueList<T>>;
+
+pub type FrozenBTree<T> = Frozen<BTree<T>>;
+
+impl<C> Frozen<C> {
+    pub fn new(collection: C) -> Self {
+        Frozen(Arc::new(collection))
+    }
+
+    /// Returns the collection if no other `Frozen` points to it.
+    pub fn try_thaw(self) -> Result<C, Self> {
+        Arc::try_unwrap(self.0).map_err(Frozen)
+    }
+}
+
+impl<C: Clone> Frozen<C> {
+    /// Returns the collection for mutating again, copying it if it's
+    /// shared.
+    pub fn thaw(self) -> C {
+        Arc::unwrap_or_clone(self.0)
+    }
+}
+
+ref_counted!(Frozen<C>, Arc, C);
+
+impl<'a, C> IntoIterator for &'a Frozen<C>
+where
+    &'a C: IntoIterator,
+{
+    type Item = <&'a C as IntoIterator>::Item;
+    type IntoIter = <&'a C as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&*self.0).into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{Frozen, FrozenBTree, FrozenDequeueList, FrozenVector};
+
+    #[test]
+    fn test_readers() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenVector<String>>();
+        assert_send_sync::<FrozenDequeueList<String>>();
+        assert_send_sync::<FrozenBTree<String>>();
+
+        let vec = vector![1, 2, 3].freeze();
+        let list = deque![4, 5].freeze();
+        let tree = btree![7, 6].freeze();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let (vec, list, tree) = (vec.clone(), list.clone(), tree.clone());
+                thread::spawn(move || {
+                    vec.iter().sum::<i32>()
+                        + (&list).into_iter().sum::<i32>()
+                        + tree.iter().sum::<i32>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 28);
+        }
+
+        assert_eq!(vec[1], 2);
+        assert!(tree.contains(&6));
+        assert_eq!(format!("{list:?}"), "[4, 5]");
+    }
+
+    #[test]
+    fn test_thaw() {
+        let frozen = vector![1, 2, 3].freeze();
+        let other = frozen.clone();
+        assert!(frozen.ptr_eq(&other));
+        assert_eq!(Frozen::from(deque![1, 2]), deque![1, 2].freeze());
+
+        // Shared, so thawing copies.
+        let frozen = frozen.try_thaw().unwrap_err();
+        let ptr = frozen.as_ptr();
+        let mut thawed = other.thaw();
+        assert_ne!(thawed.as_ptr(), ptr);
+        thawed.push(4);
+
+        // The last one left hands its buffer back.
+        let thawed = frozen.try_thaw().unwrap();
+        assert_eq!(thawed.as_ptr(), ptr);
+        assert_eq!(*thawed, [1, 2, 3]);
+    }
+}
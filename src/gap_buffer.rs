@@ -0,0 +1,440 @@
+use std::{fmt::Debug, mem, ptr};
+
+use crate::{Vector, vec::Buffer};
+use crate::traits::MemoryUsage;
+
+/// Sequence with a movable gap of free space, as used by text editors.
+///
+/// The elements sit at both ends of the buffer with the gap in between, and
+/// the gap is the cursor: inserting or removing next to it is O(1), while
+/// moving it costs the number of elements it passes over. Edits that stay
+/// close to each other are therefore cheap.
+///
+/// ```text
+/// [a b c _ _ _ d e]
+///        ^     ^
+///        |     gap_end
+///        gap_start (cursor at 3)
+/// ```
+pub struct GapBuffer<T> {
+    buf: Buffer<T>,
+    gap_start: usize,
+    gap_end: usize,
+}
+
+pub type Iter<'a, T> = std::iter::Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>>;
+
+pub type IterMut<'a, T> = std::iter::Chain<std::slice::IterMut<'a, T>, std::slice::IterMut<'a, T>>;
+
+pub struct IntoIter<T>(GapBuffer<T>);
+
+impl<T> GapBuffer<T> {
+    /// Creates a new GapBuffer with no elements.
+    pub fn new() -> Self {
+        let buf = Buffer::new();
+        let gap_end = buf.cap;
+
+        GapBuffer {
+            buf,
+            gap_start: 0,
+            gap_end,
+        }
+    }
+
+    fn ptr(&self) -> *mut T {
+        self.buf.as_ptr()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.cap
+    }
+
+    fn gap_len(&self) -> usize {
+        self.gap_end - self.gap_start
+    }
+
+    pub fn len(&self) -> usize {
+        self.capacity() - self.gap_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Position of the gap, new elements are inserted here.
+    pub fn cursor(&self) -> usize {
+        self.gap_start
+    }
+
+    /// Doubles the buffer, keeping the elements after the gap at the end.
+    fn grow(&mut self) {
+        let old_cap = self.capacity();
+        let after = old_cap - self.gap_end;
+
+        self.buf.grow();
+
+        let gap_end = self.capacity() - after;
+        unsafe {
+            ptr::copy(self.ptr().add(self.gap_end), self.ptr().add(gap_end), after);
+        }
+        self.gap_end = gap_end;
+    }
+
+    /// Moves the gap so that the cursor is at `index`, in O(distance).
+    /// Panics if `index > len`.
+    pub fn move_gap_to(&mut self, index: usize) {
+        assert!(index <= self.len(), "Index out of bounds");
+
+        unsafe {
+            if index < self.gap_start {
+                // [a b c d _ _ e] -> [a b _ _ c d e]
+                let count = self.gap_start - index;
+                ptr::copy(
+                    self.ptr().add(index),
+                    self.ptr().add(self.gap_end - count),
+                    count,
+                );
+                self.gap_start -= count;
+                self.gap_end -= count;
+            } else {
+                // [a _ _ b c d e] -> [a b c _ _ d e]
+                let count = index - self.gap_start;
+                ptr::copy(
+                    self.ptr().add(self.gap_end),
+                    self.ptr().add(self.gap_start),
+                    count,
+                );
+                self.gap_start += count;
+                self.gap_end += count;
+            }
+        }
+    }
+
+    /// Inserts `elem` at the cursor and moves the cursor after it.
+    pub fn insert(&mut self, elem: T) {
+        if self.gap_len() == 0 {
+            self.grow();
+        }
+
+        unsafe {
+            ptr::write(self.ptr().add(self.gap_start), elem);
+        }
+        self.gap_start += 1;
+    }
+
+    /// Moves the cursor to `index` and inserts `elem` there.
+    pub fn insert_at(&mut self, index: usize, elem: T) {
+        self.move_gap_to(index);
+        self.insert(elem);
+    }
+
+    /// Removes the element before the cursor, like backspace.
+    pub fn remove_before(&mut self) -> Option<T> {
+        if self.gap_start == 0 {
+            return None;
+        }
+
+        self.gap_start -= 1;
+        unsafe { Some(ptr::read(self.ptr().add(self.gap_start))) }
+    }
+
+    /// Removes the element after the cursor, like delete.
+    pub fn remove_after(&mut self) -> Option<T> {
+        if self.gap_end == self.capacity() {
+            return None;
+        }
+
+        self.gap_end += 1;
+        unsafe { Some(ptr::read(self.ptr().add(self.gap_end - 1))) }
+    }
+
+    /// Moves the cursor to `index` and removes the element there.
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len(), "Index out of bounds");
+
+        self.move_gap_to(index);
+        self.remove_after().unwrap()
+    }
+
+    /// Position of element `index` in the buffer.
+    fn physical(&self, index: usize) -> Option<usize> {
+        if index < self.gap_start {
+            Some(index)
+        } else if index < self.len() {
+            Some(index + self.gap_len())
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let i = self.physical(index)?;
+        unsafe { Some(&*self.ptr().add(i)) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let i = self.physical(index)?;
+        unsafe { Some(&mut *self.ptr().add(i)) }
+    }
+
+    /// The elements before and after the gap.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe {
+            (
+                std::slice::from_raw_parts(self.ptr(), self.gap_start),
+                std::slice::from_raw_parts(
+                    self.ptr().add(self.gap_end),
+                    self.capacity() - self.gap_end,
+                ),
+            )
+        }
+    }
+
+    /// The elements before and after the gap.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        unsafe {
+            (
+                std::slice::from_raw_parts_mut(self.ptr(), self.gap_start),
+                std::slice::from_raw_parts_mut(
+                    self.ptr().add(self.gap_end),
+                    self.capacity() - self.gap_end,
+                ),
+            )
+        }
+    }
+
+    /// Removes all the elements, keeping the capacity.
+    pub fn clear(&mut self) {
+        let (before, after) = self.as_mut_slices();
+        let (before, after): (*mut [T], *mut [T]) = (before, after);
+
+        // Reset first so a panicking destructor can't cause a double drop.
+        self.gap_start = 0;
+        self.gap_end = self.capacity();
+
+        unsafe {
+            ptr::drop_in_place(before);
+            ptr::drop_in_place(after);
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (before, after) = self.as_slices();
+        before.iter().chain(after)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (before, after) = self.as_mut_slices();
+        before.iter_mut().chain(after)
+    }
+}
+
+impl<T> Drop for GapBuffer<T> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<T>() {
+            self.clear();
+        }
+    }
+}
+
+impl<T> Default for GapBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.move_gap_to(0);
+        self.0.remove_after()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let len = self.0.len();
+        self.0.move_gap_to(len);
+        self.0.remove_before()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for GapBuffer<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a GapBuffer<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut GapBuffer<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Extend<T> for GapBuffer<T> {
+    /// Inserts the elements at the cursor, in order.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for GapBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        buffer.extend(iter);
+
+        buffer
+    }
+}
+
+impl<T> From<Vector<T>> for GapBuffer<T> {
+    /// The cursor ends up at the end.
+    fn from(vec: Vector<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T> From<GapBuffer<T>> for Vector<T> {
+    fn from(buffer: GapBuffer<T>) -> Self {
+        let mut vec = Vector::new();
+        for elem in buffer {
+            vec.push(elem);
+        }
+
+        vec
+    }
+}
+
+impl<T: PartialEq> PartialEq for GapBuffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for GapBuffer<T> {}
+
+impl<T: Debug> Debug for GapBuffer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> MemoryUsage for GapBuffer<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GapBuffer;
+    use crate::Vector;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_editing() {
+        let mut text: GapBuffer<char> = "hello world".chars().collect();
+        assert_eq!(text.cursor(), 11);
+
+        text.move_gap_to(5);
+        text.insert(',');
+        assert_eq!(text.remove_after(), Some(' '));
+        text.extend(" big ".chars());
+        assert_eq!(text.iter().collect::<String>(), "hello, big world");
+
+        assert_eq!(text.remove_before(), Some(' '));
+        assert_eq!(text.remove_before(), Some('g'));
+        assert_eq!(text.remove_before(), Some('i'));
+        assert_eq!(text.remove_before(), Some('b'));
+        text.move_gap_to(0);
+        assert_eq!(text.remove_before(), None);
+        text.insert('>');
+
+        let (before, after) = text.as_slices();
+        assert_eq!(before, ['>']);
+        assert_eq!(after.iter().collect::<String>(), "hello, world");
+
+        assert_eq!(text.remove(7), ' ');
+        text.insert_at(1, ' ');
+        text.insert_at(8, ' ');
+        assert_eq!(text.get(1), Some(&' '));
+        assert_eq!(text.iter().collect::<String>(), "> hello, world");
+
+        text.move_gap_to(text.len());
+        assert_eq!(text.remove_after(), None);
+    }
+
+    #[test]
+    fn test_vector_conversion() {
+        let mut vec = Vector::new();
+        for i in 0..10 {
+            vec.push(i);
+        }
+
+        let mut buffer = GapBuffer::from(vec);
+        buffer.move_gap_to(3);
+        for elem in &mut buffer {
+            *elem *= 2;
+        }
+
+        let vec = Vector::from(buffer);
+        assert!(vec.iter().copied().eq((0..10).map(|i| i * 2)));
+    }
+
+    #[test]
+    fn test_drop() {
+        let rc = Rc::new(());
+        let mut buffer: GapBuffer<Rc<()>> = (0..20).map(|_| rc.clone()).collect();
+        buffer.move_gap_to(7);
+        drop(buffer.remove_after());
+        assert_eq!(Rc::strong_count(&rc), 20);
+
+        let mut iter = buffer.into_iter();
+        drop(iter.next_back());
+        assert_eq!(Rc::strong_count(&rc), 19);
+
+        drop(iter);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut buffer = GapBuffer::new();
+        for _ in 0..10 {
+            buffer.insert(());
+        }
+        buffer.move_gap_to(4);
+        assert_eq!(buffer.remove_after(), Some(()));
+        assert_eq!(buffer.len(), 9);
+        assert_eq!(buffer.into_iter().count(), 9);
+    }
+}
@@ -0,0 +1,604 @@
+use std::{fmt::Debug, ops::ControlFlow};
+
+use crate::traverse::{Order, Traverse, Visit};
+use crate::{RingDeque, Stack, Vector};
+
+mod algo;
+mod matrix;
+
+pub use algo::{Cycle, ShortestPaths};
+pub use matrix::{DistanceMatrix, MatrixGraph, NegativeCycle};
+
+/// Handle to a node of a [`Graph`]. Ids are never reused, so the id of a
+/// removed node stays invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+/// Handle to an edge of a [`Graph`]. Ids are never reused, so the id of a
+/// removed edge stays invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId(usize);
+
+impl NodeId {
+    /// Position of the node in insertion order.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl EdgeId {
+    /// Position of the edge in insertion order.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+struct Node<N> {
+    weight: N,
+    /// Edges leaving this node.
+    outgoing: Vector<EdgeId>,
+    /// Edges arriving at this node.
+    incoming: Vector<EdgeId>,
+}
+
+struct Edge<E> {
+    from: NodeId,
+    to: NodeId,
+    weight: E,
+}
+
+/// Graph stored as adjacency lists, with data `N` on the nodes and `E` on
+/// the edges.
+///
+/// Every node keeps the ids of its outgoing and incoming edges, so walking
+/// the neighbors of a node is O(degree). In an undirected graph an edge is
+/// stored once and seen from both of its endpoints. Removed nodes and edges
+/// leave an empty slot behind so the remaining ids stay valid.
+pub struct Graph<N, E> {
+    nodes: Vector<Option<Node<N>>>,
+    edges: Vector<Option<Edge<E>>>,
+    node_count: usize,
+    edge_count: usize,
+    directed: bool,
+}
+
+/// Iterator over the edges touching a node, see [`Graph::edges`].
+pub struct Edges<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    node: NodeId,
+    outgoing: std::slice::Iter<'a, EdgeId>,
+    /// Empty for directed graphs.
+    incoming: std::slice::Iter<'a, EdgeId>,
+}
+
+/// Iterator over the nodes adjacent to a node, see [`Graph::neighbors`].
+pub struct Neighbors<'a, N, E>(Edges<'a, N, E>);
+
+/// Breadth-first traversal, see [`Graph::bfs`].
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    queue: RingDeque<NodeId>,
+    discovered: Vec<bool>,
+}
+
+/// Depth-first traversal in preorder, see [`Graph::dfs`].
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    stack: Stack<NodeId>,
+    visited: Vec<bool>,
+}
+
+impl<N, E> Graph<N, E> {
+    /// Creates an empty graph where edges go from one node to the other.
+    pub fn directed() -> Self {
+        Self::with_direction(true)
+    }
+
+    /// Creates an empty graph where edges connect both nodes both ways.
+    pub fn undirected() -> Self {
+        Self::with_direction(false)
+    }
+
+    fn with_direction(directed: bool) -> Self {
+        Graph {
+            nodes: Vector::new(),
+            edges: Vector::new(),
+            node_count: 0,
+            edge_count: 0,
+            directed,
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Number of nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Upper bound for the index of the node ids, useful to size lookup
+    /// tables.
+    pub fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        self.nodes.push(Some(Node {
+            weight,
+            outgoing: Vector::new(),
+            incoming: Vector::new(),
+        }));
+        self.node_count += 1;
+
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Adds an edge from `from` to `to`. Parallel edges and loops are
+    /// allowed. Panics if either node doesn't exist.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: E) -> EdgeId {
+        assert!(
+            self.contains_node(from) && self.contains_node(to),
+            "Node not found"
+        );
+
+        let id = EdgeId(self.edges.len());
+        self.edges.push(Some(Edge { from, to, weight }));
+        self.edge_count += 1;
+
+        self.node_slot_mut(from).outgoing.push(id);
+        self.node_slot_mut(to).incoming.push(id);
+
+        id
+    }
+
+    fn node_slot(&self, id: NodeId) -> Option<&Node<N>> {
+        self.nodes.get(id.0)?.as_ref()
+    }
+
+    fn node_slot_mut(&mut self, id: NodeId) -> &mut Node<N> {
+        self.nodes[id.0].as_mut().unwrap()
+    }
+
+    fn edge_slot(&self, id: EdgeId) -> Option<&Edge<E>> {
+        self.edges.get(id.0)?.as_ref()
+    }
+
+    pub fn contains_node(&self, id: NodeId) -> bool {
+        self.node_slot(id).is_some()
+    }
+
+    pub fn contains_edge(&self, id: EdgeId) -> bool {
+        self.edge_slot(id).is_some()
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.node_slot(id).map(|node| &node.weight)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes
+            .get_mut(id.0)?
+            .as_mut()
+            .map(|node| &mut node.weight)
+    }
+
+    pub fn edge(&self, id: EdgeId) -> Option<&E> {
+        self.edge_slot(id).map(|edge| &edge.weight)
+    }
+
+    pub fn edge_mut(&mut self, id: EdgeId) -> Option<&mut E> {
+        self.edges
+            .get_mut(id.0)?
+            .as_mut()
+            .map(|edge| &mut edge.weight)
+    }
+
+    /// Returns the `(from, to)` nodes of an edge.
+    pub fn edge_endpoints(&self, id: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edge_slot(id).map(|edge| (edge.from, edge.to))
+    }
+
+    /// Returns an edge going from `from` to `to`, in either direction if the
+    /// graph is undirected.
+    pub fn find_edge(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        self.edges(from)
+            .find(|&(_, other, _)| other == to)
+            .map(|(id, _, _)| id)
+    }
+
+    /// Removes an edge and returns its weight.
+    pub fn remove_edge(&mut self, id: EdgeId) -> Option<E> {
+        let edge = self.edges.get_mut(id.0)?.take()?;
+        self.edge_count -= 1;
+
+        let outgoing = &mut self.node_slot_mut(edge.from).outgoing;
+        let position = outgoing.iter().position(|&e| e == id).unwrap();
+        outgoing.swap_remove(position);
+
+        let incoming = &mut self.node_slot_mut(edge.to).incoming;
+        let position = incoming.iter().position(|&e| e == id).unwrap();
+        incoming.swap_remove(position);
+
+        Some(edge.weight)
+    }
+
+    /// Removes a node together with all the edges touching it and returns
+    /// its weight.
+    pub fn remove_node(&mut self, id: NodeId) -> Option<N> {
+        let node = self.node_slot(id)?;
+
+        let edges: Vec<EdgeId> = node
+            .outgoing
+            .iter()
+            .chain(node.incoming.iter())
+            .copied()
+            .collect();
+        for edge in edges {
+            // Loops are listed twice.
+            self.remove_edge(edge);
+        }
+
+        self.node_count -= 1;
+
+        self.nodes[id.0].take().map(|node| node.weight)
+    }
+
+    /// Iterates over the ids of all the nodes, in insertion order.
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_some())
+            .map(|(i, _)| NodeId(i))
+    }
+
+    /// Iterates over the ids of all the edges, in insertion order.
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| edge.is_some())
+            .map(|(i, _)| EdgeId(i))
+    }
+
+    /// Iterates over the edges leaving `node` as `(edge, other node, weight)`.
+    /// In an undirected graph that's every edge touching `node`.
+    pub fn edges(&self, node: NodeId) -> Edges<'_, N, E> {
+        let (outgoing, incoming) = match self.node_slot(node) {
+            Some(slot) if self.directed => (slot.outgoing.iter(), [].iter()),
+            Some(slot) => (slot.outgoing.iter(), slot.incoming.iter()),
+            None => ([].iter(), [].iter()),
+        };
+
+        Edges {
+            graph: self,
+            node,
+            outgoing,
+            incoming,
+        }
+    }
+
+    /// Iterates over the nodes reachable from `node` through one edge, once
+    /// per edge.
+    pub fn neighbors(&self, node: NodeId) -> Neighbors<'_, N, E> {
+        Neighbors(self.edges(node))
+    }
+
+    /// Visits every node reachable from `start` in breadth-first order.
+    pub fn bfs(&self, start: NodeId) -> Bfs<'_, N, E> {
+        let mut bfs = Bfs {
+            graph: self,
+            queue: RingDeque::new(),
+            discovered: vec![false; self.node_bound()],
+        };
+
+        if self.contains_node(start) {
+            bfs.discovered[start.0] = true;
+            bfs.queue.push_back(start);
+        }
+
+        bfs
+    }
+
+    /// Visits every node reachable from `start` in depth-first preorder,
+    /// taking edges in the order [`Graph::edges`] returns them.
+    pub fn dfs(&self, start: NodeId) -> Dfs<'_, N, E> {
+        let mut dfs = Dfs {
+            graph: self,
+            stack: Stack::new(),
+            visited: vec![false; self.node_bound()],
+        };
+
+        if self.contains_node(start) {
+            dfs.stack.push(start);
+        }
+
+        dfs
+    }
+}
+
+impl<'a, N, E> Iterator for Edges<'a, N, E> {
+    type Item = (EdgeId, NodeId, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(&id) = self.outgoing.next() {
+            let edge = self.graph.edge_slot(id).unwrap();
+            return Some((id, edge.to, &edge.weight));
+        }
+
+        loop {
+            let &id = self.incoming.next()?;
+            let edge = self.graph.edge_slot(id).unwrap();
+
+            // Loops were already returned as outgoing edges.
+            if edge.from != self.node {
+                return Some((id, edge.from, &edge.weight));
+            }
+        }
+    }
+}
+
+impl<'a, N, E> Iterator for Neighbors<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, node, _)| node)
+    }
+}
+
+impl<'a, N, E> Iterator for Bfs<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+
+        for next in self.graph.neighbors(node) {
+            if !self.discovered[next.0] {
+                self.discovered[next.0] = true;
+                self.queue.push_back(next);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+impl<'a, N, E> Iterator for Dfs<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+
+            if self.visited[node.0] {
+                continue;
+            }
+            self.visited[node.0] = true;
+
+            // Pushed backwards so the first neighbor is visited first.
+            let neighbors: Vec<NodeId> = self.graph.neighbors(node).collect();
+            for next in neighbors.into_iter().rev() {
+                if !self.visited[next.0] {
+                    self.stack.push(next);
+                }
+            }
+
+            return Some(node);
+        }
+    }
+}
+
+impl<N, E> Traverse for Graph<N, E> {
+    type Elem = N;
+
+    /// Walks the depth-first forest: a tree is grown from every node not
+    /// reached yet, in insertion order, and each node's children are the
+    /// neighbors not reached yet, taken in the order of [`Graph::edges`].
+    fn traverse<'a, B>(
+        &'a self,
+        order: Order,
+        visit: impl FnMut(Visit<'a, N>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let mut walk = GraphWalk {
+            graph: self,
+            order,
+            visit,
+            reached: vec![false; self.node_bound()],
+            next_index: 0,
+        };
+
+        for root in self.node_ids() {
+            if !walk.reached[root.0] {
+                walk.node(root, 0, None)?;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// State of [`Graph::traverse`].
+struct GraphWalk<'a, N, E, V> {
+    graph: &'a Graph<N, E>,
+    order: Order,
+    visit: V,
+    reached: Vec<bool>,
+    next_index: usize,
+}
+
+impl<'a, N, E, V> GraphWalk<'a, N, E, V> {
+    fn node<B>(&mut self, node: NodeId, depth: usize, parent: Option<usize>) -> ControlFlow<B>
+    where
+        V: FnMut(Visit<'a, N>) -> ControlFlow<B>,
+    {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.reached[node.0] = true;
+        let here = Visit {
+            elem: self.graph.node(node).unwrap(),
+            depth,
+            index,
+            parent,
+        };
+
+        if self.order == Order::Pre {
+            (self.visit)(here)?;
+        }
+
+        let mut first = true;
+        for next in self.graph.neighbors(node) {
+            if self.reached[next.0] {
+                continue;
+            }
+            self.node(next, depth + 1, Some(index))?;
+
+            if first && self.order == Order::In {
+                (self.visit)(here)?;
+            }
+            first = false;
+        }
+
+        // A leaf is visited in order once it has no child left to wait for.
+        if (first && self.order == Order::In) || self.order == Order::Post {
+            (self.visit)(here)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+impl<N: Debug, E: Debug> Debug for Graph<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nodes: Vec<_> = self
+            .node_ids()
+            .map(|id| (id.0, self.node(id).unwrap()))
+            .collect();
+        let edges: Vec<_> = self
+            .edges
+            .iter()
+            .flatten()
+            .map(|edge| (edge.from.0, edge.to.0, &edge.weight))
+            .collect();
+
+        f.debug_struct("Graph")
+            .field("directed", &self.directed)
+            .field("nodes", &nodes)
+            .field("edges", &edges)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use super::{Graph, NodeId};
+    use crate::traverse::{Order, Traverse};
+
+    /// ```text
+    /// a -> b -> d
+    /// |    ^    |
+    /// v    |    v
+    /// c ---+    e
+    /// ```
+    fn sample(directed: bool) -> (Graph<char, u32>, Vec<NodeId>) {
+        let mut graph = if directed {
+            Graph::directed()
+        } else {
+            Graph::undirected()
+        };
+
+        let ids: Vec<NodeId> = "abcde".chars().map(|c| graph.add_node(c)).collect();
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 1), (3, 4)] {
+            graph.add_edge(ids[from], ids[to], (from * 10 + to) as u32);
+        }
+
+        (graph, ids)
+    }
+
+    fn names(graph: &Graph<char, u32>, nodes: impl Iterator<Item = NodeId>) -> String {
+        nodes.map(|id| *graph.node(id).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_directed() {
+        let (graph, ids) = sample(true);
+
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 5);
+        assert_eq!(names(&graph, graph.neighbors(ids[0])), "bc");
+        assert_eq!(names(&graph, graph.neighbors(ids[1])), "d");
+        assert!(graph.find_edge(ids[1], ids[0]).is_none());
+
+        let edge = graph.find_edge(ids[2], ids[1]).unwrap();
+        assert_eq!(graph.edge(edge), Some(&21));
+        assert_eq!(graph.edge_endpoints(edge), Some((ids[2], ids[1])));
+
+        assert_eq!(names(&graph, graph.bfs(ids[0])), "abcde");
+        assert_eq!(names(&graph, graph.dfs(ids[0])), "abdec");
+        assert_eq!(names(&graph, graph.bfs(ids[3])), "de");
+    }
+
+    #[test]
+    fn test_undirected() {
+        let (graph, ids) = sample(false);
+
+        assert_eq!(names(&graph, graph.neighbors(ids[1])), "dac");
+        assert!(graph.find_edge(ids[1], ids[0]).is_some());
+        assert_eq!(names(&graph, graph.bfs(ids[3])), "debac");
+        assert_eq!(names(&graph, graph.dfs(ids[4])), "edbac");
+    }
+
+    #[test]
+    fn test_remove() {
+        let (mut graph, ids) = sample(true);
+        let loop_edge = graph.add_edge(ids[1], ids[1], 11);
+        assert_eq!(names(&graph, graph.neighbors(ids[1])), "db");
+
+        let edge = graph.find_edge(ids[0], ids[2]).unwrap();
+        assert_eq!(graph.remove_edge(edge), Some(2));
+        assert_eq!(graph.remove_edge(edge), None);
+        assert_eq!(names(&graph, graph.bfs(ids[0])), "abde");
+
+        assert_eq!(graph.remove_node(ids[1]), Some('b'));
+        assert!(!graph.contains_edge(loop_edge));
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(names(&graph, graph.node_ids()), "acde");
+        assert_eq!(graph.neighbors(ids[2]).count(), 0);
+        assert_eq!(graph.bfs(ids[1]).count(), 0);
+
+        let f = graph.add_node('f');
+        assert_eq!(f.index(), 5);
+    }
+
+    #[test]
+    fn test_traverse() {
+        let (mut graph, _) = sample(true);
+        let f = graph.add_node('f');
+        let g = graph.add_node('g');
+        graph.add_edge(g, f, 0);
+
+        let elems = |order| graph.elems(order).into_iter().collect::<String>();
+        // `f` is reached first, so `g` roots a tree of its own despite its
+        // edge to `f`.
+        assert_eq!(elems(Order::Pre), "abdecfg");
+        assert_eq!(elems(Order::In), "edbacfg");
+        assert_eq!(elems(Order::Post), "edbcafg");
+        assert_eq!(graph.height(), 4);
+
+        let mut parents = Vec::new();
+        let _ = graph.traverse::<()>(Order::Pre, |visit| {
+            parents.push(visit.parent);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(parents, [None, Some(0), Some(1), Some(2), Some(0), None, None]);
+    }
+}
@@ -0,0 +1,319 @@
+use std::{cmp::Reverse, ops::Add};
+
+use super::{Graph, NodeId};
+use crate::{BinaryHeap, Queue, Stack, Vector};
+
+/// Distances from a single source, see [`Graph::dijkstra`].
+pub struct ShortestPaths<W> {
+    source: NodeId,
+    /// Indexed by node, `None` if unreachable.
+    distance: Vector<Option<W>>,
+    /// Node before each node on its shortest path.
+    previous: Vector<Option<NodeId>>,
+}
+
+/// Error of [`Graph::toposort`], holds a node that is part of a cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle(pub NodeId);
+
+impl<W: Copy> ShortestPaths<W> {
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// Length of the shortest path to `node`, `None` if it can't be reached.
+    pub fn distance(&self, node: NodeId) -> Option<W> {
+        *self.distance.get(node.index())?
+    }
+
+    /// Nodes on the shortest path from the source to `node`, both included.
+    pub fn path_to(&self, node: NodeId) -> Option<Vector<NodeId>> {
+        self.distance(node)?;
+
+        let mut path = Vector::new();
+        let mut current = Some(node);
+        while let Some(node) = current {
+            path.push(node);
+            current = self.previous[node.index()];
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    /// Shortest paths from `source` to every node, with the length of each
+    /// edge given by `cost`. Costs must not be negative; `W::default()` is
+    /// taken as zero.
+    pub fn dijkstra<W, F>(&self, source: NodeId, cost: F) -> ShortestPaths<W>
+    where
+        W: Copy + Ord + Default + Add<Output = W>,
+        F: Fn(&E) -> W,
+    {
+        let mut distance = Vector::new();
+        let mut previous = Vector::new();
+        for _ in 0..self.node_bound() {
+            distance.push(None);
+            previous.push(None);
+        }
+
+        let mut heap = BinaryHeap::new();
+
+        if self.contains_node(source) {
+            distance[source.index()] = Some(W::default());
+            heap.push(Reverse((W::default(), source)));
+        }
+
+        while let Some(Reverse((dist, node))) = heap.pop() {
+            // Stale entry, the node was already reached through a shorter path.
+            if distance[node.index()].is_some_and(|best| dist > best) {
+                continue;
+            }
+
+            for (_, next, weight) in self.edges(node) {
+                let candidate = dist + cost(weight);
+
+                if distance[next.index()].is_none_or(|best| candidate < best) {
+                    distance[next.index()] = Some(candidate);
+                    previous[next.index()] = Some(node);
+                    heap.push(Reverse((candidate, next)));
+                }
+            }
+        }
+
+        ShortestPaths {
+            source,
+            distance,
+            previous,
+        }
+    }
+
+    /// Orders the nodes so that every edge goes from an earlier node to a
+    /// later one (Kahn's algorithm). Fails if the graph has a cycle, which
+    /// in an undirected graph is any edge.
+    pub fn toposort(&self) -> Result<Vector<NodeId>, Cycle> {
+        let mut in_degree = vec![0usize; self.node_bound()];
+        for node in self.node_ids() {
+            for next in self.neighbors(node) {
+                in_degree[next.index()] += 1;
+            }
+        }
+
+        let mut ready: Queue<NodeId> = self
+            .node_ids()
+            .filter(|node| in_degree[node.index()] == 0)
+            .collect();
+        let mut order = Vector::new();
+
+        while let Some(node) = ready.dequeue() {
+            order.push(node);
+
+            for next in self.neighbors(node) {
+                in_degree[next.index()] -= 1;
+                if in_degree[next.index()] == 0 {
+                    ready.enqueue(next);
+                }
+            }
+        }
+
+        if order.len() < self.node_count() {
+            let node = self
+                .node_ids()
+                .find(|node| in_degree[node.index()] > 0)
+                .unwrap();
+            return Err(Cycle(node));
+        }
+
+        Ok(order)
+    }
+
+    /// Strongly connected components (Tarjan's algorithm). Components come
+    /// out in reverse topological order: no edge leads from a component to
+    /// one listed after it.
+    pub fn strongly_connected_components(&self) -> Vector<Vector<NodeId>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let bound = self.node_bound();
+        let mut index = vec![UNVISITED; bound];
+        let mut low = vec![0; bound];
+        let mut on_stack = vec![false; bound];
+        let mut counter = 0;
+
+        let mut components = Vector::new();
+        let mut stack = Stack::new();
+        // Explicit call stack instead of recursion, with the neighbors still
+        // to visit for every node.
+        let mut calls = Stack::new();
+
+        for root in self.node_ids() {
+            if index[root.index()] != UNVISITED {
+                continue;
+            }
+
+            index[root.index()] = counter;
+            low[root.index()] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root.index()] = true;
+            calls.push((root, self.neighbors(root)));
+
+            while let Some((node, neighbors)) = calls.peek_mut() {
+                let node = *node;
+
+                if let Some(next) = neighbors.next() {
+                    if index[next.index()] == UNVISITED {
+                        index[next.index()] = counter;
+                        low[next.index()] = counter;
+                        counter += 1;
+                        stack.push(next);
+                        on_stack[next.index()] = true;
+                        calls.push((next, self.neighbors(next)));
+                    } else if on_stack[next.index()] {
+                        low[node.index()] = low[node.index()].min(index[next.index()]);
+                    }
+
+                    continue;
+                }
+
+                calls.pop();
+
+                if let Some((parent, _)) = calls.peek() {
+                    low[parent.index()] = low[parent.index()].min(low[node.index()]);
+                }
+
+                if low[node.index()] == index[node.index()] {
+                    let mut component = Vector::new();
+
+                    loop {
+                        let member: NodeId = stack.pop().unwrap();
+                        on_stack[member.index()] = false;
+                        component.push(member);
+
+                        if member == node {
+                            break;
+                        }
+                    }
+
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cycle;
+    use crate::{Graph, graph::NodeId};
+
+    fn directed(n: usize, edges: &[(usize, usize, u32)]) -> (Graph<(), u32>, Vec<NodeId>) {
+        let mut graph = Graph::directed();
+        let ids: Vec<NodeId> = (0..n).map(|_| graph.add_node(())).collect();
+
+        for &(from, to, weight) in edges {
+            graph.add_edge(ids[from], ids[to], weight);
+        }
+
+        (graph, ids)
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let (graph, ids) = directed(
+            6,
+            &[
+                (0, 1, 7),
+                (0, 2, 9),
+                (0, 5, 14),
+                (1, 2, 10),
+                (1, 3, 15),
+                (2, 3, 11),
+                (2, 5, 2),
+                (5, 4, 9),
+                (3, 4, 6),
+            ],
+        );
+
+        let paths = graph.dijkstra(ids[0], |w| *w);
+        let distances: Vec<Option<u32>> = ids.iter().map(|&id| paths.distance(id)).collect();
+        assert_eq!(
+            distances,
+            [Some(0), Some(7), Some(9), Some(20), Some(20), Some(11)]
+        );
+
+        let path = paths.path_to(ids[4]).unwrap();
+        assert_eq!(*path, [ids[0], ids[2], ids[5], ids[4]]);
+
+        let paths = graph.dijkstra(ids[3], |_| 1);
+        assert_eq!(paths.distance(ids[4]), Some(1));
+        assert_eq!(paths.distance(ids[0]), None);
+        assert!(paths.path_to(ids[0]).is_none());
+    }
+
+    #[test]
+    fn test_toposort() {
+        let (mut graph, ids) =
+            directed(5, &[(0, 1, 0), (0, 2, 0), (1, 3, 0), (2, 3, 0), (3, 4, 0)]);
+
+        let order = graph.toposort().unwrap();
+        assert_eq!(order.len(), 5);
+        for edge in graph.edge_ids() {
+            let (from, to) = graph.edge_endpoints(edge).unwrap();
+            let position = |node| order.iter().position(|&n| n == node).unwrap();
+            assert!(position(from) < position(to));
+        }
+
+        graph.add_edge(ids[4], ids[1], 0);
+        let Err(Cycle(node)) = graph.toposort() else {
+            panic!("cycle not detected");
+        };
+        assert!([ids[1], ids[3], ids[4]].contains(&node));
+    }
+
+    #[test]
+    fn test_scc() {
+        let (graph, _) = directed(
+            8,
+            &[
+                (0, 1, 0),
+                (1, 2, 0),
+                (2, 0, 0),
+                (2, 3, 0),
+                (3, 4, 0),
+                (4, 5, 0),
+                (5, 3, 0),
+                (6, 5, 0),
+                (6, 7, 0),
+                (7, 6, 0),
+            ],
+        );
+
+        let sccs = graph.strongly_connected_components();
+
+        // Reverse topological order: every edge stays in its component or
+        // leads to one listed before it.
+        let position = |node| sccs.iter().position(|c| c.contains(&node)).unwrap();
+        for edge in graph.edge_ids() {
+            let (from, to) = graph.edge_endpoints(edge).unwrap();
+            assert!(position(from) >= position(to));
+        }
+
+        let mut components: Vec<Vec<usize>> = sccs
+            .into_iter()
+            .map(|component| {
+                let mut members: Vec<usize> = component.iter().map(|id| id.index()).collect();
+                members.sort();
+                members
+            })
+            .collect();
+
+        // {3, 4, 5} is reachable from both others, so it comes out first.
+        assert_eq!(components[0], [3, 4, 5]);
+
+        components.sort();
+        assert_eq!(components, [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7]]);
+    }
+}
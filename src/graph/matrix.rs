@@ -0,0 +1,416 @@
+use std::{fmt::Debug, ops::Add};
+
+use super::{Graph, NodeId};
+use crate::Vector;
+
+/// Graph stored as an adjacency matrix, for dense graphs.
+///
+/// Every pair of nodes has a slot for an edge, so looking up, adding or
+/// removing the edge between two nodes is O(1) at the cost of O(n²) memory.
+/// There is at most one edge between two nodes. Undirected graphs only use
+/// the slots with `from <= to`.
+pub struct MatrixGraph<N, E> {
+    nodes: Vector<N>,
+    /// `stride * stride` slots in row-major order.
+    matrix: Vector<Option<E>>,
+    /// Number of nodes the matrix has room for.
+    stride: usize,
+    edge_count: usize,
+    directed: bool,
+}
+
+/// All-pairs shortest paths, see [`MatrixGraph::floyd_warshall`].
+pub struct DistanceMatrix<W> {
+    len: usize,
+    distance: Vector<Option<W>>,
+}
+
+/// Error of [`MatrixGraph::floyd_warshall`], holds a node that is part of a
+/// cycle with negative total cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegativeCycle(pub NodeId);
+
+impl<W: Copy> DistanceMatrix<W> {
+    /// Length of the shortest path from `from` to `to`, `None` if there is
+    /// no path.
+    pub fn distance(&self, from: NodeId, to: NodeId) -> Option<W> {
+        if from.0 >= self.len || to.0 >= self.len {
+            return None;
+        }
+
+        self.distance[from.0 * self.len + to.0]
+    }
+}
+
+impl<N, E> MatrixGraph<N, E> {
+    /// Creates an empty graph where edges go from one node to the other.
+    pub fn directed() -> Self {
+        Self::with_direction(true)
+    }
+
+    /// Creates an empty graph where edges connect both nodes both ways.
+    pub fn undirected() -> Self {
+        Self::with_direction(false)
+    }
+
+    fn with_direction(directed: bool) -> Self {
+        MatrixGraph {
+            nodes: Vector::new(),
+            matrix: Vector::new(),
+            stride: 0,
+            edge_count: 0,
+            directed,
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Number of nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Index in the matrix of the edge between `from` and `to`.
+    fn slot(&self, from: NodeId, to: NodeId) -> usize {
+        assert!(
+            from.0 < self.nodes.len() && to.0 < self.nodes.len(),
+            "Node not found"
+        );
+
+        let (from, to) = if !self.directed && from > to {
+            (to, from)
+        } else {
+            (from, to)
+        };
+
+        from.0 * self.stride + to.0
+    }
+
+    /// Doubles the room for nodes, moving the edges to their new slots.
+    fn grow(&mut self) {
+        let stride = (self.stride * 2).max(4);
+
+        let mut matrix = Vector::new();
+        for _ in 0..stride * stride {
+            matrix.push(None);
+        }
+
+        let old = std::mem::replace(&mut self.matrix, matrix);
+        for (i, edge) in old.into_iter().enumerate() {
+            if edge.is_some() {
+                self.matrix[(i / self.stride) * stride + i % self.stride] = edge;
+            }
+        }
+
+        self.stride = stride;
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        if self.nodes.len() == self.stride {
+            self.grow();
+        }
+
+        self.nodes.push(weight);
+
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Sets the edge from `from` to `to`, returning the weight it replaced.
+    /// Panics if either node doesn't exist.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: E) -> Option<E> {
+        let slot = self.slot(from, to);
+        let old = self.matrix[slot].replace(weight);
+
+        if old.is_none() {
+            self.edge_count += 1;
+        }
+
+        old
+    }
+
+    /// Removes the edge from `from` to `to` and returns its weight.
+    /// Panics if either node doesn't exist.
+    pub fn remove_edge(&mut self, from: NodeId, to: NodeId) -> Option<E> {
+        let slot = self.slot(from, to);
+        let old = self.matrix[slot].take();
+
+        if old.is_some() {
+            self.edge_count -= 1;
+        }
+
+        old
+    }
+
+    /// Returns the weight of the edge from `from` to `to`.
+    /// Panics if either node doesn't exist.
+    pub fn edge(&self, from: NodeId, to: NodeId) -> Option<&E> {
+        self.matrix[self.slot(from, to)].as_ref()
+    }
+
+    pub fn edge_mut(&mut self, from: NodeId, to: NodeId) -> Option<&mut E> {
+        let slot = self.slot(from, to);
+        self.matrix[slot].as_mut()
+    }
+
+    pub fn contains_edge(&self, from: NodeId, to: NodeId) -> bool {
+        self.edge(from, to).is_some()
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(id.0)
+    }
+
+    /// Iterates over the ids of all the nodes, in insertion order.
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + use<N, E> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    /// Iterates over the nodes reachable from `node` through one edge, in
+    /// O(n). Panics if `node` doesn't exist.
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.node_ids()
+            .filter(move |&other| self.contains_edge(node, other))
+    }
+
+    /// Shortest paths between every pair of nodes, with the length of each
+    /// edge given by `cost`, in O(n³). Costs may be negative as long as no
+    /// cycle adds up to less than zero; `W::default()` is taken as zero.
+    pub fn floyd_warshall<W, F>(&self, cost: F) -> Result<DistanceMatrix<W>, NegativeCycle>
+    where
+        W: Copy + Ord + Default + Add<Output = W>,
+        F: Fn(&E) -> W,
+    {
+        let n = self.nodes.len();
+        let zero = W::default();
+
+        let mut distance = Vector::new();
+        for i in 0..n * n {
+            let (from, to) = (NodeId(i / n), NodeId(i % n));
+            let direct = self.edge(from, to).map(&cost);
+
+            distance.push(match direct {
+                Some(direct) if from == to => Some(direct.min(zero)),
+                None if from == to => Some(zero),
+                direct => direct,
+            });
+        }
+
+        for k in 0..n {
+            for i in 0..n {
+                let Some(to_k) = distance[i * n + k] else {
+                    continue;
+                };
+
+                for j in 0..n {
+                    if let Some(from_k) = distance[k * n + j] {
+                        let through_k = to_k + from_k;
+
+                        if distance[i * n + j].is_none_or(|best| through_k < best) {
+                            distance[i * n + j] = Some(through_k);
+                        }
+                    }
+                }
+            }
+
+            // Stop before the cycle compounds: each round only adds up the
+            // distances of the previous one, which can't overflow yet.
+            if let Some(i) = (0..n).find(|&i| distance[i * n + i].is_some_and(|d| d < zero)) {
+                return Err(NegativeCycle(NodeId(i)));
+            }
+        }
+
+        Ok(DistanceMatrix { len: n, distance })
+    }
+}
+
+impl<N, E> From<Graph<N, E>> for MatrixGraph<N, E> {
+    /// Node ids are renumbered to skip removed nodes. Of parallel edges only
+    /// the one added last is kept.
+    fn from(graph: Graph<N, E>) -> Self {
+        let Graph {
+            nodes,
+            edges,
+            directed,
+            ..
+        } = graph;
+
+        let mut matrix = Self::with_direction(directed);
+        let mut ids = Vec::with_capacity(nodes.len());
+
+        for node in nodes {
+            ids.push(node.map(|node| matrix.add_node(node.weight)));
+        }
+
+        for edge in edges.into_iter().flatten() {
+            let from = ids[edge.from.0].unwrap();
+            let to = ids[edge.to.0].unwrap();
+            matrix.add_edge(from, to, edge.weight);
+        }
+
+        matrix
+    }
+}
+
+impl<N, E> From<MatrixGraph<N, E>> for Graph<N, E> {
+    /// Node ids are kept.
+    fn from(matrix: MatrixGraph<N, E>) -> Self {
+        let MatrixGraph {
+            nodes,
+            matrix,
+            stride,
+            directed,
+            ..
+        } = matrix;
+
+        let mut graph = Graph::with_direction(directed);
+
+        for node in nodes {
+            graph.add_node(node);
+        }
+
+        for (i, edge) in matrix.into_iter().enumerate() {
+            if let Some(edge) = edge {
+                graph.add_edge(NodeId(i / stride), NodeId(i % stride), edge);
+            }
+        }
+
+        graph
+    }
+}
+
+impl<N: Debug, E: Debug> Debug for MatrixGraph<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let edges: Vec<_> = self
+            .matrix
+            .iter()
+            .enumerate()
+            .filter_map(|(i, edge)| Some((i / self.stride, i % self.stride, edge.as_ref()?)))
+            .collect();
+
+        f.debug_struct("MatrixGraph")
+            .field("directed", &self.directed)
+            .field("nodes", &&*self.nodes)
+            .field("edges", &edges)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatrixGraph, NegativeCycle};
+    use crate::{Graph, graph::NodeId};
+
+    #[test]
+    fn test_edges() {
+        let mut graph = MatrixGraph::directed();
+        let ids: Vec<NodeId> = (0..6).map(|i| graph.add_node(i)).collect();
+
+        assert_eq!(graph.add_edge(ids[0], ids[5], 'a'), None);
+        assert_eq!(graph.add_edge(ids[5], ids[0], 'b'), None);
+        assert_eq!(graph.add_edge(ids[0], ids[5], 'c'), Some('a'));
+        graph.add_edge(ids[0], ids[2], 'd');
+        assert_eq!(graph.edge_count(), 3);
+
+        // Adding nodes moves the matrix around.
+        let extra: Vec<NodeId> = (6..20).map(|i| graph.add_node(i)).collect();
+        graph.add_edge(extra[13], ids[0], 'e');
+
+        assert_eq!(graph.edge(ids[0], ids[5]), Some(&'c'));
+        assert_eq!(graph.edge(ids[5], ids[0]), Some(&'b'));
+        assert!(!graph.contains_edge(ids[2], ids[0]));
+        assert!(graph.neighbors(ids[0]).eq([ids[2], ids[5]]));
+
+        assert_eq!(graph.remove_edge(ids[0], ids[5]), Some('c'));
+        assert_eq!(graph.remove_edge(ids[0], ids[5]), None);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.node(extra[0]), Some(&6));
+    }
+
+    #[test]
+    fn test_undirected() {
+        let mut graph = MatrixGraph::undirected();
+        let a = graph.add_node('a');
+        let b = graph.add_node('b');
+
+        graph.add_edge(b, a, 1);
+        assert_eq!(graph.edge(a, b), Some(&1));
+        assert_eq!(graph.add_edge(a, b, 2), Some(1));
+        assert_eq!(graph.edge_count(), 1);
+        assert!(graph.neighbors(a).eq([b]));
+        assert!(graph.neighbors(b).eq([a]));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let mut list = Graph::directed();
+        let ids: Vec<NodeId> = "abcd".chars().map(|c| list.add_node(c)).collect();
+        list.add_edge(ids[0], ids[1], 1);
+        list.add_edge(ids[1], ids[3], 2);
+        list.add_edge(ids[3], ids[0], 3);
+        list.add_edge(ids[2], ids[3], 4);
+        list.remove_node(ids[2]);
+
+        let matrix = MatrixGraph::from(list);
+        let d = NodeId(2);
+        assert_eq!(matrix.node_count(), 3);
+        assert_eq!(matrix.edge_count(), 3);
+        assert_eq!(matrix.node(d), Some(&'d'));
+        assert_eq!(matrix.edge(ids[1], d), Some(&2));
+
+        let list = Graph::from(matrix);
+        assert_eq!(list.node_count(), 3);
+        assert_eq!(list.edge_count(), 3);
+        let edge = list.find_edge(d, ids[0]).unwrap();
+        assert_eq!(list.edge(edge), Some(&3));
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        let mut graph = MatrixGraph::directed();
+        let ids: Vec<NodeId> = (0..4).map(|_| graph.add_node(())).collect();
+        for (from, to, cost) in [(0, 2, -2), (2, 3, 2), (3, 1, -1), (1, 0, 4), (1, 2, 3)] {
+            graph.add_edge(ids[from], ids[to], cost);
+        }
+
+        let paths = graph.floyd_warshall(|c| *c).unwrap();
+        let row: Vec<Option<i32>> = ids.iter().map(|&to| paths.distance(ids[0], to)).collect();
+        assert_eq!(row, [Some(0), Some(-1), Some(-2), Some(0)]);
+        assert_eq!(paths.distance(ids[1], ids[3]), Some(4));
+
+        graph.add_node(());
+        let paths = graph.floyd_warshall(|c| *c).unwrap();
+        assert_eq!(paths.distance(ids[0], NodeId(4)), None);
+
+        graph.add_edge(ids[2], ids[0], -1);
+        assert!(matches!(
+            graph.floyd_warshall(|c| *c),
+            Err(NegativeCycle(_))
+        ));
+
+        // Negative cycles everywhere would overflow if left to compound.
+        let mut graph = MatrixGraph::directed();
+        let ids: Vec<NodeId> = (0..40).map(|_| graph.add_node(())).collect();
+        for &from in &ids {
+            for &to in &ids {
+                if from != to {
+                    graph.add_edge(from, to, -1i32);
+                }
+            }
+        }
+        assert!(matches!(
+            graph.floyd_warshall(|c| *c),
+            Err(NegativeCycle(_))
+        ));
+    }
+}
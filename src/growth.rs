@@ -0,0 +1,142 @@
+//! How a [`Vector`](crate::Vector) picks the capacity of its next buffer.
+//!
+//! [`Vector`](crate::Vector) takes a [`GrowthStrategy`] as a type parameter
+//! defaulting to [`Doubling`], chosen with
+//! [`Vector::with_growth`](crate::Vector::with_growth). Any
+//! `Fn(usize, usize, usize) -> usize` is a strategy too:
+//!
+//! ```
+//! use ds_rust::Vector;
+//! use ds_rust::growth::Geometric;
+//!
+//! let mut v = Vector::with_growth(Geometric::ONE_AND_HALF);
+//! for i in 0..5 {
+//!     v.push(i);
+//! }
+//! assert_eq!(v.capacity(), 6);
+//!
+//! // Grows 16 values at a time.
+//! let mut v = Vector::with_growth(|_cap, needed: usize, _size| needed.next_multiple_of(16));
+//! v.push(1u8);
+//! assert_eq!(v.capacity(), 16);
+//! ```
+
+/// Picks the capacity a full buffer grows to.
+pub trait GrowthStrategy {
+    /// Capacity to reallocate to when `needed` values of `size` bytes don't
+    /// fit in `cap`. Anything below `needed` is rounded up to it.
+    fn grow(&self, cap: usize, needed: usize, size: usize) -> usize;
+}
+
+/// Starts at one value and doubles, the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Doubling;
+
+/// Starts at `initial` values and multiplies the capacity by
+/// `numerator / denominator` from there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometric {
+    initial: usize,
+    numerator: usize,
+    denominator: usize,
+}
+
+/// Rounds the buffers `G` picks up to whole pages, so the allocator can hand
+/// out pages without wasting the end of the last one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageAligned<G = Doubling>(pub G);
+
+impl Geometric {
+    /// Starts at 4 values and grows by half, trading more reallocations for
+    /// less unused capacity than [`Doubling`].
+    pub const ONE_AND_HALF: Geometric = Geometric::new(4, 3, 2);
+
+    /// # Panics
+    ///
+    /// If `denominator` is zero.
+    pub const fn new(initial: usize, numerator: usize, denominator: usize) -> Self {
+        assert!(denominator != 0, "Growth factor with a zero denominator");
+        Geometric {
+            initial,
+            numerator,
+            denominator,
+        }
+    }
+}
+
+impl<G> PageAligned<G> {
+    pub const PAGE_SIZE: usize = 4096;
+}
+
+impl GrowthStrategy for Doubling {
+    fn grow(&self, cap: usize, needed: usize, _size: usize) -> usize {
+        needed.max(cap.saturating_mul(2))
+    }
+}
+
+impl GrowthStrategy for Geometric {
+    fn grow(&self, cap: usize, needed: usize, _size: usize) -> usize {
+        let next = if cap == 0 {
+            self.initial
+        } else {
+            cap.saturating_mul(self.numerator) / self.denominator
+        };
+        needed.max(next)
+    }
+}
+
+impl<G: GrowthStrategy> GrowthStrategy for PageAligned<G> {
+    fn grow(&self, cap: usize, needed: usize, size: usize) -> usize {
+        let cap = self.0.grow(cap, needed, size).max(needed);
+        if size == 0 {
+            return cap;
+        }
+
+        // Too large to round is too large to allocate, the caller reports it.
+        match cap
+            .checked_mul(size)
+            .and_then(|bytes| bytes.checked_next_multiple_of(Self::PAGE_SIZE))
+        {
+            Some(bytes) => bytes / size,
+            None => cap,
+        }
+    }
+}
+
+impl<F: Fn(usize, usize, usize) -> usize> GrowthStrategy for F {
+    fn grow(&self, cap: usize, needed: usize, size: usize) -> usize {
+        self(cap, needed, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Doubling, Geometric, GrowthStrategy, PageAligned};
+
+    #[test]
+    fn test_strategies() {
+        assert_eq!(Doubling.grow(0, 1, 8), 1);
+        assert_eq!(Doubling.grow(4, 5, 8), 8);
+        assert_eq!(Doubling.grow(4, 20, 8), 20);
+        assert_eq!(
+            Doubling.grow(usize::MAX / 2 + 1, usize::MAX / 2 + 2, 1),
+            usize::MAX
+        );
+
+        let geometric = Geometric::ONE_AND_HALF;
+        assert_eq!(geometric.grow(0, 1, 8), 4);
+        assert_eq!(geometric.grow(4, 5, 8), 6);
+        assert_eq!(geometric.grow(6, 7, 8), 9);
+        assert_eq!(Geometric::new(0, 1, 1).grow(3, 4, 8), 4);
+
+        let paged = PageAligned(Doubling);
+        assert_eq!(paged.grow(0, 1, 8), 512);
+        assert_eq!(paged.grow(512, 513, 8), 1024);
+        assert_eq!(paged.grow(0, 1, 1500), 2);
+        assert_eq!(paged.grow(0, 1, 0), 1);
+        assert_eq!(paged.grow(0, usize::MAX, 2), usize::MAX);
+
+        let by_ten = |cap: usize, _, _| cap + 10;
+        assert_eq!(by_ten.grow(10, 11, 1), 20);
+    }
+}
@@ -0,0 +1,82 @@
+//! Drop guard for code that calls back into user code (closures, `Ord`,
+//! `Drop`) while a collection is in a temporarily broken state.
+//!
+//! The cleanup runs when the guard goes out of scope, also while unwinding,
+//! so a panicking callback still leaves the collection consistent: no value
+//! is dropped twice, no node becomes unreachable and the length is right.
+
+use std::{
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr,
+};
+
+pub(crate) struct Guard<T, F: FnMut(&mut T)> {
+    value: T,
+    on_drop: F,
+}
+
+impl<T, F: FnMut(&mut T)> Guard<T, F> {
+    pub(crate) fn new(value: T, on_drop: F) -> Self {
+        Guard { value, on_drop }
+    }
+
+    /// Disarms the guard, skipping the cleanup.
+    pub(crate) fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+
+        unsafe {
+            // The closure may own something that needs dropping.
+            drop(ptr::read(&this.on_drop));
+            ptr::read(&this.value)
+        }
+    }
+}
+
+impl<T, F: FnMut(&mut T)> Deref for Guard<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, F: FnMut(&mut T)> DerefMut for Guard<T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T, F: FnMut(&mut T)> Drop for Guard<T, F> {
+    fn drop(&mut self) {
+        (self.on_drop)(&mut self.value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Guard;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    #[test]
+    fn test_guard() {
+        let mut log = Vec::new();
+
+        {
+            let mut guard = Guard::new(1, |value| log.push(*value));
+            *guard += 1;
+        }
+        assert_eq!(log, [2]);
+
+        let guard = Guard::new(3, |value| log.push(*value));
+        assert_eq!(guard.into_inner(), 3);
+        assert_eq!(log, [2]);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _guard = Guard::new(4, |value| log.push(*value));
+            panic!("callback failed");
+        }));
+        assert!(result.is_err());
+        assert_eq!(log, [2, 4]);
+    }
+}
@@ -0,0 +1,250 @@
+//! Hashers for the hash-based containers ([`IndexMap`](crate::IndexMap),
+//! [`BiMap`](crate::BiMap), [`Counter`](crate::Counter)), which all take a
+//! `S: BuildHasher` parameter defaulting to std's `RandomState`.
+//!
+//! `RandomState` is SipHash with random keys: slow-ish, but an attacker
+//! can't pick keys that collide. When the keys aren't attacker-controlled
+//! the crate offers faster options:
+//!
+//! - [`FxBuildHasher`], the multiply-rotate hash of rustc, the fastest for
+//!   integers and short keys;
+//! - [`FnvBuildHasher`], FNV-1a, byte at a time, fine for short strings;
+//! - [`SeededState`], SipHash-1-3 with fixed keys, still hard to attack
+//!   without the keys but reproducible from run to run.
+
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+/// [`BuildHasher`] of [`FxHasher`].
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+/// [`BuildHasher`] of [`FnvHasher`].
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// The hash of rustc: each word is xored in and the state multiplied by a
+/// constant. Very fast, but trivial to make collide.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, mut bytes: &[u8]) {
+        while let Some((word, rest)) = bytes.split_first_chunk::<8>() {
+            self.add_to_hash(u64::from_le_bytes(*word));
+            bytes = rest;
+        }
+        if let Some((word, rest)) = bytes.split_first_chunk::<4>() {
+            self.add_to_hash(u32::from_le_bytes(*word) as u64);
+            bytes = rest;
+        }
+        for &byte in bytes {
+            self.add_to_hash(byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit FNV-1a: each byte is xored in and the state multiplied by a prime.
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher {
+    hash: u64,
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher { hash: FNV_OFFSET }
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash = (self.hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// [`BuildHasher`] of [`SipHasher13`] with fixed keys, so hashes (and the
+/// iteration order of the containers) are the same on every run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeededState {
+    k0: u64,
+    k1: u64,
+}
+
+impl SeededState {
+    pub const fn new(k0: u64, k1: u64) -> Self {
+        SeededState { k0, k1 }
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = SipHasher13;
+
+    fn build_hasher(&self) -> SipHasher13 {
+        SipHasher13::new_with_keys(self.k0, self.k1)
+    }
+}
+
+/// SipHash-1-3, the algorithm behind std's `RandomState`, keyed with 128
+/// bits.
+#[derive(Debug, Clone, Copy)]
+pub struct SipHasher13 {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    /// Bytes not yet compressed, little endian in the low `ntail` bytes.
+    tail: u64,
+    ntail: usize,
+    /// Total bytes written, only its low byte ends up in the hash.
+    length: usize,
+}
+
+impl SipHasher13 {
+    pub const fn new_with_keys(k0: u64, k1: u64) -> Self {
+        SipHasher13 {
+            v0: k0 ^ 0x736f_6d65_7073_6575,
+            v1: k1 ^ 0x646f_7261_6e64_6f6d,
+            v2: k0 ^ 0x6c79_6765_6e65_7261,
+            v3: k1 ^ 0x7465_6462_7974_6573,
+            tail: 0,
+            ntail: 0,
+            length: 0,
+        }
+    }
+
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    fn compress(&mut self, m: u64) {
+        self.v3 ^= m;
+        self.round();
+        self.v0 ^= m;
+    }
+}
+
+impl Hasher for SipHasher13 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.length += bytes.len();
+
+        for &byte in bytes {
+            self.tail |= (byte as u64) << (8 * self.ntail);
+            self.ntail += 1;
+
+            if self.ntail == 8 {
+                self.compress(self.tail);
+                self.tail = 0;
+                self.ntail = 0;
+            }
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut state = *self;
+
+        state.compress(((self.length as u64 & 0xff) << 56) | self.tail);
+        state.v2 ^= 0xff;
+        for _ in 0..3 {
+            state.round();
+        }
+
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FnvHasher, FxBuildHasher, SeededState, SipHasher13};
+    use std::hash::{BuildHasher, Hasher};
+
+    #[test]
+    fn test_fnv() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = FnvHasher::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+
+        // Reference values of 64-bit FNV-1a.
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_siphash() {
+        // std's `DefaultHasher` is SipHash-1-3 with zero keys.
+        let bytes: Vec<u8> = (0..=40).collect();
+        for len in 0..bytes.len() {
+            let mut ours = SipHasher13::new_with_keys(0, 0);
+            let mut std = std::hash::DefaultHasher::new();
+            // Split writes have to hash like a single one.
+            let (a, b) = bytes[..len].split_at(len / 3);
+            ours.write(a);
+            ours.write(b);
+            std.write(&bytes[..len]);
+            assert_eq!(ours.finish(), std.finish());
+        }
+
+        let a = SeededState::new(1, 2);
+        assert_eq!(a.hash_one("key"), SeededState::new(1, 2).hash_one("key"));
+        assert_ne!(a.hash_one("key"), SeededState::new(2, 1).hash_one("key"));
+    }
+
+    #[test]
+    fn test_fx() {
+        let state = FxBuildHasher::default();
+        assert_eq!(state.hash_one(42u64), state.hash_one(42u64));
+        assert_ne!(state.hash_one(1u64), state.hash_one(2u64));
+        assert_ne!(state.hash_one("ab"), state.hash_one("ba"));
+    }
+}
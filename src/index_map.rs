@@ -0,0 +1,528 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::Vector;
+use crate::stats::MapStats;
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// Hash map that remembers insertion order.
+///
+/// Entries are stored densely in a [`Vector`] in the order they were
+/// inserted, and a separate open-addressing table (linear probing) maps
+/// hashes to positions in it. Iteration follows the vector, entries can be
+/// accessed by position, and removal comes in two flavors:
+/// [`swap_remove`](IndexMap::swap_remove) is O(1) but moves the last entry
+/// into the hole, [`shift_remove`](IndexMap::shift_remove) keeps the order
+/// but is O(n).
+pub struct IndexMap<K, V, S = RandomState> {
+    entries: Vector<Bucket<K, V>>,
+    /// Positions in `entries`, its length is zero or a power of two.
+    table: Vec<Option<usize>>,
+    hash_builder: S,
+    stats: MapStats,
+}
+
+pub struct Iter<'a, K, V>(std::slice::Iter<'a, Bucket<K, V>>);
+
+pub struct IterMut<'a, K, V>(std::slice::IterMut<'a, Bucket<K, V>>);
+
+pub struct IntoIter<K, V>(crate::vec::IntoIter<Bucket<K, V>>);
+
+impl<K, V> IndexMap<K, V> {
+    /// Creates a new IndexMap with no entries.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Creates a new IndexMap that hashes keys with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        IndexMap {
+            entries: Vector::new(),
+            table: Vec::new(),
+            hash_builder,
+            stats: MapStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Counters of the probing done by inserts so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> MapStats {
+        self.stats
+    }
+
+    /// Returns the entry at position `index` in insertion order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|b| (&b.key, &b.value))
+    }
+
+    /// Returns the entry at position `index` in insertion order.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries.get_mut(index).map(|b| (&b.key, &mut b.value))
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.len().checked_sub(1)?)
+    }
+
+    /// Removes and returns the last entry.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let bucket = self.entries.pop()?;
+        let position = self.entries.len();
+
+        let slot = self.slot_of_position(bucket.hash, position);
+        self.clear_slot(slot);
+
+        Some((bucket.key, bucket.value))
+    }
+
+    /// Removes all the entries.
+    pub fn clear(&mut self) {
+        while self.entries.pop().is_some() {}
+        self.table.fill(None);
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.entries.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|b| &b.key)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|b| &b.value)
+    }
+
+    fn mask(&self) -> usize {
+        self.table.len() - 1
+    }
+
+    /// Slot of the table holding `position`, whose entry has `hash`.
+    fn slot_of_position(&self, hash: u64, position: usize) -> usize {
+        let mut slot = hash as usize & self.mask();
+
+        while self.table[slot] != Some(position) {
+            slot = (slot + 1) & self.mask();
+        }
+
+        slot
+    }
+
+    /// Empties `slot` and shifts back the entries after it that were pushed
+    /// forward by collisions, so probing never stops early.
+    fn clear_slot(&mut self, mut hole: usize) {
+        self.table[hole] = None;
+
+        let mut slot = hole;
+        loop {
+            slot = (slot + 1) & self.mask();
+
+            let Some(position) = self.table[slot] else {
+                break;
+            };
+
+            let ideal = self.entries[position].hash as usize & self.mask();
+            let stays = if hole <= slot {
+                hole < ideal && ideal <= slot
+            } else {
+                hole < ideal || ideal <= slot
+            };
+
+            if !stays {
+                self.table[hole] = self.table[slot].take();
+                hole = slot;
+            }
+        }
+    }
+
+    /// Rebuilds the table with room for more entries.
+    fn grow(&mut self) {
+        let size = (self.table.len() * 2).max(8);
+        self.table = vec![None; size];
+
+        for (position, bucket) in self.entries.iter().enumerate() {
+            let mut slot = bucket.hash as usize & (size - 1);
+
+            while self.table[slot].is_some() {
+                slot = (slot + 1) & (size - 1);
+            }
+
+            self.table[slot] = Some(position);
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> IndexMap<K, V, S> {
+    /// Slot of `key` if it is present, otherwise the empty slot where it
+    /// would go.
+    fn find_slot(&self, hash: u64, key: &K) -> Result<usize, usize> {
+        let mut slot = hash as usize & self.mask();
+
+        loop {
+            match self.table[slot] {
+                None => return Err(slot),
+                Some(position) => {
+                    let bucket = &self.entries[position];
+                    if bucket.hash == hash && bucket.key == *key {
+                        return Ok(slot);
+                    }
+                }
+            }
+
+            slot = (slot + 1) & self.mask();
+        }
+    }
+
+    /// Slot and position of `key`.
+    fn find(&self, key: &K) -> Option<(usize, usize)> {
+        if self.table.is_empty() {
+            return None;
+        }
+
+        let slot = self.find_slot(self.hash_builder.hash_one(key), key).ok()?;
+
+        Some((slot, self.table[slot].unwrap()))
+    }
+
+    /// Inserts `value` for `key`. If the key is already present its value is
+    /// replaced and returned, and the entry keeps its position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Like [`IndexMap::insert`], also returns the position of the entry.
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        // Keep the load factor under 3/4.
+        if (self.len() + 1) * 4 > self.table.len() * 3 {
+            self.grow();
+        }
+
+        let hash = self.hash_builder.hash_one(&key);
+        let found = self.find_slot(hash, &key);
+
+        let slot = found.unwrap_or_else(|slot| slot);
+        let probe_length = slot.wrapping_sub(hash as usize) & self.mask();
+        self.stats.record_insert(probe_length);
+
+        match found {
+            Ok(slot) => {
+                let position = self.table[slot].unwrap();
+                let old = std::mem::replace(&mut self.entries[position].value, value);
+                (position, Some(old))
+            }
+            Err(slot) => {
+                let position = self.entries.len();
+                self.entries.push(Bucket { hash, key, value });
+                self.table[slot] = Some(position);
+                (position, None)
+            }
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let (_, position) = self.find(key)?;
+        Some(&self.entries[position].value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (_, position) = self.find(key)?;
+        Some(&mut self.entries[position].value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Position of `key` in insertion order.
+    pub fn get_index_of(&self, key: &K) -> Option<usize> {
+        self.find(key).map(|(_, position)| position)
+    }
+
+    /// Removes `key` by moving the last entry into its position, O(1).
+    pub fn swap_remove(&mut self, key: &K) -> Option<V> {
+        let (slot, position) = self.find(key)?;
+        self.clear_slot(slot);
+
+        let last = self.entries.len() - 1;
+        let bucket = self.entries.swap_remove(position);
+
+        if position != last {
+            let moved = self.slot_of_position(self.entries[position].hash, last);
+            self.table[moved] = Some(position);
+        }
+
+        Some(bucket.value)
+    }
+
+    /// Removes `key` and shifts the entries after it, keeping their order,
+    /// O(n).
+    pub fn shift_remove(&mut self, key: &K) -> Option<V> {
+        let (slot, position) = self.find(key)?;
+        self.clear_slot(slot);
+
+        let bucket = self.entries.remove(position);
+
+        for index in self.table.iter_mut().flatten() {
+            if *index > position {
+                *index -= 1;
+            }
+        }
+
+        Some(bucket.value)
+    }
+}
+
+impl<K, V, S: Default> Default for IndexMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|b| (&b.key, &b.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|b| (&b.key, &b.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|b| (&b.key, &mut b.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|b| (&b.key, &mut b.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|b| (b.key, b.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|b| (b.key, b.value))
+    }
+}
+
+impl<K, V, S> IntoIterator for IndexMap<K, V, S> {
+    type IntoIter = IntoIter<K, V>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a IndexMap<K, V, S> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut IndexMap<K, V, S> {
+    type IntoIter = IterMut<'a, K, V>;
+    type Item = (&'a K, &'a mut V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> Extend<(K, V)> for IndexMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher + Default> FromIterator<(K, V)> for IndexMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<K: Debug, V: Debug, S> Debug for IndexMap<K, V, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexMap;
+    use crate::hash::{FnvBuildHasher, FxBuildHasher, SeededState};
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn test_insertion_order() {
+        let mut map = IndexMap::new();
+        for (i, word) in ["one", "two", "three", "four"].into_iter().enumerate() {
+            assert_eq!(map.insert(word, i), None);
+        }
+
+        assert_eq!(map.insert("two", 20), Some(1));
+        assert_eq!(map.get(&"two"), Some(&20));
+        assert_eq!(map.get_index_of(&"three"), Some(2));
+        assert_eq!(map.get_index(3), Some((&"four", &3)));
+        assert_eq!(map.first(), Some((&"one", &0)));
+        assert_eq!(map.last(), Some((&"four", &3)));
+        assert!(map.keys().eq(&["one", "two", "three", "four"]));
+        assert_eq!(
+            format!("{:?}", map),
+            r#"{"one": 0, "two": 20, "three": 2, "four": 3}"#
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map: IndexMap<i32, i32> = (0..6).map(|i| (i, i * 10)).collect();
+
+        assert_eq!(map.swap_remove(&1), Some(10));
+        assert!(map.keys().eq(&[0, 5, 2, 3, 4]));
+        assert_eq!(map.get_index_of(&5), Some(1));
+
+        assert_eq!(map.shift_remove(&2), Some(20));
+        assert!(map.keys().eq(&[0, 5, 3, 4]));
+        assert_eq!(map.get_index_of(&4), Some(3));
+        assert_eq!(map.get(&4), Some(&40));
+
+        assert_eq!(map.swap_remove(&4), Some(40));
+        assert_eq!(map.shift_remove(&4), None);
+        assert_eq!(map.pop(), Some((3, 30)));
+        assert!(map.into_iter().eq([(0, 0), (5, 50)]));
+    }
+
+    #[test]
+    fn test_many() {
+        let mut map = IndexMap::new();
+        many(&mut map);
+
+        // Same with the crate's hashers.
+        many(&mut IndexMap::<_, _, FxBuildHasher>::default());
+        many(&mut IndexMap::<_, _, FnvBuildHasher>::default());
+        many(&mut IndexMap::with_hasher(SeededState::new(1, 2)));
+    }
+
+    fn many<S: BuildHasher>(map: &mut IndexMap<i32, i32, S>) {
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        for i in (0..1000).filter(|i| i % 3 == 0) {
+            if i % 2 == 0 {
+                map.swap_remove(&i);
+            } else {
+                map.shift_remove(&i);
+            }
+        }
+
+        assert_eq!(map.len(), 666);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i).is_some(), i % 3 != 0);
+        }
+        for (position, (key, _)) in map.iter().enumerate() {
+            assert_eq!(map.get_index_of(key), Some(position));
+        }
+
+        for (_, value) in &mut *map {
+            *value += 1;
+        }
+        assert_eq!(map.get(&1), Some(&2));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        #[derive(Default)]
+        struct Collide;
+
+        impl std::hash::Hasher for Collide {
+            fn finish(&self) -> u64 {
+                0
+            }
+
+            fn write(&mut self, _: &[u8]) {}
+        }
+
+        // Every key lands on the same slot, so the n-th insert probes n
+        // slots past it.
+        let mut map = IndexMap::with_hasher(std::hash::BuildHasherDefault::<Collide>::default());
+        for i in 0..5 {
+            map.insert(i, i);
+        }
+        map.insert(2, 20);
+
+        let stats = map.stats();
+        assert_eq!(stats.inserts, 6);
+        assert_eq!(stats.total_probe_length, 12);
+        assert_eq!(stats.max_probe_length, 4);
+    }
+}
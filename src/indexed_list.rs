@@ -0,0 +1,519 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Debug,
+    hash::{BuildHasher, Hasher},
+    marker::PhantomData,
+    mem,
+};
+
+use crate::alloc::Global;
+use crate::raw::NodePtr;
+use crate::traits::MemoryUsage;
+
+/// IndexedList node.
+struct Node<T> {
+    left: Link<T>,
+    right: Link<T>,
+    /// Number of nodes in the subtree rooted at this node.
+    size: usize,
+    /// Heap priority, parents always have a higher priority than children.
+    priority: u64,
+    elem: T,
+}
+
+type Link<T> = Option<NodePtr<Node<T>>>;
+
+/// Sequence backed by an implicit treap.
+///
+/// Elements are not ordered by value but by position: the index of an
+/// element is the number of nodes before it in an in-order walk, which the
+/// subtree sizes let us find without storing it. Random priorities keep the
+/// tree balanced in expectation, so [`insert`], [`remove`], [`get`] and
+/// [`split_off`] anywhere in the list are O(log n).
+///
+/// [`insert`]: IndexedList::insert
+/// [`remove`]: IndexedList::remove
+/// [`get`]: IndexedList::get
+/// [`split_off`]: IndexedList::split_off
+pub struct IndexedList<T> {
+    root: Link<T>,
+    rng: u64,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for IndexedList<T> {}
+unsafe impl<T: Sync> Sync for IndexedList<T> {}
+
+/// In-order iterator, see [`IndexedList::iter`].
+pub struct Iter<'a, T> {
+    stack: Vec<NodePtr<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+pub struct IntoIter<T>(IndexedList<T>);
+
+impl<T> Node<T> {
+    /// Create new leaf node.
+    fn new(elem: T, priority: u64) -> NodePtr<Node<T>> {
+        NodePtr::alloc(
+            &Global,
+            Node {
+                left: None,
+                right: None,
+                size: 1,
+                priority,
+                elem,
+            },
+        )
+    }
+}
+
+unsafe fn size<T>(link: Link<T>) -> usize {
+    unsafe { link.map_or(0, |node| node.as_ref().size) }
+}
+
+unsafe fn update_size<T>(node: NodePtr<Node<T>>) {
+    unsafe {
+        node.as_mut().size = size(node.as_ref().left) + size(node.as_ref().right) + 1;
+    }
+}
+
+/// Splits the subtree at `link` into its first `at` elements and the rest.
+unsafe fn split<T>(link: Link<T>, at: usize) -> (Link<T>, Link<T>) {
+    let Some(node) = link else {
+        return (None, None);
+    };
+
+    unsafe {
+        let left_size = size(node.as_ref().left);
+
+        if at <= left_size {
+            let (left, right) = split(node.as_ref().left, at);
+            node.as_mut().left = right;
+            update_size(node);
+            (left, Some(node))
+        } else {
+            let (left, right) = split(node.as_ref().right, at - left_size - 1);
+            node.as_mut().right = left;
+            update_size(node);
+            (Some(node), right)
+        }
+    }
+}
+
+/// Concatenates two subtrees, all of `left` goes before `right`.
+unsafe fn merge<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    let (Some(l), Some(r)) = (left, right) else {
+        return left.or(right);
+    };
+
+    unsafe {
+        if l.as_ref().priority > r.as_ref().priority {
+            l.as_mut().right = merge(l.as_ref().right, right);
+            update_size(l);
+            Some(l)
+        } else {
+            r.as_mut().left = merge(left, r.as_ref().left);
+            update_size(r);
+            Some(r)
+        }
+    }
+}
+
+impl<T> IndexedList<T> {
+    /// Creates a new IndexedList with no elements.
+    pub fn new() -> Self {
+        IndexedList {
+            root: None,
+            rng: RandomState::new().build_hasher().finish() | 1,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        unsafe { size(self.root) }
+    }
+
+    /// Returns whether the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn random_priority(&mut self) -> u64 {
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+
+        self.rng
+    }
+
+    /// Inserts `elem` at position `index`, shifting everything after it.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(index <= self.len(), "Index out of bounds");
+
+        let node = Node::new(elem, self.random_priority());
+
+        unsafe {
+            let (left, right) = split(self.root.take(), index);
+            self.root = merge(merge(left, Some(node)), right);
+        }
+    }
+
+    /// Removes and returns the element at position `index`.
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len(), "Index out of bounds");
+
+        unsafe {
+            let (left, right) = split(self.root.take(), index);
+            let (node, right) = split(right, 1);
+            self.root = merge(left, right);
+
+            node.unwrap().dealloc(&Global).elem
+        }
+    }
+
+    /// Appends `elem` to the end of the list.
+    pub fn push_back(&mut self, elem: T) {
+        self.insert(self.len(), elem);
+    }
+
+    /// Inserts `elem` at the start of the list.
+    pub fn push_front(&mut self, elem: T) {
+        self.insert(0, elem);
+    }
+
+    /// Removes the last element.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let len = self.len();
+        (len > 0).then(|| self.remove(len - 1))
+    }
+
+    /// Removes the first element.
+    pub fn pop_front(&mut self) -> Option<T> {
+        (!self.is_empty()).then(|| self.remove(0))
+    }
+
+    fn node_at(&self, mut index: usize) -> Option<NodePtr<Node<T>>> {
+        let mut current = self.root;
+
+        unsafe {
+            while let Some(node) = current {
+                let left_size = size(node.as_ref().left);
+
+                if index < left_size {
+                    current = node.as_ref().left;
+                } else if index == left_size {
+                    return Some(node);
+                } else {
+                    index -= left_size + 1;
+                    current = node.as_ref().right;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns a reference to the element at position `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.node_at(index)
+            .map(|node| unsafe { &node.as_ref().elem })
+    }
+
+    /// Returns a mutable reference to the element at position `index`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.node_at(index)
+            .map(|node| unsafe { &mut node.as_mut().elem })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Splits the list in two at `at`. `self` keeps the elements `[0, at)`
+    /// and the elements `[at, len)` are returned. Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "Index out of bounds");
+
+        let mut other = Self::new();
+
+        unsafe {
+            let (left, right) = split(self.root.take(), at);
+            self.root = left;
+            other.root = right;
+        }
+
+        other
+    }
+
+    /// Moves all the elements of `other` to the end of `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        unsafe {
+            self.root = merge(self.root.take(), other.root.take());
+        }
+    }
+
+    /// Removes all the elements.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePtr<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            unsafe {
+                let node = node.dealloc(&Global);
+                stack.extend(node.left);
+                stack.extend(node.right);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+            marker: PhantomData,
+        };
+
+        iter.push_left(self.root);
+
+        iter
+    }
+}
+
+impl<T> Drop for IndexedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for IndexedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// Pushes `link` and all its left descendants.
+    fn push_left(&mut self, mut link: Link<T>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = unsafe { node.as_ref().left };
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        unsafe {
+            self.push_left(node.as_ref().right);
+            self.len -= 1;
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for IndexedList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IndexedList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for IndexedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for IndexedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+
+        list
+    }
+}
+
+impl<T: Debug> Debug for IndexedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for IndexedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for IndexedList<T> {}
+
+impl<T> MemoryUsage for IndexedList<T> {
+    fn heap_bytes(&self) -> usize {
+        self.len() * mem::size_of::<Node<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexedList, Link};
+
+    /// Checks the heap order of the priorities and the stored sizes.
+    fn check_treap<T>(list: &IndexedList<T>) {
+        fn check<T>(link: Link<T>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+
+            unsafe {
+                let node = node.as_ref();
+
+                for child in [node.left, node.right].into_iter().flatten() {
+                    assert!(child.as_ref().priority <= node.priority, "heap order");
+                }
+
+                let size = check(node.left) + check(node.right) + 1;
+                assert_eq!(node.size, size, "stale size");
+
+                size
+            }
+        }
+
+        check(list.root);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut list = IndexedList::new();
+        let mut expected = Vec::new();
+
+        for i in 0..200 {
+            let index = (i * 7) % (expected.len() + 1);
+            list.insert(index, i);
+            expected.insert(index, i);
+        }
+        check_treap(&list);
+        assert!(list.iter().eq(expected.iter()));
+
+        for i in 0..100 {
+            let index = (i * 13) % expected.len();
+            assert_eq!(list.remove(index), expected.remove(index));
+        }
+        check_treap(&list);
+        assert_eq!(list.len(), expected.len());
+
+        for (i, elem) in expected.iter().enumerate() {
+            assert_eq!(list.get(i), Some(elem));
+        }
+        assert_eq!(list.get(expected.len()), None);
+    }
+
+    #[test]
+    fn test_split_append() {
+        let mut list: IndexedList<i32> = (0..10).collect();
+
+        let mut tail = list.split_off(6);
+        check_treap(&list);
+        check_treap(&tail);
+        assert!(list.iter().copied().eq(0..6));
+        assert!(tail.iter().copied().eq(6..10));
+
+        assert!(list.split_off(6).is_empty());
+
+        *tail.get_mut(0).unwrap() = 60;
+        tail.append(&mut list);
+        assert!(list.is_empty());
+        assert_eq!(format!("{:?}", tail), "[60, 7, 8, 9, 0, 1, 2, 3, 4, 5]");
+    }
+
+    #[test]
+    fn test_deque_ops() {
+        let mut list = IndexedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), None);
+
+        let list: IndexedList<i32> = (0..5).collect();
+        assert!(list.into_iter().rev().eq((0..5).rev()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_remove_out_of_bounds() {
+        let mut list: IndexedList<i32> = (0..3).collect();
+        list.remove(3);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<IndexedList<String>>();
+        assert_send_sync::<super::Iter<'_, String>>();
+    }
+}
@@ -0,0 +1,621 @@
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, Range},
+};
+
+use crate::alloc::Global;
+use crate::raw::NodePtr;
+use crate::traits::MemoryUsage;
+
+/// IntervalTree node.
+struct Node<K, V> {
+    left: Link<K, V>,
+    right: Link<K, V>,
+    /// Height of the subtree rooted at this node, a leaf has height 1.
+    height: usize,
+    /// Largest `range.end` in the subtree rooted at this node.
+    max_end: K,
+    range: Range<K>,
+    value: V,
+}
+
+type Link<K, V> = Option<NodePtr<Node<K, V>>>;
+
+/// Map from half-open intervals to values.
+///
+/// An AVL tree ordered by interval start where every node also remembers the
+/// largest end below it. That lets [`query_point`] and [`query_overlapping`]
+/// skip whole subtrees that end too early, so a query costs O(log n + m) for
+/// `m` matches. The same interval may be inserted more than once.
+///
+/// [`query_point`]: IntervalTree::query_point
+/// [`query_overlapping`]: IntervalTree::query_overlapping
+pub struct IntervalTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+    _marker: PhantomData<(K, V)>,
+}
+
+unsafe impl<K: Send, V: Send> Send for IntervalTree<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for IntervalTree<K, V> {}
+
+/// In-order iterator, see [`IntervalTree::iter`].
+pub struct Iter<'a, K, V> {
+    stack: Vec<NodePtr<Node<K, V>>>,
+    len: usize,
+    marker: PhantomData<&'a (Range<K>, V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Iter<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
+
+/// Iterator over the intervals that intersect a query, in order of start.
+/// See [`IntervalTree::query_point`] and [`IntervalTree::query_overlapping`].
+pub struct Overlapping<'a, K, V> {
+    stack: Vec<NodePtr<Node<K, V>>>,
+    /// Intervals must end after this.
+    lo: &'a K,
+    /// Intervals must start before (or at, if included) this.
+    hi: Bound<&'a K>,
+    marker: PhantomData<&'a (Range<K>, V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Overlapping<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Overlapping<'a, K, V> {}
+
+impl<K: Clone, V> Node<K, V> {
+    /// Create new leaf node.
+    fn new(range: Range<K>, value: V) -> NodePtr<Node<K, V>> {
+        NodePtr::alloc(
+            &Global,
+            Node {
+                left: None,
+                right: None,
+                height: 1,
+                max_end: range.end.clone(),
+                range,
+                value,
+            },
+        )
+    }
+}
+
+unsafe fn height<K, V>(link: Link<K, V>) -> usize {
+    unsafe { link.map_or(0, |node| node.as_ref().height) }
+}
+
+/// Recomputes the height and the max end of `node` from its children.
+unsafe fn update<K: Ord + Clone, V>(node: NodePtr<Node<K, V>>) {
+    unsafe {
+        let node = node.as_mut();
+        node.height = height(node.left).max(height(node.right)) + 1;
+
+        let mut max_end = &node.range.end;
+        for child in [node.left, node.right].into_iter().flatten() {
+            let child_max = &child.as_ref().max_end;
+            if child_max > max_end {
+                max_end = child_max;
+            }
+        }
+
+        node.max_end = max_end.clone();
+    }
+}
+
+/// Height of the left subtree minus height of the right one.
+unsafe fn balance_factor<K, V>(node: NodePtr<Node<K, V>>) -> isize {
+    unsafe { height(node.as_ref().left) as isize - height(node.as_ref().right) as isize }
+}
+
+/// See [`crate::avl_tree`], the rotations only differ in updating `max_end`.
+unsafe fn rotate_left<K: Ord + Clone, V>(x: NodePtr<Node<K, V>>) -> NodePtr<Node<K, V>> {
+    unsafe {
+        let y = x.as_ref().right.unwrap();
+        x.as_mut().right = y.as_ref().left;
+        y.as_mut().left = Some(x);
+        update(x);
+        update(y);
+        y
+    }
+}
+
+/// Mirror image of [`rotate_left`].
+unsafe fn rotate_right<K: Ord + Clone, V>(x: NodePtr<Node<K, V>>) -> NodePtr<Node<K, V>> {
+    unsafe {
+        let y = x.as_ref().left.unwrap();
+        x.as_mut().left = y.as_ref().right;
+        y.as_mut().right = Some(x);
+        update(x);
+        update(y);
+        y
+    }
+}
+
+/// Fixes `node` and rotates if its subtrees are more than one level apart.
+/// Returns the new root of the subtree.
+unsafe fn rebalance<K: Ord + Clone, V>(node: NodePtr<Node<K, V>>) -> NodePtr<Node<K, V>> {
+    unsafe {
+        update(node);
+
+        match balance_factor(node) {
+            2.. => {
+                let left = node.as_ref().left.unwrap();
+                if balance_factor(left) < 0 {
+                    node.as_mut().left = Some(rotate_left(left));
+                }
+                rotate_right(node)
+            }
+            ..=-2 => {
+                let right = node.as_ref().right.unwrap();
+                if balance_factor(right) > 0 {
+                    node.as_mut().right = Some(rotate_right(right));
+                }
+                rotate_left(node)
+            }
+            _ => node,
+        }
+    }
+}
+
+/// Detaches the minimum of the subtree rooted at `node`. Returns the new
+/// root of the subtree and the detached node.
+unsafe fn remove_min<K: Ord + Clone, V>(
+    node: NodePtr<Node<K, V>>,
+) -> (Link<K, V>, NodePtr<Node<K, V>>) {
+    unsafe {
+        match node.as_ref().left {
+            None => (node.as_ref().right, node),
+            Some(left) => {
+                let (new_left, min) = remove_min(left);
+                node.as_mut().left = new_left;
+                (Some(rebalance(node)), min)
+            }
+        }
+    }
+}
+
+/// Intervals are ordered by start, then by end.
+fn compare<K: Ord>(a: &Range<K>, b: &Range<K>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+impl<K, V> IntervalTree<K, V> {
+    /// Creates a new IntervalTree with no intervals.
+    pub fn new() -> Self {
+        IntervalTree {
+            root: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of intervals stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over all the intervals in order of start.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+            marker: PhantomData,
+        };
+
+        iter.push_left(self.root);
+
+        iter
+    }
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    /// Stores `value` for `range`.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        unsafe {
+            self.root = Some(Self::insert_recursive(self.root, range, value));
+        }
+        self.len += 1;
+    }
+
+    /// Recursive function to insert a new node, returns the new root of the
+    /// subtree.
+    unsafe fn insert_recursive(
+        current: Link<K, V>,
+        range: Range<K>,
+        value: V,
+    ) -> NodePtr<Node<K, V>> {
+        let Some(node) = current else {
+            return Node::new(range, value);
+        };
+
+        unsafe {
+            if compare(&range, &node.as_ref().range) == Ordering::Less {
+                let left = Self::insert_recursive(node.as_ref().left, range, value);
+                node.as_mut().left = Some(left);
+            } else {
+                let right = Self::insert_recursive(node.as_ref().right, range, value);
+                node.as_mut().right = Some(right);
+            }
+
+            rebalance(node)
+        }
+    }
+
+    /// Removes one interval equal to `range` and returns its value.
+    pub fn remove(&mut self, range: &Range<K>) -> Option<V> {
+        let mut removed = None;
+
+        unsafe {
+            self.root = Self::remove_recursive(self.root, range, &mut removed);
+        }
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    /// IntervalTree remove algorithm, returns the new root of the subtree.
+    unsafe fn remove_recursive(
+        current: Link<K, V>,
+        range: &Range<K>,
+        removed: &mut Option<V>,
+    ) -> Link<K, V> {
+        let node = current?;
+
+        unsafe {
+            match compare(range, &node.as_ref().range) {
+                Ordering::Less => {
+                    let left = node.as_ref().left;
+                    node.as_mut().left = Self::remove_recursive(left, range, removed);
+                }
+                Ordering::Greater => {
+                    let right = node.as_ref().right;
+                    node.as_mut().right = Self::remove_recursive(right, range, removed);
+                }
+                Ordering::Equal => {
+                    let node = node.dealloc(&Global);
+                    *removed = Some(node.value);
+
+                    // The in-order successor replaces the removed node.
+                    return match (node.left, node.right) {
+                        (None, child) | (child, None) => child,
+                        (Some(left), Some(right)) => {
+                            let (new_right, successor) = remove_min(right);
+                            successor.as_mut().left = Some(left);
+                            successor.as_mut().right = new_right;
+                            Some(rebalance(successor))
+                        }
+                    };
+                }
+            }
+
+            Some(rebalance(node))
+        }
+    }
+
+    /// Returns whether an interval equal to `range` is stored.
+    pub fn contains(&self, range: &Range<K>) -> bool {
+        let mut current = self.root;
+
+        unsafe {
+            while let Some(node) = current {
+                current = match compare(range, &node.as_ref().range) {
+                    Ordering::Less => node.as_ref().left,
+                    Ordering::Greater => node.as_ref().right,
+                    Ordering::Equal => return true,
+                };
+            }
+        }
+
+        false
+    }
+
+    /// Iterates over the intervals that contain `point`.
+    pub fn query_point<'a>(&'a self, point: &'a K) -> Overlapping<'a, K, V> {
+        self.overlapping(point, Bound::Included(point))
+    }
+
+    /// Iterates over the intervals that share at least one point with
+    /// `range`. Empty intervals, stored or queried, overlap nothing.
+    pub fn query_overlapping<'a>(&'a self, range: &'a Range<K>) -> Overlapping<'a, K, V> {
+        let mut iter = self.overlapping(&range.start, Bound::Excluded(&range.end));
+
+        if range.start >= range.end {
+            iter.stack.clear();
+        }
+
+        iter
+    }
+
+    fn overlapping<'a>(&'a self, lo: &'a K, hi: Bound<&'a K>) -> Overlapping<'a, K, V> {
+        let mut iter = Overlapping {
+            stack: Vec::new(),
+            lo,
+            hi,
+            marker: PhantomData,
+        };
+
+        iter.push_left(self.root);
+
+        iter
+    }
+}
+
+impl<K, V> Drop for IntervalTree<K, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<NodePtr<Node<K, V>>> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            unsafe {
+                let node = node.dealloc(&Global);
+                stack.extend(node.left);
+                stack.extend(node.right);
+            }
+        }
+    }
+}
+
+impl<K, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    /// Pushes `link` and all its left descendants.
+    fn push_left(&mut self, mut link: Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = unsafe { node.as_ref().left };
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        unsafe {
+            self.push_left(node.as_ref().right);
+            self.len -= 1;
+
+            let node = node.as_ref();
+            Some((&node.range, &node.value))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: Ord, V> Overlapping<'a, K, V> {
+    /// Pushes `link` and its left descendants, stopping at the first subtree
+    /// where every interval ends before `lo`.
+    fn push_left(&mut self, mut link: Link<K, V>) {
+        while let Some(ptr) = link {
+            let node = unsafe { ptr.as_ref() };
+
+            if node.max_end <= *self.lo {
+                break;
+            }
+
+            self.stack.push(ptr);
+            link = node.left;
+        }
+    }
+
+    fn starts_in_time(&self, start: &K) -> bool {
+        match self.hi {
+            Bound::Included(hi) => start <= hi,
+            Bound::Excluded(hi) => start < hi,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = unsafe { self.stack.pop()?.as_ref() };
+
+            // Everything left in the walk starts even later.
+            if !self.starts_in_time(&node.range.start) {
+                self.stack.clear();
+                return None;
+            }
+
+            self.push_left(node.right);
+
+            // Empty intervals hold no point, so they never overlap.
+            if node.range.end > *self.lo && node.range.start < node.range.end {
+                return Some((&node.range, &node.value));
+            }
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a IntervalTree<K, V> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a Range<K>, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord + Clone, V> Extend<(Range<K>, V)> for IntervalTree<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> FromIterator<(Range<K>, V)> for IntervalTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for IntervalTree<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V> MemoryUsage for IntervalTree<K, V> {
+    fn heap_bytes(&self) -> usize {
+        self.len * mem::size_of::<Node<K, V>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntervalTree, Link, height};
+    use std::ops::Range;
+
+    /// Checks the AVL balance and the stored max ends.
+    fn check_tree<K: Ord + Clone + std::fmt::Debug, V>(tree: &IntervalTree<K, V>) {
+        fn check<K: Ord + Clone + std::fmt::Debug, V>(link: Link<K, V>) -> Option<K> {
+            let node = unsafe { link?.as_ref() };
+
+            unsafe {
+                assert!(
+                    height(node.left).abs_diff(height(node.right)) <= 1,
+                    "unbalanced"
+                );
+            }
+
+            let max_end = [
+                check(node.left),
+                check(node.right),
+                Some(node.range.end.clone()),
+            ]
+            .into_iter()
+            .flatten()
+            .max();
+            assert_eq!(max_end.as_ref(), Some(&node.max_end), "stale max end");
+
+            max_end
+        }
+
+        check(tree.root);
+    }
+
+    fn intervals() -> Vec<Range<u32>> {
+        let mut seed = 7u32;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % 100
+        };
+
+        (0..200)
+            .map(|_| {
+                let start = next();
+                start..start + next() % 20
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_queries() {
+        let ranges = intervals();
+        let mut tree: IntervalTree<u32, usize> = ranges.iter().cloned().zip(0..).collect();
+        check_tree(&tree);
+        assert_eq!(tree.len(), ranges.len());
+
+        for point in 0..120 {
+            let mut found: Vec<usize> = tree.query_point(&point).map(|(_, v)| *v).collect();
+            let mut expected: Vec<usize> = (0..ranges.len())
+                .filter(|&i| ranges[i].contains(&point))
+                .collect();
+            found.sort();
+            expected.sort();
+            assert_eq!(found, expected);
+        }
+
+        for query in [10..20, 0..1, 50..51, 95..200, 30..30] {
+            let mut found: Vec<usize> = tree.query_overlapping(&query).map(|(_, v)| *v).collect();
+            let mut expected: Vec<usize> = (0..ranges.len())
+                .filter(|_| !query.is_empty())
+                .filter(|&i| !ranges[i].is_empty())
+                .filter(|&i| ranges[i].start < query.end && query.start < ranges[i].end)
+                .collect();
+            found.sort();
+            expected.sort();
+            assert_eq!(found, expected);
+        }
+
+        for range in &ranges[..100] {
+            assert!(tree.remove(range).is_some());
+        }
+        check_tree(&tree);
+        assert_eq!(tree.len(), 100);
+
+        let starts: Vec<u32> = tree.iter().map(|(range, _)| range.start).collect();
+        assert!(starts.is_sorted());
+    }
+
+    #[test]
+    fn test_empty_intervals() {
+        let mut tree = IntervalTree::new();
+        tree.insert(28..28, "empty");
+        tree.insert(30..40, "full");
+        tree.insert(50..50, "empty");
+
+        let found: Vec<_> = tree.query_overlapping(&(26..45)).map(|(_, v)| *v).collect();
+        assert_eq!(found, ["full"]);
+        assert_eq!(tree.query_point(&28).count(), 0);
+        assert_eq!(tree.query_overlapping(&(0..100)).count(), 1);
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut tree = IntervalTree::new();
+        tree.insert(1..5, 'a');
+        tree.insert(1..5, 'b');
+        tree.insert(3..4, 'c');
+
+        assert_eq!(tree.query_point(&3).count(), 3);
+        assert!(tree.query_point(&5).next().is_none());
+        assert!(tree.contains(&(1..5)));
+
+        assert!(tree.remove(&(1..5)).is_some());
+        assert!(tree.remove(&(1..5)).is_some());
+        assert_eq!(tree.remove(&(1..5)), None);
+        assert!(!tree.contains(&(1..5)));
+        assert_eq!(format!("{:?}", tree), "{3..4: 'c'}");
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<IntervalTree<i32, String>>();
+        assert_send_sync::<super::Iter<'_, i32, String>>();
+        assert_send_sync::<super::Overlapping<'_, i32, String>>();
+    }
+}
@@ -0,0 +1,273 @@
+//! Iterator adapters that work on anything iterable, so on every container
+//! of the crate.
+
+use std::{cmp::Ordering, cmp::Reverse, iter::Peekable};
+
+use crate::{BinaryHeap, Vector};
+
+/// Merges iterators that are each sorted into one sorted iterator.
+///
+/// The next element of every source waits in a [`BinaryHeap`], so each step
+/// costs `O(log k)` for `k` sources. Equal elements come out in the order of
+/// their sources.
+///
+/// ```
+/// use ds_rust::{iter_utils::merge_sorted, vector};
+///
+/// let runs = [vector![1, 4], vector![2, 5], vector![3]];
+/// let merged: Vec<_> = merge_sorted(runs.iter().map(|run| run.iter())).collect();
+/// assert_eq!(merged, [&1, &2, &3, &4, &5]);
+/// ```
+pub fn merge_sorted<I, S>(sources: S) -> MergeSorted<I>
+where
+    S: IntoIterator<Item = I>,
+    I: Iterator,
+    I::Item: Ord,
+{
+    let mut iters = Vector::new();
+    let mut heads = BinaryHeap::new();
+
+    for (source, mut iter) in sources.into_iter().enumerate() {
+        if let Some(elem) = iter.next() {
+            heads.push(Reverse(Head { elem, source }));
+        }
+        iters.push(iter);
+    }
+
+    MergeSorted { iters, heads }
+}
+
+pub struct MergeSorted<I: Iterator> {
+    iters: Vector<I>,
+    heads: BinaryHeap<Reverse<Head<I::Item>>>,
+}
+
+/// Next element of one of the sources, ordered by element and then source.
+struct Head<T> {
+    elem: T,
+    source: usize,
+}
+
+impl<T: Ord> PartialEq for Head<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Head<T> {}
+
+impl<T: Ord> PartialOrd for Head<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Head<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.elem
+            .cmp(&other.elem)
+            .then(self.source.cmp(&other.source))
+    }
+}
+
+impl<I> Iterator for MergeSorted<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse(Head { elem, source }) = self.heads.pop()?;
+
+        if let Some(next) = self.iters[source].next() {
+            self.heads.push(Reverse(Head { elem: next, source }));
+        }
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iters.iter().fold(
+            (self.heads.len(), Some(self.heads.len())),
+            |(lo, hi), iter| {
+                let (iter_lo, iter_hi) = iter.size_hint();
+                (
+                    lo.saturating_add(iter_lo),
+                    hi.zip(iter_hi).and_then(|(a, b)| a.checked_add(b)),
+                )
+            },
+        )
+    }
+}
+
+/// Drops consecutive equal elements, so a sorted input comes out with each
+/// element once.
+pub fn dedup_sorted<I>(iter: I) -> DedupSorted<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: PartialEq,
+{
+    DedupSorted {
+        iter: iter.into_iter().peekable(),
+    }
+}
+
+pub struct DedupSorted<I: Iterator> {
+    iter: Peekable<I>,
+}
+
+impl<I> Iterator for DedupSorted<I>
+where
+    I: Iterator,
+    I::Item: PartialEq,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let elem = self.iter.next()?;
+        while self.iter.next_if_eq(&elem).is_some() {}
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        (lo.min(1), hi)
+    }
+}
+
+/// Groups the elements into [`Vector`]s of `size`, the last one may be
+/// shorter.
+///
+/// # Panics
+///
+/// If `size` is 0.
+pub fn chunks<I: IntoIterator>(iter: I, size: usize) -> Chunks<I::IntoIter> {
+    assert!(size > 0, "Chunk size must be positive");
+
+    Chunks {
+        iter: iter.into_iter(),
+        size,
+    }
+}
+
+pub struct Chunks<I> {
+    iter: I,
+    size: usize,
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Vector<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vector::new();
+
+        for elem in self.iter.by_ref().take(self.size) {
+            chunk.push(elem);
+        }
+
+        (!chunk.is_empty()).then_some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        (lo.div_ceil(self.size), hi.map(|hi| hi.div_ceil(self.size)))
+    }
+}
+
+/// The adapters of this module as methods.
+pub trait IterUtils: Iterator + Sized {
+    /// See [`merge_sorted`], merging just `self` and `other`.
+    fn merge_sorted<J>(self, other: J) -> MergeSorted<Self>
+    where
+        J: IntoIterator<IntoIter = Self>,
+        Self::Item: Ord,
+    {
+        merge_sorted([self, other.into_iter()])
+    }
+
+    /// See [`dedup_sorted`].
+    fn dedup_sorted(self) -> DedupSorted<Self>
+    where
+        Self::Item: PartialEq,
+    {
+        dedup_sorted(self)
+    }
+
+    /// See [`chunks`].
+    fn chunks(self, size: usize) -> Chunks<Self> {
+        chunks(self, size)
+    }
+}
+
+impl<I: Iterator> IterUtils for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BTree, DequeueList};
+
+    #[test]
+    fn test_merge_sorted() {
+        let vector = vector![1, 4, 4, 9];
+        let deque = deque![0, 4, 10];
+        let tree = btree![3, 5, 7];
+
+        let merged: Vec<_> = merge_sorted([
+            Box::new(vector.iter()) as Box<dyn Iterator<Item = &i32>>,
+            Box::new(deque.iter()),
+            Box::new(tree.iter()),
+            Box::new(std::iter::empty()),
+        ])
+        .collect();
+        assert_eq!(merged, [&0, &1, &3, &4, &4, &4, &5, &7, &9, &10]);
+
+        // Equal elements keep the order of their sources.
+        let (a, b) = (vector![1, 2], vector![1, 3]);
+        let merged: Vec<_> = a.iter().merge_sorted(b.iter()).collect();
+        assert_eq!(merged, [&1, &1, &2, &3]);
+        assert!(std::ptr::eq(merged[0], &a[0]) && std::ptr::eq(merged[1], &b[0]));
+
+        let mut none = merge_sorted(Vec::<std::vec::IntoIter<i32>>::new());
+        assert_eq!(none.size_hint(), (0, Some(0)));
+        assert_eq!(none.next(), None);
+    }
+
+    #[test]
+    fn test_dedup_sorted() {
+        let list: DequeueList<_> = [1, 1, 2, 3, 3, 3, 1].into_iter().collect();
+        let deduped: Vec<_> = list.into_iter().dedup_sorted().collect();
+        assert_eq!(deduped, [1, 2, 3, 1]);
+
+        let tree = btree![2, 1, 3];
+        let merged: Vec<_> = tree
+            .iter()
+            .merge_sorted(tree.iter())
+            .dedup_sorted()
+            .collect();
+        assert_eq!(merged, [&1, &2, &3]);
+
+        assert_eq!(dedup_sorted(Vec::<i32>::new()).next(), None);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut iter = (0..7).chunks(3);
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(*iter.next().unwrap(), [0, 1, 2]);
+        assert_eq!(*iter.next().unwrap(), [3, 4, 5]);
+        assert_eq!(*iter.next().unwrap(), [6]);
+        assert!(iter.next().is_none());
+
+        let tree: BTree<_> = btree!["b", "a"];
+        let sizes: Vec<_> = chunks(tree.iter(), 5).map(|chunk| chunk.len()).collect();
+        assert_eq!(sizes, [2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_chunks_zero() {
+        chunks([1], 0);
+    }
+}
@@ -1,9 +1,116 @@
+#[macro_use]
+mod macros;
+
+pub mod alloc;
+pub mod error;
+pub mod extract;
+mod guard;
+mod raw;
 pub mod list;
 pub mod dequeue;
 pub mod vec;
 pub mod binary_tree;
+pub mod ring_deque;
+pub mod circular_buffer;
+pub mod array;
+pub mod stack;
+pub mod queue;
+pub mod trie;
+pub mod skip_list;
+pub mod skip_list_map;
+pub mod red_black_tree;
+pub mod avl_tree;
+pub mod weight_balanced_tree;
+pub mod indexed_list;
+pub mod interval_tree;
+pub mod segment_tree;
+pub mod union_find;
+pub mod graph;
+pub mod binary_heap;
+pub mod ttl_cache;
+pub mod slab;
+pub mod slot_map;
+pub mod stable_vec;
+pub mod index_map;
+pub mod hash;
+pub mod bimap;
+pub mod counter;
+pub mod bit_vec;
+pub mod rank_select;
+pub mod gap_buffer;
+pub mod piece_table;
+pub mod suffix_automaton;
+pub mod spatial_tree;
+pub mod dancing_links;
+pub mod persistent_list;
+pub mod lock_free_queue;
+pub mod spsc;
+pub mod x_fast_trie;
+pub mod compare;
+pub mod growth;
+pub mod sort;
+pub mod iter_utils;
+pub mod display_tree;
+pub mod traverse;
+pub mod shared;
+pub mod frozen;
+#[cfg(feature = "safe-impls")]
+pub mod safe;
+pub mod traits;
+
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(not(feature = "stats"))]
+mod stats;
+
+#[cfg(feature = "proptest")]
+pub mod strategy;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[cfg(feature = "rayon")]
+mod rayon_impls;
 
 pub use list::LinkedList;
 pub use dequeue::DequeueList;
-pub use vec::Vector;
-pub use binary_tree::BTree;
\ No newline at end of file
+pub use vec::{SmallVector, Vector};
+pub use binary_tree::BTree;
+pub use ring_deque::RingDeque;
+pub use circular_buffer::CircularBuffer;
+pub use array::{ArrayDeque, ArrayStack, ArrayVector};
+pub use stack::Stack;
+pub use queue::Queue;
+pub use trie::Trie;
+pub use skip_list::SkipList;
+pub use skip_list_map::SkipListMap;
+pub use red_black_tree::RBTree;
+pub use avl_tree::AvlTree;
+pub use weight_balanced_tree::WBTree;
+pub use indexed_list::IndexedList;
+pub use interval_tree::IntervalTree;
+pub use segment_tree::SegmentTree;
+pub use union_find::RollbackUnionFind;
+pub use graph::{Graph, MatrixGraph};
+pub use binary_heap::BinaryHeap;
+pub use ttl_cache::TtlCache;
+pub use slab::Slab;
+pub use slot_map::SlotMap;
+pub use stable_vec::StableVec;
+pub use index_map::IndexMap;
+pub use bimap::BiMap;
+pub use counter::Counter;
+pub use bit_vec::{BitSet, BitVec};
+pub use rank_select::RankSelect;
+pub use gap_buffer::GapBuffer;
+pub use piece_table::PieceTable;
+pub use suffix_automaton::SuffixAutomaton;
+pub use spatial_tree::{BoundingBox, Octree, Quadtree, SpatialTree};
+pub use dancing_links::DancingLinks;
+pub use persistent_list::PersistentList;
+pub use lock_free_queue::LockFreeQueue;
+pub use x_fast_trie::XFastTrie;
+pub use shared::{CowVector, Shared};
+pub use frozen::Frozen;
+pub use traits::{Collection, MemoryUsage, OrderedSet};
+pub use traverse::Traverse;
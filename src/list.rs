@@ -1,28 +1,71 @@
 #![allow(unused)]
 
+use std::{
+    cmp::Ordering,
+    mem,
+    ops::{Index, IndexMut},
+};
+
+use crate::alloc::{Allocator, Global};
+use crate::error::out_of_bounds;
+use crate::extract::{Drain, ExtractIf, Sealed, Source};
+use crate::guard::Guard;
+use crate::raw::NodePtr;
+use crate::sort::{self, ChainNode};
+use crate::stats::ListStats;
+use crate::traits::MemoryUsage;
+
+type Link<T> = Option<NodePtr<Node<T>>>;
+
 struct Node<T> {
     elem: T,
-    next: Option<Box<Node<T>>>,
+    next: Link<T>,
 }
 
 impl<T> Node<T> {
-    pub fn new(elem: T, next: Option<Box<Self>>) -> Self {
+    pub fn new(elem: T, next: Link<T>) -> Self {
         Node { elem, next }
     }
 }
 
-pub struct LinkedList<T> {
-    head: Option<Box<Node<T>>>,
-    tail: *mut Node<T>,
+impl<T> ChainNode for Node<T> {
+    type Elem = T;
+
+    fn elem(&self) -> &T {
+        &self.elem
+    }
+
+    fn next_link(&mut self) -> &mut Link<T> {
+        &mut self.next
+    }
+}
+
+pub struct LinkedList<T, A: Allocator = Global> {
+    head: Link<T>,
+    tail: Link<T>,
     size: u32,
+    alloc: A,
+    stats: ListStats,
 }
 
+unsafe impl<T: Send, A: Allocator + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for LinkedList<T, A> {}
+
 impl<T: std::cmp::PartialEq> LinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T: std::cmp::PartialEq, A: Allocator> LinkedList<T, A> {
+    /// Creates an empty list whose nodes are allocated from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
         LinkedList {
             head: None,
-            tail: std::ptr::null_mut(),
+            tail: None,
             size: 0,
+            alloc,
+            stats: ListStats::default(),
         }
     }
 
@@ -30,20 +73,43 @@ impl<T: std::cmp::PartialEq> LinkedList<T> {
         self.size
     }
 
-    pub fn add(&mut self, elem: T) -> () {
-        let mut node = Box::new(Node::new(elem, None));
+    /// Counters of the nodes allocated so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> ListStats {
+        self.stats
+    }
+
+    pub fn contains(&self, elem: &T) -> bool {
+        let mut node = self.head;
+
+        while let Some(current) = node {
+            let current = unsafe { current.as_ref() };
+            if current.elem == *elem {
+                return true;
+            }
+            node = current.next;
+        }
+
+        false
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
 
-        let new_tail: *mut Node<T> = &mut *node;
+    pub fn add(&mut self, elem: T) {
+        self.stats.record_node_allocation();
+        let node = NodePtr::alloc(&self.alloc, Node::new(elem, None));
 
-        if !self.tail.is_null() {
+        if let Some(tail) = self.tail {
             unsafe {
-                (*self.tail).next = Some(node);
+                tail.as_mut().next = Some(node);
             }
         } else {
             self.head = Some(node);
         }
 
-        self.tail = new_tail;
+        self.tail = Some(node);
 
         self.size += 1;
     }
@@ -52,10 +118,11 @@ impl<T: std::cmp::PartialEq> LinkedList<T> {
     pub fn pop(&mut self) -> Option<T> {
         /// take() replaces the actual head by None an returns it's original value
         self.head.take().map(|h| {
+            let h = unsafe { h.dealloc(&self.alloc) };
             self.head = h.next;
 
             if self.head.is_none() {
-                self.tail = std::ptr::null_mut();
+                self.tail = None;
             }
 
             self.size -= 1;
@@ -67,28 +134,269 @@ impl<T: std::cmp::PartialEq> LinkedList<T> {
     /// Removes the first node with value `elem`
     /// Unlike [`LinkedList::pop`], you can choose which element to remove.
     pub fn remove(&mut self, elem: T) -> Option<T> {
-        let mut node_it = &mut self.head;
-
-        while !node_it.is_none() {
-            /// Avoid borrow checker problems with this approach.
-            let to_remove = node_it.as_ref().unwrap().elem == elem;
-
-            if to_remove {
-                let mut removed = node_it.take().unwrap();
-                *node_it = removed.next.take();
-                
-                let empty = self.head.is_none();
-                if empty {
-                    self.tail = std::ptr::null_mut();
+        let mut prev: Link<T> = None;
+        let mut node_it = self.head;
+
+        while let Some(node) = node_it {
+            unsafe {
+                let next = node.as_ref().next;
+
+                if node.as_ref().elem == elem {
+                    match prev {
+                        Some(prev) => prev.as_mut().next = next,
+                        None => self.head = next,
+                    }
+
+                    if next.is_none() {
+                        self.tail = prev;
+                    }
+
+                    self.size -= 1;
+                    return Some(node.dealloc(&self.alloc).elem);
                 }
 
-                self.size -= 1;
-                return Some(removed.elem);
+                prev = Some(node);
+                node_it = next;
+            }
+        }
+        None
+    }
+
+    /// Keeps only the elements for which `keep` returns `true`.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        self.extract_if(|elem| !keep(elem)).for_each(drop);
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Stable merge sort that relinks the nodes instead of moving elements.
+    /// If `compare` panics the list keeps all its elements, in no particular
+    /// order.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let size = self.size as usize;
+
+        // The old tail may end up anywhere, it's found again when sorting is
+        // done or unwinding.
+        let mut list = Guard::new(self, |list| {
+            let mut node = list.head;
+            while let Some(current) = node {
+                list.tail = node;
+                node = unsafe { current.as_ref().next };
+            }
+        });
+        unsafe { sort::merge_sort_chain(&mut list.head, size, &mut compare) }
+    }
+}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    fn node_at(&self, index: usize) -> Link<T> {
+        let mut node = self.head;
+        for _ in 0..index {
+            node = unsafe { node?.as_ref().next };
+        }
+        node
+    }
+
+    /// Element at `index`, walking from the head.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        unsafe { Some(&self.node_at(index)?.as_ref().elem) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        unsafe { Some(&mut self.node_at(index)?.as_mut().elem) }
+    }
+
+    /// Removes and yields the elements for which `pred` returns `true`, as
+    /// the iterator is advanced. See [`extract`](crate::extract) for what
+    /// happens when it's dropped early or something panics.
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> ExtractIf<ExtractSource<'_, T, A>, F> {
+        ExtractIf::new(self.extract_source(), pred)
+    }
+
+    /// Removes and yields every element from the head, the list is empty
+    /// once the iterator is dropped.
+    pub fn drain(&mut self) -> Drain<ExtractSource<'_, T, A>> {
+        Drain::new(self.extract_source())
+    }
+
+    fn extract_source(&mut self) -> ExtractSource<'_, T, A> {
+        ExtractSource {
+            prev: None,
+            current: self.head,
+            remaining: self.size as usize,
+            list: self,
+        }
+    }
+}
+
+/// [`Source`] of [`LinkedList::extract_if`] and [`LinkedList::drain`]. Nodes
+/// are unlinked one at a time, so the list is whole in between.
+pub struct ExtractSource<'a, T, A: Allocator = Global> {
+    list: &'a mut LinkedList<T, A>,
+    /// Last node kept, the one to relink when `current` is removed.
+    prev: Link<T>,
+    current: Link<T>,
+    remaining: usize,
+}
+
+impl<T, A: Allocator> Sealed for ExtractSource<'_, T, A> {}
+
+impl<T, A: Allocator> Source for ExtractSource<'_, T, A> {
+    type Item = T;
+
+    fn current(&mut self) -> Option<&mut T> {
+        unsafe { Some(&mut self.current?.as_mut().elem) }
+    }
+
+    fn keep(&mut self) {
+        if let Some(node) = self.current {
+            self.prev = Some(node);
+            self.current = unsafe { node.as_ref().next };
+            self.remaining -= 1;
+        }
+    }
+
+    fn remove(&mut self) -> T {
+        let node = self.current.expect("no current element");
+
+        unsafe {
+            let next = node.as_ref().next;
+            match self.prev {
+                Some(prev) => prev.as_mut().next = next,
+                None => self.list.head = next,
             }
-            let node = node_it.as_mut().unwrap();
-            node_it = &mut node.next;
+
+            if next.is_none() {
+                self.list.tail = self.prev;
+            }
+
+            self.current = next;
+            self.remaining -= 1;
+            self.list.size -= 1;
+
+            node.dealloc(&self.list.alloc).elem
+        }
+    }
+
+    fn finish(&mut self) {}
+
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<T, A: Allocator> Index<usize> for LinkedList<T, A> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index)
+            .unwrap_or_else(|| out_of_bounds(index, self.size as usize))
+    }
+}
+
+impl<T, A: Allocator> IndexMut<usize> for LinkedList<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.size as usize;
+        self.get_mut(index).unwrap_or_else(|| out_of_bounds(index, len))
+    }
+}
+
+impl<T, A: Allocator> Drop for LinkedList<T, A> {
+    fn drop(&mut self) {
+        let mut node_it = self.head.take();
+
+        while let Some(node) = node_it {
+            let node = unsafe { node.dealloc(&self.alloc) };
+            node_it = node.next;
+        }
+    }
+}
+
+impl<T: std::cmp::PartialEq> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: std::fmt::Debug, A: Allocator> std::fmt::Debug for LinkedList<T, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+        let mut node = self.head;
+        while let Some(current) = node {
+            let current = unsafe { current.as_ref() };
+            list.entry(&current.elem);
+            node = current.next;
+        }
+        list.finish()
+    }
+}
+
+impl<T, A: Allocator> MemoryUsage for LinkedList<T, A> {
+    fn heap_bytes(&self) -> usize {
+        self.size as usize * mem::size_of::<Node<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.size as usize
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: Allocator> serde::Serialize for LinkedList<T, A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.size as usize))?;
+        let mut node = self.head;
+        while let Some(current) = node {
+            let current = unsafe { current.as_ref() };
+            seq.serialize_element(&current.elem)?;
+            node = current.next;
         }
-        return None;
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de> + PartialEq> serde::Deserialize<'de> for LinkedList<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = crate::serde_impls::SeqVisitor::new(LinkedList::new(), LinkedList::add);
+        deserializer.deserialize_seq(visitor)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a> + PartialEq> arbitrary::Arbitrary<'a> for LinkedList<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.try_fold(LinkedList::new(), |mut list, elem| {
+            list.add(elem?);
+            Ok(list)
+        })
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.try_fold(LinkedList::new(), |mut list, elem| {
+            list.add(elem?);
+            Ok(list)
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + PartialEq> proptest::arbitrary::Arbitrary for LinkedList<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = crate::strategy::LinkedListStrategy<T::Strategy>;
+
+    fn arbitrary_with((size, params): Self::Parameters) -> Self::Strategy {
+        crate::strategy::linked_list(proptest::arbitrary::any_with::<T>(params), size)
     }
 }
 
@@ -125,4 +433,204 @@ mod tests {
         assert_eq!(list.size(), 4);
         assert_eq!(list.remove(5), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn index() {
+        let mut list = LinkedList::new();
+        for elem in [1, 2, 3] {
+            list.add(elem);
+        }
+
+        list[2] *= 10;
+        assert_eq!((list[0], list[1], list[2]), (1, 2, 30));
+        assert_eq!(list.get(3), None);
+        assert_eq!(list.get_mut(3), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 out of bounds for length 1")]
+    fn index_out_of_bounds() {
+        let mut list = LinkedList::new();
+        list.add(1);
+        let _ = list[1];
+    }
+
+    #[test]
+    fn sort() {
+        let mut list = LinkedList::new();
+        for elem in [3, 8, 1, 9, 4, 1] {
+            list.add(elem);
+        }
+
+        list.sort();
+        assert_eq!(format!("{list:?}"), "[1, 1, 3, 4, 8, 9]");
+
+        // The tail has to point at the new last node.
+        list.add(0);
+        list.sort_by(|a, b| b.cmp(a));
+        list.add(-1);
+        assert_eq!(format!("{list:?}"), "[9, 8, 4, 3, 1, 1, 0, -1]");
+        assert_eq!(list.size(), 8);
+    }
+
+    #[test]
+    fn retain() {
+        let mut list = LinkedList::new();
+        for elem in 1..=6 {
+            list.add(elem);
+        }
+
+        list.retain(|elem| elem % 3 != 0);
+        assert_eq!(format!("{list:?}"), "[1, 2, 4, 5]");
+
+        // Removing the last node has to move the tail.
+        list.retain(|elem| *elem != 5);
+        list.add(7);
+        assert_eq!(format!("{list:?}"), "[1, 2, 4, 7]");
+        assert_eq!(list.size(), 4);
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut list = LinkedList::new();
+        for elem in 1..=8 {
+            list.add(elem);
+        }
+
+        let mut evens = list.extract_if(|elem| *elem % 2 == 0);
+        assert_eq!(evens.next(), Some(2));
+        drop(evens);
+        assert_eq!(format!("{list:?}"), "[1, 3, 4, 5, 6, 7, 8]");
+
+        let big: Vec<_> = list.extract_if(|elem| *elem > 5).collect();
+        assert_eq!(big, [6, 7, 8]);
+        list.add(9);
+        assert_eq!(format!("{list:?}"), "[1, 3, 4, 5, 9]");
+
+        let mut drain = list.drain();
+        assert_eq!(drain.next(), Some(1));
+        drop(drain);
+        assert_eq!(list.size(), 0);
+        list.add(1);
+        assert_eq!(format!("{list:?}"), "[1]");
+    }
+
+    #[test]
+    fn panic_safety() {
+        use crate::alloc::Counting;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let alloc = Counting::new();
+        let mut list = LinkedList::new_in(&alloc);
+        for elem in [8, 3, 9, 1, 7, 2, 6, 4, 5, 0] {
+            list.add(elem);
+        }
+
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.sort_by(|a: &i32, b| {
+                calls += 1;
+                assert!(calls < 12);
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.size(), 10);
+        assert_eq!(alloc.live_allocations(), 10);
+
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.retain(|elem| {
+                calls += 1;
+                assert!(calls < 6);
+                elem % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.size() as usize, alloc.live_allocations());
+
+        // The tail is still the last node.
+        list.add(10);
+        list.sort();
+        let mut elems = Vec::new();
+        while let Some(elem) = list.pop() {
+            elems.push(elem);
+        }
+        assert!(elems.is_sorted() && elems.last() == Some(&10));
+        assert_eq!(alloc.live_allocations(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut list = LinkedList::new();
+        list.add(1);
+        list.add(2);
+        list.add(3);
+
+        use serde::{Deserialize, de::value::{Error, SeqDeserializer}};
+        use serde_test::{Token, assert_ser_tokens};
+
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let input = SeqDeserializer::<_, Error>::new([2, 3].into_iter());
+        let mut back = LinkedList::<i32>::deserialize(input).unwrap();
+        assert_eq!(back.size(), 2);
+
+        // The tail pointer has to be valid for `add`.
+        back.add(4);
+        assert_eq!(back.pop(), Some(2));
+        assert_eq!(back.pop(), Some(3));
+        assert_eq!(back.pop(), Some(4));
+    }
+
+    #[test]
+    fn send_sync() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LinkedList<String>>();
+
+        let list = Arc::new(Mutex::new(LinkedList::new()));
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || list.lock().unwrap().add(i))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut list = Arc::try_unwrap(list).ok().unwrap().into_inner().unwrap();
+        let mut values = Vec::new();
+        while let Some(value) = list.pop() {
+            values.push(value);
+        }
+        values.sort_unstable();
+        assert_eq!(values, [0, 1, 2, 3]);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn stats() {
+        let mut list = LinkedList::new();
+        for elem in [1, 2, 3] {
+            list.add(elem);
+        }
+        list.remove(2);
+        list.add(4);
+
+        assert_eq!(list.stats().node_allocations, 4);
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    fmt::Debug,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+struct Node<T> {
+    /// Uninitialized in the dummy node at the head.
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+    /// Link in the list of nodes waiting to be freed, separate from `next`
+    /// since other threads may still follow `next` after removal.
+    retired_next: *mut Node<T>,
+}
+
+/// Unbounded multi-producer multi-consumer FIFO queue by Michael and Scott.
+///
+/// The queue is a singly linked list whose first node is a dummy: `head`
+/// points at the dummy and `tail` at the last node or close to it. Pushing
+/// links a node after the last one with a CAS, popping moves `head` to the
+/// next node with a CAS, and threads that find `tail` lagging behind help
+/// to move it forward. Neither [`push`] nor [`try_pop`] ever block.
+///
+/// Removed nodes can't be freed right away, as other threads may still be
+/// reading them. They are kept in a retired list and freed by the last
+/// thread to leave an operation: at that moment no thread can still hold a
+/// pointer to them. Under constant contention freeing is delayed until the
+/// queue goes quiet, and [`Drop`] frees whatever is left.
+///
+/// [`push`]: LockFreeQueue::push
+/// [`try_pop`]: LockFreeQueue::try_pop
+pub struct LockFreeQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    /// Threads currently inside `push` or `try_pop`.
+    active: AtomicUsize,
+    /// Removed nodes, linked through `retired_next`.
+    retired: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for LockFreeQueue<T> {}
+unsafe impl<T: Send> Sync for LockFreeQueue<T> {}
+
+/// Marks a thread as inside an operation while alive.
+struct Guard<'a, T> {
+    queue: &'a LockFreeQueue<T>,
+}
+
+impl<T> Node<T> {
+    fn new(value: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+            retired_next: ptr::null_mut(),
+        }))
+    }
+}
+
+impl<T> LockFreeQueue<T> {
+    /// Creates a new LockFreeQueue with no elements.
+    pub fn new() -> Self {
+        let dummy = Node::new(MaybeUninit::uninit());
+
+        LockFreeQueue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            active: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    fn enter(&self) -> Guard<'_, T> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        Guard { queue: self }
+    }
+
+    /// Adds `value` at the back of the queue.
+    pub fn push(&self, value: T) {
+        let node = Node::new(MaybeUninit::new(value));
+        let _guard = self.enter();
+
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+
+            if tail != self.tail.load(Ordering::Acquire) {
+                continue;
+            }
+
+            if next.is_null() {
+                let linked = unsafe {
+                    (*tail).next.compare_exchange(
+                        ptr::null_mut(),
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    )
+                };
+
+                if linked.is_ok() {
+                    // Fine if it fails, someone else already helped.
+                    let _ = self.tail.compare_exchange(
+                        tail,
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    );
+                    return;
+                }
+            } else {
+                // `tail` is lagging behind, help the other push finish.
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes the value at the front of the queue, `None` if it is empty.
+    pub fn try_pop(&self) -> Option<T> {
+        let _guard = self.enter();
+
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+
+            if head != self.head.load(Ordering::Acquire) {
+                continue;
+            }
+
+            if next.is_null() {
+                return None;
+            }
+
+            if head == tail {
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                // `next` is the new dummy, only the thread that moved `head`
+                // reads its value.
+                let value = unsafe { ptr::read((*next).value.as_ptr()) };
+                unsafe { self.retire(head) };
+
+                return Some(value);
+            }
+        }
+    }
+
+    /// Returns whether the queue was empty at the time of the call.
+    pub fn is_empty(&self) -> bool {
+        let _guard = self.enter();
+        let head = self.head.load(Ordering::Acquire);
+
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+
+    /// Adds `node` to the retired list.
+    ///
+    /// # Safety
+    ///
+    /// `node` has to be unreachable from the queue.
+    unsafe fn retire(&self, node: *mut Node<T>) {
+        unsafe { self.retire_chain(node, node) };
+    }
+
+    /// Adds the chain from `first` to `last` to the retired list.
+    ///
+    /// # Safety
+    ///
+    /// The chain has to be unreachable from the queue and owned by the caller.
+    unsafe fn retire_chain(&self, first: *mut Node<T>, last: *mut Node<T>) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+
+        loop {
+            unsafe { (*last).retired_next = head };
+
+            match self
+                .retired
+                .compare_exchange(head, first, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Frees the nodes of a chain taken from the retired list.
+    ///
+    /// # Safety
+    ///
+    /// No thread may still hold a pointer to the nodes.
+    unsafe fn free_chain(mut node: *mut Node<T>) {
+        while !node.is_null() {
+            let next = unsafe { (*node).retired_next };
+            drop(unsafe { Box::from_raw(node) });
+            node = next;
+        }
+    }
+}
+
+impl<'a, T> Drop for Guard<'a, T> {
+    fn drop(&mut self) {
+        let queue = self.queue;
+
+        // Take the list before leaving: any thread that could still read
+        // these nodes entered before this point, so if the count drops to
+        // zero below they are all gone. Threads entering later can't reach
+        // nodes that were already unlinked.
+        let retired = if queue.retired.load(Ordering::Relaxed).is_null() {
+            ptr::null_mut()
+        } else {
+            queue.retired.swap(ptr::null_mut(), Ordering::SeqCst)
+        };
+
+        if queue.active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            unsafe { LockFreeQueue::free_chain(retired) };
+        } else if !retired.is_null() {
+            let mut last = retired;
+            unsafe {
+                while !(*last).retired_next.is_null() {
+                    last = (*last).retired_next;
+                }
+                queue.retire_chain(retired, last);
+            }
+        }
+    }
+}
+
+impl<T> Drop for LockFreeQueue<T> {
+    fn drop(&mut self) {
+        while self.try_pop().is_some() {}
+
+        unsafe {
+            LockFreeQueue::free_chain(*self.retired.get_mut());
+            drop(Box::from_raw(*self.head.get_mut()));
+        }
+    }
+}
+
+impl<T> Default for LockFreeQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for LockFreeQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for LockFreeQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Self::new();
+        queue.extend(iter);
+
+        queue
+    }
+}
+
+impl<T> Debug for LockFreeQueue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LockFreeQueue").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LockFreeQueue;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    #[test]
+    fn test_fifo() {
+        let queue = LockFreeQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.try_pop(), None);
+
+        for i in 0..10 {
+            queue.push(i);
+        }
+        assert!(!queue.is_empty());
+
+        for i in 0..5 {
+            assert_eq!(queue.try_pop(), Some(i));
+        }
+        queue.push(10);
+        assert_eq!(
+            (0..6).map(|_| queue.try_pop().unwrap()).collect::<Vec<_>>(),
+            [5, 6, 7, 8, 9, 10]
+        );
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn test_mpmc() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = LockFreeQueue::new();
+        let popped = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+
+        thread::scope(|s| {
+            for p in 0..PRODUCERS {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push(p * PER_PRODUCER + i);
+                    }
+                });
+            }
+
+            for _ in 0..4 {
+                s.spawn(|| {
+                    // Values from one producer come out in order.
+                    let mut last = [None; PRODUCERS];
+                    while popped.load(Ordering::Relaxed) < PRODUCERS * PER_PRODUCER {
+                        if let Some(value) = queue.try_pop() {
+                            let p = value / PER_PRODUCER;
+                            assert!(last[p] < Some(value));
+                            last[p] = Some(value);
+
+                            sum.fetch_add(value, Ordering::Relaxed);
+                            popped.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        let n = PRODUCERS * PER_PRODUCER;
+        assert_eq!(sum.into_inner(), n * (n - 1) / 2);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_drop() {
+        let rc = Arc::new(());
+        let queue: LockFreeQueue<Arc<()>> = (0..10).map(|_| rc.clone()).collect();
+        drop(queue.try_pop());
+        assert_eq!(Arc::strong_count(&rc), 10);
+
+        drop(queue);
+        assert_eq!(Arc::strong_count(&rc), 1);
+    }
+}
@@ -0,0 +1,145 @@
+//! `vec!`-like literals for the collections.
+
+/// Creates a [`Vector`](crate::Vector) holding the arguments, or `n` clones
+/// of a value with `vector![elem; n]`.
+///
+/// ```
+/// use ds_rust::vector;
+///
+/// let v = vector![1, 2, 3];
+/// assert_eq!(*v, [1, 2, 3]);
+///
+/// let zeros = vector![0; 4];
+/// assert_eq!(*zeros, [0, 0, 0, 0]);
+/// ```
+#[macro_export]
+macro_rules! vector {
+    () => {
+        $crate::Vector::new()
+    };
+    ($elem:expr; $n:expr) => {{
+        let elem = $elem;
+        let n: usize = $n;
+        let mut v = $crate::Vector::new();
+        if n > 0 {
+            for _ in 1..n {
+                v.push(::core::clone::Clone::clone(&elem));
+            }
+            v.push(elem);
+        }
+        v
+    }};
+    ($($x:expr),+ $(,)?) => {{
+        let mut v = $crate::Vector::new();
+        $(v.push($x);)+
+        v
+    }};
+}
+
+/// Creates a [`DequeueList`](crate::DequeueList) holding the arguments from
+/// front to back.
+///
+/// ```
+/// use ds_rust::deque;
+///
+/// let list = deque![1, 2, 3];
+/// assert_eq!(list.front(), Some(&1));
+/// assert_eq!(list.back(), Some(&3));
+/// ```
+#[macro_export]
+macro_rules! deque {
+    () => {
+        $crate::DequeueList::new()
+    };
+    ($($x:expr),+ $(,)?) => {{
+        let mut list = $crate::DequeueList::new();
+        $(list.push_back($x);)+
+        list
+    }};
+}
+
+/// Creates a [`LinkedList`](crate::LinkedList) holding the arguments in
+/// order.
+///
+/// ```
+/// use ds_rust::linked_list;
+///
+/// let mut list = linked_list!['a', 'b'];
+/// assert_eq!(list.size(), 2);
+/// assert_eq!(list.pop(), Some('a'));
+/// ```
+#[macro_export]
+macro_rules! linked_list {
+    () => {
+        $crate::LinkedList::new()
+    };
+    ($($x:expr),+ $(,)?) => {{
+        let mut list = $crate::LinkedList::new();
+        $(list.add($x);)+
+        list
+    }};
+}
+
+/// Creates a [`BTree`](crate::BTree) by inserting the arguments in order,
+/// so the first one ends up as the root.
+///
+/// ```
+/// use ds_rust::btree;
+///
+/// let tree = btree![2, 1, 3];
+/// assert!(tree.iter().eq([1, 2, 3].iter()));
+/// ```
+#[macro_export]
+macro_rules! btree {
+    () => {
+        $crate::BTree::new()
+    };
+    ($($x:expr),+ $(,)?) => {{
+        let mut tree = $crate::BTree::new();
+        $(tree.insert($x);)+
+        tree
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BTree, DequeueList, LinkedList, Vector};
+
+    #[test]
+    fn test_vector() {
+        let empty: Vector<i32> = vector![];
+        assert!(empty.is_empty());
+
+        let v = vector!["a".to_string(), "b".to_string(),];
+        assert_eq!(*v, ["a", "b"]);
+
+        let v = vector!["x".to_string(); 3];
+        assert_eq!(*v, ["x", "x", "x"]);
+
+        let v: Vector<String> = vector![String::new(); 0];
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_lists() {
+        let empty: DequeueList<i32> = deque![];
+        assert!(empty.is_empty());
+        assert!(deque![1, 2, 3].into_iter().eq(1..=3));
+
+        let empty: LinkedList<i32> = linked_list![];
+        assert_eq!(empty.size(), 0);
+        let mut list = linked_list![1, 2, 3];
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.size(), 2);
+    }
+
+    #[test]
+    fn test_btree() {
+        let empty: BTree<i32> = btree![];
+        assert!(empty.is_empty());
+
+        let tree = btree![40, 20, 60, 20];
+        assert_eq!(tree.size(), 3);
+        assert!(tree.iter().eq([20, 40, 60].iter()));
+    }
+}
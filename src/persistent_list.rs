@@ -0,0 +1,256 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    /// Length of the list starting at this node.
+    len: usize,
+}
+
+/// Immutable singly linked list whose tails are shared between lists.
+///
+/// Lists are never modified: [`push_front`] and [`tail`] return new lists in
+/// O(1) that point to the nodes of the original one, and cloning a list only
+/// bumps a reference count. A node is freed once no list reaches it anymore.
+///
+/// [`push_front`]: PersistentList::push_front
+/// [`tail`]: PersistentList::tail
+pub struct PersistentList<T> {
+    head: Link<T>,
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<T> PersistentList<T> {
+    /// Creates an empty PersistentList.
+    pub fn new() -> Self {
+        PersistentList { head: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns a new list with `elem` in front of `self`, in O(1).
+    pub fn push_front(&self, elem: T) -> Self {
+        PersistentList {
+            head: Some(Rc::new(Node {
+                elem,
+                next: self.head.clone(),
+                len: self.len() + 1,
+            })),
+        }
+    }
+
+    /// Returns the first element.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    /// Returns the list without its first element in O(1), `None` if the
+    /// list is empty.
+    pub fn tail(&self) -> Option<Self> {
+        self.head.as_ref().map(|node| PersistentList {
+            head: node.next.clone(),
+        })
+    }
+
+    /// Splits the list into its first element and the rest.
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.head.as_ref().map(|node| {
+            (
+                &node.elem,
+                PersistentList {
+                    head: node.next.clone(),
+                },
+            )
+        })
+    }
+
+    /// Returns whether both lists are the same nodes, in O(1).
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T: Clone> PersistentList<T> {
+    /// Returns a new list with the elements in reverse order, in O(n).
+    pub fn reverse(&self) -> Self {
+        let mut reversed = Self::new();
+        for elem in self {
+            reversed = reversed.push_front(elem.clone());
+        }
+
+        reversed
+    }
+}
+
+impl<T> Clone for PersistentList<T> {
+    /// O(1), the nodes are shared.
+    fn clone(&self) -> Self {
+        PersistentList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        // Iteratively free the nodes only this list owns, dropping them
+        // recursively would overflow the stack on long lists.
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for PersistentList<T> {
+    /// Keeps the order of `iter`, the first element ends up at the head.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+
+        let mut list = Self::new();
+        for elem in elems.into_iter().rev() {
+            list = list.push_front(elem);
+        }
+
+        list
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && (self.ptr_eq(other) || self.iter().eq(other))
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+impl<T: Hash> Hash for PersistentList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T: Debug> Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersistentList;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_sharing() {
+        let empty = PersistentList::new();
+        let a = empty.push_front(1);
+        let b = a.push_front(2);
+        let c = a.push_front(3);
+
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(c.iter().copied().collect::<Vec<_>>(), [3, 1]);
+        assert!(b.tail().unwrap().ptr_eq(&c.tail().unwrap()));
+        assert_eq!(a.len(), 1);
+        assert_eq!(empty.len(), 0);
+
+        let (head, rest) = c.uncons().unwrap();
+        assert_eq!(*head, 3);
+        assert_eq!(rest, a);
+        assert!(empty.tail().is_none());
+        assert_eq!(empty.head(), None);
+    }
+
+    #[test]
+    fn test_collect_reverse() {
+        let list: PersistentList<i32> = (0..5).collect();
+        assert_eq!(list.head(), Some(&0));
+        assert_eq!(list.iter().len(), 5);
+        assert_eq!(list.reverse(), (0..5).rev().collect());
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4]");
+    }
+
+    #[test]
+    fn test_drop() {
+        let rc = Rc::new(());
+        let base: PersistentList<Rc<()>> = (0..10).map(|_| rc.clone()).collect();
+        let longer = base.push_front(rc.clone());
+        let copy = longer.clone();
+        assert_eq!(Rc::strong_count(&rc), 12);
+
+        drop(longer);
+        drop(base);
+        assert_eq!(Rc::strong_count(&rc), 12);
+
+        drop(copy);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        // Would overflow the stack if nodes were dropped recursively.
+        let long: PersistentList<u32> = (0..1_000_000).collect();
+        drop(long);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::{Bound, RangeBounds},
+};
+
+/// Text buffer that never moves or rewrites the text it holds.
+///
+/// The document is a sequence of pieces, each one a slice of either the
+/// original text or of an append-only buffer that receives everything typed
+/// since. Editing only splits and replaces pieces, so an edit is O(pieces)
+/// no matter how large the text is.
+///
+/// Since the buffers are never modified, old pieces stay valid forever and
+/// every edit is logged as the pieces it replaced, which makes [`undo`] and
+/// [`redo`] just another replacement.
+///
+/// Positions are byte offsets and have to fall on char boundaries.
+///
+/// [`undo`]: PieceTable::undo
+/// [`redo`]: PieceTable::redo
+pub struct PieceTable {
+    original: String,
+    add: String,
+    pieces: Vec<Piece>,
+    len: usize,
+    /// Edits that can be undone, most recent last.
+    done: Vec<Edit>,
+    /// Undone edits that can be redone, most recent last.
+    undone: Vec<Edit>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Original,
+    Add,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    source: Source,
+    start: usize,
+    len: usize,
+}
+
+/// `removed` were replaced by `inserted` starting at piece `index`.
+struct Edit {
+    index: usize,
+    removed: Vec<Piece>,
+    inserted: Vec<Piece>,
+}
+
+pub struct Chunks<'a> {
+    table: &'a PieceTable,
+    pieces: std::slice::Iter<'a, Piece>,
+}
+
+impl Piece {
+    fn slice(self, start: usize, end: usize) -> Piece {
+        Piece {
+            source: self.source,
+            start: self.start + start,
+            len: end - start,
+        }
+    }
+}
+
+impl PieceTable {
+    /// Creates a PieceTable holding `original`.
+    pub fn new(original: impl Into<String>) -> Self {
+        let original = original.into();
+        let len = original.len();
+        let mut pieces = Vec::new();
+
+        if len > 0 {
+            pieces.push(Piece {
+                source: Source::Original,
+                start: 0,
+                len,
+            });
+        }
+
+        PieceTable {
+            original,
+            add: String::new(),
+            pieces,
+            len,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// Length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of pieces the text is currently split into.
+    pub fn piece_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    fn piece_str(&self, piece: &Piece) -> &str {
+        let buf = match piece.source {
+            Source::Original => &self.original,
+            Source::Add => &self.add,
+        };
+
+        &buf[piece.start..piece.start + piece.len]
+    }
+
+    /// Iterates over the pieces of the text, in order.
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            table: self,
+            pieces: self.pieces.iter(),
+        }
+    }
+
+    /// Rebuilds the whole text.
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.len);
+        text.extend(self.chunks());
+
+        text
+    }
+
+    /// Inserts `text` at byte offset `pos`.
+    /// Panics if `pos` is out of bounds or not on a char boundary.
+    pub fn insert(&mut self, pos: usize, text: &str) {
+        self.replace(pos..pos, text);
+    }
+
+    /// Removes the text in `range`.
+    /// Panics if `range` is out of bounds or not on char boundaries.
+    pub fn delete<R: RangeBounds<usize>>(&mut self, range: R) {
+        self.replace(range, "");
+    }
+
+    /// Replaces the text in `range` with `text`, as a single edit.
+    /// Panics if `range` is out of bounds or not on char boundaries.
+    pub fn replace<R: RangeBounds<usize>>(&mut self, range: R, text: &str) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+
+        assert!(start <= end && end <= self.len, "Range out of bounds");
+        assert!(
+            self.is_char_boundary(start) && self.is_char_boundary(end),
+            "Range not on char boundaries"
+        );
+
+        if start == end && text.is_empty() {
+            return;
+        }
+
+        // First piece that has bytes at or after `start`.
+        let mut first = self.pieces.len();
+        let mut first_pos = self.len;
+        let mut pos = 0;
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if start < pos + piece.len {
+                first = i;
+                first_pos = pos;
+                break;
+            }
+            pos += piece.len;
+        }
+
+        let added = Piece {
+            source: Source::Add,
+            start: self.add.len(),
+            len: text.len(),
+        };
+        self.add.push_str(text);
+
+        let mut inserted = Vec::new();
+        let (index, removed_len);
+
+        if start == end && start == first_pos {
+            // Pure insertion between two pieces. Typing grows the previous
+            // piece instead of adding one per keystroke.
+            match first.checked_sub(1).map(|i| self.pieces[i]) {
+                Some(prev)
+                    if prev.source == Source::Add && prev.start + prev.len == added.start =>
+                {
+                    index = first - 1;
+                    removed_len = 1;
+                    inserted.push(Piece {
+                        len: prev.len + added.len,
+                        ..prev
+                    });
+                }
+                _ => {
+                    index = first;
+                    removed_len = 0;
+                    inserted.push(added);
+                }
+            }
+        } else {
+            // Last piece that has bytes before `end`, or `first` for an
+            // insertion in the middle of a piece.
+            let mut last = first;
+            let mut last_pos = first_pos;
+            while last_pos + self.pieces[last].len < end {
+                last_pos += self.pieces[last].len;
+                last += 1;
+            }
+
+            let head = self.pieces[first];
+            let tail = self.pieces[last];
+
+            if start > first_pos {
+                inserted.push(head.slice(0, start - first_pos));
+            }
+            if !text.is_empty() {
+                inserted.push(added);
+            }
+            if end < last_pos + tail.len {
+                inserted.push(tail.slice(end - last_pos, tail.len));
+            }
+
+            index = first;
+            removed_len = last + 1 - first;
+        }
+
+        let removed = self
+            .pieces
+            .splice(index..index + removed_len, inserted.iter().copied())
+            .collect();
+        self.len = self.len - (end - start) + text.len();
+
+        self.done.push(Edit {
+            index,
+            removed,
+            inserted,
+        });
+        self.undone.clear();
+    }
+
+    fn is_char_boundary(&self, pos: usize) -> bool {
+        let mut offset = 0;
+        for chunk in self.chunks() {
+            if pos <= offset + chunk.len() {
+                return chunk.is_char_boundary(pos - offset);
+            }
+            offset += chunk.len();
+        }
+
+        pos == 0
+    }
+
+    /// Swaps the pieces of `edit` back, returning the edit to redo it.
+    fn revert(&mut self, edit: Edit) -> Edit {
+        let Edit {
+            index,
+            removed,
+            inserted,
+        } = edit;
+
+        self.pieces
+            .splice(index..index + inserted.len(), removed.iter().copied());
+        self.len = self.len + removed.iter().map(|p| p.len).sum::<usize>()
+            - inserted.iter().map(|p| p.len).sum::<usize>();
+
+        Edit {
+            index,
+            removed: inserted,
+            inserted: removed,
+        }
+    }
+
+    /// Reverts the last edit. Returns `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.done.pop() else {
+            return false;
+        };
+
+        let edit = self.revert(edit);
+        self.undone.push(edit);
+
+        true
+    }
+
+    /// Applies the last undone edit again. Returns `false` if there is none.
+    /// Any new edit clears what can be redone.
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.undone.pop() else {
+            return false;
+        };
+
+        let edit = self.revert(edit);
+        self.done.push(edit);
+
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces.next().map(|piece| self.table.piece_str(piece))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pieces.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Chunks<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back()
+            .map(|piece| self.table.piece_str(piece))
+    }
+}
+
+impl<'a> ExactSizeIterator for Chunks<'a> {}
+
+impl Default for PieceTable {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl From<&str> for PieceTable {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for PieceTable {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl Display for PieceTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for PieceTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.chunks()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PieceTable;
+
+    #[test]
+    fn test_edits() {
+        let mut table = PieceTable::new("the quick fox");
+        table.insert(10, "brown ");
+        table.insert(0, "> ");
+        table.insert(table.len(), " jumps");
+        assert_eq!(table.text(), "> the quick brown fox jumps");
+
+        table.delete(6..12);
+        assert_eq!(table.text(), "> the brown fox jumps");
+
+        table.replace(..2, "");
+        table.replace(4..=8, "red");
+        assert_eq!(table.to_string(), "the red fox jumps");
+        assert_eq!(table.len(), 17);
+
+        table.delete(..);
+        assert!(table.is_empty());
+        assert_eq!(table.piece_count(), 0);
+    }
+
+    #[test]
+    fn test_typing_extends_piece() {
+        let mut table = PieceTable::new("ab");
+        for (i, c) in ["x", "y", "z"].into_iter().enumerate() {
+            table.insert(1 + i, c);
+        }
+
+        assert_eq!(table.text(), "axyzb");
+        assert_eq!(table.chunks().collect::<Vec<_>>(), ["a", "xyz", "b"]);
+
+        // Every keystroke is still its own edit.
+        table.undo();
+        assert_eq!(table.text(), "axyb");
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut table = PieceTable::from("hello");
+        assert!(!table.undo());
+
+        table.insert(5, " world");
+        table.replace(0..1, "J");
+        table.delete(5..);
+        assert_eq!(table.text(), "Jello");
+
+        assert!(table.undo());
+        assert_eq!(table.text(), "Jello world");
+        assert!(table.undo());
+        assert!(table.undo());
+        assert_eq!(table.text(), "hello");
+        assert!(!table.can_undo());
+
+        assert!(table.redo());
+        assert_eq!(table.text(), "hello world");
+
+        table.insert(0, "> ");
+        assert!(!table.can_redo());
+        assert_eq!(table.text(), "> hello world");
+
+        table.undo();
+        table.undo();
+        assert_eq!(table.text(), "hello");
+        assert_eq!(table.len(), 5);
+    }
+
+    #[test]
+    fn test_matches_string() {
+        let mut table = PieceTable::new("0123456789");
+        let mut text = String::from("0123456789");
+
+        let mut seed = 7usize;
+        for step in 0..200 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let a = (seed >> 33) % (text.len() + 1);
+            let b = (seed >> 17) % (text.len() + 1);
+            let (start, end) = (a.min(b), a.max(b));
+            let insert = ["", "ab", "c", "defg"][step % 4];
+
+            table.replace(start..end, insert);
+            text.replace_range(start..end, insert);
+            assert_eq!(table.text(), text);
+        }
+
+        while table.undo() {}
+        assert_eq!(table.text(), "0123456789");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_char_boundary() {
+        let mut table = PieceTable::new("ñ");
+        table.insert(1, "n");
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+
+use crate::ring_deque::RingDeque;
+use crate::traits::MemoryUsage;
+
+/// FIFO queue stored in a [`RingDeque`].
+///
+/// Only the queue operations are exposed, values go in at the back and come
+/// out at the front.
+pub struct Queue<T> {
+    deque: RingDeque<T>,
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue {
+            deque: RingDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deque.is_empty()
+    }
+
+    /// Adds `value` to the back of the queue.
+    pub fn enqueue(&mut self, value: T) {
+        self.deque.push_back(value);
+    }
+
+    /// Removes the value at the front of the queue.
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    /// Returns the value that the next [`Queue::dequeue`] would remove.
+    pub fn peek(&self) -> Option<&T> {
+        self.deque.front()
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for Queue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.enqueue(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Self::new();
+        queue.extend(iter);
+
+        queue
+    }
+}
+
+impl<T: Debug> Debug for Queue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.deque.fmt(f)
+    }
+}
+
+impl<T> MemoryUsage for Queue<T> {
+    fn heap_bytes(&self) -> usize {
+        self.deque.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Queue;
+
+    #[test]
+    fn test_basics() {
+        let mut queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.dequeue(), None);
+        assert_eq!(queue.peek(), None);
+
+        queue.enqueue(1);
+        queue.enqueue(2);
+        queue.enqueue(3);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.peek(), Some(&1));
+        assert_eq!(queue.dequeue(), Some(1));
+
+        queue.enqueue(4);
+        assert_eq!(format!("{:?}", queue), "[2, 3, 4]");
+        assert_eq!(queue.dequeue(), Some(2));
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.dequeue(), Some(4));
+        assert_eq!(queue.dequeue(), None);
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::Debug;
+
+use crate::{BitVec, Vector};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Words per superblock, 512 bits.
+const SUPERBLOCK_WORDS: usize = 8;
+
+/// Read-only [`BitVec`] indexed to count and find set bits quickly.
+///
+/// Every superblock of 512 bits stores the number of ones before it, and
+/// every word the number of ones before it inside its superblock, which
+/// costs about 25% on top of the bits. [`rank1`] adds those two to the ones
+/// of a single word, O(1). [`select1`] binary searches the superblocks then
+/// scans at most 8 words, O(log n).
+///
+/// [`rank1`]: RankSelect::rank1
+/// [`select1`]: RankSelect::select1
+pub struct RankSelect {
+    bits: BitVec,
+    /// Ones before each superblock.
+    superblocks: Vector<usize>,
+    /// Ones before each word, counted from the start of its superblock.
+    blocks: Vector<u16>,
+    ones: usize,
+}
+
+impl RankSelect {
+    /// Builds the index over `bits` in O(n).
+    pub fn new(bits: BitVec) -> Self {
+        let mut superblocks = Vector::new();
+        let mut blocks = Vector::new();
+        let mut ones = 0;
+        let mut in_superblock = 0;
+
+        for (i, word) in bits.as_words().iter().enumerate() {
+            if i % SUPERBLOCK_WORDS == 0 {
+                superblocks.push(ones);
+                in_superblock = 0;
+            }
+
+            blocks.push(in_superblock as u16);
+
+            let count = word.count_ones() as usize;
+            in_superblock += count;
+            ones += count;
+        }
+
+        RankSelect {
+            bits,
+            superblocks,
+            blocks,
+            ones,
+        }
+    }
+
+    /// Number of bits.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    pub fn count_zeros(&self) -> usize {
+        self.len() - self.ones
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.bits.get(index)
+    }
+
+    pub fn bit_vec(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Gives back the bits, dropping the index.
+    pub fn into_inner(self) -> BitVec {
+        self.bits
+    }
+
+    /// Number of ones in the first `index` bits. Panics if `index > len`.
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(index <= self.len(), "Index out of bounds");
+
+        let word = index / WORD_BITS;
+        let bit = index % WORD_BITS;
+
+        // `index == len` on a word boundary points past the last word.
+        if word == self.blocks.len() {
+            return self.ones;
+        }
+
+        let partial = self.bits.as_words()[word] & ((1 << bit) - 1);
+
+        self.superblocks[word / SUPERBLOCK_WORDS]
+            + self.blocks[word] as usize
+            + partial.count_ones() as usize
+    }
+
+    /// Number of zeros in the first `index` bits. Panics if `index > len`.
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Position of the one with rank `k`, that is the `k + 1`-th one.
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones {
+            return None;
+        }
+
+        // Last superblock with fewer than `k + 1` ones before it.
+        let superblock = self.superblocks.partition_point(|&ones| ones <= k) - 1;
+        let mut remaining = k - self.superblocks[superblock];
+
+        let words = self.bits.as_words();
+        let first = superblock * SUPERBLOCK_WORDS;
+
+        for (i, &word) in words.iter().enumerate().skip(first).take(SUPERBLOCK_WORDS) {
+            let count = word.count_ones() as usize;
+
+            if remaining < count {
+                return Some(i * WORD_BITS + select_in_word(word, remaining));
+            }
+
+            remaining -= count;
+        }
+
+        unreachable!("superblock counts out of sync")
+    }
+
+    /// Position of the zero with rank `k`, that is the `k + 1`-th zero.
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.count_zeros() {
+            return None;
+        }
+
+        let zeros_before = |superblock: usize| {
+            superblock * SUPERBLOCK_WORDS * WORD_BITS - self.superblocks[superblock]
+        };
+
+        // Last superblock with fewer than `k + 1` zeros before it.
+        let (mut superblock, mut end) = (0, self.superblocks.len());
+        while end - superblock > 1 {
+            let mid = (superblock + end) / 2;
+            if zeros_before(mid) <= k {
+                superblock = mid;
+            } else {
+                end = mid;
+            }
+        }
+
+        let mut remaining = k - zeros_before(superblock);
+
+        let words = self.bits.as_words();
+        let first = superblock * SUPERBLOCK_WORDS;
+
+        for (i, &word) in words.iter().enumerate().skip(first).take(SUPERBLOCK_WORDS) {
+            // The padding past `len` is zero too, but `k < count_zeros`
+            // keeps the search from reaching it.
+            let count = word.count_zeros() as usize;
+
+            if remaining < count {
+                return Some(i * WORD_BITS + select_in_word(!word, remaining));
+            }
+
+            remaining -= count;
+        }
+
+        unreachable!("superblock counts out of sync")
+    }
+}
+
+/// Position of the set bit with rank `k` in `word`.
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0..k {
+        // Clear the lowest set bit.
+        word &= word - 1;
+    }
+
+    word.trailing_zeros() as usize
+}
+
+impl From<BitVec> for RankSelect {
+    fn from(bits: BitVec) -> Self {
+        Self::new(bits)
+    }
+}
+
+impl Debug for RankSelect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RankSelect").field(&self.bits).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RankSelect;
+    use crate::BitVec;
+
+    fn sample() -> Vec<bool> {
+        (0..2000usize).map(|i| (i * i + i / 7) % 5 < 2).collect()
+    }
+
+    #[test]
+    fn test_rank() {
+        let bits = sample();
+        let index = RankSelect::new(bits.iter().copied().collect());
+
+        let mut ones = 0;
+        for (i, &bit) in bits.iter().enumerate() {
+            assert_eq!(index.rank1(i), ones);
+            assert_eq!(index.rank0(i), i - ones);
+            ones += bit as usize;
+        }
+
+        assert_eq!(index.rank1(bits.len()), ones);
+        assert_eq!(index.count_ones(), ones);
+    }
+
+    #[test]
+    fn test_select() {
+        let bits = sample();
+        let index = RankSelect::from(bits.iter().copied().collect::<BitVec>());
+
+        let ones: Vec<usize> = (0..bits.len()).filter(|&i| bits[i]).collect();
+        let zeros: Vec<usize> = (0..bits.len()).filter(|&i| !bits[i]).collect();
+
+        for (k, &position) in ones.iter().enumerate() {
+            assert_eq!(index.select1(k), Some(position));
+        }
+        for (k, &position) in zeros.iter().enumerate() {
+            assert_eq!(index.select0(k), Some(position));
+        }
+
+        assert_eq!(index.select1(ones.len()), None);
+        assert_eq!(index.select0(zeros.len()), None);
+    }
+
+    #[test]
+    fn test_edges() {
+        let empty = RankSelect::new(BitVec::new());
+        assert_eq!(empty.rank1(0), 0);
+        assert_eq!(empty.select1(0), None);
+
+        let full = RankSelect::new(BitVec::repeat(true, 1024));
+        assert_eq!(full.rank1(1024), 1024);
+        assert_eq!(full.select1(1023), Some(1023));
+        assert_eq!(full.select0(0), None);
+    }
+}
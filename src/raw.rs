@@ -0,0 +1,264 @@
+//! Node pointers shared by the linked structures, so the unsafe pointer code
+//! (allocating, freeing and relinking nodes) is written and audited once.
+
+use std::{fmt, ptr::NonNull};
+
+use crate::alloc::{self, Allocator};
+
+/// Pointer to a node allocated by one of the collections. It's `Copy` like
+/// `NonNull`, the owning collection decides when the node gets freed.
+pub(crate) struct NodePtr<N>(NonNull<N>);
+
+impl<N> NodePtr<N> {
+    /// Moves `node` into a block from `alloc`.
+    pub(crate) fn alloc<A: Allocator>(alloc: &A, node: N) -> Self {
+        NodePtr(alloc::allocate_value(alloc, node))
+    }
+
+    /// Moves the node out and frees its block.
+    ///
+    /// # Safety
+    ///
+    /// The node has to come from [`NodePtr::alloc`] with the same allocator,
+    /// and neither this pointer nor its copies can be used afterwards.
+    pub(crate) unsafe fn dealloc<A: Allocator>(self, alloc: &A) -> N {
+        unsafe { alloc::take_value(alloc, self.0) }
+    }
+
+    /// # Safety
+    ///
+    /// The node has to be alive for `'a` and not mutated meanwhile.
+    pub(crate) unsafe fn as_ref<'a>(self) -> &'a N {
+        unsafe { &*self.0.as_ptr() }
+    }
+
+    /// # Safety
+    ///
+    /// The node has to be alive for `'a` and not accessed through any other
+    /// pointer meanwhile.
+    pub(crate) unsafe fn as_mut<'a>(self) -> &'a mut N {
+        unsafe { &mut *self.0.as_ptr() }
+    }
+}
+
+impl<N> Clone for NodePtr<N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N> Copy for NodePtr<N> {}
+
+impl<N> PartialEq for NodePtr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<N> Eq for NodePtr<N> {}
+
+impl<N> fmt::Debug for NodePtr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.0, f)
+    }
+}
+
+/// Nodes of a doubly linked chain, whose ends are kept by the owner as a
+/// `head` and a `tail`.
+pub(crate) trait DoublyLinked: Sized {
+    fn prev_mut(&mut self) -> &mut Option<NodePtr<Self>>;
+
+    fn next_mut(&mut self) -> &mut Option<NodePtr<Self>>;
+}
+
+/// Links the chain `first..=last` between `prev` and `next`.
+///
+/// # Safety
+///
+/// `prev` and `next` have to be adjacent nodes of the chain starting at
+/// `head` and ending at `tail`, `None` standing for the respective end. The
+/// new nodes have to be alive and not part of any chain.
+pub(crate) unsafe fn link_chain<N: DoublyLinked>(
+    head: &mut Option<NodePtr<N>>,
+    tail: &mut Option<NodePtr<N>>,
+    prev: Option<NodePtr<N>>,
+    next: Option<NodePtr<N>>,
+    first: NodePtr<N>,
+    last: NodePtr<N>,
+) {
+    unsafe {
+        *first.as_mut().prev_mut() = prev;
+        *last.as_mut().next_mut() = next;
+
+        match prev {
+            Some(prev) => *prev.as_mut().next_mut() = Some(first),
+            None => *head = Some(first),
+        }
+
+        match next {
+            Some(next) => *next.as_mut().prev_mut() = Some(last),
+            None => *tail = Some(last),
+        }
+    }
+}
+
+/// Links a single `node` between `prev` and `next`.
+///
+/// # Safety
+///
+/// Same as [`link_chain`].
+pub(crate) unsafe fn link<N: DoublyLinked>(
+    head: &mut Option<NodePtr<N>>,
+    tail: &mut Option<NodePtr<N>>,
+    prev: Option<NodePtr<N>>,
+    next: Option<NodePtr<N>>,
+    node: NodePtr<N>,
+) {
+    unsafe { link_chain(head, tail, prev, next, node, node) }
+}
+
+/// Takes `node` out of the chain, joining its neighbours. The node is left
+/// with no links, still allocated.
+///
+/// # Safety
+///
+/// `node` has to be part of the chain starting at `head` and ending at
+/// `tail`.
+pub(crate) unsafe fn unlink<N: DoublyLinked>(
+    head: &mut Option<NodePtr<N>>,
+    tail: &mut Option<NodePtr<N>>,
+    node: NodePtr<N>,
+) {
+    unsafe {
+        let prev = node.as_mut().prev_mut().take();
+        let next = node.as_mut().next_mut().take();
+
+        match prev {
+            Some(prev) => *prev.as_mut().next_mut() = next,
+            None => *head = next,
+        }
+
+        match next {
+            Some(next) => *next.as_mut().prev_mut() = prev,
+            None => *tail = prev,
+        }
+    }
+}
+
+/// Cuts the chain between `node` and the node before it, returning that
+/// one: the last node of the first half.
+///
+/// # Safety
+///
+/// `node` has to be alive, and so does the node before it.
+pub(crate) unsafe fn cut_before<N: DoublyLinked>(node: NodePtr<N>) -> Option<NodePtr<N>> {
+    unsafe {
+        let prev = node.as_mut().prev_mut().take();
+
+        if let Some(prev) = prev {
+            *prev.as_mut().next_mut() = None;
+        }
+
+        prev
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DoublyLinked, NodePtr, cut_before, link, link_chain, unlink};
+    use crate::alloc::Global;
+
+    struct Node {
+        prev: Option<NodePtr<Node>>,
+        next: Option<NodePtr<Node>>,
+        elem: i32,
+    }
+
+    impl DoublyLinked for Node {
+        fn prev_mut(&mut self) -> &mut Option<NodePtr<Self>> {
+            &mut self.prev
+        }
+
+        fn next_mut(&mut self) -> &mut Option<NodePtr<Self>> {
+            &mut self.next
+        }
+    }
+
+    fn node(elem: i32) -> NodePtr<Node> {
+        NodePtr::alloc(&Global, Node { prev: None, next: None, elem })
+    }
+
+    /// Elements from the head, checking the back links on the way.
+    fn elems(head: Option<NodePtr<Node>>, tail: Option<NodePtr<Node>>) -> Vec<i32> {
+        let mut elems = Vec::new();
+        let mut prev = None;
+        let mut current = head;
+
+        while let Some(node) = current {
+            let node_ref = unsafe { node.as_ref() };
+            assert_eq!(node_ref.prev, prev);
+            elems.push(node_ref.elem);
+            prev = Some(node);
+            current = node_ref.next;
+        }
+
+        assert_eq!(prev, tail);
+        elems
+    }
+
+    fn free(mut head: Option<NodePtr<Node>>) {
+        while let Some(node) = head {
+            head = unsafe { node.dealloc(&Global).next };
+        }
+    }
+
+    #[test]
+    fn test_link_unlink() {
+        let (mut head, mut tail) = (None, None);
+
+        let (two, four) = (node(2), node(4));
+        unsafe {
+            link(&mut head, &mut tail, None, None, two);
+            link(&mut head, &mut tail, None, Some(two), node(1));
+            link(&mut head, &mut tail, Some(two), None, four);
+            link(&mut head, &mut tail, Some(two), Some(four), node(3));
+        }
+        assert_eq!(elems(head, tail), [1, 2, 3, 4]);
+
+        unsafe {
+            unlink(&mut head, &mut tail, two);
+            assert_eq!(two.dealloc(&Global).elem, 2);
+
+            unlink(&mut head, &mut tail, four);
+            four.dealloc(&Global);
+        }
+        assert_eq!(elems(head, tail), [1, 3]);
+
+        free(head);
+    }
+
+    #[test]
+    fn test_chains() {
+        let (mut head, mut tail) = (None, None);
+        let (mut other_head, mut other_tail) = (None, None);
+
+        let (one, two, three, four) = (node(1), node(2), node(3), node(4));
+        unsafe {
+            link(&mut head, &mut tail, None, None, one);
+            link(&mut head, &mut tail, Some(one), None, four);
+            link(&mut other_head, &mut other_tail, None, None, two);
+            link(&mut other_head, &mut other_tail, Some(two), None, three);
+        }
+        assert_eq!(elems(other_head, other_tail), [2, 3]);
+
+        unsafe { link_chain(&mut head, &mut tail, Some(one), Some(four), two, three) }
+        assert_eq!(elems(head, tail), [1, 2, 3, 4]);
+
+        assert_eq!(unsafe { cut_before(three) }, Some(two));
+        assert_eq!(elems(head, Some(two)), [1, 2]);
+        assert_eq!(elems(Some(three), tail), [3, 4]);
+
+        free(head);
+        free(Some(three));
+    }
+}
@@ -0,0 +1,78 @@
+//! Shared pieces of the `rayon` implementations, which live next to each
+//! collection.
+
+use std::{iter::FusedIterator, mem, ptr, slice};
+
+use rayon::iter::plumbing::Producer;
+
+/// Producer that moves the values out of a slice whose owner has already
+/// given them up, dropping whatever is never consumed.
+pub(crate) struct DrainProducer<'a, T> {
+    slice: &'a mut [T],
+}
+
+impl<'a, T> DrainProducer<'a, T> {
+    /// # Safety
+    ///
+    /// The values in `slice` must not be read or dropped by anyone else.
+    pub(crate) unsafe fn new(slice: &'a mut [T]) -> Self {
+        DrainProducer { slice }
+    }
+}
+
+impl<'a, T: Send> Producer for DrainProducer<'a, T> {
+    type Item = T;
+    type IntoIter = SliceDrain<'a, T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        SliceDrain {
+            iter: mem::take(&mut self.slice).iter_mut(),
+        }
+    }
+
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let (left, right) = mem::take(&mut self.slice).split_at_mut(index);
+        (DrainProducer { slice: left }, DrainProducer { slice: right })
+    }
+}
+
+impl<'a, T> Drop for DrainProducer<'a, T> {
+    fn drop(&mut self) {
+        let rest = mem::take(&mut self.slice);
+        unsafe { ptr::drop_in_place(rest) }
+    }
+}
+
+/// Sequential half of [`DrainProducer`].
+pub(crate) struct SliceDrain<'a, T> {
+    iter: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for SliceDrain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.iter.next().map(|elem| unsafe { ptr::read(elem) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for SliceDrain<'a, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back().map(|elem| unsafe { ptr::read(elem) })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for SliceDrain<'a, T> {}
+
+impl<'a, T> FusedIterator for SliceDrain<'a, T> {}
+
+impl<'a, T> Drop for SliceDrain<'a, T> {
+    fn drop(&mut self) {
+        let rest = mem::take(&mut self.iter).into_slice();
+        unsafe { ptr::drop_in_place(rest) }
+    }
+}
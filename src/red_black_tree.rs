@@ -0,0 +1,927 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem,
+    ops::{Bound, ControlFlow, RangeBounds},
+};
+
+use crate::alloc::Global;
+use crate::display_tree::{DisplayTree, TreeNode, display_tree};
+use crate::raw::NodePtr;
+use crate::stats::TreeStats;
+use crate::traits::MemoryUsage;
+use crate::traverse::{Order, Traverse, Visit, walk_binary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Red,
+    Black,
+}
+
+/// RBTree node. Missing children (`None`) count as black leaves.
+struct Node<T> {
+    left: Link<T>,
+    right: Link<T>,
+    parent: Link<T>,
+    color: Color,
+    elem: T,
+}
+
+type Link<T> = Option<NodePtr<Node<T>>>;
+
+/// Self-balancing binary search tree with the same ordered-set API as
+/// [`BTree`](crate::BTree).
+///
+/// Nodes are colored so that no red node has a red child and every path from
+/// the root to a leaf crosses the same number of black nodes, which keeps the
+/// height under `2 * log2(n + 1)` and makes every operation O(log n) in the
+/// worst case.
+pub struct RBTree<T> {
+    root: Link<T>,
+    size: usize,
+    stats: TreeStats,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for RBTree<T> {}
+unsafe impl<T: Sync> Sync for RBTree<T> {}
+
+/// In-order iterator, see [`RBTree::iter`].
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+/// Iterator over the elements inside a range, see [`RBTree::range`].
+pub struct Range<'a, T> {
+    next: Link<T>,
+    end: Link<T>,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<'a, T: Sync> Send for Range<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Range<'a, T> {}
+
+impl<T> Node<T> {
+    /// Create new red node.
+    fn new(parent: Link<T>, elem: T) -> NodePtr<Node<T>> {
+        NodePtr::alloc(
+            &Global,
+            Node {
+                left: None,
+                right: None,
+                parent,
+                color: Color::Red,
+                elem,
+            },
+        )
+    }
+}
+
+unsafe fn color<T>(link: Link<T>) -> Color {
+    unsafe { link.map_or(Color::Black, |node| node.as_ref().color) }
+}
+
+unsafe fn set_color<T>(link: Link<T>, color: Color) {
+    if let Some(node) = link {
+        unsafe { node.as_mut().color = color }
+    }
+}
+
+/// Leftmost node of the subtree rooted at `node`.
+unsafe fn minimum<T>(mut node: NodePtr<Node<T>>) -> NodePtr<Node<T>> {
+    unsafe {
+        while let Some(left) = node.as_ref().left {
+            node = left;
+        }
+    }
+    node
+}
+
+/// Rightmost node of the subtree rooted at `node`.
+unsafe fn maximum<T>(mut node: NodePtr<Node<T>>) -> NodePtr<Node<T>> {
+    unsafe {
+        while let Some(right) = node.as_ref().right {
+            node = right;
+        }
+    }
+    node
+}
+
+/// Next node in order.
+unsafe fn successor<T>(node: NodePtr<Node<T>>) -> Link<T> {
+    unsafe {
+        if let Some(right) = node.as_ref().right {
+            return Some(minimum(right));
+        }
+
+        let mut child = node;
+        let mut parent = node.as_ref().parent;
+
+        while let Some(p) = parent {
+            if p.as_ref().left == Some(child) {
+                break;
+            }
+            child = p;
+            parent = p.as_ref().parent;
+        }
+
+        parent
+    }
+}
+
+/// Previous node in order.
+unsafe fn predecessor<T>(node: NodePtr<Node<T>>) -> Link<T> {
+    unsafe {
+        if let Some(left) = node.as_ref().left {
+            return Some(maximum(left));
+        }
+
+        let mut child = node;
+        let mut parent = node.as_ref().parent;
+
+        while let Some(p) = parent {
+            if p.as_ref().right == Some(child) {
+                break;
+            }
+            child = p;
+            parent = p.as_ref().parent;
+        }
+
+        parent
+    }
+}
+
+impl<T> RBTree<T> {
+    /// Creates a new RBTree with no elements.
+    pub fn new() -> Self {
+        RBTree {
+            root: None,
+            size: 0,
+            stats: TreeStats::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns RBTree size.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether the RBTree has no values.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Counters of the rebalancing done so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TreeStats {
+        self.stats
+    }
+
+    /// Returns the smallest element.
+    pub fn min(&self) -> Option<&T> {
+        unsafe { Some(&minimum(self.root?).as_ref().elem) }
+    }
+
+    /// Returns the largest element.
+    pub fn max(&self) -> Option<&T> {
+        unsafe { Some(&maximum(self.root?).as_ref().elem) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        unsafe {
+            Iter {
+                front: self.root.map(|root| minimum(root)),
+                back: self.root.map(|root| maximum(root)),
+                len: self.size,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    /// Removes every element from the tree.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePtr<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            unsafe {
+                let node = node.dealloc(&Global);
+                stack.extend(node.left);
+                stack.extend(node.right);
+            }
+        }
+        self.size = 0;
+    }
+
+    /// Puts `new` in the place of `old` as far as `old`'s parent is concerned.
+    unsafe fn transplant(&mut self, old: NodePtr<Node<T>>, new: Link<T>) {
+        unsafe {
+            let parent = old.as_ref().parent;
+
+            match parent {
+                None => self.root = new,
+                Some(parent) if parent.as_ref().left == Some(old) => parent.as_mut().left = new,
+                Some(parent) => parent.as_mut().right = new,
+            }
+
+            if let Some(new) = new {
+                new.as_mut().parent = parent;
+            }
+        }
+    }
+
+    /// ```text
+    ///     x              y
+    ///    / \            / \
+    ///   a   y    =>    x   c
+    ///      / \        / \
+    ///     b   c      a   b
+    /// ```
+    unsafe fn rotate_left(&mut self, x: NodePtr<Node<T>>) {
+        self.stats.record_rotation();
+
+        unsafe {
+            let y = x.as_ref().right.unwrap();
+
+            x.as_mut().right = y.as_ref().left;
+            if let Some(b) = y.as_ref().left {
+                b.as_mut().parent = Some(x);
+            }
+
+            self.transplant(x, Some(y));
+
+            y.as_mut().left = Some(x);
+            x.as_mut().parent = Some(y);
+        }
+    }
+
+    /// Mirror image of [`RBTree::rotate_left`].
+    unsafe fn rotate_right(&mut self, x: NodePtr<Node<T>>) {
+        self.stats.record_rotation();
+
+        unsafe {
+            let y = x.as_ref().left.unwrap();
+
+            x.as_mut().left = y.as_ref().right;
+            if let Some(b) = y.as_ref().right {
+                b.as_mut().parent = Some(x);
+            }
+
+            self.transplant(x, Some(y));
+
+            y.as_mut().right = Some(x);
+            x.as_mut().parent = Some(y);
+        }
+    }
+
+    /// Restores the coloring rules after inserting the red node `node`.
+    unsafe fn insert_fixup(&mut self, mut node: NodePtr<Node<T>>) {
+        unsafe {
+            while let Some(mut parent) = node.as_ref().parent {
+                if parent.as_ref().color == Color::Black {
+                    break;
+                }
+
+                // A red parent is never the root, so there is a grandparent.
+                let grandparent = parent.as_ref().parent.unwrap();
+                let parent_is_left = grandparent.as_ref().left == Some(parent);
+                let uncle = if parent_is_left {
+                    grandparent.as_ref().right
+                } else {
+                    grandparent.as_ref().left
+                };
+
+                // Case 1: red uncle, push the blackness down from the
+                // grandparent and continue from there.
+                if color(uncle) == Color::Red {
+                    parent.as_mut().color = Color::Black;
+                    set_color(uncle, Color::Black);
+                    grandparent.as_mut().color = Color::Red;
+                    node = grandparent;
+                    continue;
+                }
+
+                // Case 2: node is an inner grandchild, rotate it outwards.
+                if parent_is_left && parent.as_ref().right == Some(node) {
+                    self.rotate_left(parent);
+                    node = parent;
+                    parent = node.as_ref().parent.unwrap();
+                } else if !parent_is_left && parent.as_ref().left == Some(node) {
+                    self.rotate_right(parent);
+                    node = parent;
+                    parent = node.as_ref().parent.unwrap();
+                }
+
+                // Case 3: node is an outer grandchild, rotate the grandparent.
+                parent.as_mut().color = Color::Black;
+                grandparent.as_mut().color = Color::Red;
+                if parent_is_left {
+                    self.rotate_right(grandparent);
+                } else {
+                    self.rotate_left(grandparent);
+                }
+            }
+
+            set_color(self.root, Color::Black);
+        }
+    }
+
+    /// Restores the coloring rules after removing a black node. `node` is
+    /// the child that took its place (maybe a leaf) and `parent` its parent.
+    unsafe fn remove_fixup(&mut self, mut node: Link<T>, mut parent: Link<T>) {
+        unsafe {
+            while node != self.root && color(node) == Color::Black {
+                let p = parent.unwrap();
+
+                if p.as_ref().left == node {
+                    // The removed side was short of one black node, so the
+                    // sibling can't be a leaf.
+                    let mut sibling = p.as_ref().right.unwrap();
+
+                    if sibling.as_ref().color == Color::Red {
+                        sibling.as_mut().color = Color::Black;
+                        p.as_mut().color = Color::Red;
+                        self.rotate_left(p);
+                        sibling = p.as_ref().right.unwrap();
+                    }
+
+                    if color(sibling.as_ref().left) == Color::Black
+                        && color(sibling.as_ref().right) == Color::Black
+                    {
+                        sibling.as_mut().color = Color::Red;
+                        node = Some(p);
+                        parent = p.as_ref().parent;
+                    } else {
+                        if color(sibling.as_ref().right) == Color::Black {
+                            set_color(sibling.as_ref().left, Color::Black);
+                            sibling.as_mut().color = Color::Red;
+                            self.rotate_right(sibling);
+                            sibling = p.as_ref().right.unwrap();
+                        }
+
+                        sibling.as_mut().color = p.as_ref().color;
+                        p.as_mut().color = Color::Black;
+                        set_color(sibling.as_ref().right, Color::Black);
+                        self.rotate_left(p);
+                        node = self.root;
+                    }
+                } else {
+                    let mut sibling = p.as_ref().left.unwrap();
+
+                    if sibling.as_ref().color == Color::Red {
+                        sibling.as_mut().color = Color::Black;
+                        p.as_mut().color = Color::Red;
+                        self.rotate_right(p);
+                        sibling = p.as_ref().left.unwrap();
+                    }
+
+                    if color(sibling.as_ref().left) == Color::Black
+                        && color(sibling.as_ref().right) == Color::Black
+                    {
+                        sibling.as_mut().color = Color::Red;
+                        node = Some(p);
+                        parent = p.as_ref().parent;
+                    } else {
+                        if color(sibling.as_ref().left) == Color::Black {
+                            set_color(sibling.as_ref().right, Color::Black);
+                            sibling.as_mut().color = Color::Red;
+                            self.rotate_left(sibling);
+                            sibling = p.as_ref().left.unwrap();
+                        }
+
+                        sibling.as_mut().color = p.as_ref().color;
+                        p.as_mut().color = Color::Black;
+                        set_color(sibling.as_ref().left, Color::Black);
+                        self.rotate_right(p);
+                        node = self.root;
+                    }
+                }
+            }
+
+            set_color(node, Color::Black);
+        }
+    }
+
+    /// Unlinks `node` from the tree, rebalances and returns its element.
+    unsafe fn remove_node(&mut self, node: NodePtr<Node<T>>) -> T {
+        unsafe {
+            let mut removed_color = node.as_ref().color;
+            let replacement;
+            let replacement_parent;
+
+            if node.as_ref().left.is_none() {
+                replacement = node.as_ref().right;
+                replacement_parent = node.as_ref().parent;
+                self.transplant(node, replacement);
+            } else if node.as_ref().right.is_none() {
+                replacement = node.as_ref().left;
+                replacement_parent = node.as_ref().parent;
+                self.transplant(node, replacement);
+            } else {
+                // Two children: the in-order successor takes the place of
+                // `node` and the successor's right child takes its place.
+                let next = minimum(node.as_ref().right.unwrap());
+                removed_color = next.as_ref().color;
+                replacement = next.as_ref().right;
+
+                if next.as_ref().parent == Some(node) {
+                    replacement_parent = Some(next);
+                } else {
+                    replacement_parent = next.as_ref().parent;
+                    self.transplant(next, replacement);
+                    next.as_mut().right = node.as_ref().right;
+                    next.as_ref().right.unwrap().as_mut().parent = Some(next);
+                }
+
+                self.transplant(node, Some(next));
+                next.as_mut().left = node.as_ref().left;
+                next.as_ref().left.unwrap().as_mut().parent = Some(next);
+                next.as_mut().color = node.as_ref().color;
+            }
+
+            self.size -= 1;
+
+            if removed_color == Color::Black {
+                self.remove_fixup(replacement, replacement_parent);
+            }
+
+            node.dealloc(&Global).elem
+        }
+    }
+
+    /// Returns the first node for which `before` is `false`, in order.
+    /// `before` must be `true` for a prefix of the elements and `false` for
+    /// the rest.
+    fn first_where_not(&self, before: impl Fn(&T) -> bool) -> Link<T> {
+        let mut current = self.root;
+        let mut found = None;
+
+        unsafe {
+            while let Some(node) = current {
+                if before(&node.as_ref().elem) {
+                    current = node.as_ref().right;
+                } else {
+                    found = Some(node);
+                    current = node.as_ref().left;
+                }
+            }
+        }
+
+        found
+    }
+}
+
+impl<T: Ord> RBTree<T> {
+    /// Insert a new node. Does nothing if `elem` is already in the tree.
+    pub fn insert(&mut self, elem: T) {
+        let mut parent = None;
+        let mut current = self.root;
+        let mut depth = 1;
+
+        unsafe {
+            while let Some(node) = current {
+                parent = current;
+                depth += 1;
+                current = match elem.cmp(&node.as_ref().elem) {
+                    Ordering::Less => node.as_ref().left,
+                    Ordering::Greater => node.as_ref().right,
+                    Ordering::Equal => return,
+                };
+            }
+
+            self.stats.record_depth(depth);
+            let new_node = Node::new(parent, elem);
+
+            match parent {
+                None => self.root = Some(new_node),
+                Some(parent) => {
+                    if new_node.as_ref().elem < parent.as_ref().elem {
+                        parent.as_mut().left = Some(new_node);
+                    } else {
+                        parent.as_mut().right = Some(new_node);
+                    }
+                }
+            }
+
+            self.size += 1;
+            self.insert_fixup(new_node);
+        }
+    }
+
+    fn find(&self, elem: &T) -> Link<T> {
+        let mut current = self.root;
+
+        unsafe {
+            while let Some(node) = current {
+                current = match elem.cmp(&node.as_ref().elem) {
+                    Ordering::Less => node.as_ref().left,
+                    Ordering::Greater => node.as_ref().right,
+                    Ordering::Equal => return current,
+                };
+            }
+        }
+
+        None
+    }
+
+    /// Returns `true` if `elem` is in the tree.
+    pub fn contains(&self, elem: &T) -> bool {
+        self.find(elem).is_some()
+    }
+
+    /// Returns the element in the tree that is equal to `elem`.
+    pub fn get(&self, elem: &T) -> Option<&T> {
+        unsafe { Some(&self.find(elem)?.as_ref().elem) }
+    }
+
+    /// Removes `elem` from the RBTree.
+    pub fn remove(&mut self, elem: &T) {
+        self.take(elem);
+    }
+
+    /// Removes `elem` from the RBTree and returns it.
+    pub fn take(&mut self, elem: &T) -> Option<T> {
+        let node = self.find(elem)?;
+
+        unsafe { Some(self.remove_node(node)) }
+    }
+
+    /// Iterates in order over the elements contained in `range`.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.first_where_not(|e| e < start),
+            Bound::Excluded(start) => self.first_where_not(|e| e <= start),
+            Bound::Unbounded => self.root.map(|root| unsafe { minimum(root) }),
+        };
+
+        let in_range = |e: &T| match range.end_bound() {
+            Bound::Included(end) => e <= end,
+            Bound::Excluded(end) => e < end,
+            Bound::Unbounded => true,
+        };
+
+        // Empty when the range starts past its end.
+        let next = next.filter(|node| unsafe { in_range(&node.as_ref().elem) });
+        let end = self.first_where_not(in_range);
+
+        Range {
+            next,
+            end,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for RBTree<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for RBTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.front.map(|node| unsafe {
+            self.len -= 1;
+            self.front = successor(node);
+            &node.as_ref().elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.back.map(|node| unsafe {
+            self.len -= 1;
+            self.back = predecessor(node);
+            &node.as_ref().elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.next.map(|node| unsafe {
+            self.next = successor(node);
+            &node.as_ref().elem
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RBTree<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> Extend<T> for RBTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for RBTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<T: Debug> Debug for RBTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T: fmt::Display> RBTree<T> {
+    /// Draws the shape of the tree with the color of every node, see
+    /// [`display_tree`].
+    pub fn display_tree(&self) -> DisplayTree<impl TreeNode + '_> {
+        display_tree(self.root.map(|root| unsafe { root.as_ref() }))
+    }
+}
+
+impl<T: fmt::Display> TreeNode for &Node<T> {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let color = match self.color {
+            Color::Red => "red",
+            Color::Black => "black",
+        };
+        write!(f, "{} ({color})", self.elem)
+    }
+
+    fn children(&self) -> Vec<Option<Self>> {
+        unsafe {
+            vec![
+                self.left.map(|n| n.as_ref()),
+                self.right.map(|n| n.as_ref()),
+            ]
+        }
+    }
+}
+
+impl<T> Traverse for RBTree<T> {
+    type Elem = T;
+
+    fn traverse<'a, B>(
+        &'a self,
+        order: Order,
+        visit: impl FnMut(Visit<'a, T>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let split = |node: NodePtr<Node<T>>| unsafe {
+            let node = node.as_ref();
+            (&node.elem, node.left, node.right)
+        };
+
+        walk_binary(self.root, order, split, visit)
+    }
+}
+
+impl<T> MemoryUsage for RBTree<T> {
+    fn heap_bytes(&self) -> usize {
+        self.size * mem::size_of::<Node<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, Link, RBTree};
+
+    fn tree_values() -> Vec<i32> {
+        vec![40, 20, 60, 10, 30, 25, 35, 50, 45, 70, 80, 75]
+    }
+
+    /// Checks the search tree order, the parent links and the coloring
+    /// rules, panicking if any of them is broken.
+    fn check_invariants<T: Ord>(tree: &RBTree<T>) {
+        /// Returns the black height of the subtree and its size.
+        unsafe fn check<T: Ord>(link: Link<T>, parent: Link<T>) -> (usize, usize) {
+            let Some(node) = link else {
+                return (1, 0);
+            };
+
+            unsafe {
+                let node = node.as_ref();
+                assert!(node.parent == parent, "broken parent link");
+
+                if node.color == Color::Red {
+                    assert!(
+                        super::color(node.left) == Color::Black,
+                        "red node with red child"
+                    );
+                    assert!(
+                        super::color(node.right) == Color::Black,
+                        "red node with red child"
+                    );
+                }
+
+                if let Some(left) = node.left {
+                    assert!(left.as_ref().elem < node.elem, "left child out of order");
+                }
+                if let Some(right) = node.right {
+                    assert!(right.as_ref().elem > node.elem, "right child out of order");
+                }
+
+                let (left_height, left_size) = check(node.left, link);
+                let (right_height, right_size) = check(node.right, link);
+                assert_eq!(left_height, right_height, "unequal black heights");
+
+                let own = (node.color == Color::Black) as usize;
+                (left_height + own, left_size + right_size + 1)
+            }
+        }
+
+        unsafe {
+            assert!(super::color(tree.root) == Color::Black, "red root");
+            let (_, size) = check(tree.root, None);
+            assert_eq!(size, tree.size());
+        }
+
+        assert!(tree.iter().zip(tree.iter().skip(1)).all(|(a, b)| a < b));
+    }
+
+    #[test]
+    fn test_insert() {
+        let numbers = tree_values();
+        let mut tree = RBTree::new();
+
+        for n in &numbers {
+            tree.insert(*n);
+            check_invariants(&tree);
+        }
+
+        tree.insert(40);
+        assert_eq!(tree.size(), numbers.len());
+
+        for n in &numbers {
+            assert!(tree.contains(n));
+        }
+
+        assert!(!tree.contains(&0));
+        assert_eq!(tree.get(&25), Some(&25));
+        assert_eq!(tree.min(), Some(&10));
+        assert_eq!(tree.max(), Some(&80));
+    }
+
+    #[test]
+    fn test_sequential_insert() {
+        // Sorted input degenerates a plain BST into a list.
+        let tree: RBTree<i32> = (0..1000).collect();
+        check_invariants(&tree);
+
+        // Height is bounded by 2 * log2(n + 1).
+        fn height<T>(link: Link<T>) -> usize {
+            link.map_or(0, |node| unsafe {
+                1 + height(node.as_ref().left).max(height(node.as_ref().right))
+            })
+        }
+        assert!(height(tree.root) <= 20);
+    }
+
+    #[test]
+    fn test_remove() {
+        let numbers = tree_values();
+        let mut tree: RBTree<i32> = numbers.iter().copied().collect();
+
+        // Leaf, one child, two children and root.
+        for n in [75, 70, 50, 20, 40] {
+            tree.remove(&n);
+            assert!(!tree.contains(&n));
+            check_invariants(&tree);
+        }
+
+        assert_eq!(tree.take(&35), Some(35));
+        assert_eq!(tree.take(&35), None);
+        assert_eq!(tree.size(), numbers.len() - 6);
+
+        for n in [80, 60, 45, 25, 30, 10] {
+            assert!(tree.contains(&n));
+        }
+    }
+
+    #[test]
+    fn test_remove_all() {
+        let mut tree: RBTree<u32> = (0..200).map(|i| (i * 7919) % 200).collect();
+
+        for i in (0..200).map(|i| (i * 31) % 200) {
+            tree.remove(&i);
+            check_invariants(&tree);
+        }
+
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter().next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut values = tree_values();
+        let tree: RBTree<i32> = values.iter().copied().collect();
+
+        values.sort();
+
+        assert!(tree.iter().eq(values.iter()));
+        assert!(tree.iter().rev().eq(values.iter().rev()));
+        assert_eq!(
+            format!("{:?}", tree),
+            "{10, 20, 25, 30, 35, 40, 45, 50, 60, 70, 75, 80}"
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let tree: RBTree<i32> = tree_values().into_iter().collect();
+
+        assert_eq!(
+            tree.range(25..50).copied().collect::<Vec<_>>(),
+            [25, 30, 35, 40, 45]
+        );
+        assert_eq!(
+            tree.range(26..=50).copied().collect::<Vec<_>>(),
+            [30, 35, 40, 45, 50]
+        );
+        assert_eq!(tree.range(76..).copied().collect::<Vec<_>>(), [80]);
+        assert_eq!(tree.range(..11).copied().collect::<Vec<_>>(), [10]);
+        assert_eq!(tree.range(90..).count(), 0);
+        assert_eq!(tree.range(50..50).count(), 0);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RBTree<String>>();
+        assert_send_sync::<super::Iter<'_, String>>();
+        assert_send_sync::<super::Range<'_, String>>();
+    }
+
+    #[test]
+    fn test_display_tree() {
+        let mut tree = RBTree::new();
+        for value in 1..=5 {
+            tree.insert(value);
+        }
+
+        assert_eq!(
+            tree.display_tree().to_string(),
+            "2 (black)\n├── 1 (black)\n└── 4 (black)\n    ├── 3 (red)\n    └── 5 (red)"
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut tree = RBTree::new();
+        for i in 1..=7 {
+            tree.insert(i);
+        }
+
+        // Ascending inserts rotate after 3, 5 and 7, the others only recolor.
+        let stats = tree.stats();
+        assert_eq!(stats.rotations, 3);
+        assert_eq!(stats.max_depth, 5);
+    }
+}
@@ -0,0 +1,711 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    io, mem,
+    ops::{Index, IndexMut},
+    ptr, slice,
+};
+
+use crate::error::{self, CapacityError, TryReserveError, out_of_bounds};
+use crate::vec::Buffer;
+use crate::traits::MemoryUsage;
+
+/// Double-ended queue stored in a growable ring buffer.
+///
+/// Elements live in a single allocation starting at `head` and wrap around
+/// the end of the buffer, so pushing and popping at both ends is O(1) while
+/// keeping the values contiguous in memory (at most two slices).
+pub struct RingDeque<T> {
+    buf: Buffer<T>,
+    head: usize,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+pub struct IntoIter<T>(RingDeque<T>);
+
+impl<T> RingDeque<T> {
+    /// Creates an empty `RingDeque` without allocating.
+    pub fn new() -> Self {
+        RingDeque {
+            buf: Buffer::new(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn ptr(&self) -> *mut T {
+        self.buf.as_ptr()
+    }
+
+    /// Returns the number of elements the buffer can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.buf.cap
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Maps the logical index `index` to its slot in the buffer.
+    fn wrap_add(&self, index: usize) -> usize {
+        // Written so that it can't overflow when `cap == usize::MAX` (ZSTs).
+        if index >= self.capacity() - self.head {
+            index - (self.capacity() - self.head)
+        } else {
+            self.head + index
+        }
+    }
+
+    /// Slot right before `head`, wrapping to the end of the buffer.
+    fn wrap_sub_head(&self) -> usize {
+        if self.head == 0 {
+            self.capacity() - 1
+        } else {
+            self.head - 1
+        }
+    }
+
+    /// Grows the buffer and moves the wrapped part so elements stay in
+    /// logical order.
+    fn grow(&mut self) {
+        error::handle_reserve(self.try_reserve(1));
+    }
+
+    /// Grows the buffer to `new_cap`, which has to be at least twice the old
+    /// capacity so the wrapped part fits after the old end.
+    fn try_grow_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        let old_cap = self.capacity();
+        self.buf.try_grow_to(new_cap)?;
+        let new_cap = self.capacity();
+
+        // Not wrapped, nothing to move.
+        if self.head <= old_cap - self.len {
+            return Ok(());
+        }
+
+        let head_len = old_cap - self.head;
+        let tail_len = self.len - head_len;
+
+        unsafe {
+            if tail_len < head_len {
+                // [D E . . A B C] -> [. . . . A B C D E . . .]
+                ptr::copy_nonoverlapping(self.ptr(), self.ptr().add(old_cap), tail_len);
+            } else {
+                // [C D E . . A B] -> [C D E . . . . . . . A B]
+                let new_head = new_cap - head_len;
+                ptr::copy_nonoverlapping(
+                    self.ptr().add(self.head),
+                    self.ptr().add(new_head),
+                    head_len,
+                );
+                self.head = new_head;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes room for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        error::handle_reserve(self.try_reserve(additional));
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
+        if needed <= self.capacity() {
+            return Ok(());
+        }
+
+        let doubled = self
+            .capacity()
+            .checked_mul(2)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        self.try_grow_to(needed.max(doubled))
+    }
+
+    /// Like [`RingDeque::push_back`], but gives `elem` back if the buffer
+    /// can't grow.
+    pub fn try_push_back(&mut self, elem: T) -> Result<(), CapacityError<T>> {
+        if self.try_reserve(1).is_err() {
+            return Err(CapacityError::new(elem));
+        }
+
+        self.push_back(elem);
+        Ok(())
+    }
+
+    /// Like [`RingDeque::push_front`], but gives `elem` back if the buffer
+    /// can't grow.
+    pub fn try_push_front(&mut self, elem: T) -> Result<(), CapacityError<T>> {
+        if self.try_reserve(1).is_err() {
+            return Err(CapacityError::new(elem));
+        }
+
+        self.push_front(elem);
+        Ok(())
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        if self.len == self.capacity() {
+            self.grow();
+        }
+
+        unsafe {
+            ptr::write(self.ptr().add(self.wrap_add(self.len)), elem);
+        }
+
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        if self.len == self.capacity() {
+            self.grow();
+        }
+
+        self.head = self.wrap_sub_head();
+
+        unsafe {
+            ptr::write(self.ptr().add(self.head), elem);
+        }
+
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let old_head = self.head;
+        self.head = self.wrap_add(1);
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr().add(old_head))) }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr().add(self.wrap_add(self.len)))) }
+    }
+
+    /// Returns a reference to the element at logical position `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(&*self.ptr().add(self.wrap_add(index))) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        unsafe { Some(&mut *self.ptr().add(self.wrap_add(index))) }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the contents as two slices: the part starting at the head and
+    /// the part that wrapped around to the start of the buffer. The second
+    /// slice is empty when the deque is contiguous.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe {
+            if self.head <= self.capacity() - self.len {
+                (slice::from_raw_parts(self.ptr().add(self.head), self.len), &[])
+            } else {
+                let head_len = self.capacity() - self.head;
+                (
+                    slice::from_raw_parts(self.ptr().add(self.head), head_len),
+                    slice::from_raw_parts(self.ptr(), self.len - head_len),
+                )
+            }
+        }
+    }
+
+    /// Mutable version of [`RingDeque::as_slices`].
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        unsafe {
+            if self.head <= self.capacity() - self.len {
+                (slice::from_raw_parts_mut(self.ptr().add(self.head), self.len), &mut [])
+            } else {
+                let head_len = self.capacity() - self.head;
+                (
+                    slice::from_raw_parts_mut(self.ptr().add(self.head), head_len),
+                    slice::from_raw_parts_mut(self.ptr(), self.len - head_len),
+                )
+            }
+        }
+    }
+
+    /// Rearranges the buffer so that all the elements are stored in one
+    /// contiguous slice, which is returned.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head > self.capacity() - self.len {
+            let head_len = self.capacity() - self.head;
+            let tail_len = self.len - head_len;
+
+            unsafe {
+                // [D E . . A B C] -> [D E A B C . .] -> [A B C D E . .]
+                ptr::copy(self.ptr().add(self.head), self.ptr().add(tail_len), head_len);
+                slice::from_raw_parts_mut(self.ptr(), self.len).rotate_left(tail_len);
+            }
+
+            self.head = 0;
+        }
+
+        unsafe { slice::from_raw_parts_mut(self.ptr().add(self.head), self.len) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+}
+
+impl<T> Drop for RingDeque<T> {
+    fn drop(&mut self) {
+        // Elements are dropped here, the allocation is freed by `Buffer`.
+        self.clear();
+    }
+}
+
+impl<T> Default for RingDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingDeque<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a mut RingDeque<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<T> IntoIterator for RingDeque<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Clone> Clone for RingDeque<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for RingDeque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for RingDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+
+        deque
+    }
+}
+
+impl<T> Index<usize> for RingDeque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).unwrap_or_else(|| out_of_bounds(index, self.len))
+    }
+}
+
+impl<T> IndexMut<usize> for RingDeque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index).unwrap_or_else(|| out_of_bounds(index, len))
+    }
+}
+
+impl<T: Debug> Debug for RingDeque<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for RingDeque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for RingDeque<T> {}
+
+impl<T: PartialOrd> PartialOrd for RingDeque<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for RingDeque<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for RingDeque<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+/// Reads bytes from the front.
+impl io::Read for RingDeque<u8> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (mut front, mut back) = self.as_slices();
+        let mut read = front.read(buf)?;
+        read += back.read(&mut buf[read..])?;
+
+        // Bytes need no dropping, skipping them is enough.
+        self.head = self.wrap_add(read);
+        self.len -= read;
+
+        Ok(read)
+    }
+}
+
+/// Appends bytes to the back, growing as needed.
+impl io::Write for RingDeque<u8> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.reserve(buf.len());
+
+        for &byte in buf {
+            self.push_back(byte);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T> MemoryUsage for RingDeque<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RingDeque;
+
+    /// Builds a deque whose contents wrap around the end of the buffer.
+    fn wrapped() -> RingDeque<i32> {
+        let mut deque = RingDeque::new();
+        deque.extend([3, 4, 5, 6]);
+        deque.pop_front();
+        deque.pop_front();
+        deque.push_back(7);
+        deque.push_back(8);
+
+        deque
+    }
+
+    #[test]
+    fn test_basics() {
+        let mut deque = RingDeque::new();
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+
+        deque.push_back(2);
+        deque.push_back(3);
+        deque.push_front(1);
+        deque.push_front(0);
+        assert_eq!(deque.len(), 4);
+        assert_eq!(deque.front(), Some(&0));
+        assert_eq!(deque.back(), Some(&3));
+        assert_eq!(deque.get(2), Some(&2));
+        assert_eq!(deque.get(4), None);
+
+        *deque.front_mut().unwrap() = 10;
+        *deque.back_mut().unwrap() = 30;
+
+        assert_eq!(deque.pop_front(), Some(10));
+        assert_eq!(deque.pop_back(), Some(30));
+        assert_eq!(deque.pop_back(), Some(2));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), None);
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn test_grow_wrapped() {
+        let mut deque = RingDeque::new();
+
+        for i in 0..100 {
+            if i % 2 == 0 {
+                deque.push_back(i);
+            } else {
+                deque.push_front(i);
+            }
+        }
+
+        let expected: Vec<_> = (0..100)
+            .rev()
+            .filter(|i| i % 2 == 1)
+            .chain((0..100).filter(|i| i % 2 == 0))
+            .collect();
+
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), expected);
+        assert!(deque.into_iter().rev().eq(expected.into_iter().rev()));
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        use crate::error::TryReserveError;
+
+        let mut deque = wrapped();
+        assert_eq!(deque.try_reserve(100), Ok(()));
+        assert!(deque.capacity() >= 104);
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [5, 6, 7, 8]);
+
+        assert_eq!(deque.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+        assert!(deque.try_push_front(4).is_ok());
+        assert!(deque.try_push_back(9).is_ok());
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [4, 5, 6, 7, 8, 9]);
+
+        let mut units = RingDeque::new();
+        units.push_back(());
+        assert_eq!(units.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+    }
+
+    #[test]
+    fn test_slices() {
+        let mut deque = wrapped();
+        assert_eq!(deque.as_slices(), (&[5, 6][..], &[7, 8][..]));
+
+        for elem in deque.iter_mut() {
+            *elem *= 10;
+        }
+
+        assert_eq!(deque.make_contiguous(), &[50, 60, 70, 80]);
+        assert_eq!(deque.as_slices(), (&[50, 60, 70, 80][..], &[][..]));
+
+        deque.push_front(40);
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [40, 50, 60, 70, 80]);
+    }
+
+    #[test]
+    fn test_io() {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let mut pipe = RingDeque::new();
+        writeln!(pipe, "first").unwrap();
+        pipe.push_front(b'>');
+        pipe.write_all(b"second\n").unwrap();
+
+        let mut byte = [0];
+        pipe.read_exact(&mut byte).unwrap();
+        assert_eq!(&byte, b">");
+
+        let lines: Vec<_> = BufReader::new(&mut pipe).lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["first", "second"]);
+        assert!(pipe.is_empty());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut deque = wrapped();
+        deque[1] += 10;
+        assert_eq!((deque[0], deque[1], deque[3]), (5, 16, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 4 out of bounds for length 4")]
+    fn test_index_out_of_bounds() {
+        let deque = wrapped();
+        let _ = deque[4];
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut deque = RingDeque::new();
+        deque.push_back(());
+        deque.push_front(());
+        deque.push_front(());
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.iter().count(), 3);
+        assert_eq!(deque.pop_back(), Some(()));
+        assert_eq!(deque.make_contiguous().len(), 2);
+    }
+
+    #[test]
+    fn test_drop() {
+        use std::rc::Rc;
+
+        let elem = Rc::new(0);
+        let mut deque = RingDeque::new();
+
+        for _ in 0..5 {
+            deque.push_front(Rc::clone(&elem));
+        }
+        deque.pop_back();
+
+        assert_eq!(Rc::strong_count(&elem), 5);
+        drop(deque);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn test_traits() {
+        let deque = wrapped();
+        let same: RingDeque<i32> = [5, 6, 7, 8].into_iter().collect();
+
+        assert_eq!(deque, same);
+        assert_eq!(deque.clone(), deque);
+        assert!(deque < [5, 6, 7, 9].into_iter().collect());
+        assert_eq!(format!("{:?}", deque), "[5, 6, 7, 8]");
+    }
+}
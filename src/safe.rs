@@ -0,0 +1,57 @@
+//! Versions of [`LinkedList`](crate::LinkedList),
+//! [`DequeueList`](crate::DequeueList) and [`BTree`](crate::BTree) written
+//! without a single `unsafe` block, for when `unsafe` is off-limits and as
+//! oracles to test the real ones against.
+//!
+//! The lists link their nodes with `Rc<RefCell<_>>` (`Weak` going
+//! backwards), the tree owns its children through `Box`. The API is the
+//! same, except that an element behind a `RefCell` can't be lent as a plain
+//! reference: the ends of the lists come as [`Ref`](std::cell::Ref) and
+//! [`RefMut`](std::cell::RefMut) guards, and borrowing iteration clones the
+//! elements.
+
+#![forbid(unsafe_code)]
+
+use std::{cmp::Ordering, mem};
+
+mod binary_tree;
+mod dequeue;
+mod list;
+
+pub use binary_tree::BTree;
+pub use dequeue::DequeueList;
+pub use list::LinkedList;
+
+/// Sorts `elems` and hands them to `push` in order. If `compare` panics
+/// they're handed over anyway, in no particular order, so the list they
+/// were taken from keeps them all.
+fn sort_into<T>(
+    elems: Vec<T>,
+    compare: impl FnMut(&T, &T) -> Ordering,
+    push: impl FnMut(T),
+) {
+    struct Refill<T, F: FnMut(T)> {
+        elems: Vec<T>,
+        push: F,
+    }
+
+    impl<T, F: FnMut(T)> Drop for Refill<T, F> {
+        fn drop(&mut self) {
+            for elem in mem::take(&mut self.elems) {
+                (self.push)(elem);
+            }
+        }
+    }
+
+    let mut refill = Refill { elems, push };
+    refill.elems.sort_by(compare);
+}
+
+/// xorshift64, the random operations of the differential tests.
+#[cfg(test)]
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
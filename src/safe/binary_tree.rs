@@ -0,0 +1,260 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::display_tree::{DisplayTree, TreeNode, display_tree};
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    left: Link<T>,
+    right: Link<T>,
+    elem: T,
+}
+
+/// Safe [`BTree`](crate::BTree), owning its children through `Box`. It
+/// removes elements the same way, so both trees keep the same shape.
+pub struct BTree<T> {
+    root: Link<T>,
+    size: usize,
+}
+
+pub struct Iter<'a, T> {
+    elems: std::vec::IntoIter<&'a T>,
+}
+
+impl<T: Ord> BTree<T> {
+    pub fn new() -> Self {
+        BTree {
+            root: None,
+            size: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn insert(&mut self, elem: T) {
+        let mut link = &mut self.root;
+
+        while let Some(node) = link {
+            link = match elem.cmp(&node.elem) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return,
+            };
+        }
+
+        *link = Some(Box::new(Node {
+            left: None,
+            right: None,
+            elem,
+        }));
+        self.size += 1;
+    }
+
+    pub fn contains(&self, elem: &T) -> bool {
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            link = match elem.cmp(&node.elem) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+
+        false
+    }
+
+    pub fn remove(&mut self, elem: &T) {
+        if remove_from(&mut self.root, elem) {
+            self.size -= 1;
+        }
+    }
+}
+
+/// Removes `elem` from the subtree at `link`, a node with two children
+/// taking the element of its in-order successor.
+fn remove_from<T: Ord>(link: &mut Link<T>, elem: &T) -> bool {
+    let Some(node) = link else {
+        return false;
+    };
+
+    match elem.cmp(&node.elem) {
+        Ordering::Less => remove_from(&mut node.left, elem),
+        Ordering::Greater => remove_from(&mut node.right, elem),
+        Ordering::Equal => {
+            if node.left.is_none() {
+                *link = node.right.take();
+            } else if node.right.is_none() {
+                *link = node.left.take();
+            } else {
+                node.elem = take_min(&mut node.right);
+            }
+            true
+        }
+    }
+}
+
+/// Takes the smallest node out of a non-empty subtree, its right child
+/// taking its place.
+fn take_min<T>(link: &mut Link<T>) -> T {
+    match link {
+        Some(node) if node.left.is_some() => take_min(&mut node.left),
+        _ => {
+            let node = link.take().expect("empty subtree");
+            *link = node.right;
+            node.elem
+        }
+    }
+}
+
+impl<T> BTree<T> {
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        fn push_inorder<'a, T>(link: &'a Link<T>, elems: &mut Vec<&'a T>) {
+            if let Some(node) = link {
+                push_inorder(&node.left, elems);
+                elems.push(&node.elem);
+                push_inorder(&node.right, elems);
+            }
+        }
+
+        let mut elems = Vec::with_capacity(self.size);
+        push_inorder(&self.root, &mut elems);
+
+        Iter {
+            elems: elems.into_iter(),
+        }
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+
+        Some(&node.elem)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+
+        Some(&node.elem)
+    }
+
+    pub fn clear(&mut self) {
+        // Takes the nodes apart one by one, dropping the root would recurse
+        // as deep as the tree.
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+        self.size = 0;
+    }
+}
+
+impl<T> Drop for BTree<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Ord> Default for BTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.elems.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elems.size_hint()
+    }
+}
+
+impl<T: fmt::Display> BTree<T> {
+    /// Draws the shape of the tree, see [`display_tree`].
+    pub fn display_tree(&self) -> DisplayTree<impl TreeNode + '_> {
+        display_tree(self.root.as_deref())
+    }
+}
+
+impl<T: fmt::Display> TreeNode for &Node<T> {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.elem.fmt(f)
+    }
+
+    fn children(&self) -> Vec<Option<Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BTree;
+    use crate::safe::xorshift;
+
+    #[test]
+    fn test_basics() {
+        let mut tree = BTree::new();
+        for value in [40, 20, 60, 10, 30, 50, 70, 20] {
+            tree.insert(value);
+        }
+        assert_eq!(tree.size(), 7);
+        assert_eq!((tree.min(), tree.max()), (Some(&10), Some(&70)));
+
+        // Two children, 50 takes its place.
+        tree.remove(&40);
+        tree.remove(&99);
+        assert!(!tree.contains(&40) && tree.contains(&50));
+        assert_eq!(format!("{tree:?}"), "{10, 20, 30, 50, 60, 70}");
+        assert_eq!(tree.display_tree().to_string().lines().next(), Some("50"));
+
+        tree.clear();
+        assert!(tree.is_empty() && tree.min().is_none());
+    }
+
+    #[test]
+    fn test_against_unsafe() {
+        let mut safe = BTree::new();
+        let mut real = crate::BTree::new();
+        let mut state = 0xd1b5_4a32_d192_ed03;
+
+        for _ in 0..2000 {
+            let value = xorshift(&mut state) % 100;
+            if xorshift(&mut state).is_multiple_of(3) {
+                safe.remove(&value);
+                real.remove(&value);
+            } else {
+                safe.insert(value);
+                real.insert(value);
+            }
+
+            assert_eq!(safe.size(), real.size());
+            assert_eq!(safe.contains(&value), real.contains(&value));
+        }
+        assert!(safe.iter().eq(real.iter()));
+        assert_eq!(safe.display_tree().to_string(), real.display_tree().to_string());
+    }
+}
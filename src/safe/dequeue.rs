@@ -0,0 +1,408 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    cmp::Ordering,
+    fmt::Debug,
+    rc::{Rc, Weak},
+};
+
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    /// Weak so the two directions don't keep each other alive.
+    prev: Option<Weak<RefCell<Node<T>>>>,
+}
+
+impl<T> Node<T> {
+    fn new(elem: T) -> Rc<RefCell<Node<T>>> {
+        Rc::new(RefCell::new(Node {
+            elem,
+            next: None,
+            prev: None,
+        }))
+    }
+
+    fn prev(&self) -> Link<T> {
+        self.prev.as_ref().and_then(Weak::upgrade)
+    }
+}
+
+/// Safe [`DequeueList`](crate::DequeueList): the nodes are owned by their
+/// predecessor, point back to it weakly, and the last one is shared with
+/// `tail`.
+pub struct DequeueList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+}
+
+/// Iterator cloning the elements of a [`DequeueList`], the nodes can't lend
+/// them for longer than a `Ref` lives.
+pub struct Iter<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+}
+
+pub struct IntoIter<T>(DequeueList<T>);
+
+impl<T> DequeueList<T> {
+    pub fn new() -> Self {
+        DequeueList {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let node = Node::new(elem);
+
+        match self.head.take() {
+            Some(head) => {
+                head.borrow_mut().prev = Some(Rc::downgrade(&node));
+                node.borrow_mut().next = Some(head);
+            }
+            None => self.tail = Some(Rc::clone(&node)),
+        }
+
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let node = Node::new(elem);
+
+        match self.tail.take() {
+            Some(tail) => {
+                node.borrow_mut().prev = Some(Rc::downgrade(&tail));
+                tail.borrow_mut().next = Some(Rc::clone(&node));
+            }
+            None => self.head = Some(Rc::clone(&node)),
+        }
+
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head.take()?;
+        self.unlink(&head);
+
+        Some(into_elem(head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail.clone()?;
+        self.unlink(&tail);
+
+        Some(into_elem(tail))
+    }
+
+    /// Takes `node` out of the list, joining its neighbours.
+    fn unlink(&mut self, node: &Rc<RefCell<Node<T>>>) {
+        let (prev, next) = {
+            let mut node = node.borrow_mut();
+            (node.prev(), node.next.take())
+        };
+
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.clone(),
+        }
+
+        match prev {
+            Some(prev) => prev.borrow_mut().next = next,
+            None => self.head = next,
+        }
+
+        self.len -= 1;
+    }
+
+    pub fn front(&self) -> Option<Ref<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.elem))
+    }
+
+    pub fn front_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
+    }
+
+    pub fn back(&self) -> Option<Ref<'_, T>> {
+        self.tail
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.elem))
+    }
+
+    pub fn back_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.tail
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
+    }
+
+    /// Iterates over clones of the elements.
+    pub fn iter(&self) -> Iter<T>
+    where
+        T: Clone,
+    {
+        Iter {
+            front: self.head.clone(),
+            back: self.tail.clone(),
+            len: self.len,
+        }
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        self.retain_mut(|elem| keep(elem));
+    }
+
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut keep: F) {
+        let mut node = self.head.clone();
+
+        // Each node is only unlinked after `keep` returned, so a panic
+        // leaves the list whole.
+        while let Some(current) = node {
+            node = current.borrow().next.clone();
+
+            if !keep(&mut current.borrow_mut().elem) {
+                self.unlink(&current);
+            }
+        }
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Stable sort. If `compare` panics the list keeps all its elements, in
+    /// no particular order.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        let mut elems = Vec::with_capacity(self.len);
+        while let Some(elem) = self.pop_front() {
+            elems.push(elem);
+        }
+
+        super::sort_into(elems, compare, |elem| self.push_back(elem));
+    }
+}
+
+/// Moves the element out of a node nothing else points to.
+fn into_elem<T>(node: Rc<RefCell<Node<T>>>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(node) => node.into_inner().elem,
+        Err(_) => unreachable!("node still linked"),
+    }
+}
+
+impl<T> Drop for DequeueList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for DequeueList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for DequeueList<T> {
+    fn clone(&self) -> Self {
+        self.iter().collect()
+    }
+}
+
+impl<T> Extend<T> for DequeueList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DequeueList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for DequeueList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+        let mut node = self.head.clone();
+
+        while let Some(current) = node {
+            list.entry(&current.borrow().elem);
+            node = current.borrow().next.clone();
+        }
+
+        list.finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DequeueList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+
+        let (mut a, mut b) = (self.head.clone(), other.head.clone());
+        while let (Some(x), Some(y)) = (a, b) {
+            if x.borrow().elem != y.borrow().elem {
+                return false;
+            }
+            a = x.borrow().next.clone();
+            b = y.borrow().next.clone();
+        }
+
+        true
+    }
+}
+
+impl<T: Eq> Eq for DequeueList<T> {}
+
+impl<T: Clone> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let node = self.front.take()?;
+        let node = node.borrow();
+        self.front = node.next.clone();
+        self.len -= 1;
+
+        Some(node.elem.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for Iter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let node = self.back.take()?;
+        let node = node.borrow();
+        self.back = node.prev();
+        self.len -= 1;
+
+        Some(node.elem.clone())
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for Iter<T> {}
+
+impl<T> IntoIterator for DequeueList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::DequeueList;
+    use crate::safe::xorshift;
+
+    #[test]
+    fn test_basics() {
+        let mut list: DequeueList<_> = (1..=3).collect();
+        list.push_front(0);
+        *list.back_mut().unwrap() = 30;
+        assert_eq!((*list.front().unwrap(), *list.back().unwrap()), (0, 30));
+        assert_eq!(list.iter().rev().collect::<Vec<_>>(), [30, 2, 1, 0]);
+
+        list.retain(|elem| elem % 2 == 0);
+        assert_eq!(format!("{list:?}"), "[0, 2, 30]");
+        assert_eq!(list.clone(), list);
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [0, 2]);
+    }
+
+    #[test]
+    fn test_against_unsafe() {
+        let mut safe = DequeueList::new();
+        let mut real = crate::DequeueList::new();
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+
+        for _ in 0..2000 {
+            let value = xorshift(&mut state) % 50;
+            match xorshift(&mut state) % 8 {
+                0 | 1 => {
+                    safe.push_back(value);
+                    real.push_back(value);
+                }
+                2 | 3 => {
+                    safe.push_front(value);
+                    real.push_front(value);
+                }
+                4 => assert_eq!(safe.pop_front(), real.pop_front()),
+                5 => assert_eq!(safe.pop_back(), real.pop_back()),
+                6 => {
+                    safe.retain(|elem| elem % 5 != value % 5);
+                    real.retain(|elem| elem % 5 != value % 5);
+                }
+                _ => {
+                    safe.sort();
+                    real.sort();
+                }
+            }
+
+            assert_eq!(safe.len(), real.len());
+            assert_eq!(safe.front().as_deref(), real.front());
+            assert_eq!(safe.back().as_deref(), real.back());
+        }
+        assert!(safe.iter().eq(real.iter().copied()));
+        assert!(safe.iter().rev().eq(real.iter().rev().copied()));
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    cmp::Ordering,
+    fmt::Debug,
+    rc::Rc,
+};
+
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+/// Safe [`LinkedList`](crate::LinkedList): the nodes are owned by their
+/// predecessor, the last one being shared with `tail`.
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    size: u32,
+}
+
+impl<T: PartialEq> LinkedList<T> {
+    pub fn new() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            size: 0,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn contains(&self, elem: &T) -> bool {
+        let mut node = self.head.clone();
+
+        while let Some(current) = node {
+            if current.borrow().elem == *elem {
+                return true;
+            }
+            node = current.borrow().next.clone();
+        }
+
+        false
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn add(&mut self, elem: T) {
+        let node = Rc::new(RefCell::new(Node { elem, next: None }));
+
+        match self.tail.take() {
+            Some(tail) => tail.borrow_mut().next = Some(Rc::clone(&node)),
+            None => self.head = Some(Rc::clone(&node)),
+        }
+
+        self.tail = Some(node);
+        self.size += 1;
+    }
+
+    /// Removes the first node from the list and returns its value.
+    pub fn pop(&mut self) -> Option<T> {
+        self.head.take().map(|head| {
+            self.head = head.borrow_mut().next.take();
+
+            if self.head.is_none() {
+                self.tail = None;
+            }
+
+            self.size -= 1;
+
+            into_elem(head)
+        })
+    }
+
+    /// Removes the first node with value `elem`.
+    pub fn remove(&mut self, elem: T) -> Option<T> {
+        let mut prev: Link<T> = None;
+        let mut node = self.head.clone();
+
+        while let Some(current) = node {
+            let next = current.borrow().next.clone();
+
+            if current.borrow().elem == elem {
+                self.unlink(prev.as_ref(), next);
+                return Some(into_elem(current));
+            }
+
+            prev = Some(current);
+            node = next;
+        }
+
+        None
+    }
+
+    /// Keeps only the elements for which `keep` returns `true`.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let mut prev: Link<T> = None;
+        let mut node = self.head.clone();
+
+        while let Some(current) = node {
+            let next = current.borrow().next.clone();
+
+            if keep(&current.borrow().elem) {
+                prev = Some(current);
+            } else {
+                self.unlink(prev.as_ref(), next.clone());
+            }
+
+            node = next;
+        }
+    }
+
+    /// Takes the node between `prev` and `next` out of the list.
+    fn unlink(&mut self, prev: Option<&Rc<RefCell<Node<T>>>>, next: Link<T>) {
+        if next.is_none() {
+            self.tail = prev.cloned();
+        }
+
+        match prev {
+            Some(prev) => prev.borrow_mut().next = next,
+            None => self.head = next,
+        }
+
+        self.size -= 1;
+    }
+
+    /// First element, borrowed from its node.
+    pub fn first(&self) -> Option<Ref<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.elem))
+    }
+
+    pub fn first_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Stable sort. If `compare` panics the list keeps all its elements, in
+    /// no particular order.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        let mut elems = Vec::with_capacity(self.size as usize);
+        while let Some(elem) = self.pop() {
+            elems.push(elem);
+        }
+
+        super::sort_into(elems, compare, |elem| self.add(elem));
+    }
+}
+
+/// Moves the element out of a node nothing else points to.
+fn into_elem<T>(node: Rc<RefCell<Node<T>>>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(node) => node.into_inner().elem,
+        Err(_) => unreachable!("node still linked"),
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // Unlinks one node at a time, dropping the head would recurse down
+        // the whole list.
+        let mut node = self.head.take();
+        while let Some(current) = node {
+            node = current.borrow_mut().next.take();
+        }
+    }
+}
+
+impl<T: PartialEq> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Debug> Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+        let mut node = self.head.clone();
+
+        while let Some(current) = node {
+            list.entry(&current.borrow().elem);
+            node = current.borrow().next.clone();
+        }
+
+        list.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+    use crate::safe::xorshift;
+
+    #[test]
+    fn test_basics() {
+        let mut list = LinkedList::new();
+        for elem in [3, 1, 2] {
+            list.add(elem);
+        }
+        assert!(list.contains(&1));
+        assert_eq!(list.remove(1), Some(1));
+        assert_eq!(list.remove(1), None);
+
+        *list.first_mut().unwrap() = 30;
+        list.add(4);
+        assert_eq!(format!("{list:?}"), "[30, 2, 4]");
+        list.sort();
+        assert_eq!(*list.first().unwrap(), 2);
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.size(), 2);
+    }
+
+    #[test]
+    fn test_long_drop() {
+        let mut list = LinkedList::new();
+        for elem in 0..100_000 {
+            list.add(elem);
+        }
+    }
+
+    #[test]
+    fn test_against_unsafe() {
+        let mut safe = LinkedList::new();
+        let mut real = crate::LinkedList::new();
+        let mut state = 0x2545_f491_4f6c_dd1d;
+
+        for _ in 0..2000 {
+            let value = xorshift(&mut state) % 50;
+            match xorshift(&mut state) % 6 {
+                0 | 1 => {
+                    safe.add(value);
+                    real.add(value);
+                }
+                2 => assert_eq!(safe.pop(), real.pop()),
+                3 => assert_eq!(safe.remove(value), real.remove(value)),
+                4 => {
+                    safe.retain(|elem| elem % 7 != value % 7);
+                    real.retain(|elem| elem % 7 != value % 7);
+                }
+                _ => {
+                    safe.sort();
+                    real.sort();
+                }
+            }
+
+            assert_eq!(safe.size(), real.size());
+            assert_eq!(safe.contains(&value), real.contains(&value));
+        }
+        assert_eq!(format!("{safe:?}"), format!("{real:?}"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    fmt::Debug,
+    ops::{Bound, RangeBounds},
+};
+
+/// Segment tree over a fixed number of values, generic over how two values
+/// are combined.
+///
+/// `combine` has to be associative and `identity` has to be its neutral
+/// element (`0` for sums, `T::MAX` for minimums, ...). It doesn't need to be
+/// commutative: results are always combined left to right.
+///
+/// Stored bottom-up in a single buffer of `2n` values, the leaves live in
+/// `tree[n..]` and the parent of `i` is `i / 2`, so both [`update`] and
+/// [`query`] are O(log n).
+///
+/// [`update`]: SegmentTree::update
+/// [`query`]: SegmentTree::query
+pub struct SegmentTree<T, F> {
+    tree: Vec<T>,
+    len: usize,
+    identity: T,
+    combine: F,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> SegmentTree<T, F> {
+    /// Builds the tree over `values` in O(n).
+    pub fn build(values: &[T], identity: T, combine: F) -> Self {
+        let len = values.len();
+        let mut tree = Vec::with_capacity(2 * len);
+
+        // tree[0] is never used.
+        tree.resize(len, identity.clone());
+        tree.extend_from_slice(values);
+
+        for i in (1..len).rev() {
+            tree[i] = combine(&tree[2 * i], &tree[2 * i + 1]);
+        }
+
+        SegmentTree {
+            tree,
+            len,
+            identity,
+            combine,
+        }
+    }
+
+    /// Replaces the value at `index` and recomputes its ancestors.
+    /// Panics if `index >= len`.
+    pub fn update(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "Index out of bounds");
+
+        let mut i = index + self.len;
+        self.tree[i] = value;
+
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = (self.combine)(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    /// Combines all the values in `range`, returns `identity` if it is
+    /// empty. Panics if `range` is out of bounds.
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+
+        assert!(start <= end && end <= self.len, "Range out of bounds");
+
+        // Walk up from both ends, collecting the nodes that are only partly
+        // covered by their parent in separate accumulators to keep the order.
+        let mut left = self.identity.clone();
+        let mut right = self.identity.clone();
+        let (mut l, mut r) = (start + self.len, end + self.len);
+
+        while l < r {
+            if l % 2 == 1 {
+                left = (self.combine)(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right = (self.combine)(&self.tree[r], &right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+
+        (self.combine)(&left, &right)
+    }
+}
+
+impl<T, F> SegmentTree<T, F> {
+    /// Number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.values().get(index)
+    }
+
+    /// Returns the values the tree was built over, with updates applied.
+    pub fn values(&self) -> &[T] {
+        &self.tree[self.len..]
+    }
+}
+
+impl<T: Debug, F> Debug for SegmentTree<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentTree;
+
+    #[test]
+    fn test_sum() {
+        let mut values: Vec<i64> = (0..37).map(|i| (i * 17) % 11 - 5).collect();
+        let mut tree = SegmentTree::build(&values, 0, |a, b| a + b);
+
+        for i in [0, 5, 36, 12] {
+            tree.update(i, i as i64 * 3);
+            values[i] = i as i64 * 3;
+        }
+
+        for start in 0..=values.len() {
+            for end in start..=values.len() {
+                assert_eq!(tree.query(start..end), values[start..end].iter().sum());
+            }
+        }
+
+        assert_eq!(tree.query(..), values.iter().sum());
+        assert_eq!(tree.query(3..=3), values[3]);
+        assert_eq!(tree.values(), values);
+    }
+
+    #[test]
+    fn test_min() {
+        let tree = SegmentTree::build(&[5, 3, 8, 1, 9, 2], i32::MAX, |a, b| *a.min(b));
+
+        assert_eq!(tree.query(..3), 3);
+        assert_eq!(tree.query(2..), 1);
+        assert_eq!(tree.query(4..), 2);
+        assert_eq!(tree.query(2..2), i32::MAX);
+        assert_eq!(tree.get(2), Some(&8));
+    }
+
+    #[test]
+    fn test_non_commutative() {
+        let words: Vec<String> = "abcdefghij".chars().map(String::from).collect();
+        let mut tree = SegmentTree::build(&words, String::new(), |a, b| format!("{a}{b}"));
+
+        assert_eq!(tree.query(..), "abcdefghij");
+        assert_eq!(tree.query(1..8), "bcdefgh");
+
+        tree.update(4, "E".to_string());
+        assert_eq!(tree.query(3..6), "dEf");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_query_out_of_bounds() {
+        let tree = SegmentTree::build(&[1, 2, 3], 0, |a, b| a + b);
+        tree.query(1..4);
+    }
+}
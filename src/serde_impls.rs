@@ -0,0 +1,40 @@
+//! Shared pieces of the `serde` implementations, which live next to each
+//! collection. Collections are serialized as plain sequences of their
+//! elements.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::de::{SeqAccess, Visitor};
+
+/// Deserializes a sequence by pushing every element into `collection`.
+pub(crate) struct SeqVisitor<C, T> {
+    collection: C,
+    push: fn(&mut C, T),
+    marker: PhantomData<T>,
+}
+
+impl<C, T> SeqVisitor<C, T> {
+    pub(crate) fn new(collection: C, push: fn(&mut C, T)) -> Self {
+        SeqVisitor {
+            collection,
+            push,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, C, T: serde::Deserialize<'de>> Visitor<'de> for SeqVisitor<C, T> {
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<C, A::Error> {
+        while let Some(elem) = seq.next_element()? {
+            (self.push)(&mut self.collection, elem);
+        }
+
+        Ok(self.collection)
+    }
+}
@@ -0,0 +1,260 @@
+//! Copy-on-write handles to a collection, for cheap snapshots like the
+//! states of an undo stack. Cloning a handle only bumps its reference count.
+
+use std::{rc::Rc, sync::Arc};
+
+use crate::{DequeueList, Vector};
+
+/// Implements what a handle `$name(Rc<$target>)` or `$name(Arc<$target>)`
+/// forwards to its value: `ptr_eq`, `Clone`, `Deref`, `From`, `Debug`,
+/// `PartialEq` and `Eq`.
+macro_rules! ref_counted {
+    ($name:ident<$param:ident>, $rc:ident, $target:ty) => {
+        impl<$param> $name<$param> {
+            /// Returns `true` if both point to the same value.
+            pub fn ptr_eq(&self, other: &Self) -> bool {
+                $rc::ptr_eq(&self.0, &other.0)
+            }
+        }
+
+        impl<$param> Clone for $name<$param> {
+            fn clone(&self) -> Self {
+                $name($rc::clone(&self.0))
+            }
+        }
+
+        impl<$param> std::ops::Deref for $name<$param> {
+            type Target = $target;
+
+            fn deref(&self) -> &$target {
+                &self.0
+            }
+        }
+
+        impl<$param> From<$target> for $name<$param> {
+            fn from(value: $target) -> Self {
+                $name($rc::new(value))
+            }
+        }
+
+        impl<$param> std::fmt::Debug for $name<$param>
+        where
+            $target: std::fmt::Debug,
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Debug::fmt(&self.0, f)
+            }
+        }
+
+        impl<$param> PartialEq for $name<$param>
+        where
+            $target: PartialEq,
+        {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl<$param> Eq for $name<$param> where $target: Eq {}
+    };
+}
+
+pub(crate) use ref_counted;
+
+/// Collection behind an `Rc`, copied the first time it's mutated while
+/// shared.
+///
+/// After [`Shared::make_mut`] made the copy, it's owned and mutated in place.
+/// For a [`Vector`] the copy is a single buffer, a [`DequeueList`] has its
+/// nodes linked both ways so every node is copied.
+pub struct Shared<C>(Rc<C>);
+
+pub type SharedVector<T> = Shared<Vector<T>>;
+
+pub type SharedDequeueList<T> = Shared<DequeueList<T>>;
+
+impl<C> Shared<C> {
+    pub fn new(collection: C) -> Self {
+        Shared(Rc::new(collection))
+    }
+
+    /// Returns `true` if no other `Shared` points to the same collection, so
+    /// mutating it won't copy.
+    pub fn is_unique(&self) -> bool {
+        Rc::strong_count(&self.0) == 1
+    }
+}
+
+impl<C: Clone> Shared<C> {
+    /// Mutable access to the collection, copying it first if it's shared.
+    pub fn make_mut(&mut self) -> &mut C {
+        Rc::make_mut(&mut self.0)
+    }
+
+    /// Returns the collection, copying it if it's shared.
+    pub fn into_inner(self) -> C {
+        Rc::unwrap_or_clone(self.0)
+    }
+}
+
+ref_counted!(Shared<C>, Rc, C);
+
+impl<C: Default> Default for Shared<C> {
+    fn default() -> Self {
+        Self::new(C::default())
+    }
+}
+
+/// [`Vector`] behind an `Arc`, whose mutating methods copy the buffer the
+/// first time a shared one is changed. Snapshots can be read from other
+/// threads while the original keeps changing.
+pub struct CowVector<T>(Arc<Vector<T>>);
+
+impl<T> CowVector<T> {
+    pub fn new() -> Self {
+        CowVector(Arc::new(Vector::new()))
+    }
+
+    /// Returns `true` if no other `CowVector` points to the same buffer, so
+    /// mutating it won't copy.
+    pub fn is_unique(&self) -> bool {
+        Arc::strong_count(&self.0) == 1
+    }
+}
+
+impl<T: Clone> CowVector<T> {
+    /// Mutable access to the vector, copying it first if it's shared.
+    pub fn make_mut(&mut self) -> &mut Vector<T> {
+        Arc::make_mut(&mut self.0)
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.make_mut().push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.make_mut().pop()
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.make_mut().insert(index, value);
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        self.make_mut().remove(index)
+    }
+
+    /// Mutable access to the value at `index`, copying the buffer first if
+    /// it's shared. Out of bounds indices don't copy.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len() {
+            return None;
+        }
+        self.make_mut().get_mut(index)
+    }
+
+    /// Returns the vector, copying it if it's shared.
+    pub fn into_vector(self) -> Vector<T> {
+        Arc::unwrap_or_clone(self.0)
+    }
+}
+
+ref_counted!(CowVector<T>, Arc, Vector<T>);
+
+impl<T> FromIterator<T> for CowVector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vector<T>>())
+    }
+}
+
+impl<T> Default for CowVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{CowVector, Shared, SharedDequeueList, SharedVector};
+
+    #[test]
+    fn test_copy_on_write() {
+        let mut vec = SharedVector::new(vector![1, 2, 3]);
+        let snapshot = vec.clone();
+        assert!(vec.ptr_eq(&snapshot) && !vec.is_unique());
+
+        vec.make_mut().push(4);
+        assert!(!vec.ptr_eq(&snapshot) && vec.is_unique());
+        assert_eq!(**vec, [1, 2, 3, 4]);
+        assert_eq!(**snapshot, [1, 2, 3]);
+
+        // Owned now, so the buffer is reused.
+        let ptr = vec.as_ptr();
+        vec.make_mut()[0] = 10;
+        assert_eq!(vec.as_ptr(), ptr);
+
+        drop(snapshot);
+        assert_eq!(*vec.into_inner(), [10, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_undo_stack() {
+        let mut current = SharedDequeueList::new(deque![1]);
+        let mut undo = Vec::new();
+
+        for elem in 2..=4 {
+            undo.push(current.clone());
+            current.make_mut().push_back(elem);
+        }
+        assert_eq!(current, Shared::new(deque![1, 2, 3, 4]));
+
+        current = undo.pop().unwrap();
+        assert_eq!(format!("{current:?}"), "[1, 2, 3]");
+        current = undo.pop().unwrap();
+        assert_eq!(format!("{current:?}"), "[1, 2]");
+        assert!(current.ptr_eq(&current.clone()));
+        assert_eq!(SharedDequeueList::<i32>::default().len(), 0);
+    }
+
+    #[test]
+    fn test_cow_vector() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CowVector<String>>();
+
+        let mut vec: CowVector<_> = (1..=3).collect();
+        let snapshot = vec.clone();
+        assert!(vec.ptr_eq(&snapshot) && !vec.is_unique());
+
+        // Reading from another thread while the original is mutated.
+        let reader = thread::spawn(move || snapshot.iter().sum::<i32>());
+        assert_eq!(vec.get_mut(3), None);
+        *vec.get_mut(0).unwrap() = 10;
+        assert_eq!(reader.join().unwrap(), 6);
+
+        // Owned now, so the buffer is reused.
+        let snapshot = vec.clone();
+        assert!(!vec.is_unique());
+        drop(snapshot);
+        assert!(vec.is_unique());
+        vec.make_mut().reserve(2);
+        let ptr = vec.as_ptr();
+        vec.insert(1, 20);
+        vec.push(4);
+        assert_eq!(vec.remove(2), 2);
+        assert_eq!(vec.pop(), Some(4));
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(**vec, [10, 20, 3]);
+
+        let snapshot = vec.clone();
+        vec.push(5);
+        assert_eq!(format!("{snapshot:?}"), "[10, 20, 3]");
+        assert_ne!(vec, snapshot);
+        assert_eq!(vec.into_vector(), vector![10, 20, 3, 5]);
+        assert_eq!(CowVector::<i32>::default().pop(), None);
+    }
+}
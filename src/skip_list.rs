@@ -0,0 +1,270 @@
+use std::{fmt::Debug, ops::RangeBounds};
+
+use crate::skip_list_map::{self, SkipListMap};
+use crate::traits::MemoryUsage;
+
+/// Ordered set stored in a probabilistic skip list.
+///
+/// Every element lives on level 0 and is promoted to each level above with
+/// probability 1/2, which gives expected O(log n) search, insert and remove.
+/// This is a [`SkipListMap`] with `()` values.
+pub struct SkipList<T> {
+    map: SkipListMap<T, ()>,
+}
+
+pub struct Iter<'a, T>(skip_list_map::Keys<'a, T, ()>);
+
+/// Iterator over the elements inside a range, see [`SkipList::range`].
+pub struct Range<'a, T>(skip_list_map::Range<'a, T, ()>);
+
+pub struct IntoIter<T>(skip_list_map::IntoIter<T, ()>);
+
+impl<T> SkipList<T> {
+    pub fn new() -> Self {
+        SkipList {
+            map: SkipListMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Removes and returns the smallest element.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.map.pop_first().map(|(elem, _)| elem)
+    }
+
+    /// Returns the smallest element.
+    pub fn first(&self) -> Option<&T> {
+        self.map.first_key_value().map(|(elem, _)| elem)
+    }
+
+    /// Returns the largest element.
+    pub fn last(&self) -> Option<&T> {
+        self.map.last_key_value().map(|(elem, _)| elem)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.map.keys())
+    }
+}
+
+impl<T: Ord> SkipList<T> {
+    /// Adds `elem` to the set. Returns `false` if it was already present.
+    pub fn insert(&mut self, elem: T) -> bool {
+        self.map.insert(elem, ()).is_none()
+    }
+
+    /// Returns `true` if `elem` is in the set.
+    pub fn contains(&self, elem: &T) -> bool {
+        self.map.contains_key(elem)
+    }
+
+    /// Returns the element in the set that is equal to `elem`.
+    pub fn get(&self, elem: &T) -> Option<&T> {
+        self.map.get_key_value(elem).map(|(elem, _)| elem)
+    }
+
+    /// Removes `elem` from the set and returns it.
+    pub fn remove(&mut self, elem: &T) -> Option<T> {
+        self.map.remove_entry(elem).map(|(elem, _)| elem)
+    }
+
+    /// Iterates in order over the elements contained in `range`.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        Range(self.map.range(range))
+    }
+}
+
+impl<T> Default for SkipList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(elem, _)| elem)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SkipList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for SkipList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.map.into_iter())
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(elem, _)| elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Ord> Extend<T> for SkipList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkipList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+
+        list
+    }
+}
+
+impl<T: Debug> Debug for SkipList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T> MemoryUsage for SkipList<T> {
+    fn heap_bytes(&self) -> usize {
+        self.map.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        self.map.node_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipList;
+
+    fn list_values() -> Vec<i32> {
+        vec![40, 20, 60, 10, 30, 25, 35, 50, 45, 70, 80, 75]
+    }
+
+    #[test]
+    fn test_insert_contains() {
+        let mut list = SkipList::new();
+
+        for value in list_values() {
+            assert!(list.insert(value));
+        }
+
+        assert!(!list.insert(40));
+        assert_eq!(list.len(), 12);
+
+        for value in list_values() {
+            assert!(list.contains(&value));
+        }
+
+        assert!(!list.contains(&0));
+        assert!(!list.contains(&55));
+        assert!(!list.contains(&100));
+        assert_eq!(list.first(), Some(&10));
+        assert_eq!(list.last(), Some(&80));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut list: SkipList<i32> = list_values().into_iter().collect();
+
+        assert_eq!(list.remove(&55), None);
+        assert_eq!(list.remove(&10), Some(10));
+        assert_eq!(list.remove(&80), Some(80));
+        assert_eq!(list.remove(&40), Some(40));
+        assert_eq!(list.remove(&40), None);
+        assert_eq!(list.len(), 9);
+
+        assert_eq!(list.first(), Some(&20));
+        assert_eq!(list.last(), Some(&75));
+        assert!(!list.contains(&40));
+        assert!(list.contains(&45));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut values = list_values();
+        let list: SkipList<i32> = values.iter().copied().collect();
+
+        values.sort();
+        assert!(list.iter().eq(values.iter()));
+        assert_eq!(list.iter().len(), values.len());
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_range() {
+        let list: SkipList<i32> = (0..100).map(|i| i * 2).collect();
+
+        let range = |r: std::ops::Range<i32>| list.range(r).copied().collect::<Vec<_>>();
+        assert_eq!(range(10..20), [10, 12, 14, 16, 18]);
+        assert_eq!(range(11..19), [12, 14, 16, 18]);
+        assert_eq!(range(20..20), []);
+        assert_eq!(range(500..600), []);
+
+        assert_eq!(list.range(..=4).copied().collect::<Vec<_>>(), [0, 2, 4]);
+        assert_eq!(list.range(194..).copied().collect::<Vec<_>>(), [194, 196, 198]);
+        assert_eq!(list.range(..).count(), 100);
+    }
+
+    #[test]
+    fn test_drop() {
+        use std::rc::Rc;
+
+        let elem = Rc::new(0);
+        let mut list = SkipList::new();
+
+        for i in 0..10 {
+            list.insert((i, Rc::clone(&elem)));
+        }
+        list.remove(&(3, Rc::clone(&elem)));
+
+        assert_eq!(Rc::strong_count(&elem), 10);
+        drop(list);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+}
@@ -0,0 +1,598 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Debug,
+    hash::{BuildHasher, Hasher},
+    marker::PhantomData,
+    mem,
+    ops::{Bound, RangeBounds},
+    ptr,
+};
+
+use crate::alloc::Global;
+use crate::raw::NodePtr;
+use crate::traits::MemoryUsage;
+
+/// Maximum height of a tower. With `p = 1/2` this comfortably covers lists
+/// of up to `2^32` elements.
+const MAX_LEVEL: usize = 32;
+
+/// Skip list node. `next[i]` is the following node on level `i`, so the
+/// height of the tower is `next.len()`.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Vec<Link<K, V>>,
+}
+
+type Link<K, V> = Option<NodePtr<Node<K, V>>>;
+
+/// Ordered map stored in a probabilistic skip list.
+///
+/// Every entry lives on level 0 and is promoted to each level above with
+/// probability 1/2, which gives expected O(log n) search, insert and remove.
+/// [`SkipList`](crate::SkipList) is built on top of this map.
+pub struct SkipListMap<K, V> {
+    head: [Link<K, V>; MAX_LEVEL],
+    len: usize,
+    rng: u64,
+    _marker: PhantomData<(K, V)>,
+}
+
+unsafe impl<K: Send, V: Send> Send for SkipListMap<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipListMap<K, V> {}
+
+pub struct Iter<'a, K, V> {
+    next: Link<K, V>,
+    len: usize,
+    marker: PhantomData<(&'a K, &'a V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Iter<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    next: Link<K, V>,
+    len: usize,
+    marker: PhantomData<(&'a K, &'a mut V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Send> Send for IterMut<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for IterMut<'a, K, V> {}
+
+pub struct Keys<'a, K, V>(Iter<'a, K, V>);
+
+pub struct Values<'a, K, V>(Iter<'a, K, V>);
+
+/// Iterator over the entries inside a range, see [`SkipListMap::range`].
+pub struct Range<'a, K, V> {
+    next: Link<K, V>,
+    end: Link<K, V>,
+    marker: PhantomData<(&'a K, &'a V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Range<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Range<'a, K, V> {}
+
+pub struct IntoIter<K, V>(SkipListMap<K, V>);
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V, height: usize) -> NodePtr<Node<K, V>> {
+        NodePtr::alloc(
+            &Global,
+            Node {
+                key,
+                value,
+                next: vec![None; height],
+            },
+        )
+    }
+}
+
+impl<K, V> SkipListMap<K, V> {
+    pub fn new() -> Self {
+        SkipListMap {
+            head: [None; MAX_LEVEL],
+            len: 0,
+            // Any non-zero seed works for xorshift.
+            rng: RandomState::new().build_hasher().finish() | 1,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_first().is_some() {}
+    }
+
+    /// Picks the height of a new tower, each extra level with probability 1/2.
+    fn random_level(&mut self) -> usize {
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+
+        (self.rng.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    /// Returns the first node on level 0 whose key doesn't satisfy `before`.
+    /// `before` must be monotonic: `true` for a prefix of the list and
+    /// `false` for the rest.
+    fn first_where_not(&self, before: impl Fn(&K) -> bool) -> Link<K, V> {
+        let mut links: &[Link<K, V>] = &self.head;
+
+        unsafe {
+            for level in (0..MAX_LEVEL).rev() {
+                while let Some(next) = links[level] {
+                    if !before(&next.as_ref().key) {
+                        break;
+                    }
+                    links = &next.as_ref().next;
+                }
+            }
+        }
+
+        links[0]
+    }
+
+    /// Removes and returns the entry with the smallest key.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let node = self.head[0]?;
+
+        unsafe {
+            let node = node.dealloc(&Global);
+
+            // The first node is right after the head on every level it's in.
+            for (level, next) in node.next.iter().enumerate() {
+                self.head[level] = *next;
+            }
+
+            self.len -= 1;
+
+            Some((node.key, node.value))
+        }
+    }
+
+    /// Returns the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        unsafe {
+            let node = self.head[0]?.as_ref();
+            Some((&node.key, &node.value))
+        }
+    }
+
+    /// Returns the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut links: &[Link<K, V>] = &self.head;
+        let mut last = None;
+
+        unsafe {
+            for level in (0..MAX_LEVEL).rev() {
+                while let Some(next) = links[level] {
+                    last = Some(next);
+                    links = &next.as_ref().next;
+                }
+            }
+
+            let node = last?.as_ref();
+            Some((&node.key, &node.value))
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            next: self.head[0],
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            next: self.head[0],
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    /// For every level, a pointer to the link that points to the first node
+    /// whose key is not less than `key` on that level.
+    unsafe fn search(&mut self, key: &K) -> [*mut Link<K, V>; MAX_LEVEL] {
+        let mut update = [ptr::null_mut(); MAX_LEVEL];
+        let mut links: *mut [Link<K, V>] = &mut self.head[..];
+
+        unsafe {
+            for level in (0..MAX_LEVEL).rev() {
+                while let Some(next) = (*links)[level] {
+                    if next.as_ref().key >= *key {
+                        break;
+                    }
+                    let next = next.as_mut();
+                    links = next.next.as_mut_slice();
+                }
+
+                update[level] = &mut (*links)[level];
+            }
+        }
+
+        update
+    }
+
+    /// Returns the node whose key is equal to `key`.
+    fn find(&self, key: &K) -> Link<K, V> {
+        self.first_where_not(|k| k < key)
+            .filter(|node| unsafe { node.as_ref().key == *key })
+    }
+
+    /// Inserts `value` under `key`, returning the old value if the key was
+    /// already present. The key itself is not updated in that case.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let height = self.random_level();
+
+        unsafe {
+            let update = self.search(&key);
+
+            if let Some(next) = *update[0]
+                && next.as_ref().key == key
+            {
+                return Some(std::mem::replace(&mut next.as_mut().value, value));
+            }
+
+            let node = Node::new(key, value, height);
+            let tower = &mut node.as_mut().next;
+
+            for (level, link) in update.iter().enumerate().take(height) {
+                tower[level] = **link;
+                **link = Some(node);
+            }
+        }
+
+        self.len += 1;
+
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        unsafe { Some(&self.find(key)?.as_ref().value) }
+    }
+
+    /// Returns the stored key equal to `key` along with its value.
+    pub fn get_key_value(&self, key: &K) -> Option<(&K, &V)> {
+        unsafe {
+            let node = self.find(key)?.as_ref();
+            Some((&node.key, &node.value))
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        unsafe { Some(&mut self.find(key)?.as_mut().value) }
+    }
+
+    /// Removes `key` from the map, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes `key` from the map, returning the stored key and value.
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        unsafe {
+            let update = self.search(key);
+            let node = (*update[0])?;
+
+            if node.as_ref().key != *key {
+                return None;
+            }
+
+            let node = node.dealloc(&Global);
+
+            for (level, next) in node.next.iter().enumerate() {
+                *update[level] = *next;
+            }
+
+            self.len -= 1;
+
+            Some((node.key, node.value))
+        }
+    }
+
+    /// Iterates in order over the entries whose keys are contained in `range`.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.first_where_not(|k| k < start),
+            Bound::Excluded(start) => self.first_where_not(|k| k <= start),
+            Bound::Unbounded => self.head[0],
+        };
+
+        let in_range = |k: &K| match range.end_bound() {
+            Bound::Included(end) => k <= end,
+            Bound::Excluded(end) => k < end,
+            Bound::Unbounded => true,
+        };
+
+        // Empty when the range starts past its end.
+        let next = next.filter(|node| unsafe { in_range(&node.as_ref().key) });
+        let end = self.first_where_not(in_range);
+
+        Range {
+            next,
+            end,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Drop for SkipListMap<K, V> {
+    fn drop(&mut self) {
+        let mut current = self.head[0];
+
+        while let Some(node) = current {
+            unsafe {
+                let node = node.dealloc(&Global);
+                current = node.next[0];
+            }
+        }
+    }
+}
+
+impl<K, V> MemoryUsage for SkipListMap<K, V> {
+    /// Walks the whole list, since the towers vary in height.
+    fn heap_bytes(&self) -> usize {
+        let mut bytes = 0;
+        let mut current = self.head[0];
+
+        while let Some(node) = current {
+            let node = unsafe { node.as_ref() };
+            bytes +=
+                mem::size_of::<Node<K, V>>() + node.next.capacity() * mem::size_of::<Link<K, V>>();
+            current = node.next[0];
+        }
+
+        bytes
+    }
+
+    fn node_count(&self) -> usize {
+        self.len
+    }
+}
+
+impl<K, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| unsafe {
+            let node = node.as_ref();
+            self.len -= 1;
+            self.next = node.next[0];
+            (&node.key, &node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| unsafe {
+            let node = node.as_mut();
+            self.len -= 1;
+            self.next = node.next[0];
+            (&node.key, &mut node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.next.map(|node| unsafe {
+            let node = node.as_ref();
+            self.next = node.next[0];
+            (&node.key, &node.value)
+        })
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipListMap<K, V> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SkipListMap<K, V> {
+    type IntoIter = IterMut<'a, K, V>;
+    type Item = (&'a K, &'a mut V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for SkipListMap<K, V> {
+    type IntoIter = IntoIter<K, V>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipListMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipListMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipListMap;
+
+    fn map_from(keys: &[i32]) -> SkipListMap<i32, String> {
+        keys.iter().map(|key| (*key, key.to_string())).collect()
+    }
+
+    #[test]
+    fn test_insert_get() {
+        let mut map = map_from(&[30, 10, 20]);
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.get(&10), Some(&"10".to_string()));
+        assert_eq!(map.get(&15), None);
+        assert_eq!(map.insert(10, "ten".to_string()), Some("10".to_string()));
+        assert_eq!(map.len(), 3);
+
+        map.get_mut(&20).unwrap().push('!');
+        assert_eq!(map.get(&20).map(String::as_str), Some("20!"));
+        assert!(map.contains_key(&30));
+        assert_eq!(map.first_key_value(), Some((&10, &"ten".to_string())));
+        assert_eq!(map.last_key_value(), Some((&30, &"30".to_string())));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = map_from(&[5, 1, 4, 2, 3]);
+
+        assert_eq!(map.remove(&3), Some("3".to_string()));
+        assert_eq!(map.remove(&3), None);
+        assert_eq!(map.remove_entry(&1), Some((1, "1".to_string())));
+        assert_eq!(map.pop_first(), Some((2, "2".to_string())));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [4, 5]);
+    }
+
+    #[test]
+    fn test_iter_range() {
+        let mut map = map_from(&[8, 2, 6, 4, 10]);
+
+        for (_, value) in map.iter_mut() {
+            value.insert(0, '#');
+        }
+
+        assert_eq!(
+            format!("{:?}", map),
+            r##"{2: "#2", 4: "#4", 6: "#6", 8: "#8", 10: "#10"}"##
+        );
+
+        let range: Vec<_> = map.range(3..=8).map(|(key, _)| *key).collect();
+        assert_eq!(range, [4, 6, 8]);
+
+        let values: Vec<_> = map.values().cloned().collect();
+        assert_eq!(values, ["#2", "#4", "#6", "#8", "#10"]);
+        assert_eq!(
+            map.into_iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            [2, 4, 6, 8, 10]
+        );
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SkipListMap<i32, String>>();
+        assert_send_sync::<super::Iter<'_, i32, String>>();
+        assert_send_sync::<super::IterMut<'_, i32, String>>();
+        assert_send_sync::<super::Range<'_, i32, String>>();
+    }
+}
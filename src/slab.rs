@@ -0,0 +1,305 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::Vector;
+use crate::traits::MemoryUsage;
+
+enum Slot<T> {
+    Occupied(T),
+    /// Holds the key of the next vacant slot, the free list ends at the
+    /// length of `slots`.
+    Vacant(usize),
+}
+
+/// Storage with stable `usize` keys.
+///
+/// Values live in a [`Vector`] of slots and the key of a value is the index
+/// of its slot, so lookups are a plain index. Removed slots are chained into
+/// a free list and reused by later inserts, which makes both [`insert`] and
+/// [`remove`] O(1). Keys are reused, so a key must not be used after its
+/// value was removed.
+///
+/// [`insert`]: Slab::insert
+/// [`remove`]: Slab::remove
+pub struct Slab<T> {
+    slots: Vector<Slot<T>>,
+    /// First vacant slot.
+    next_free: usize,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T> {
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<T> Slab<T> {
+    /// Creates a new Slab with no values.
+    pub fn new() -> Self {
+        Slab {
+            slots: Vector::new(),
+            next_free: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of values stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Key the next call to [`Slab::insert`] will return.
+    pub fn vacant_key(&self) -> usize {
+        self.next_free
+    }
+
+    /// Stores `value` and returns its key.
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = self.next_free;
+
+        if key == self.slots.len() {
+            self.slots.push(Slot::Occupied(value));
+            self.next_free += 1;
+        } else {
+            let Slot::Vacant(next) = self.slots[key] else {
+                unreachable!("free list points to an occupied slot");
+            };
+
+            self.slots[key] = Slot::Occupied(value);
+            self.next_free = next;
+        }
+
+        self.len += 1;
+
+        key
+    }
+
+    /// Removes and returns the value for `key`, its slot will be reused.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let slot = self.slots.get_mut(key)?;
+
+        if let Slot::Vacant(_) = slot {
+            return None;
+        }
+
+        let Slot::Occupied(value) = std::mem::replace(slot, Slot::Vacant(self.next_free)) else {
+            unreachable!();
+        };
+
+        self.next_free = key;
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get(&self, key: usize) -> Option<&T> {
+        match self.slots.get(key)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        match self.slots.get_mut(key)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => None,
+        }
+    }
+
+    /// Removes all the values, keys start again from `0`.
+    pub fn clear(&mut self) {
+        while self.slots.pop().is_some() {}
+
+        self.next_free = 0;
+        self.len = 0;
+    }
+
+    /// Iterates over `(key, value)` in key order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Iterates over `(key, value)` in key order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, key: usize) -> &Self::Output {
+        self.get(key).expect("Invalid key")
+    }
+}
+
+impl<T> IndexMut<usize> for Slab<T> {
+    fn index_mut(&mut self, key: usize) -> &mut Self::Output {
+        self.get_mut(key).expect("Invalid key")
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, slot) in self.slots.by_ref() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, slot) in self.slots.by_ref() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a Slab<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = (usize, &'a T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Slab<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = (usize, &'a mut T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Debug> Debug for Slab<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<T> MemoryUsage for Slab<T> {
+    fn heap_bytes(&self) -> usize {
+        self.slots.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Slab;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut slab = Slab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        let c = slab.insert('c');
+        assert_eq!((a, b, c), (0, 1, 2));
+
+        assert_eq!(slab.remove(b), Some('b'));
+        assert_eq!(slab.remove(b), None);
+        assert_eq!(slab.remove(10), None);
+        assert_eq!(slab.remove(a), Some('a'));
+        assert_eq!(slab.len(), 1);
+
+        // Most recently freed slot first.
+        assert_eq!(slab.vacant_key(), a);
+        assert_eq!(slab.insert('d'), a);
+        assert_eq!(slab.insert('e'), b);
+        assert_eq!(slab.insert('f'), 3);
+
+        assert_eq!(slab[c], 'c');
+        slab[c] = 'C';
+        assert_eq!(format!("{:?}", slab), "{0: 'd', 1: 'e', 2: 'C', 3: 'f'}");
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut slab = Slab::new();
+        for i in 0..10 {
+            slab.insert(i);
+        }
+        for key in (0..10).step_by(3) {
+            slab.remove(key);
+        }
+
+        for (_, value) in &mut slab {
+            *value *= 10;
+        }
+
+        let iter = slab.iter();
+        assert_eq!(iter.len(), 6);
+        assert!(iter.eq([(1, &10), (2, &20), (4, &40), (5, &50), (7, &70), (8, &80)]));
+
+        slab.clear();
+        assert!(slab.is_empty());
+        assert_eq!(slab.insert(0), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_vacant() {
+        let mut slab = Slab::new();
+        let key = slab.insert(1);
+        slab.remove(key);
+        let _ = slab[key];
+    }
+}
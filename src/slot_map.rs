@@ -0,0 +1,358 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::Vector;
+use crate::traits::MemoryUsage;
+
+/// Key of a [`SlotMap`] value.
+///
+/// Besides the slot it remembers the generation of the slot when the value
+/// was inserted, so once the value is removed the key stops matching even if
+/// the slot is reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key {
+    index: u32,
+    generation: u32,
+}
+
+impl Key {
+    /// Slot of the key.
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+enum Content<T> {
+    Occupied(T),
+    /// Holds the index of the next vacant slot, the free list ends at the
+    /// length of `slots`.
+    Vacant(u32),
+}
+
+struct Slot<T> {
+    /// Bumped every time the value in the slot is removed.
+    generation: u32,
+    content: Content<T>,
+}
+
+/// Generational arena: a [`Slab`](crate::Slab) whose keys can't be used
+/// after their value is removed.
+///
+/// Every slot carries a generation counter that changes when its value is
+/// removed. A [`Key`] only matches the slot while the generation is the one
+/// it was created with, so stale keys get `None` instead of whatever value
+/// reused the slot. All operations are O(1).
+pub struct SlotMap<T> {
+    slots: Vector<Slot<T>>,
+    /// First vacant slot.
+    next_free: u32,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T> {
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<T> SlotMap<T> {
+    /// Creates a new SlotMap with no values.
+    pub fn new() -> Self {
+        SlotMap {
+            slots: Vector::new(),
+            next_free: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of values stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stores `value` and returns its key. Panics if more than `u32::MAX`
+    /// slots are needed.
+    pub fn insert(&mut self, value: T) -> Key {
+        let index = self.next_free;
+
+        if index as usize == self.slots.len() {
+            assert!(index < u32::MAX, "Too many slots");
+
+            self.slots.push(Slot {
+                generation: 0,
+                content: Content::Occupied(value),
+            });
+            self.next_free += 1;
+        } else {
+            let slot = &mut self.slots[index as usize];
+            let Content::Vacant(next) = slot.content else {
+                unreachable!("free list points to an occupied slot");
+            };
+
+            slot.content = Content::Occupied(value);
+            self.next_free = next;
+        }
+
+        self.len += 1;
+
+        Key {
+            index,
+            generation: self.slots[index as usize].generation,
+        }
+    }
+
+    fn slot(&self, key: Key) -> Option<&Slot<T>> {
+        self.slots
+            .get(key.index())
+            .filter(|slot| slot.generation == key.generation)
+    }
+
+    /// Removes and returns the value for `key`, which is invalid afterwards.
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        let slot = self.slots.get_mut(key.index())?;
+
+        if slot.generation != key.generation || matches!(slot.content, Content::Vacant(_)) {
+            return None;
+        }
+
+        let Content::Occupied(value) =
+            std::mem::replace(&mut slot.content, Content::Vacant(self.next_free))
+        else {
+            unreachable!();
+        };
+
+        slot.generation = slot.generation.wrapping_add(1);
+        self.next_free = key.index;
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get(&self, key: Key) -> Option<&T> {
+        match &self.slot(key)?.content {
+            Content::Occupied(value) => Some(value),
+            Content::Vacant(_) => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        let slot = self
+            .slots
+            .get_mut(key.index())
+            .filter(|slot| slot.generation == key.generation)?;
+
+        match &mut slot.content {
+            Content::Occupied(value) => Some(value),
+            Content::Vacant(_) => None,
+        }
+    }
+
+    /// Removes all the values. Keys given out before stay invalid.
+    pub fn clear(&mut self) {
+        let mut next = self.next_free;
+
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            if let Content::Occupied(_) = slot.content {
+                slot.content = Content::Vacant(next);
+                slot.generation = slot.generation.wrapping_add(1);
+                next = index as u32;
+            }
+        }
+
+        self.next_free = next;
+        self.len = 0;
+    }
+
+    /// Iterates over `(key, value)` in slot order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Iterates over `(key, value)` in slot order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<Key> for SlotMap<T> {
+    type Output = T;
+
+    fn index(&self, key: Key) -> &Self::Output {
+        self.get(key).expect("Invalid key")
+    }
+}
+
+impl<T> IndexMut<Key> for SlotMap<T> {
+    fn index_mut(&mut self, key: Key) -> &mut Self::Output {
+        self.get_mut(key).expect("Invalid key")
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Content::Occupied(value) = &slot.content {
+                self.len -= 1;
+
+                let key = Key {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Key, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Content::Occupied(value) = &mut slot.content {
+                self.len -= 1;
+
+                let key = Key {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a SlotMap<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = (Key, &'a T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SlotMap<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = (Key, &'a mut T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Debug> Debug for SlotMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<T> MemoryUsage for SlotMap<T> {
+    fn heap_bytes(&self) -> usize {
+        self.slots.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlotMap;
+
+    #[test]
+    fn test_stale_keys() {
+        let mut map = SlotMap::new();
+        let a = map.insert("a");
+        let b = map.insert("b");
+
+        assert_eq!(map.remove(a), Some("a"));
+        assert_eq!(map.remove(a), None);
+
+        // Reuses the slot of `a` with a new generation.
+        let c = map.insert("c");
+        assert_eq!(c.index(), a.index());
+        assert_ne!(c, a);
+
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(c), Some(&"c"));
+        assert_eq!(map.remove(a), None);
+        assert_eq!(map[b], "b");
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_clear() {
+        let mut map = SlotMap::new();
+        let keys: Vec<_> = (0..6).map(|i| map.insert(i)).collect();
+        map.remove(keys[2]);
+
+        for (_, value) in &mut map {
+            *value += 10;
+        }
+        assert!(map.iter().map(|(_, v)| *v).eq([10, 11, 13, 14, 15]));
+        assert!(
+            map.iter()
+                .map(|(k, _)| k)
+                .eq(keys.iter().copied().filter(|&k| k != keys[2]))
+        );
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(keys.iter().all(|&key| !map.contains_key(key)));
+
+        // Slots are reused from the front.
+        let key = map.insert(100);
+        assert_eq!(key.index(), 0);
+        assert_eq!(key.generation(), 1);
+        assert_eq!(map.iter().count(), 1);
+    }
+}
@@ -0,0 +1,650 @@
+//! Classic comparison sorts over slices, written to be read.
+//!
+//! Every algorithm comes as `name(slice)` for `Ord` elements and
+//! `name_by(slice, compare)` with a comparator, like `slice::sort_by`.
+//! [`Vector`](crate::Vector) can pick one through [`Algorithm`], which also
+//! sorts by a [`Comparator`] shared with the ordered structures. The linked
+//! lists sort themselves by relinking their nodes with a merge sort.
+
+use std::{cmp::Ordering, mem, ptr};
+
+use crate::compare::Comparator;
+use crate::guard::Guard;
+use crate::raw::NodePtr;
+
+/// Slices this short are left to insertion sort by the recursive sorts.
+const INSERTION_THRESHOLD: usize = 16;
+
+/// Sorting algorithms of this module, for the `sort_with` adapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Stable, `O(n^2)` but the fastest on short or nearly sorted input.
+    Insertion,
+    /// Stable, `O(n log n)` with `n / 2` extra space.
+    Merge,
+    /// Unstable, `O(n log n)` expected, in place.
+    Quick,
+    /// Unstable, `O(n log n)` worst case, in place.
+    Heap,
+    /// Unstable, quick sort falling back to heap sort, `O(n log n)` worst
+    /// case and in place.
+    Intro,
+}
+
+impl Algorithm {
+    pub fn sort_by<T, F>(self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        match self {
+            Algorithm::Insertion => insertion_sort_by(slice, compare),
+            Algorithm::Merge => merge_sort_by(slice, compare),
+            Algorithm::Quick => quick_sort_by(slice, compare),
+            Algorithm::Heap => heap_sort_by(slice, compare),
+            Algorithm::Intro => intro_sort_by(slice, compare),
+        }
+    }
+
+    /// Sorts by `comparator`, like [`BTree`](crate::BTree) and
+    /// [`BinaryHeap`](crate::BinaryHeap) built with it would order.
+    pub fn sort_by_comparator<T, C: Comparator<T>>(self, slice: &mut [T], comparator: &C) {
+        self.sort_by(slice, |a, b| comparator.compare(a, b));
+    }
+}
+
+pub fn insertion_sort<T: Ord>(slice: &mut [T]) {
+    insertion_sort_by(slice, T::cmp);
+}
+
+/// Grows a sorted prefix one element at a time, swapping each new element
+/// back until it's in place.
+pub fn insertion_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in 1..slice.len() {
+        let mut j = i;
+
+        while j > 0 && compare(&slice[j], &slice[j - 1]) == Ordering::Less {
+            slice.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+pub fn merge_sort<T: Ord>(slice: &mut [T]) {
+    merge_sort_by(slice, T::cmp);
+}
+
+/// Sorts both halves and merges them, the left half is moved to a buffer so
+/// the merge can write over it.
+pub fn merge_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // Zero sized values are all the same, and the pointer arithmetic below
+    // wouldn't move.
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let mut buf: Vec<T> = Vec::with_capacity(slice.len() / 2);
+    merge_sort_recursive(slice, buf.as_mut_ptr(), &mut compare);
+}
+
+fn merge_sort_recursive<T, F>(slice: &mut [T], buf: *mut T, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.len() <= INSERTION_THRESHOLD {
+        insertion_sort_by(slice, compare);
+        return;
+    }
+
+    let mid = slice.len() / 2;
+    merge_sort_recursive(&mut slice[..mid], buf, compare);
+    merge_sort_recursive(&mut slice[mid..], buf, compare);
+
+    unsafe { merge(slice, mid, buf, compare) }
+}
+
+pub fn bottom_up_merge_sort<T: Ord>(slice: &mut [T]) {
+    bottom_up_merge_sort_by(slice, T::cmp);
+}
+
+/// Merge sort without recursion: sorts short runs with insertion sort, then
+/// merges neighbouring runs of doubling width. Stable, needs room for the
+/// widest left run, less than `n` values.
+pub fn bottom_up_merge_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = slice.len();
+    if mem::size_of::<T>() == 0 || len <= 1 {
+        return;
+    }
+
+    for run in slice.chunks_mut(INSERTION_THRESHOLD) {
+        insertion_sort_by(run, &mut compare);
+    }
+
+    let mut widest = INSERTION_THRESHOLD;
+    while widest * 2 < len {
+        widest *= 2;
+    }
+    let mut buf: Vec<T> = Vec::with_capacity(widest);
+
+    let mut width = INSERTION_THRESHOLD;
+    while width < len {
+        for start in (0..len - width).step_by(2 * width) {
+            let end = (start + 2 * width).min(len);
+            unsafe { merge(&mut slice[start..end], width, buf.as_mut_ptr(), &mut compare) };
+        }
+        width *= 2;
+    }
+}
+
+/// Merges the sorted runs `slice[..mid]` and `slice[mid..]`.
+///
+/// # Safety
+///
+/// `buf` has room for `mid` values.
+unsafe fn merge<T, F>(slice: &mut [T], mid: usize, buf: *mut T, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = slice.len();
+    let v = slice.as_mut_ptr();
+
+    unsafe {
+        ptr::copy_nonoverlapping(v, buf, mid);
+
+        // Whatever is left of the left run goes back to the slice even if
+        // `compare` panics, so every value is in the slice exactly once.
+        let mut hole = Hole {
+            start: buf,
+            end: buf.add(mid),
+            dest: v,
+        };
+
+        let mut right = v.add(mid);
+        let right_end = v.add(len);
+
+        // `dest` stays behind `right` while the left run isn't empty.
+        while hole.start < hole.end && right < right_end {
+            // Ties go to the left run, that's what makes the sort stable.
+            if compare(&*right, &*hole.start) == Ordering::Less {
+                ptr::copy_nonoverlapping(right, hole.dest, 1);
+                right = right.add(1);
+            } else {
+                ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                hole.start = hole.start.add(1);
+            }
+
+            hole.dest = hole.dest.add(1);
+        }
+
+        // The rest of the right run is already in place.
+    }
+}
+
+/// Length of the prefix of `run` whose values satisfy `pred`, which holds
+/// for a prefix only. Probes 1, 2, 4... values in and binary searches the
+/// last step, so it's O(log k) for a prefix of length k instead of the
+/// O(log n) of `partition_point`.
+pub(crate) fn gallop<T>(run: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let mut end = 1;
+    while end <= run.len() && pred(&run[end - 1]) {
+        end *= 2;
+    }
+
+    // `pred` holds up to `end / 2` and fails at `end - 1`, if it's in `run`.
+    let start = end / 2;
+    let end = (end - 1).min(run.len());
+    start + run[start..end].partition_point(pred)
+}
+
+/// Values moved out to the merge buffer, `start..end`, and the gap in the
+/// slice they belong to.
+struct Hole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+pub fn quick_sort<T: Ord>(slice: &mut [T]) {
+    quick_sort_by(slice, T::cmp);
+}
+
+/// Partitions around the median of the first, middle and last elements and
+/// sorts both sides. Only the smaller side is sorted recursively, so the
+/// stack stays `O(log n)` deep.
+pub fn quick_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quick_sort_recursive(slice, &mut compare);
+}
+
+fn quick_sort_recursive<T, F>(mut slice: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while slice.len() > INSERTION_THRESHOLD {
+        let pivot = partition(slice, compare);
+        let (left, right) = slice.split_at_mut(pivot);
+        let right = &mut right[1..];
+
+        if left.len() < right.len() {
+            quick_sort_recursive(left, compare);
+            slice = right;
+        } else {
+            quick_sort_recursive(right, compare);
+            slice = left;
+        }
+    }
+
+    insertion_sort_by(slice, compare);
+}
+
+/// Moves the pivot to its final position and returns it, with nothing
+/// greater before it and nothing smaller after it.
+fn partition<T, F>(slice: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (a, b, c) = (0, slice.len() / 2, slice.len() - 1);
+    let mut less =
+        |slice: &[T], i: usize, j: usize| compare(&slice[i], &slice[j]) == Ordering::Less;
+
+    // Order the three candidates so the median ends up at `b`.
+    if less(slice, b, a) {
+        slice.swap(a, b);
+    }
+    if less(slice, c, b) {
+        slice.swap(b, c);
+    }
+    if less(slice, b, a) {
+        slice.swap(a, b);
+    }
+    slice.swap(0, b);
+
+    let (pivot, rest) = slice.split_first_mut().unwrap();
+
+    // `rest[..l]` is never greater than the pivot and `rest[r..]` never
+    // smaller. Stopping at equal elements on both sides keeps the halves
+    // balanced when there are many duplicates.
+    let (mut l, mut r) = (0, rest.len());
+    loop {
+        while l < r && compare(&rest[l], pivot) == Ordering::Less {
+            l += 1;
+        }
+        while l < r && compare(&rest[r - 1], pivot) == Ordering::Greater {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+
+        r -= 1;
+        rest.swap(l, r);
+        l += 1;
+    }
+
+    slice.swap(0, l);
+    l
+}
+
+pub fn intro_sort<T: Ord>(slice: &mut [T]) {
+    intro_sort_by(slice, T::cmp);
+}
+
+/// Quick sort that counts how deep it went and hands the slices it keeps
+/// partitioning badly over to heap sort, so it stays `O(n log n)` even on
+/// input crafted against the median of three.
+pub fn intro_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // Twice the depth of balanced partitions.
+    let limit = 2 * (usize::BITS - slice.len().leading_zeros());
+    intro_sort_recursive(slice, limit, &mut compare);
+}
+
+fn intro_sort_recursive<T, F>(mut slice: &mut [T], mut limit: u32, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while slice.len() > INSERTION_THRESHOLD {
+        if limit == 0 {
+            heap_sort_by(slice, compare);
+            return;
+        }
+        limit -= 1;
+
+        let pivot = partition(slice, compare);
+        let (left, right) = slice.split_at_mut(pivot);
+        let right = &mut right[1..];
+
+        if left.len() < right.len() {
+            intro_sort_recursive(left, limit, compare);
+            slice = right;
+        } else {
+            intro_sort_recursive(right, limit, compare);
+            slice = left;
+        }
+    }
+
+    insertion_sort_by(slice, compare);
+}
+
+pub fn heap_sort<T: Ord>(slice: &mut [T]) {
+    heap_sort_by(slice, T::cmp);
+}
+
+/// Turns the slice into a max heap, then repeatedly swaps the maximum to
+/// the end and restores the heap on what's left.
+pub fn heap_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for node in (0..slice.len() / 2).rev() {
+        sift_down(slice, node, &mut compare);
+    }
+
+    for end in (1..slice.len()).rev() {
+        slice.swap(0, end);
+        sift_down(&mut slice[..end], 0, &mut compare);
+    }
+}
+
+fn sift_down<T, F>(heap: &mut [T], mut node: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= heap.len() {
+            break;
+        }
+
+        if child + 1 < heap.len() && compare(&heap[child], &heap[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+
+        if compare(&heap[node], &heap[child]) != Ordering::Less {
+            break;
+        }
+
+        heap.swap(node, child);
+        node = child;
+    }
+}
+
+/// Nodes of a singly (or doubly) linked chain that [`merge_sort_chain`] can
+/// relink.
+pub(crate) trait ChainNode: Sized {
+    type Elem;
+
+    fn elem(&self) -> &Self::Elem;
+
+    fn next_link(&mut self) -> &mut Option<NodePtr<Self>>;
+}
+
+/// Sorts the chain of `len` nodes starting at `head` by its `next` links,
+/// leaving the new first node in `head`. Stable, no allocations.
+///
+/// If `compare` panics `head` still starts a chain of all the nodes, just
+/// not in order.
+///
+/// # Safety
+///
+/// The chain has exactly `len` live nodes. Any other links (`prev`, the
+/// owner's tail) are stale afterwards.
+pub(crate) unsafe fn merge_sort_chain<N, F>(
+    head: &mut Option<NodePtr<N>>,
+    len: usize,
+    compare: &mut F,
+) where
+    N: ChainNode,
+    F: FnMut(&N::Elem, &N::Elem) -> Ordering,
+{
+    if len <= 1 {
+        return;
+    }
+
+    unsafe {
+        // Cut the chain after its first half.
+        let mut last_left = head.unwrap();
+        for _ in 1..len / 2 {
+            last_left = last_left.as_mut().next_link().unwrap();
+        }
+        let right = last_left.as_mut().next_link().take();
+
+        // Glues the halves back together if sorting one of them panics.
+        let mut halves = Guard::new((&mut *head, right), |(left, right)| {
+            append_chain(left, right.take())
+        });
+
+        let (left, right) = &mut *halves;
+        merge_sort_chain(left, len / 2, compare);
+        merge_sort_chain(right, len - len / 2, compare);
+
+        let (head, right) = halves.into_inner();
+        merge_chains(head, right, compare);
+    }
+}
+
+/// Appends the chain `tail` to the end of the chain starting at `head`.
+unsafe fn append_chain<N: ChainNode>(head: &mut Option<NodePtr<N>>, tail: Option<NodePtr<N>>) {
+    unsafe {
+        let mut link = head;
+        while let Some(node) = *link {
+            link = node.as_mut().next_link();
+        }
+        *link = tail;
+    }
+}
+
+/// Merges the sorted chain in `left` with `right`, leaving the result in
+/// `left`.
+unsafe fn merge_chains<N, F>(
+    left: &mut Option<NodePtr<N>>,
+    right: Option<NodePtr<N>>,
+    compare: &mut F,
+) where
+    N: ChainNode,
+    F: FnMut(&N::Elem, &N::Elem) -> Ordering,
+{
+    struct Merge<N> {
+        head: Option<NodePtr<N>>,
+        last: Option<NodePtr<N>>,
+        left: Option<NodePtr<N>>,
+        right: Option<NodePtr<N>>,
+    }
+
+    impl<N: ChainNode> Merge<N> {
+        /// Links `node` after the merged part.
+        unsafe fn push(&mut self, node: Option<NodePtr<N>>) {
+            match self.last {
+                Some(last) => unsafe { *last.as_mut().next_link() = node },
+                None => self.head = node,
+            }
+        }
+    }
+
+    // Both runs end in `None`, so appending what's left of them after the
+    // merged part always gives back a whole chain, sorted or not.
+    let first = left.take();
+    let mut merge = Guard::new(
+        Merge {
+            head: None,
+            last: None,
+            left: first,
+            right,
+        },
+        |merge| unsafe {
+            let rest = merge.left.take();
+            merge.push(rest);
+            append_chain(&mut merge.head, merge.right.take());
+            *left = merge.head;
+        },
+    );
+
+    unsafe {
+        while let (Some(l), Some(r)) = (merge.left, merge.right) {
+            let next = if compare(r.as_ref().elem(), l.as_ref().elem()) == Ordering::Less {
+                merge.right = *r.as_mut().next_link();
+                r
+            } else {
+                merge.left = *l.as_mut().next_link();
+                l
+            };
+
+            merge.push(Some(next));
+            merge.last = Some(next);
+        }
+    }
+
+    // The guard appends the run that's left.
+    drop(merge);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare::{ByKey, NaturalOrder, Reverse};
+    use proptest::prelude::*;
+
+    const ALGORITHMS: [Algorithm; 5] = [
+        Algorithm::Insertion,
+        Algorithm::Merge,
+        Algorithm::Quick,
+        Algorithm::Heap,
+        Algorithm::Intro,
+    ];
+
+    proptest! {
+        #[test]
+        fn matches_std(values in proptest::collection::vec(any::<i32>(), 0..300)) {
+            let mut expected = values.clone();
+            expected.sort();
+
+            for algorithm in ALGORITHMS {
+                let mut sorted = values.clone();
+                algorithm.sort_by(&mut sorted, i32::cmp);
+                prop_assert_eq!(&sorted, &expected, "{:?}", algorithm);
+            }
+        }
+
+        #[test]
+        fn stable_sorts_keep_order(values in proptest::collection::vec((0..8u8, any::<u16>()), 0..300)) {
+            let mut expected = values.clone();
+            expected.sort_by_key(|(key, _)| *key);
+
+            let mut merged = values.clone();
+            merge_sort_by(&mut merged, |a, b| a.0.cmp(&b.0));
+            prop_assert_eq!(&merged, &expected);
+
+            let mut merged = values.clone();
+            bottom_up_merge_sort_by(&mut merged, |a, b| a.0.cmp(&b.0));
+            prop_assert_eq!(&merged, &expected);
+
+            let mut inserted = values.clone();
+            insertion_sort_by(&mut inserted, |a, b| a.0.cmp(&b.0));
+            prop_assert_eq!(&inserted, &expected);
+        }
+
+        #[test]
+        fn gallop_matches_partition_point(len in 0..200usize, split in 0..200usize) {
+            let run: Vec<_> = (0..len).collect();
+            prop_assert_eq!(gallop(&run, |&n| n < split), run.partition_point(|&n| n < split));
+        }
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        for algorithm in ALGORITHMS {
+            let mut empty: [i32; 0] = [];
+            algorithm.sort_by(&mut empty, i32::cmp);
+
+            let mut same = vec![7; 100];
+            algorithm.sort_by(&mut same, i32::cmp);
+            assert_eq!(same, vec![7; 100]);
+
+            let mut descending: Vec<_> = (0..100).rev().collect();
+            algorithm.sort_by(&mut descending, i32::cmp);
+            assert!(descending.iter().copied().eq(0..100));
+
+            let mut units = vec![(); 10];
+            algorithm.sort_by(&mut units, <()>::cmp);
+        }
+
+        let mut words = ["pear", "fig", "apple"];
+        quick_sort(&mut words);
+        assert_eq!(words, ["apple", "fig", "pear"]);
+        heap_sort_by(&mut words, |a, b| b.len().cmp(&a.len()));
+        assert_eq!(words[0], "apple");
+
+        // Stable, the long words keep their order.
+        let mut words = ["pear", "apple", "fig", "kiwi"];
+        Algorithm::Merge.sort_by_comparator(&mut words, &ByKey(|w: &&str| w.len() > 3));
+        assert_eq!(words, ["fig", "pear", "apple", "kiwi"]);
+        Algorithm::Quick.sort_by_comparator(&mut words, &Reverse(NaturalOrder));
+        assert_eq!(words, ["pear", "kiwi", "fig", "apple"]);
+    }
+
+    #[test]
+    fn test_intro_fallback() {
+        // Out of depth right away, so heap sort does all the work.
+        let mut values: Vec<i32> = (0..200).map(|i| (i * 7919) % 200).collect();
+        intro_sort_recursive(&mut values, 0, &mut i32::cmp);
+        assert!(values.iter().copied().eq(0..200));
+
+        // Organ pipe input, with some unlucky pivots on the way.
+        let mut values: Vec<i32> = (0..500).chain((0..500).rev()).collect();
+        intro_sort(&mut values);
+        assert!(values.is_sorted());
+    }
+
+    #[test]
+    fn test_merge_panic() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        use std::rc::Rc;
+
+        // The last comparisons happen in the final merge, a comparator
+        // panicking there has to leave every value in the slice once.
+        let mut total = 0;
+        merge_sort_by(&mut (0..100).rev().collect::<Vec<_>>(), |a: &i32, b| {
+            total += 1;
+            a.cmp(b)
+        });
+
+        let counter = Rc::new(());
+        let mut values: Vec<_> = (0..100).rev().map(|i| (i, Rc::clone(&counter))).collect();
+        let mut calls = 0;
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            merge_sort_by(&mut values, |a, b| {
+                calls += 1;
+                assert!(calls < total - 10);
+                a.0.cmp(&b.0)
+            })
+        }));
+
+        assert!(result.is_err());
+        let mut keys: Vec<_> = values.iter().map(|(i, _)| *i).collect();
+        keys.sort();
+        assert!(keys.into_iter().eq(0..100));
+        drop(values);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,401 @@
+use std::fmt::Debug;
+
+use crate::Slab;
+
+/// Items a node holds before it is split.
+const NODE_CAPACITY: usize = 8;
+/// Nodes this deep are never split, which bounds the tree when many items
+/// share the same spot.
+const MAX_DEPTH: usize = 16;
+
+/// Axis-aligned box in `D` dimensions, bounds included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox<const D: usize> {
+    pub min: [f64; D],
+    pub max: [f64; D],
+}
+
+/// Region tree that splits every node in `2^D` equal children: a quadtree
+/// for `D = 2` and an octree for `D = 3`.
+///
+/// Each item lives in the deepest node whose region fully contains its
+/// bounding box, so an item that crosses a split line stays in the parent.
+/// Queries only visit the nodes whose region touches the query box. Items
+/// that fall outside the region of the tree are kept in the root, which
+/// stays correct but makes them part of every query.
+///
+/// Items get a `usize` key on insert, used to remove them later.
+pub struct SpatialTree<T, const D: usize> {
+    root: Node<D>,
+    entries: Slab<Entry<T, D>>,
+}
+
+pub type Quadtree<T> = SpatialTree<T, 2>;
+
+pub type Octree<T> = SpatialTree<T, 3>;
+
+struct Entry<T, const D: usize> {
+    bounds: BoundingBox<D>,
+    value: T,
+}
+
+struct Node<const D: usize> {
+    region: BoundingBox<D>,
+    depth: usize,
+    /// Keys of the items stored in this node.
+    items: Vec<usize>,
+    /// Either empty or `2^D` children, bit `k` of the index is set for the
+    /// upper half along axis `k`.
+    children: Vec<Node<D>>,
+    /// Items in the whole subtree.
+    count: usize,
+}
+
+pub struct Query<'a, T, const D: usize> {
+    entries: &'a Slab<Entry<T, D>>,
+    area: BoundingBox<D>,
+    stack: Vec<&'a Node<D>>,
+    items: std::slice::Iter<'a, usize>,
+}
+
+impl<const D: usize> BoundingBox<D> {
+    /// Panics if `min` is greater than `max` along some axis.
+    pub fn new(min: [f64; D], max: [f64; D]) -> Self {
+        assert!((0..D).all(|k| min[k] <= max[k]), "Invalid bounding box");
+
+        BoundingBox { min, max }
+    }
+
+    /// Box of a single point.
+    pub fn point(point: [f64; D]) -> Self {
+        BoundingBox {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// Returns whether the boxes share at least one point.
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..D).all(|k| self.min[k] <= other.max[k] && other.min[k] <= self.max[k])
+    }
+
+    /// Returns whether `other` is entirely inside `self`.
+    pub fn contains(&self, other: &Self) -> bool {
+        (0..D).all(|k| self.min[k] <= other.min[k] && other.max[k] <= self.max[k])
+    }
+
+    fn center(&self) -> [f64; D] {
+        std::array::from_fn(|k| (self.min[k] + self.max[k]) / 2.0)
+    }
+
+    /// Region of child `index` when `self` is split at its center.
+    fn child(&self, index: usize) -> Self {
+        let center = self.center();
+        let mut child = *self;
+
+        for (k, &center) in center.iter().enumerate() {
+            if index & (1 << k) == 0 {
+                child.max[k] = center;
+            } else {
+                child.min[k] = center;
+            }
+        }
+
+        child
+    }
+
+    /// Index of the child of `self` that fully contains `other`, `None` if
+    /// `other` crosses the center on some axis.
+    fn child_containing(&self, other: &Self) -> Option<usize> {
+        let center = self.center();
+        let mut index = 0;
+
+        for (k, &center) in center.iter().enumerate() {
+            if other.min[k] >= center {
+                index |= 1 << k;
+            } else if other.max[k] > center {
+                return None;
+            }
+        }
+
+        Some(index)
+    }
+}
+
+impl<const D: usize> Node<D> {
+    fn new(region: BoundingBox<D>, depth: usize) -> Self {
+        Node {
+            region,
+            depth,
+            items: Vec::new(),
+            children: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Child to descend into for an item with `bounds`, if any.
+    fn child_for(&self, bounds: &BoundingBox<D>) -> Option<usize> {
+        if self.children.is_empty() || !self.region.contains(bounds) {
+            return None;
+        }
+
+        self.region.child_containing(bounds)
+    }
+
+    fn insert<T>(&mut self, key: usize, entries: &Slab<Entry<T, D>>) {
+        self.count += 1;
+
+        let bounds = &entries[key].bounds;
+        if let Some(child) = self.child_for(bounds) {
+            self.children[child].insert(key, entries);
+            return;
+        }
+
+        self.items.push(key);
+
+        if self.children.is_empty() && self.items.len() > NODE_CAPACITY && self.depth < MAX_DEPTH {
+            self.split(entries);
+        }
+    }
+
+    fn split<T>(&mut self, entries: &Slab<Entry<T, D>>) {
+        self.children = (0..1 << D)
+            .map(|i| Node::new(self.region.child(i), self.depth + 1))
+            .collect();
+
+        let items = std::mem::take(&mut self.items);
+        for key in items {
+            match self.child_for(&entries[key].bounds) {
+                Some(child) => self.children[child].insert(key, entries),
+                None => self.items.push(key),
+            }
+        }
+    }
+
+    /// Removes `key` from the subtree, merging the children back once few
+    /// enough items are left.
+    fn remove(&mut self, key: usize, bounds: &BoundingBox<D>) {
+        self.count -= 1;
+
+        match self.child_for(bounds) {
+            Some(child) => self.children[child].remove(key, bounds),
+            None => {
+                let i = self.items.iter().position(|&k| k == key).unwrap();
+                self.items.swap_remove(i);
+            }
+        }
+
+        if !self.children.is_empty() && self.count <= NODE_CAPACITY {
+            let mut stack = std::mem::take(&mut self.children);
+            while let Some(mut node) = stack.pop() {
+                self.items.append(&mut node.items);
+                stack.append(&mut node.children);
+            }
+        }
+    }
+}
+
+impl<T, const D: usize> SpatialTree<T, D> {
+    /// Creates an empty tree covering `region`.
+    pub fn new(region: BoundingBox<D>) -> Self {
+        SpatialTree {
+            root: Node::new(region, 0),
+            entries: Slab::new(),
+        }
+    }
+
+    /// Region covered by the tree.
+    pub fn region(&self) -> &BoundingBox<D> {
+        &self.root.region
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Stores `value` with `bounds` and returns its key.
+    pub fn insert(&mut self, bounds: BoundingBox<D>, value: T) -> usize {
+        let key = self.entries.insert(Entry { bounds, value });
+        self.root.insert(key, &self.entries);
+
+        key
+    }
+
+    /// Removes the item with `key` and returns its value.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let bounds = self.entries.get(key)?.bounds;
+        self.root.remove(key, &bounds);
+
+        self.entries.remove(key).map(|entry| entry.value)
+    }
+
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.entries.get_mut(key).map(|entry| &mut entry.value)
+    }
+
+    /// Bounding box the item with `key` was inserted with.
+    pub fn bounds(&self, key: usize) -> Option<&BoundingBox<D>> {
+        self.entries.get(key).map(|entry| &entry.bounds)
+    }
+
+    pub fn clear(&mut self) {
+        self.root = Node::new(self.root.region, 0);
+        self.entries.clear();
+    }
+
+    /// Iterates over the items whose bounding box intersects `area`, as
+    /// `(key, &value)` pairs in no particular order.
+    pub fn query(&self, area: BoundingBox<D>) -> Query<'_, T, D> {
+        Query {
+            entries: &self.entries,
+            area,
+            stack: vec![&self.root],
+            items: [].iter(),
+        }
+    }
+
+    /// Iterates over all the items as `(key, &value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.entries.iter().map(|(key, entry)| (key, &entry.value))
+    }
+}
+
+impl<'a, T, const D: usize> Iterator for Query<'a, T, D> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for &key in self.items.by_ref() {
+                let entry = &self.entries[key];
+                if entry.bounds.intersects(&self.area) {
+                    return Some((key, &entry.value));
+                }
+            }
+
+            let node = self.stack.pop()?;
+            self.items = node.items.iter();
+            self.stack.extend(
+                node.children
+                    .iter()
+                    .filter(|child| child.count > 0 && child.region.intersects(&self.area)),
+            );
+        }
+    }
+}
+
+impl<T: Debug, const D: usize> Debug for SpatialTree<T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.entries
+                    .iter()
+                    .map(|(_, entry)| (&entry.bounds, &entry.value)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoundingBox, Octree, Quadtree};
+
+    fn square(x: f64, y: f64, size: f64) -> BoundingBox<2> {
+        BoundingBox::new([x, y], [x + size, y + size])
+    }
+
+    #[test]
+    fn test_quadtree() {
+        let mut tree = Quadtree::new(square(0.0, 0.0, 100.0));
+        let mut keys = Vec::new();
+
+        for i in 0..20 {
+            for j in 0..20 {
+                keys.push(tree.insert(square(i as f64 * 5.0, j as f64 * 5.0, 1.0), (i, j)));
+            }
+        }
+        // Crosses the first split line.
+        let big = tree.insert(square(40.0, 40.0, 20.0), (-1, -1));
+        // Outside the region.
+        let outside = tree.insert(square(200.0, 200.0, 1.0), (-2, -2));
+        assert_eq!(tree.len(), 402);
+
+        let mut found: Vec<_> = tree
+            .query(square(12.0, 12.0, 10.0))
+            .map(|(_, v)| *v)
+            .collect();
+        found.sort();
+        assert_eq!(found, [(3, 3), (3, 4), (4, 3), (4, 4)]);
+
+        let found: Vec<_> = tree
+            .query(square(51.5, 51.5, 1.0))
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(found, [big]);
+
+        let found: Vec<_> = tree
+            .query(BoundingBox::point([200.5, 200.5]))
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(found, [outside]);
+
+        for &key in keys.iter().step_by(2) {
+            assert!(tree.remove(key).is_some());
+        }
+        assert_eq!(tree.remove(keys[0]), None);
+        assert_eq!(tree.remove(big), Some((-1, -1)));
+
+        let brute: usize = keys
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|&&k| tree.bounds(k).unwrap().intersects(&square(0.0, 0.0, 50.0)))
+            .count();
+        assert_eq!(tree.query(square(0.0, 0.0, 50.0)).count(), brute);
+        assert_eq!(tree.query(square(-10.0, -10.0, 300.0)).count(), 201);
+    }
+
+    #[test]
+    fn test_merge_after_remove() {
+        let mut tree = Quadtree::new(square(0.0, 0.0, 1.0));
+        let keys: Vec<_> = (0..100)
+            .map(|i| tree.insert(BoundingBox::point([0.25, 0.25]), i))
+            .collect();
+
+        for key in keys {
+            tree.remove(key);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.root.children.is_empty());
+        assert_eq!(tree.query(square(0.0, 0.0, 1.0)).count(), 0);
+    }
+
+    #[test]
+    fn test_octree() {
+        let mut tree = Octree::new(BoundingBox::new([0.0; 3], [8.0; 3]));
+        for x in 0..8 {
+            for y in 0..8 {
+                for z in 0..8 {
+                    tree.insert(
+                        BoundingBox::point([x as f64, y as f64, z as f64]),
+                        (x, y, z),
+                    );
+                }
+            }
+        }
+
+        let area = BoundingBox::new([1.5, 2.0, 0.0], [3.0, 2.5, 7.0]);
+        assert_eq!(tree.query(area).count(), 2 * 8);
+        assert!(
+            tree.query(area)
+                .all(|(_, &(x, y, _))| (2..=3).contains(&x) && y == 2)
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Bounded single-producer single-consumer queue.
+//!
+//! [`channel`] returns the two ends of a fixed ring buffer. The producer only
+//! writes `tail` and the consumer only writes `head`, so neither operation
+//! needs a CAS or a lock: both are wait-free. The indices live on separate
+//! cache lines so the two threads don't invalidate each other's cache on
+//! every operation, and each side keeps a copy of the other's index that is
+//! only refreshed when the queue looks full or empty.
+
+use std::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// Aligns its content to its own cache line.
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Ring<T> {
+    /// Rounded up to a power of two, so the indices can wrap around `usize`
+    /// and still map to the same slots.
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    capacity: usize,
+    /// Number of values popped so far, wrapping, the next one is in the slot
+    /// `head` masked by the number of slots.
+    head: CachePadded<AtomicUsize>,
+    /// Number of values pushed so far, wrapping.
+    tail: CachePadded<AtomicUsize>,
+}
+
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+/// Sending end of a queue created by [`channel`].
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    tail: usize,
+    /// Last `head` seen, the queue has at least this much free space.
+    head_cache: usize,
+}
+
+/// Receiving end of a queue created by [`channel`].
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    head: usize,
+    /// Last `tail` seen, the queue holds at least up to this value.
+    tail_cache: usize,
+}
+
+/// Creates a queue that holds up to `capacity` values, in a buffer of
+/// `capacity` rounded up to a power of two slots.
+/// Panics if `capacity` is 0.
+pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    channel_from(capacity, 0)
+}
+
+/// [`channel`] whose indices start at `start` instead of 0.
+fn channel_from<T>(capacity: usize, start: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(capacity > 0, "Capacity must be positive");
+    let slots = capacity.checked_next_power_of_two().expect("Capacity overflow");
+
+    let ring = Arc::new(Ring {
+        slots: (0..slots)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+        capacity,
+        head: CachePadded(AtomicUsize::new(start)),
+        tail: CachePadded(AtomicUsize::new(start)),
+    });
+
+    let producer = Producer {
+        ring: ring.clone(),
+        tail: start,
+        head_cache: start,
+    };
+    let consumer = Consumer {
+        ring,
+        head: start,
+        tail_cache: start,
+    };
+
+    (producer, consumer)
+}
+
+impl<T> Ring<T> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index & (self.slots.len() - 1)].get()
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let (head, tail) = (*self.head.0.get_mut(), *self.tail.0.get_mut());
+
+        for i in 0..tail.wrapping_sub(head) {
+            unsafe { (*self.slot(head.wrapping_add(i))).assume_init_drop() };
+        }
+    }
+}
+
+impl<T> Producer<T> {
+    /// Adds `value` at the back of the queue, or gives it back if the queue
+    /// is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.tail.wrapping_sub(self.head_cache) == self.ring.capacity() {
+            self.head_cache = self.ring.head.load(Ordering::Acquire);
+
+            if self.tail.wrapping_sub(self.head_cache) == self.ring.capacity() {
+                return Err(value);
+            }
+        }
+
+        unsafe { (*self.ring.slot(self.tail)).write(value) };
+        self.tail = self.tail.wrapping_add(1);
+        self.ring.tail.store(self.tail, Ordering::Release);
+
+        Ok(())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Number of values in the queue, the consumer may be popping some.
+    pub fn len(&self) -> usize {
+        self.tail.wrapping_sub(self.ring.head.load(Ordering::Acquire))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns whether the consumer was dropped.
+    pub fn is_abandoned(&self) -> bool {
+        Arc::strong_count(&self.ring) == 1
+    }
+}
+
+impl<T> Consumer<T> {
+    /// Whether a value is ready, refreshing the cached `tail` if needed.
+    fn ready(&mut self) -> bool {
+        if self.head == self.tail_cache {
+            self.tail_cache = self.ring.tail.load(Ordering::Acquire);
+        }
+
+        self.head != self.tail_cache
+    }
+
+    /// Removes the value at the front of the queue, `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if !self.ready() {
+            return None;
+        }
+
+        let value = unsafe { (*self.ring.slot(self.head)).assume_init_read() };
+        self.head = self.head.wrapping_add(1);
+        self.ring.head.store(self.head, Ordering::Release);
+
+        Some(value)
+    }
+
+    /// Returns the value at the front of the queue without removing it.
+    pub fn peek(&mut self) -> Option<&T> {
+        if !self.ready() {
+            return None;
+        }
+
+        unsafe { Some((*self.ring.slot(self.head)).assume_init_ref()) }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Number of values in the queue, the producer may be pushing more.
+    pub fn len(&self) -> usize {
+        self.ring.tail.load(Ordering::Acquire).wrapping_sub(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the producer was dropped, values already pushed can
+    /// still be popped.
+    pub fn is_abandoned(&self) -> bool {
+        Arc::strong_count(&self.ring) == 1
+    }
+}
+
+impl<T> Iterator for Consumer<T> {
+    type Item = T;
+
+    /// Same as [`Consumer::pop`], ends as soon as the queue is empty.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+}
+
+impl<T> Debug for Producer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Producer")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Debug for Consumer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Consumer")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{channel, channel_from};
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn test_bounded() {
+        let (mut tx, mut rx) = channel(3);
+        assert_eq!(rx.pop(), None);
+
+        assert_eq!(tx.push(1), Ok(()));
+        assert_eq!(tx.push(2), Ok(()));
+        assert_eq!(tx.push(3), Ok(()));
+        assert_eq!(tx.push(4), Err(4));
+        assert!(tx.is_full());
+
+        assert_eq!(rx.peek(), Some(&1));
+        assert_eq!(rx.pop(), Some(1));
+        assert_eq!(tx.push(4), Ok(()));
+        assert_eq!(rx.len(), 3);
+
+        assert_eq!(rx.by_ref().collect::<Vec<_>>(), [2, 3, 4]);
+        assert!(tx.is_empty());
+    }
+
+    #[test]
+    fn test_threads() {
+        const N: usize = 10_000;
+        let (mut tx, mut rx) = channel(64);
+
+        let producer = thread::spawn(move || {
+            for i in 0..N {
+                let mut value = i;
+                while let Err(v) = tx.push(value) {
+                    value = v;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut expected = 0;
+        while expected < N {
+            if let Some(value) = rx.pop() {
+                assert_eq!(value, expected);
+                expected += 1;
+            } else {
+                thread::yield_now();
+            }
+        }
+
+        producer.join().unwrap();
+        assert!(rx.is_abandoned());
+    }
+
+    #[test]
+    fn test_wrapping_indices() {
+        let rc = Arc::new(());
+        let (mut tx, mut rx) = channel_from(3, usize::MAX - 4);
+
+        for i in 0..20 {
+            tx.push((i, rc.clone())).unwrap();
+            tx.push((i, rc.clone())).unwrap();
+            assert_eq!(rx.len(), 2);
+            assert_eq!(rx.pop().unwrap().0, i);
+            assert_eq!(rx.peek().unwrap().0, i);
+            assert_eq!(rx.pop().unwrap().0, i);
+        }
+
+        // Full across the wrap, then dropped with values still in it.
+        for i in 0..3 {
+            tx.push((i, rc.clone())).unwrap();
+        }
+        assert!(tx.is_full() && tx.push((3, rc.clone())).is_err());
+        assert_eq!(rx.pop().unwrap().0, 0);
+        drop((tx, rx));
+        assert_eq!(Arc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_drop() {
+        let rc = Arc::new(());
+        let (mut tx, mut rx) = channel(4);
+        for _ in 0..4 {
+            tx.push(rc.clone()).unwrap();
+        }
+        drop(rx.pop());
+        tx.push(rc.clone()).unwrap();
+
+        drop(tx);
+        assert_eq!(Arc::strong_count(&rc), 5);
+        drop(rx);
+        assert_eq!(Arc::strong_count(&rc), 1);
+    }
+}
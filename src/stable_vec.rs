@@ -0,0 +1,325 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::Vector;
+use crate::traits::MemoryUsage;
+
+/// Vector where removing an element leaves a hole instead of shifting the
+/// elements after it, so indices stay valid until [`compact`] is called.
+///
+/// Holes are never filled again: [`push`] always appends. Iterators skip the
+/// holes, but walking a vector with many of them costs time proportional to
+/// its slots, not to its elements.
+///
+/// [`compact`]: StableVec::compact
+/// [`push`]: StableVec::push
+pub struct StableVec<T> {
+    slots: Vector<Option<T>>,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, Option<T>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T> {
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, Option<T>>>,
+    len: usize,
+}
+
+pub struct IntoIter<T> {
+    slots: crate::vec::IntoIter<Option<T>>,
+    len: usize,
+}
+
+impl<T> StableVec<T> {
+    /// Creates a new StableVec with no elements.
+    pub fn new() -> Self {
+        StableVec {
+            slots: Vector::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of elements, not counting holes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of slots, elements and holes. The next element pushed gets
+    /// this index.
+    pub fn slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of holes left by removed elements.
+    pub fn holes(&self) -> usize {
+        self.slots.len() - self.len
+    }
+
+    /// Appends `elem` and returns its index.
+    pub fn push(&mut self, elem: T) -> usize {
+        self.slots.push(Some(elem));
+        self.len += 1;
+
+        self.slots.len() - 1
+    }
+
+    /// Removes the element at `index`, leaving a hole.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let elem = self.slots.get_mut(index)?.take()?;
+        self.len -= 1;
+
+        Some(elem)
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.slots.get(index)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.slots.get_mut(index)?.as_mut()
+    }
+
+    /// Moves the elements down over the holes, keeping their order. This
+    /// changes the index of every element after the first hole.
+    pub fn compact(&mut self) {
+        let mut write = 0;
+
+        for read in 0..self.slots.len() {
+            if self.slots[read].is_some() {
+                self.slots.swap(read, write);
+                write += 1;
+            }
+        }
+
+        while self.slots.len() > write {
+            self.slots.pop();
+        }
+    }
+
+    /// Removes all the elements and holes.
+    pub fn clear(&mut self) {
+        while self.slots.pop().is_some() {}
+
+        self.len = 0;
+    }
+
+    /// Iterates over `(index, element)`, skipping holes.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Iterates over `(index, element)`, skipping holes.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for StableVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for StableVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("No element at index")
+    }
+}
+
+impl<T> IndexMut<usize> for StableVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("No element at index")
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, elem) = self.slots.find_map(|(i, slot)| Some((i, slot.as_ref()?)))?;
+        self.len -= 1;
+
+        Some((index, elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (index, elem) = self
+            .slots
+            .rfind(|(_, slot)| slot.is_some())
+            .map(|(i, slot)| (i, slot.as_ref().unwrap()))?;
+        self.len -= 1;
+
+        Some((index, elem))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, elem) = self.slots.find_map(|(i, slot)| Some((i, slot.as_mut()?)))?;
+        self.len -= 1;
+
+        Some((index, elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let elem = self.slots.by_ref().flatten().next()?;
+        self.len -= 1;
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for StableVec<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            len: self.len,
+            slots: self.slots.into_iter(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a StableVec<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = (usize, &'a T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut StableVec<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = (usize, &'a mut T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Extend<T> for StableVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for StableVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+
+        vec
+    }
+}
+
+impl<T: Debug> Debug for StableVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<T> MemoryUsage for StableVec<T> {
+    fn heap_bytes(&self) -> usize {
+        self.slots.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StableVec;
+
+    #[test]
+    fn test_stable_indices() {
+        let mut vec: StableVec<char> = "abcdef".chars().collect();
+
+        assert_eq!(vec.remove(1), Some('b'));
+        assert_eq!(vec.remove(1), None);
+        assert_eq!(vec.remove(4), Some('e'));
+        assert_eq!(vec.len(), 4);
+        assert_eq!(vec.holes(), 2);
+
+        assert_eq!(vec[5], 'f');
+        assert_eq!(vec.get(4), None);
+        assert_eq!(vec.push('g'), 6);
+
+        vec[0] = 'A';
+        assert_eq!(
+            format!("{:?}", vec),
+            "{0: 'A', 2: 'c', 3: 'd', 5: 'f', 6: 'g'}"
+        );
+        assert!(vec.iter().rev().map(|(i, _)| i).eq([6, 5, 3, 2, 0]));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut vec: StableVec<i32> = (0..8).collect();
+        for i in [0, 3, 4, 7] {
+            vec.remove(i);
+        }
+
+        for (_, elem) in &mut vec {
+            *elem *= 10;
+        }
+
+        vec.compact();
+        assert_eq!(vec.holes(), 0);
+        assert_eq!(vec.slots(), 4);
+        assert!(vec.iter().eq([(0, &10), (1, &20), (2, &50), (3, &60)]));
+        assert!(vec.into_iter().eq([10, 20, 50, 60]));
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt::Debug, iter::Rev, slice};
+
+use crate::vec::Vector;
+use crate::traits::MemoryUsage;
+
+/// LIFO stack stored in a [`Vector`], the top of the stack being the end of
+/// the vector.
+pub struct Stack<T> {
+    vec: Vector<T>,
+}
+
+/// Iterates from the top of the stack to the bottom.
+pub struct Iter<'a, T>(Rev<slice::Iter<'a, T>>);
+
+pub struct IterMut<'a, T>(Rev<slice::IterMut<'a, T>>);
+
+pub struct IntoIter<T>(Stack<T>);
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack { vec: Vector::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Puts `value` on top of the stack.
+    pub fn push(&mut self, value: T) {
+        self.vec.push(value);
+    }
+
+    /// Removes the top of the stack.
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+
+    /// Returns the top of the stack without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.vec.last()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.vec.last_mut()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.vec.iter().rev())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(self.vec.iter_mut().rev())
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for Stack<T> {
+    /// Pushes every item in order, so the last one ends up on top.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = Self::new();
+        stack.extend(iter);
+
+        stack
+    }
+}
+
+impl<T: Debug> Debug for Stack<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stack<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a mut Stack<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<T> IntoIterator for Stack<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> MemoryUsage for Stack<T> {
+    fn heap_bytes(&self) -> usize {
+        self.vec.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stack;
+
+    #[test]
+    fn test_basics() {
+        let mut stack = Stack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.peek(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack.peek(), Some(&3));
+
+        *stack.peek_mut().unwrap() = 30;
+        assert_eq!(stack.pop(), Some(30));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.len(), 1);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut stack: Stack<i32> = (1..=4).collect();
+        assert_eq!(stack.iter().copied().collect::<Vec<_>>(), [4, 3, 2, 1]);
+        assert_eq!(format!("{:?}", stack), "[4, 3, 2, 1]");
+
+        for elem in &mut stack {
+            *elem *= 10;
+        }
+
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), [40, 30, 20, 10]);
+    }
+}
@@ -0,0 +1,101 @@
+//! Counters kept by the containers when the `stats` feature is on, read
+//! with their `stats()` methods.
+//!
+//! Without the feature the structs have no fields and recording does
+//! nothing, so the containers pay nothing for them.
+
+/// Growth of the buffer of a [`Vector`](crate::Vector).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VectorStats {
+    /// Times a non-empty buffer was reallocated to grow.
+    #[cfg(feature = "stats")]
+    pub reallocations: u64,
+    /// Bytes moved by those reallocations. Blocks the allocator could grow
+    /// in place don't count.
+    #[cfg(feature = "stats")]
+    pub bytes_copied: u64,
+}
+
+/// Nodes allocated by a [`LinkedList`](crate::LinkedList) or a
+/// [`DequeueList`](crate::DequeueList).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListStats {
+    #[cfg(feature = "stats")]
+    pub node_allocations: u64,
+}
+
+/// Rebalancing work of the self-balancing trees.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TreeStats {
+    /// Single rotations, a double rotation counts twice.
+    #[cfg(feature = "stats")]
+    pub rotations: u64,
+    /// Deepest level a node was inserted at, before rebalancing. The root
+    /// is at depth 1.
+    #[cfg(feature = "stats")]
+    pub max_depth: usize,
+}
+
+/// Probing of the [`IndexMap`](crate::IndexMap) table on inserts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MapStats {
+    /// Inserts, replacing ones included.
+    #[cfg(feature = "stats")]
+    pub inserts: u64,
+    /// Slots looked at by those inserts past the first one.
+    #[cfg(feature = "stats")]
+    pub total_probe_length: u64,
+    #[cfg(feature = "stats")]
+    pub max_probe_length: usize,
+}
+
+#[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+impl VectorStats {
+    pub(crate) fn record_reallocation(&mut self, moved: bool, old_bytes: usize) {
+        #[cfg(feature = "stats")]
+        {
+            self.reallocations += 1;
+            if moved {
+                self.bytes_copied += old_bytes as u64;
+            }
+        }
+    }
+}
+
+impl ListStats {
+    pub(crate) fn record_node_allocation(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.node_allocations += 1;
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+impl TreeStats {
+    pub(crate) fn record_rotation(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.rotations += 1;
+        }
+    }
+
+    pub(crate) fn record_depth(&mut self, depth: usize) {
+        #[cfg(feature = "stats")]
+        {
+            self.max_depth = self.max_depth.max(depth);
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+impl MapStats {
+    pub(crate) fn record_insert(&mut self, probe_length: usize) {
+        #[cfg(feature = "stats")]
+        {
+            self.inserts += 1;
+            self.total_probe_length += probe_length as u64;
+            self.max_probe_length = self.max_probe_length.max(probe_length);
+        }
+    }
+}
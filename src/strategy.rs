@@ -0,0 +1,154 @@
+//! `proptest` strategies for the collections, the same ones used by their
+//! `Arbitrary` implementations plus a few that aim for the edge cases.
+//!
+//! ```
+//! use ds_rust::strategy;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(mut v in strategy::vector(any::<i32>(), 1..100))| {
+//!     let last = v[v.len() - 1];
+//!     prop_assert_eq!(v.pop(), Some(last));
+//! });
+//! ```
+
+use proptest::{
+    collection::{self, SizeRange, VecStrategy},
+    prelude::*,
+    strategy::Map,
+};
+
+use crate::{BTree, DequeueList, LinkedList, Vector};
+
+pub type VectorStrategy<S> =
+    Map<VecStrategy<S>, fn(Vec<<S as Strategy>::Value>) -> Vector<<S as Strategy>::Value>>;
+pub type LinkedListStrategy<S> =
+    Map<VecStrategy<S>, fn(Vec<<S as Strategy>::Value>) -> LinkedList<<S as Strategy>::Value>>;
+pub type DequeueListStrategy<S> =
+    Map<VecStrategy<S>, fn(Vec<<S as Strategy>::Value>) -> DequeueList<<S as Strategy>::Value>>;
+pub type BTreeStrategy<S> =
+    Map<VecStrategy<S>, fn(Vec<<S as Strategy>::Value>) -> BTree<<S as Strategy>::Value>>;
+
+fn to_vector<T>(values: Vec<T>) -> Vector<T> {
+    let mut v = Vector::new();
+    for value in values {
+        v.push(value);
+    }
+    v
+}
+
+fn to_linked_list<T: PartialEq>(values: Vec<T>) -> LinkedList<T> {
+    let mut list = LinkedList::new();
+    for value in values {
+        list.add(value);
+    }
+    list
+}
+
+fn to_dequeue_list<T>(values: Vec<T>) -> DequeueList<T> {
+    values.into_iter().collect()
+}
+
+fn to_btree<T: Ord>(values: Vec<T>) -> BTree<T> {
+    let mut tree = BTree::new();
+    for value in values {
+        tree.insert(value);
+    }
+    tree
+}
+
+/// `Vec`s with a length in `size` and elements drawn from `element`.
+pub fn vector<S: Strategy>(element: S, size: impl Into<SizeRange>) -> VectorStrategy<S> {
+    collection::vec(element, size).prop_map(to_vector)
+}
+
+/// Lists with a length in `size` and elements drawn from `element`.
+pub fn linked_list<S>(element: S, size: impl Into<SizeRange>) -> LinkedListStrategy<S>
+where
+    S: Strategy,
+    S::Value: PartialEq,
+{
+    collection::vec(element, size).prop_map(to_linked_list)
+}
+
+/// Deques with a length in `size` and elements drawn from `element`.
+pub fn dequeue_list<S: Strategy>(element: S, size: impl Into<SizeRange>) -> DequeueListStrategy<S> {
+    collection::vec(element, size).prop_map(to_dequeue_list)
+}
+
+/// Trees built by inserting up to `size` elements in random order, so
+/// duplicates are dropped and the shape is whatever the order produces.
+pub fn btree<S>(element: S, size: impl Into<SizeRange>) -> BTreeStrategy<S>
+where
+    S: Strategy,
+    S::Value: Ord,
+{
+    collection::vec(element, size).prop_map(to_btree)
+}
+
+/// Trees built from sorted input, ascending or descending, which leaves them
+/// as a single path: the worst case for the unbalanced `BTree`.
+pub fn degenerate_btree<S>(
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = BTree<S::Value>>
+where
+    S: Strategy,
+    S::Value: Ord,
+{
+    (collection::vec(element, size), any::<bool>()).prop_map(|(mut values, ascending)| {
+        values.sort_unstable();
+        if !ascending {
+            values.reverse();
+        }
+        to_btree(values)
+    })
+}
+
+/// Lengths biased towards the edge cases: empty, a single element, a few
+/// elements, or exactly `huge`. Meant for `prop_flat_map` into the
+/// collection strategies.
+pub fn edge_lengths(huge: usize) -> impl Strategy<Value = usize> {
+    prop_oneof![Just(0), Just(1), 2..16usize, Just(huge)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn arbitrary_vector(v in any::<Vector<u8>>()) {
+            prop_assert!(v.len() < 100);
+        }
+
+        #[test]
+        fn vector_lengths(v in vector(any::<i32>(), 3..6)) {
+            prop_assert!((3..6).contains(&v.len()));
+        }
+
+        #[test]
+        fn lists_match(values in collection::vec(any::<i32>(), 0..50)) {
+            let mut list = to_linked_list(values.clone());
+            let deque = to_dequeue_list(values.clone());
+            prop_assert_eq!(list.size() as usize, values.len());
+            prop_assert!(deque.iter().eq(values.iter()));
+            prop_assert_eq!(list.pop(), values.first().copied());
+        }
+
+        #[test]
+        fn btree_is_sorted(tree in any::<BTree<i16>>()) {
+            let values: Vec<_> = tree.iter().collect();
+            prop_assert!(values.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        #[test]
+        fn degenerate_btree_keeps_values(tree in degenerate_btree(any::<u8>(), 0..50)) {
+            prop_assert_eq!(tree.iter().count(), tree.size());
+        }
+
+        #[test]
+        fn edge_lengths_hit_huge(v in edge_lengths(1000).prop_flat_map(|len| vector(Just(()), len))) {
+            prop_assert!(v.len() < 16 || v.len() == 1000);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::{cell::OnceCell, collections::BTreeMap, fmt::Debug};
+
+/// Smallest automaton accepting every suffix of a byte string, built online.
+///
+/// Each state stands for a set of substrings that end at the same positions,
+/// so the automaton has at most `2n` states and answers substring queries
+/// in O(pattern) by simply following transitions. Bytes can be appended one
+/// at a time in amortized O(1) (times the O(log σ) transition lookup).
+pub struct SuffixAutomaton {
+    states: Vec<State>,
+    /// State reached by the whole text.
+    last: usize,
+    len: usize,
+    distinct: usize,
+    /// Number of end positions of each state, computed when first needed.
+    occurrences: OnceCell<Vec<usize>>,
+}
+
+struct State {
+    /// Length of the longest substring in the state.
+    len: usize,
+    /// State of the longest suffix that ends at more positions, `None` for
+    /// the root.
+    link: Option<usize>,
+    next: BTreeMap<u8, usize>,
+    /// Clones don't add an end position of their own.
+    cloned: bool,
+}
+
+impl SuffixAutomaton {
+    /// Creates the automaton of the empty string.
+    pub fn new() -> Self {
+        SuffixAutomaton {
+            states: vec![State {
+                len: 0,
+                link: None,
+                next: BTreeMap::new(),
+                cloned: false,
+            }],
+            last: 0,
+            len: 0,
+            distinct: 0,
+            occurrences: OnceCell::new(),
+        }
+    }
+
+    /// Length of the text.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of states, including the root.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Appends `byte` to the text.
+    pub fn push(&mut self, byte: u8) {
+        let cur = self.states.len();
+        self.states.push(State {
+            len: self.len + 1,
+            link: Some(0),
+            next: BTreeMap::new(),
+            cloned: false,
+        });
+        self.len += 1;
+        self.occurrences.take();
+
+        // Every suffix of the old text without a `byte` transition can now be
+        // followed by it.
+        let mut p = Some(self.last);
+        while let Some(state) = p {
+            if self.states[state].next.contains_key(&byte) {
+                break;
+            }
+            self.states[state].next.insert(byte, cur);
+            p = self.states[state].link;
+        }
+
+        if let Some(p) = p {
+            let q = self.states[p].next[&byte];
+
+            if self.states[p].len + 1 == self.states[q].len {
+                self.states[cur].link = Some(q);
+            } else {
+                // `q` holds strings longer than the suffix, split it so the
+                // suffix gets its own state.
+                let clone = self.states.len();
+                self.states.push(State {
+                    len: self.states[p].len + 1,
+                    link: self.states[q].link,
+                    next: self.states[q].next.clone(),
+                    cloned: true,
+                });
+
+                let mut p = Some(p);
+                while let Some(state) = p {
+                    if self.states[state].next.get(&byte) != Some(&q) {
+                        break;
+                    }
+                    self.states[state].next.insert(byte, clone);
+                    p = self.states[state].link;
+                }
+
+                self.states[q].link = Some(clone);
+                self.states[cur].link = Some(clone);
+            }
+        }
+
+        let link = self.states[cur].link.unwrap();
+        self.distinct += self.states[cur].len - self.states[link].len;
+        self.last = cur;
+    }
+
+    /// State reached by reading `pattern` from the root.
+    fn walk(&self, pattern: &[u8]) -> Option<usize> {
+        let mut state = 0;
+        for byte in pattern {
+            state = *self.states[state].next.get(byte)?;
+        }
+
+        Some(state)
+    }
+
+    /// Returns whether `pattern` is a substring of the text, in O(pattern).
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.walk(pattern).is_some()
+    }
+
+    /// Number of distinct non-empty substrings of the text.
+    pub fn distinct_substrings(&self) -> usize {
+        self.distinct
+    }
+
+    /// Number of (possibly overlapping) occurrences of `pattern` in the
+    /// text. The empty pattern occurs at every one of the `len + 1` positions.
+    ///
+    /// The first call after the text changed takes O(n) to count the end
+    /// positions of every state, the following ones are O(pattern).
+    pub fn occurrences(&self, pattern: &[u8]) -> usize {
+        if pattern.is_empty() {
+            return self.len + 1;
+        }
+
+        match self.walk(pattern) {
+            Some(state) => self.occurrences.get_or_init(|| self.count_occurrences())[state],
+            None => 0,
+        }
+    }
+
+    fn count_occurrences(&self) -> Vec<usize> {
+        let mut counts: Vec<usize> = self.states.iter().map(|s| usize::from(!s.cloned)).collect();
+
+        // Longer states first, every state passes its end positions to its
+        // suffix link.
+        let mut by_len: Vec<usize> = (0..self.states.len()).collect();
+        by_len.sort_unstable_by_key(|&s| std::cmp::Reverse(self.states[s].len));
+
+        for state in by_len {
+            if let Some(link) = self.states[state].link {
+                counts[link] += counts[state];
+            }
+        }
+
+        counts
+    }
+}
+
+impl Default for SuffixAutomaton {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extend<u8> for SuffixAutomaton {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        for byte in iter {
+            self.push(byte);
+        }
+    }
+}
+
+impl FromIterator<u8> for SuffixAutomaton {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut automaton = Self::new();
+        automaton.extend(iter);
+
+        automaton
+    }
+}
+
+impl From<&[u8]> for SuffixAutomaton {
+    fn from(text: &[u8]) -> Self {
+        text.iter().copied().collect()
+    }
+}
+
+impl Debug for SuffixAutomaton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuffixAutomaton")
+            .field("len", &self.len)
+            .field("states", &self.states.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SuffixAutomaton;
+    use std::collections::HashSet;
+
+    fn brute_occurrences(text: &[u8], pattern: &[u8]) -> usize {
+        text.windows(pattern.len())
+            .filter(|w| *w == pattern)
+            .count()
+    }
+
+    #[test]
+    fn test_queries() {
+        let sam = SuffixAutomaton::from(&b"abracadabra"[..]);
+
+        assert!(sam.contains(b"cad"));
+        assert!(sam.contains(b"abracadabra"));
+        assert!(sam.contains(b""));
+        assert!(!sam.contains(b"abc"));
+
+        assert_eq!(sam.occurrences(b"abra"), 2);
+        assert_eq!(sam.occurrences(b"a"), 5);
+        assert_eq!(sam.occurrences(b"bra"), 2);
+        assert_eq!(sam.occurrences(b"x"), 0);
+        assert_eq!(sam.occurrences(b""), 12);
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut sam = SuffixAutomaton::new();
+        let mut text = Vec::new();
+
+        for i in 0..60u32 {
+            let byte = b"ab"[(i * i % 7 % 2) as usize];
+            sam.push(byte);
+            text.push(byte);
+
+            let substrings: HashSet<&[u8]> = (0..text.len())
+                .flat_map(|i| (i + 1..=text.len()).map(move |j| (i, j)))
+                .map(|(i, j)| &text[i..j])
+                .collect();
+            assert_eq!(sam.distinct_substrings(), substrings.len());
+            assert!(sam.state_count() <= 2 * text.len());
+
+            if i % 10 == 9 {
+                for pattern in substrings {
+                    assert_eq!(sam.occurrences(pattern), brute_occurrences(&text, pattern));
+                }
+                assert!(!sam.contains(b"c"));
+            }
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::ops::{Bound, RangeBounds};
+
+use crate::{
+    AvlTree, BTree, DequeueList, LinkedList, RBTree, RingDeque, SkipList, Vector, WBTree,
+    alloc::Allocator, avl_tree, binary_tree, growth::GrowthStrategy, red_black_tree, skip_list,
+    weight_balanced_tree,
+};
+
+/// Operations every container in the crate supports, so generic code and
+/// benchmarks don't depend on a particular one.
+pub trait Collection<T> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every element.
+    fn clear(&mut self);
+
+    fn contains(&self, elem: &T) -> bool;
+}
+
+/// Heap memory held by a container, so memory budgets can be enforced
+/// without an instrumented allocator. Whatever the elements themselves own
+/// on the heap isn't counted.
+pub trait MemoryUsage {
+    /// Bytes allocated by the container, unused capacity included.
+    fn heap_bytes(&self) -> usize;
+
+    /// Number of separately allocated nodes, zero for the containers backed
+    /// by a single buffer.
+    fn node_count(&self) -> usize;
+}
+
+/// Common interface of the ordered sets in the crate, so code written against
+/// it can swap one implementation for another.
+pub trait OrderedSet<T: Ord>: Collection<T> {
+    type Iter<'a>: Iterator<Item = &'a T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    type Range<'a, R>: Iterator<Item = &'a T>
+    where
+        Self: 'a,
+        T: 'a,
+        R: RangeBounds<T>;
+
+    /// Adds `elem` to the set, returns `false` if it was already present.
+    fn insert(&mut self, elem: T) -> bool;
+
+    /// Removes `elem` from the set, returns `false` if it wasn't present.
+    fn remove(&mut self, elem: &T) -> bool;
+
+    fn min(&self) -> Option<&T>;
+
+    fn max(&self) -> Option<&T>;
+
+    /// Iterates over the elements in ascending order.
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Iterates in ascending order over the elements contained in `range`.
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R>;
+}
+
+/// Range over the in-order iterator of a set that can't seek to the start of
+/// the range, the elements before it are skipped one by one.
+pub struct InRange<I, R> {
+    iter: I,
+    range: R,
+    done: bool,
+}
+
+impl<I, R> InRange<I, R> {
+    fn new(iter: I, range: R) -> Self {
+        InRange {
+            iter,
+            range,
+            done: false,
+        }
+    }
+}
+
+impl<'a, T, I, R> Iterator for InRange<I, R>
+where
+    T: Ord + 'a,
+    I: Iterator<Item = &'a T>,
+    R: RangeBounds<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.done {
+            return None;
+        }
+
+        let elem = self.iter.by_ref().find(|elem| match self.range.start_bound() {
+            Bound::Included(start) => *elem >= start,
+            Bound::Excluded(start) => *elem > start,
+            Bound::Unbounded => true,
+        })?;
+
+        let before_end = match self.range.end_bound() {
+            Bound::Included(end) => elem <= end,
+            Bound::Excluded(end) => elem < end,
+            Bound::Unbounded => true,
+        };
+
+        // Nothing after the end can be in the range either.
+        self.done = !before_end;
+        before_end.then_some(elem)
+    }
+}
+
+impl<T: PartialEq, A: Allocator, G: GrowthStrategy> Collection<T> for Vector<T, A, G> {
+    fn len(&self) -> usize {
+        Vector::len(self)
+    }
+
+    fn clear(&mut self) {
+        Vector::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        self.iter().any(|e| e == elem)
+    }
+}
+
+impl<T: PartialEq, A: Allocator> Collection<T> for DequeueList<T, A> {
+    fn len(&self) -> usize {
+        DequeueList::len(self)
+    }
+
+    fn clear(&mut self) {
+        DequeueList::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        self.iter().any(|e| e == elem)
+    }
+}
+
+impl<T: PartialEq, A: Allocator> Collection<T> for LinkedList<T, A> {
+    fn len(&self) -> usize {
+        self.size() as usize
+    }
+
+    fn clear(&mut self) {
+        LinkedList::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        LinkedList::contains(self, elem)
+    }
+}
+
+impl<T: PartialEq> Collection<T> for RingDeque<T> {
+    fn len(&self) -> usize {
+        RingDeque::len(self)
+    }
+
+    fn clear(&mut self) {
+        RingDeque::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        self.iter().any(|e| e == elem)
+    }
+}
+
+impl<T: Ord> Collection<T> for BTree<T> {
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    fn clear(&mut self) {
+        BTree::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        BTree::contains(self, elem)
+    }
+}
+
+impl<T: Ord> Collection<T> for AvlTree<T> {
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    fn clear(&mut self) {
+        AvlTree::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        AvlTree::contains(self, elem)
+    }
+}
+
+impl<T: Ord> Collection<T> for RBTree<T> {
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    fn clear(&mut self) {
+        RBTree::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        RBTree::contains(self, elem)
+    }
+}
+
+impl<T: Ord> Collection<T> for WBTree<T> {
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    fn clear(&mut self) {
+        WBTree::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        WBTree::contains(self, elem)
+    }
+}
+
+impl<T: Ord> Collection<T> for SkipList<T> {
+    fn len(&self) -> usize {
+        SkipList::len(self)
+    }
+
+    fn clear(&mut self) {
+        SkipList::clear(self)
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+        SkipList::contains(self, elem)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for BTree<T> {
+    type Iter<'a> = binary_tree::Iter<'a, T> where T: 'a;
+    type Range<'a, R> = InRange<binary_tree::Iter<'a, T>, R> where T: 'a, R: RangeBounds<T>;
+
+    fn insert(&mut self, elem: T) -> bool {
+        let size = self.size();
+        BTree::insert(self, elem);
+        self.size() != size
+    }
+
+    fn remove(&mut self, elem: &T) -> bool {
+        let size = self.size();
+        BTree::remove(self, elem);
+        self.size() != size
+    }
+
+    fn min(&self) -> Option<&T> {
+        BTree::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        BTree::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        BTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        InRange::new(BTree::iter(self), range)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for AvlTree<T> {
+    type Iter<'a> = avl_tree::Iter<'a, T> where T: 'a;
+    type Range<'a, R> = InRange<avl_tree::Iter<'a, T>, R> where T: 'a, R: RangeBounds<T>;
+
+    fn insert(&mut self, elem: T) -> bool {
+        let size = self.size();
+        AvlTree::insert(self, elem);
+        self.size() != size
+    }
+
+    fn remove(&mut self, elem: &T) -> bool {
+        self.take(elem).is_some()
+    }
+
+    fn min(&self) -> Option<&T> {
+        AvlTree::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        AvlTree::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        AvlTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        InRange::new(AvlTree::iter(self), range)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for RBTree<T> {
+    type Iter<'a> = red_black_tree::Iter<'a, T> where T: 'a;
+    type Range<'a, R> = red_black_tree::Range<'a, T> where T: 'a, R: RangeBounds<T>;
+
+    fn insert(&mut self, elem: T) -> bool {
+        let size = self.size();
+        RBTree::insert(self, elem);
+        self.size() != size
+    }
+
+    fn remove(&mut self, elem: &T) -> bool {
+        self.take(elem).is_some()
+    }
+
+    fn min(&self) -> Option<&T> {
+        RBTree::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        RBTree::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        RBTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        RBTree::range(self, range)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for SkipList<T> {
+    type Iter<'a> = skip_list::Iter<'a, T> where T: 'a;
+    type Range<'a, R> = skip_list::Range<'a, T> where T: 'a, R: RangeBounds<T>;
+
+    fn insert(&mut self, elem: T) -> bool {
+        SkipList::insert(self, elem)
+    }
+
+    fn remove(&mut self, elem: &T) -> bool {
+        SkipList::remove(self, elem).is_some()
+    }
+
+    fn min(&self) -> Option<&T> {
+        self.first()
+    }
+
+    fn max(&self) -> Option<&T> {
+        self.last()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        SkipList::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        SkipList::range(self, range)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for WBTree<T> {
+    type Iter<'a> = weight_balanced_tree::Iter<'a, T> where T: 'a;
+    type Range<'a, R> = InRange<weight_balanced_tree::Iter<'a, T>, R> where T: 'a, R: RangeBounds<T>;
+
+    fn insert(&mut self, elem: T) -> bool {
+        let size = self.size();
+        WBTree::insert(self, elem);
+        self.size() != size
+    }
+
+    fn remove(&mut self, elem: &T) -> bool {
+        self.take(elem).is_some()
+    }
+
+    fn min(&self) -> Option<&T> {
+        WBTree::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        WBTree::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        WBTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        InRange::new(WBTree::iter(self), range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collection, MemoryUsage, OrderedSet};
+    use crate::{AvlTree, BTree, DequeueList, RBTree, RingDeque, SkipList, WBTree};
+
+    /// Runs the same operations against any implementation.
+    fn exercise<S: OrderedSet<i32> + Default>() {
+        let mut set = S::default();
+        assert!(set.is_empty());
+
+        for n in [40, 20, 60, 10, 30, 50, 70] {
+            assert!(set.insert(n));
+        }
+
+        assert!(!set.insert(40));
+        assert_eq!(set.len(), 7);
+        assert!(set.contains(&30));
+
+        assert!(set.remove(&20));
+        assert!(!set.remove(&20));
+        assert!(!set.contains(&20));
+
+        assert!(set.iter().copied().eq([10, 30, 40, 50, 60, 70]));
+        assert_eq!(set.min(), Some(&10));
+        assert_eq!(set.max(), Some(&70));
+
+        assert!(set.range(30..60).copied().eq([30, 40, 50]));
+        assert!(set.range(31..=60).copied().eq([40, 50, 60]));
+        assert!(set.range(..).copied().eq([10, 30, 40, 50, 60, 70]));
+        assert_eq!(set.range(41..50).count(), 0);
+        assert_eq!(set.range(80..).count(), 0);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.min(), None);
+        assert!(set.insert(20));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_implementations() {
+        exercise::<BTree<i32>>();
+        exercise::<AvlTree<i32>>();
+        exercise::<RBTree<i32>>();
+        exercise::<SkipList<i32>>();
+        exercise::<WBTree<i32>>();
+    }
+
+    fn fill<C: Collection<i32> + Default + Extend<i32>>() -> C {
+        let mut collection = C::default();
+        collection.extend([3, 1, 2]);
+        collection
+    }
+
+    #[test]
+    fn test_collections() {
+        fn check<C: Collection<i32>>(mut collection: C) {
+            assert_eq!(collection.len(), 3);
+            assert!(collection.contains(&2));
+            assert!(!collection.contains(&4));
+
+            collection.clear();
+            assert!(collection.is_empty());
+            assert!(!collection.contains(&2));
+        }
+
+        check(fill::<DequeueList<i32>>());
+        check(fill::<RingDeque<i32>>());
+        check(vector![3, 1, 2]);
+        check(linked_list![3, 1, 2]);
+        check(btree![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        use crate::alloc::Counting;
+        use crate::{LinkedList, Vector};
+
+        // The allocator sees exactly what the containers report.
+        let counting = Counting::new();
+
+        let mut v = Vector::new_in(&counting);
+        for i in 0..100u64 {
+            v.push(i);
+        }
+        assert_eq!(v.heap_bytes(), counting.bytes_in_use());
+        assert_eq!(v.heap_bytes(), v.capacity() * 8);
+        assert_eq!(v.node_count(), 0);
+        drop(v);
+
+        let mut list = DequeueList::new_in(&counting);
+        list.extend(0..10);
+        assert_eq!(list.heap_bytes(), counting.bytes_in_use());
+        assert_eq!(list.node_count(), 10);
+        drop(list);
+
+        let mut linked = LinkedList::new_in(&counting);
+        linked.add(1);
+        linked.add(2);
+        assert_eq!(linked.heap_bytes(), counting.bytes_in_use());
+        assert_eq!(linked.node_count(), 2);
+        drop(linked);
+
+        let mut tree = BTree::new_in(&counting);
+        for n in [5, 3, 8] {
+            tree.insert(n);
+        }
+        assert_eq!(tree.heap_bytes(), counting.bytes_in_use());
+        assert_eq!(tree.node_count(), 3);
+        drop(tree);
+
+        let set: SkipList<i32> = (0..50).collect();
+        assert_eq!(set.node_count(), 50);
+        assert!(set.heap_bytes() > 50 * size_of::<i32>());
+
+        let tree: RBTree<i32> = (0..50).collect();
+        assert_eq!(tree.node_count(), 50);
+        assert_eq!(RingDeque::<u8>::new().heap_bytes(), 0);
+    }
+}
@@ -0,0 +1,169 @@
+//! Depth-first traversal of the tree- and graph-shaped structures.
+//!
+//! [`Traverse`] walks the nodes in [`Order::Pre`], [`Order::In`] or
+//! [`Order::Post`] order, handing each one to a callback as a [`Visit`]. The
+//! callback returns a [`ControlFlow`], breaking stops the walk right away.
+//! Since every visit knows its depth and its parent, algorithms over the
+//! shape (the height, a flat listing, a Graphviz drawing) are written once
+//! as provided methods of the trait.
+
+use std::{fmt::Display, fmt::Write, ops::ControlFlow};
+
+/// When a node is visited relative to its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Before its children.
+    Pre,
+    /// Between its first child and the others. For binary trees that's the
+    /// usual in-order, the first child being the left one even when
+    /// missing.
+    In,
+    /// After its children.
+    Post,
+}
+
+/// A node reached by [`Traverse::traverse`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Visit<'a, T> {
+    pub elem: &'a T,
+    /// Distance from the root, `0` for the root.
+    pub depth: usize,
+    /// Position of the node in preorder, whatever the order of the
+    /// traversal. Identifies the node within one traversal.
+    pub index: usize,
+    /// [`index`](Visit::index) of the parent, `None` for a root.
+    pub parent: Option<usize>,
+}
+
+// Copyable whatever `T`, it only holds a reference to it.
+impl<T> Clone for Visit<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Visit<'_, T> {}
+
+/// Structures that can be walked depth first.
+pub trait Traverse {
+    type Elem;
+
+    /// Visits every node in `order` until `visit` breaks, returning the
+    /// break value.
+    fn traverse<'a, B>(
+        &'a self,
+        order: Order,
+        visit: impl FnMut(Visit<'a, Self::Elem>) -> ControlFlow<B>,
+    ) -> ControlFlow<B>;
+
+    /// Number of levels, `0` when empty.
+    fn height(&self) -> usize {
+        let mut height = 0;
+        let _ = self.traverse::<()>(Order::Pre, |visit| {
+            height = height.max(visit.depth + 1);
+            ControlFlow::Continue(())
+        });
+
+        height
+    }
+
+    /// The elements in `order`.
+    fn elems(&self, order: Order) -> Vec<&Self::Elem> {
+        let mut elems = Vec::new();
+        let _ = self.traverse::<()>(order, |visit| {
+            elems.push(visit.elem);
+            ControlFlow::Continue(())
+        });
+
+        elems
+    }
+
+    /// Draws the shape in the Graphviz DOT language, the nodes named after
+    /// their preorder index and labeled with their element.
+    fn to_dot(&self) -> String
+    where
+        Self::Elem: Display,
+    {
+        let mut dot = String::from("digraph {\n");
+        let _ = self.traverse::<()>(Order::Pre, |visit| {
+            let label = visit.elem.to_string().replace('"', "\\\"");
+            writeln!(dot, "    {} [label=\"{label}\"];", visit.index).unwrap();
+            if let Some(parent) = visit.parent {
+                writeln!(dot, "    {parent} -> {};", visit.index).unwrap();
+            }
+            ControlFlow::Continue(())
+        });
+        dot.push('}');
+
+        dot
+    }
+}
+
+/// Walks the binary tree under `root`, `split` giving the element and the
+/// children of a node.
+pub(crate) fn walk_binary<'a, N: Copy, T: 'a, B>(
+    root: Option<N>,
+    order: Order,
+    split: impl Fn(N) -> (&'a T, Option<N>, Option<N>),
+    visit: impl FnMut(Visit<'a, T>) -> ControlFlow<B>,
+) -> ControlFlow<B> {
+    struct Walk<S, V> {
+        order: Order,
+        split: S,
+        visit: V,
+        next_index: usize,
+    }
+
+    impl<S, V> Walk<S, V> {
+        fn node<'a, N: Copy, T: 'a, B>(
+            &mut self,
+            node: N,
+            depth: usize,
+            parent: Option<usize>,
+        ) -> ControlFlow<B>
+        where
+            S: Fn(N) -> (&'a T, Option<N>, Option<N>),
+            V: FnMut(Visit<'a, T>) -> ControlFlow<B>,
+        {
+            let (elem, left, right) = (self.split)(node);
+            let index = self.next_index;
+            self.next_index += 1;
+            let here = Visit {
+                elem,
+                depth,
+                index,
+                parent,
+            };
+
+            if self.order == Order::Pre {
+                (self.visit)(here)?;
+            }
+            if let Some(left) = left {
+                self.node(left, depth + 1, Some(index))?;
+            }
+            if self.order == Order::In {
+                (self.visit)(here)?;
+            }
+            if let Some(right) = right {
+                self.node(right, depth + 1, Some(index))?;
+            }
+            if self.order == Order::Post {
+                (self.visit)(here)?;
+            }
+
+            ControlFlow::Continue(())
+        }
+    }
+
+    let Some(root) = root else {
+        return ControlFlow::Continue(());
+    };
+
+    let mut walk = Walk {
+        order,
+        split,
+        visit,
+        next_index: 0,
+    };
+    walk.node(root, 0, None)
+}
@@ -0,0 +1,345 @@
+use std::fmt::Debug;
+
+/// Trie node. Children are kept sorted by their byte so that iteration
+/// yields keys in lexicographic order.
+struct Node<V> {
+    value: Option<V>,
+    children: Vec<(u8, Box<Node<V>>)>,
+}
+
+/// Prefix tree mapping byte strings (`&str`, `&[u8]`, ...) to values.
+pub struct Trie<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+/// Iterates over `(key, value)` pairs in lexicographic order of the keys.
+pub struct Iter<'a, V> {
+    stack: Vec<(&'a Node<V>, usize)>,
+    key: Vec<u8>,
+    pending: Option<&'a V>,
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Node {
+            value: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn child(&self, byte: u8) -> Option<&Node<V>> {
+        self.children
+            .binary_search_by_key(&byte, |(b, _)| *b)
+            .ok()
+            .map(|i| &*self.children[i].1)
+    }
+
+    fn child_mut(&mut self, byte: u8) -> Option<&mut Node<V>> {
+        self.children
+            .binary_search_by_key(&byte, |(b, _)| *b)
+            .ok()
+            .map(|i| &mut *self.children[i].1)
+    }
+
+    fn is_useless(&self) -> bool {
+        self.value.is_none() && self.children.is_empty()
+    }
+}
+
+impl<V> Trie<V> {
+    pub fn new() -> Self {
+        Trie {
+            root: Node::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of keys stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.root = Node::new();
+        self.len = 0;
+    }
+
+    /// Walks down the path spelled by `key`.
+    fn find(&self, key: &[u8]) -> Option<&Node<V>> {
+        key.iter().try_fold(&self.root, |node, byte| node.child(*byte))
+    }
+
+    /// Inserts `key` with `value`, returning the previous value if the key
+    /// was already present.
+    pub fn insert<K: AsRef<[u8]> + ?Sized>(&mut self, key: &K, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+
+        for byte in key.as_ref() {
+            let idx = match node.children.binary_search_by_key(byte, |(b, _)| *b) {
+                Ok(idx) => idx,
+                Err(idx) => {
+                    node.children.insert(idx, (*byte, Box::new(Node::new())));
+                    idx
+                }
+            };
+
+            node = &mut node.children[idx].1;
+        }
+
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    pub fn get<K: AsRef<[u8]> + ?Sized>(&self, key: &K) -> Option<&V> {
+        self.find(key.as_ref())?.value.as_ref()
+    }
+
+    pub fn get_mut<K: AsRef<[u8]> + ?Sized>(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = &mut self.root;
+
+        for byte in key.as_ref() {
+            node = node.child_mut(*byte)?;
+        }
+
+        node.value.as_mut()
+    }
+
+    /// Returns `true` if `key` itself was inserted (not just a longer key
+    /// starting with it).
+    pub fn contains<K: AsRef<[u8]> + ?Sized>(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns `true` if any stored key starts with `prefix`.
+    pub fn starts_with<K: AsRef<[u8]> + ?Sized>(&self, prefix: &K) -> bool {
+        // Nodes without values are pruned on remove, so any node on the path
+        // leads to at least one key.
+        match self.find(prefix.as_ref()) {
+            Some(node) => !node.is_useless(),
+            None => false,
+        }
+    }
+
+    /// Removes `key`, pruning the branches that no longer lead anywhere.
+    pub fn remove<K: AsRef<[u8]> + ?Sized>(&mut self, key: &K) -> Option<V> {
+        let removed = Self::remove_recursive(&mut self.root, key.as_ref());
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    fn remove_recursive(node: &mut Node<V>, key: &[u8]) -> Option<V> {
+        let Some((byte, rest)) = key.split_first() else {
+            return node.value.take();
+        };
+
+        let idx = node.children.binary_search_by_key(byte, |(b, _)| *b).ok()?;
+        let removed = Self::remove_recursive(&mut node.children[idx].1, rest);
+
+        if node.children[idx].1.is_useless() {
+            node.children.remove(idx);
+        }
+
+        removed
+    }
+
+    /// Returns the longest prefix of `key` that is stored in the trie,
+    /// together with its value. Useful for routing-table style lookups.
+    pub fn longest_prefix<'k, K>(&self, key: &'k K) -> Option<(&'k [u8], &V)>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let key = key.as_ref();
+        let mut node = &self.root;
+        let mut longest = node.value.as_ref().map(|value| (0, value));
+
+        for (i, byte) in key.iter().enumerate() {
+            match node.child(*byte) {
+                Some(child) => node = child,
+                None => break,
+            }
+
+            if let Some(value) = &node.value {
+                longest = Some((i + 1, value));
+            }
+        }
+
+        longest.map(|(len, value)| (&key[..len], value))
+    }
+
+    /// Iterates over all the entries whose key starts with `prefix`.
+    pub fn iter_prefix<K: AsRef<[u8]> + ?Sized>(&self, prefix: &K) -> Iter<'_, V> {
+        let prefix = prefix.as_ref();
+
+        match self.find(prefix) {
+            Some(node) => Iter {
+                stack: vec![(node, 0)],
+                key: prefix.to_vec(),
+                pending: node.value.as_ref(),
+            },
+            None => Iter {
+                stack: Vec::new(),
+                key: Vec::new(),
+                pending: None,
+            },
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix(&[])
+    }
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some((self.key.clone(), value));
+        }
+
+        loop {
+            let (node, idx) = self.stack.last_mut()?;
+
+            if let Some((byte, child)) = node.children.get(*idx) {
+                *idx += 1;
+                self.key.push(*byte);
+                self.stack.push((child, 0));
+
+                if let Some(value) = &child.value {
+                    return Some((self.key.clone(), value));
+                }
+            } else {
+                self.stack.pop();
+                self.key.pop();
+            }
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Trie<V> {
+    type IntoIter = Iter<'a, V>;
+    type Item = (Vec<u8>, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: AsRef<[u8]>, V> Extend<(K, V)> for Trie<V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(&key, value);
+        }
+    }
+}
+
+impl<K: AsRef<[u8]>, V> FromIterator<(K, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        trie.extend(iter);
+
+        trie
+    }
+}
+
+impl<V: Debug> Debug for Trie<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self
+            .iter()
+            .map(|(key, value)| (String::from_utf8_lossy(&key).into_owned(), value));
+
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Trie;
+
+    fn words() -> Trie<usize> {
+        ["tea", "ten", "to", "inn", "in", "team"]
+            .iter()
+            .enumerate()
+            .map(|(i, word)| (word, i))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_contains() {
+        let mut trie = words();
+        assert_eq!(trie.len(), 6);
+
+        assert!(trie.contains("tea"));
+        assert!(trie.contains("in"));
+        assert!(!trie.contains("te"));
+        assert!(!trie.contains("tean"));
+        assert_eq!(trie.get("team"), Some(&5));
+
+        assert_eq!(trie.insert("tea", 10), Some(0));
+        assert_eq!(trie.len(), 6);
+        *trie.get_mut("tea").unwrap() += 1;
+        assert_eq!(trie.get(b"tea"), Some(&11));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut trie = words();
+
+        assert_eq!(trie.remove("te"), None);
+        assert_eq!(trie.remove("tea"), Some(0));
+        assert!(!trie.contains("tea"));
+        assert!(trie.contains("team"));
+
+        assert_eq!(trie.remove("team"), Some(5));
+        assert!(trie.starts_with("te"));
+        assert_eq!(trie.remove("ten"), Some(1));
+        assert!(!trie.starts_with("te"));
+        assert!(trie.starts_with("t"));
+        assert_eq!(trie.len(), 3);
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let trie = words();
+
+        let keys: Vec<_> = trie.iter().map(|(key, _)| String::from_utf8(key).unwrap()).collect();
+        assert_eq!(keys, ["in", "inn", "tea", "team", "ten", "to"]);
+
+        let keys: Vec<_> = trie.iter_prefix("te").map(|(key, value)| (key, *value)).collect();
+        assert_eq!(keys, [(b"tea".to_vec(), 0), (b"team".to_vec(), 5), (b"ten".to_vec(), 1)]);
+
+        assert_eq!(trie.iter_prefix("x").next(), None);
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut routes = Trie::new();
+        routes.insert("/", "root");
+        routes.insert("/api", "api");
+        routes.insert("/api/users", "users");
+
+        assert_eq!(routes.longest_prefix("/api/users/42"), Some((&b"/api/users"[..], &"users")));
+        assert_eq!(routes.longest_prefix("/api/posts"), Some((&b"/api"[..], &"api")));
+        assert_eq!(routes.longest_prefix("/static"), Some((&b"/"[..], &"root")));
+        assert_eq!(routes.longest_prefix("static"), None);
+    }
+}
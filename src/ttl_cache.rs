@@ -0,0 +1,408 @@
+use std::{
+    cell::Cell,
+    cmp::{Ordering, Reverse},
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::BinaryHeap;
+
+/// Source of the current time for a [`TtlCache`].
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// Reads the time from [`Instant::now`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when told to, for tests.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Cell<Instant>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> Self {
+        ManualClock {
+            now: Cell::new(Instant::now()),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Rc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    /// `None` if the ttl is too long to be represented, the entry never
+    /// expires.
+    deadline: Option<Instant>,
+}
+
+/// Heap entry, ordered by deadline only.
+struct Deadline<K> {
+    at: Instant,
+    key: K,
+}
+
+impl<K> PartialEq for Deadline<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.at == other.at
+    }
+}
+
+impl<K> Eq for Deadline<K> {}
+
+impl<K> PartialOrd for Deadline<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K> Ord for Deadline<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.at.cmp(&other.at)
+    }
+}
+
+/// Map whose entries expire a fixed time after they are inserted.
+///
+/// Expired entries are dropped lazily when they are looked up, or all at
+/// once by [`purge_expired`], which pops a min-heap of deadlines instead of
+/// scanning the map. Until then they still count towards [`len`].
+///
+/// The time is read from a [`Clock`], [`SystemClock`] by default; tests can
+/// pass a [`ManualClock`] instead.
+///
+/// [`purge_expired`]: TtlCache::purge_expired
+/// [`len`]: TtlCache::len
+pub struct TtlCache<K, V, C = SystemClock> {
+    map: HashMap<K, Entry<V>>,
+    /// May hold stale deadlines of keys that were removed or reinserted.
+    deadlines: BinaryHeap<Reverse<Deadline<K>>>,
+    ttl: Duration,
+    clock: C,
+}
+
+impl<K: Eq + Hash + Clone, V> TtlCache<K, V> {
+    /// Creates an empty cache where entries live for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_clock(ttl, SystemClock)
+    }
+}
+
+impl<K: Eq + Hash + Clone, V, C: Clock> TtlCache<K, V, C> {
+    /// Creates an empty cache where entries live for `ttl` as told by
+    /// `clock`.
+    pub fn with_clock(ttl: Duration, clock: C) -> Self {
+        TtlCache {
+            map: HashMap::new(),
+            deadlines: BinaryHeap::new(),
+            ttl,
+            clock,
+        }
+    }
+
+    /// Number of entries, including expired ones that weren't dropped yet.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Time to live of new entries.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Inserts an entry that expires after the default ttl, returns the
+    /// previous value if it hadn't expired.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_with_ttl(key, value, self.ttl)
+    }
+
+    /// Inserts an entry that expires after `ttl`, returns the previous value
+    /// if it hadn't expired.
+    ///
+    /// A `ttl` too long to be added to the current time, like
+    /// [`Duration::MAX`], never expires.
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        let deadline = now.checked_add(ttl);
+
+        if let Some(at) = deadline {
+            self.deadlines.push(Reverse(Deadline {
+                at,
+                key: key.clone(),
+            }));
+        }
+
+        let old = self.map.insert(key, Entry { value, deadline });
+        if self.deadlines.len() > 2 * self.map.len() {
+            self.rebuild_deadlines();
+        }
+
+        old.and_then(|old| old.deadline.is_none_or(|at| at > now).then_some(old.value))
+    }
+
+    /// Rebuilds the heap from the map, dropping stale deadlines that piled
+    /// up from removed or reinserted keys.
+    fn rebuild_deadlines(&mut self) {
+        self.deadlines = self
+            .map
+            .iter()
+            .filter_map(|(key, entry)| {
+                Some(Reverse(Deadline {
+                    at: entry.deadline?,
+                    key: key.clone(),
+                }))
+            })
+            .collect();
+    }
+
+    /// Drops the entry for `key` if it has expired. Returns whether a live
+    /// entry is left.
+    fn expire(&mut self, key: &K) -> bool {
+        let now = self.clock.now();
+
+        match self.map.get(key) {
+            Some(entry) if entry.deadline.is_some_and(|at| at <= now) => {
+                self.map.remove(key);
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Returns the value for `key` unless it has expired.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.expire(key) {
+            return None;
+        }
+
+        self.map.get(key).map(|entry| &entry.value)
+    }
+
+    /// Returns the value for `key` unless it has expired.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if !self.expire(key) {
+            return None;
+        }
+
+        self.map.get_mut(key).map(|entry| &mut entry.value)
+    }
+
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.expire(key)
+    }
+
+    /// Time left before the entry for `key` expires, [`Duration::MAX`] if it
+    /// never does.
+    pub fn remaining(&mut self, key: &K) -> Option<Duration> {
+        if !self.expire(key) {
+            return None;
+        }
+
+        let now = self.clock.now();
+        self.map
+            .get(key)
+            .map(|entry| entry.deadline.map_or(Duration::MAX, |at| at - now))
+    }
+
+    /// Removes the entry for `key`, returns its value if it hadn't expired.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.expire(key) {
+            return None;
+        }
+
+        self.map.remove(key).map(|entry| entry.value)
+    }
+
+    /// Drops every expired entry, returns how many were dropped.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut purged = 0;
+
+        while let Some(Reverse(next)) = self.deadlines.peek()
+            && next.at <= now
+        {
+            let Reverse(Deadline { at, key }) = self.deadlines.pop().unwrap();
+
+            // Skip deadlines of entries that were replaced since.
+            if self
+                .map
+                .get(&key)
+                .is_some_and(|entry| entry.deadline == Some(at))
+            {
+                self.map.remove(&key);
+                purged += 1;
+            }
+        }
+
+        purged
+    }
+
+    /// Removes all the entries.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.deadlines.clear();
+    }
+}
+
+impl<K: Debug, V: Debug, C> Debug for TtlCache<K, V, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.map.iter().map(|(k, entry)| (k, &entry.value)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ManualClock, TtlCache};
+    use std::time::Duration;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_lazy_expiry() {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(10 * SECOND, &clock);
+
+        cache.insert("a", 1);
+        cache.insert_with_ttl("b", 2, 20 * SECOND);
+
+        clock.advance(5 * SECOND);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.remaining(&"a"), Some(5 * SECOND));
+
+        clock.advance(5 * SECOND);
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&"b"), Some(&2));
+
+        *cache.get_mut(&"b").unwrap() = 3;
+        assert_eq!(cache.remove(&"b"), Some(3));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_reinsert() {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(10 * SECOND, &clock);
+
+        cache.insert("a", 1);
+        clock.advance(8 * SECOND);
+        assert_eq!(cache.insert("a", 2), Some(1));
+
+        clock.advance(8 * SECOND);
+        assert_eq!(cache.purge_expired(), 0);
+        assert_eq!(cache.get(&"a"), Some(&2));
+
+        clock.advance(8 * SECOND);
+        assert_eq!(cache.insert("a", 3), None);
+    }
+
+    #[test]
+    fn test_purge() {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(SECOND, &clock);
+
+        for i in 0..10 {
+            cache.insert_with_ttl(i, i, i * SECOND);
+        }
+        cache.remove(&9);
+
+        clock.advance(4 * SECOND);
+        assert_eq!(cache.purge_expired(), 5);
+        assert_eq!(cache.len(), 4);
+        assert!(!cache.contains_key(&4));
+        assert!(cache.contains_key(&5));
+
+        clock.advance(10 * SECOND);
+        assert_eq!(cache.purge_expired(), 4);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_unbounded_ttl() {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(Duration::MAX, &clock);
+
+        cache.insert("a", 1);
+        cache.insert_with_ttl("b", 2, SECOND);
+        assert_eq!(cache.remaining(&"a"), Some(Duration::MAX));
+
+        clock.advance(1000 * SECOND);
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.insert("a", 3), Some(1));
+        assert_eq!(cache.insert_with_ttl("a", 4, SECOND), Some(3));
+
+        clock.advance(SECOND);
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn test_stale_deadlines() {
+        let clock = ManualClock::new();
+        let mut cache = TtlCache::with_clock(10 * SECOND, &clock);
+
+        for i in 0..1000 {
+            cache.insert(if i % 2 == 0 { "b" } else { "c" }, i);
+            cache.remove(&"c");
+            cache.insert("a", i);
+            assert!(cache.deadlines.len() <= 2 * cache.len());
+        }
+
+        assert_eq!(cache.get(&"a"), Some(&999));
+        assert_eq!(cache.get(&"b"), Some(&998));
+        clock.advance(10 * SECOND);
+        assert_eq!(cache.purge_expired(), 2);
+        assert!(cache.is_empty());
+    }
+}
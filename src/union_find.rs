@@ -0,0 +1,179 @@
+/// Disjoint set union that can undo its unions.
+///
+/// Sets are merged by size and `find` doesn't compress paths, so every union
+/// only changes two slots and can be reverted exactly. Trees stay O(log n)
+/// deep thanks to the union by size, which bounds [`find`] as well.
+///
+/// [`find`]: RollbackUnionFind::find
+pub struct RollbackUnionFind {
+    parent: Vec<usize>,
+    /// Size of the set, only meaningful for roots.
+    size: Vec<usize>,
+    /// Roots that were attached under another root, in union order.
+    history: Vec<usize>,
+    components: usize,
+}
+
+/// Point in the history of a [`RollbackUnionFind`] to go back to, see
+/// [`RollbackUnionFind::snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Snapshot(usize);
+
+impl RollbackUnionFind {
+    /// Creates `n` singleton sets, `0..n`.
+    pub fn new(n: usize) -> Self {
+        RollbackUnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            history: Vec::new(),
+            components: n,
+        }
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Returns the representative of the set containing `x`.
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+
+        x
+    }
+
+    /// Merges the sets of `a` and `b`. Returns `false` if they were already
+    /// the same set, which isn't recorded in the history.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.history.push(b);
+        self.components -= 1;
+
+        true
+    }
+
+    /// Returns whether `a` and `b` are in the same set.
+    pub fn connected(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set containing `x`.
+    pub fn size_of(&self, x: usize) -> usize {
+        self.size[self.find(x)]
+    }
+
+    /// Marks the current state, to be restored later with [`rollback`].
+    ///
+    /// [`rollback`]: RollbackUnionFind::rollback
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.history.len())
+    }
+
+    /// Undoes every union made after `snapshot` was taken. Panics if the
+    /// history was already rolled back to before `snapshot`.
+    pub fn rollback(&mut self, snapshot: Snapshot) {
+        assert!(snapshot.0 <= self.history.len(), "Snapshot no longer valid");
+
+        while self.history.len() > snapshot.0 {
+            self.undo();
+        }
+    }
+
+    /// Undoes the last union. Returns `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        let Some(b) = self.history.pop() else {
+            return false;
+        };
+
+        let a = self.parent[b];
+        self.parent[b] = b;
+        self.size[a] -= self.size[b];
+        self.components += 1;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RollbackUnionFind;
+
+    #[test]
+    fn test_union_find() {
+        let mut dsu = RollbackUnionFind::new(6);
+        assert_eq!(dsu.components(), 6);
+
+        assert!(dsu.union(0, 1));
+        assert!(dsu.union(2, 3));
+        assert!(dsu.union(1, 3));
+        assert!(!dsu.union(0, 2));
+
+        assert!(dsu.connected(0, 3));
+        assert!(!dsu.connected(0, 4));
+        assert_eq!(dsu.size_of(2), 4);
+        assert_eq!(dsu.components(), 3);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut dsu = RollbackUnionFind::new(5);
+        dsu.union(0, 1);
+
+        let snapshot = dsu.snapshot();
+        dsu.union(1, 2);
+        dsu.union(3, 4);
+        dsu.union(2, 4);
+        assert_eq!(dsu.size_of(0), 5);
+
+        let inner = dsu.snapshot();
+        dsu.union(0, 4);
+        dsu.rollback(inner);
+        assert_eq!(dsu.size_of(0), 5);
+
+        dsu.rollback(snapshot);
+        assert!(dsu.connected(0, 1));
+        assert!(!dsu.connected(1, 2));
+        assert!(!dsu.connected(3, 4));
+        assert_eq!(dsu.size_of(0), 2);
+        assert_eq!(dsu.size_of(4), 1);
+        assert_eq!(dsu.components(), 4);
+
+        assert!(dsu.undo());
+        assert!(!dsu.undo());
+        assert_eq!(dsu.components(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_stale_snapshot() {
+        let mut dsu = RollbackUnionFind::new(3);
+        let start = dsu.snapshot();
+        dsu.union(0, 1);
+        let after = dsu.snapshot();
+
+        dsu.rollback(start);
+        dsu.rollback(after);
+    }
+}
@@ -1,23 +1,77 @@
+use crate::alloc::{Allocator, Global};
+use crate::error::{self, CapacityError, IndexError, TryReserveError};
+use crate::extract::{ExtractIf, Sealed, Source};
+use crate::frozen::Frozen;
+use crate::growth::{Doubling, GrowthStrategy};
+use crate::guard::Guard;
+use crate::sort::{self, Algorithm};
+use crate::stats::VectorStats;
+use crate::traits::MemoryUsage;
+
 use std::{
-    alloc, marker, mem,
-    ops::{Deref, DerefMut},
+    alloc,
+    borrow::{Borrow, BorrowMut},
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    io,
+    iter::FusedIterator,
+    marker,
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut, Range, RangeBounds},
     ptr,
     ptr::NonNull,
 };
 
 /// Buffer of fixed capacity that stores the values.
-struct Buffer<T> {
-    ptr: NonNull<T>,
-    cap: usize,
+///
+/// The slots are `MaybeUninit`, the owner keeps track of which ones hold a
+/// value.
+pub(crate) struct Buffer<T, A: Allocator = Global, G: GrowthStrategy = Doubling> {
+    ptr: NonNull<MaybeUninit<T>>,
+    pub(crate) cap: usize,
+    alloc: A,
+    growth: G,
+    pub(crate) stats: VectorStats,
     _marker: marker::PhantomData<T>,
 }
 
-// unsafe impl<T: Send> Send for Vec<T> {}
-// unsafe impl<T: Sync> Sync for Vec<T> {}
+// The buffer owns its values like a `Box<[T]>` would.
+unsafe impl<T: Send, A: Allocator + Send, G: GrowthStrategy + Send> Send for Buffer<T, A, G> {}
+unsafe impl<T: Sync, A: Allocator + Sync, G: GrowthStrategy + Sync> Sync for Buffer<T, A, G> {}
 
 impl<T> Buffer<T> {
     /// Create a new RawVec with zero capacity.
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> Buffer<T, A> {
+    /// Create a new RawVec with zero capacity that allocates from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_growth_in(Doubling, alloc)
+    }
+
+    /// Takes over a buffer of `cap` values allocated by `alloc`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` is non-null and aligned, and unless `cap` is zero or `T` is zero
+    /// sized it was allocated by `alloc` with the layout of `[T; cap]`.
+    pub(crate) unsafe fn from_raw_parts_in(ptr: *mut T, cap: usize, alloc: A) -> Self {
+        let mut buf = Self::new_in(alloc);
+        if mem::size_of::<T>() != 0 {
+            buf.ptr = unsafe { NonNull::new_unchecked(ptr.cast()) };
+            buf.cap = cap;
+        }
+        buf
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Buffer<T, A, G> {
+    /// Create a new RawVec with zero capacity that allocates from `alloc`
+    /// and grows by `growth`.
+    pub fn with_growth_in(growth: G, alloc: A) -> Self {
         let cap = if mem::size_of::<T>() == 0 {
             usize::MAX
         } else {
@@ -27,56 +81,106 @@ impl<T> Buffer<T> {
         Self {
             ptr: NonNull::dangling(),
             cap,
+            alloc,
+            growth,
+            stats: VectorStats::default(),
             _marker: marker::PhantomData,
         }
     }
 
-    /// Allocates a new buffer if the capacity is zero, otherwise it doubles
-    /// the size of the buffer and reallocates it.
-    fn grow(&mut self) {
-        // We shouldn't get to this point if `T` is zero sized.
-        assert!(mem::size_of::<T>() != 0, "Capacity overflow");
+    pub(crate) fn allocator(&self) -> &A {
+        &self.alloc
+    }
 
-        let (new_cap, new_layout, new_ptr) = if self.cap == 0 {
-            let new_layout = alloc::Layout::array::<T>(1).unwrap();
-            let new_ptr = unsafe { alloc::alloc(new_layout) };
+    /// Pointer to the first slot. Every pointer into the buffer has to be
+    /// derived from this one, so it keeps the allocation's provenance.
+    pub(crate) fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr().cast()
+    }
 
-            (1, new_layout, new_ptr)
-        } else {
-            let new_cap = self.cap * 2;
-            let new_layout = alloc::Layout::array::<T>(new_cap).unwrap();
+    /// Reallocates the buffer to hold at least one more value, to the
+    /// capacity the growth strategy picks.
+    pub(crate) fn grow(&mut self) {
+        error::handle_reserve(self.try_grow());
+    }
 
-            assert!(
-                new_layout.size() <= isize::MAX as usize,
-                "Allocation too large"
-            );
+    /// [`Buffer::grow`] that reports failures instead of panicking.
+    pub(crate) fn try_grow(&mut self) -> Result<(), TryReserveError> {
+        // Zero sized types start at `usize::MAX`, so they can't get here
+        // without overflowing.
+        let needed = self.cap.checked_add(1).ok_or(TryReserveError::CapacityOverflow)?;
+        self.try_grow_for(needed)
+    }
 
-            let new_ptr = unsafe {
-                alloc::realloc(
-                    self.ptr.as_ptr() as *mut u8,
-                    alloc::Layout::array::<T>(self.cap).unwrap(),
-                    new_layout.size(),
-                )
-            };
+    /// Reallocates the buffer to hold at least `needed` values, to the
+    /// capacity the growth strategy picks.
+    pub(crate) fn try_grow_for(&mut self, needed: usize) -> Result<(), TryReserveError> {
+        let new_cap = self.growth.grow(self.cap, needed, mem::size_of::<T>());
+        self.try_grow_to(new_cap.max(needed))
+    }
 
-            (new_cap, new_layout, new_ptr)
-        };
+    /// Reallocates the buffer to hold `new_cap` values, if it's larger than
+    /// the current capacity.
+    pub(crate) fn try_grow_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        if new_cap <= self.cap {
+            return Ok(());
+        }
+
+        // Also rejects sizes over `isize::MAX` bytes.
+        let new_layout =
+            alloc::Layout::array::<T>(new_cap).map_err(|_| TryReserveError::CapacityOverflow)?;
+
+        let new_ptr = if self.cap == 0 {
+            self.alloc.allocate(new_layout)
+        } else {
+            let old_layout = alloc::Layout::array::<T>(self.cap).unwrap();
+            let new_ptr = unsafe { self.alloc.grow(self.ptr.cast(), old_layout, new_layout) };
 
-        self.ptr = match ptr::NonNull::new(new_ptr as *mut T) {
-            Some(ptr) => ptr,
-            None => alloc::handle_alloc_error(new_layout),
+            if let Ok(new_ptr) = new_ptr {
+                let moved = new_ptr != self.ptr.cast();
+                self.stats.record_reallocation(moved, old_layout.size());
+            }
+            new_ptr
         };
 
+        self.ptr = new_ptr
+            .map_err(|_| TryReserveError::AllocError { layout: new_layout })?
+            .cast();
+        self.cap = new_cap;
+
+        Ok(())
+    }
+
+    /// Reallocates the buffer to hold `new_cap` values, if it's smaller than
+    /// the current capacity. A capacity of zero frees it.
+    pub(crate) fn shrink_to(&mut self, new_cap: usize) {
+        if new_cap >= self.cap || mem::size_of::<T>() == 0 {
+            return;
+        }
+
+        let old_layout = alloc::Layout::array::<T>(self.cap).unwrap();
+        if new_cap == 0 {
+            unsafe { self.alloc.deallocate(self.ptr.cast(), old_layout) };
+            self.ptr = NonNull::dangling();
+            self.cap = 0;
+            return;
+        }
+
+        let new_layout = alloc::Layout::array::<T>(new_cap).unwrap();
+        match unsafe { self.alloc.shrink(self.ptr.cast(), old_layout, new_layout) } {
+            Ok(new_ptr) => self.ptr = new_ptr.cast(),
+            Err(_) => std::alloc::handle_alloc_error(new_layout),
+        }
         self.cap = new_cap;
     }
 }
 
-impl<T> Drop for Buffer<T> {
+impl<T, A: Allocator, G: GrowthStrategy> Drop for Buffer<T, A, G> {
     fn drop(&mut self) {
         if self.cap != 0 && mem::size_of::<T>() != 0 {
             unsafe {
-                alloc::dealloc(
-                    self.ptr.as_ptr() as *mut u8,
+                self.alloc.deallocate(
+                    self.ptr.cast(),
                     alloc::Layout::array::<T>(self.cap).unwrap(),
                 );
             }
@@ -84,35 +188,129 @@ impl<T> Drop for Buffer<T> {
     }
 }
 /// List data structure stored as an array.
-pub struct Vector<T> {
-    buf: Buffer<T>,
+pub struct Vector<T, A: Allocator = Global, G: GrowthStrategy = Doubling> {
+    buf: Buffer<T, A, G>,
     len: usize,
 }
 
 impl<T> Vector<T> {
+    /// Creates and returns a new `Vec` with zero length.
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Shrinks the buffer to fit and hands it over to a `Box`, without
+    /// moving the values.
+    pub fn into_boxed_slice(mut self) -> Box<[T]> {
+        self.shrink_to_fit();
+
+        let vec = mem::ManuallyDrop::new(self);
+        unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(vec.ptr(), vec.len)) }
+    }
+
+    /// Takes over a buffer from the global allocator, like the parts of
+    /// [`Vector::into_raw_parts`] or of a `Vec`, without copying it.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` of the `cap` slots at `ptr` hold initialized values.
+    /// `ptr` is non-null and aligned, and unless `cap` is zero or `T` is zero
+    /// sized it was allocated by the global allocator with the layout of
+    /// `[T; cap]`. Nothing else uses or frees the buffer afterwards.
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize, cap: usize) -> Self {
+        debug_assert!(len <= cap || mem::size_of::<T>() == 0);
+        Self {
+            buf: unsafe { Buffer::from_raw_parts_in(ptr, cap, Global) },
+            len,
+        }
+    }
+
+    /// Hands over the buffer as its pointer, length and capacity, without
+    /// dropping the values. Freeing them is up to the caller, for instance
+    /// with [`Vector::from_raw_parts`].
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        let vec = mem::ManuallyDrop::new(self);
+        (vec.ptr(), vec.len, vec.capacity())
+    }
+}
+
+impl<T, G: GrowthStrategy> Vector<T, Global, G> {
+    /// Creates a new `Vec` with zero length that grows by `growth`.
+    pub fn with_growth(growth: G) -> Self {
+        Self::with_growth_in(growth, Global)
+    }
+}
+
+impl<T, A: Allocator> Vector<T, A> {
+    /// Creates a new `Vec` with zero length that allocates from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_growth_in(Doubling, alloc)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Vector<T, A, G> {
+    /// Creates a new `Vec` with zero length that grows by `growth` and
+    /// allocates from `alloc`.
+    pub fn with_growth_in(growth: G, alloc: A) -> Self {
+        Self {
+            buf: Buffer::with_growth_in(growth, alloc),
+            len: 0,
+        }
+    }
+
+    /// Returns the allocator the `Vec` allocates from.
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
     fn ptr(&self) -> *mut T {
-        self.buf.ptr.as_ptr()
+        self.buf.as_ptr()
     }
 
-    /// Returns the capacity of the buffer.
-    fn cap(&self) -> usize {
+    /// Returns the number of values the buffer holds without reallocating.
+    pub fn capacity(&self) -> usize {
         self.buf.cap
     }
 
+    /// The slots past the length, to be filled in place and then made part
+    /// of the vector with [`set_len`](Vector::set_len).
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.ptr().add(self.len).cast::<MaybeUninit<T>>(),
+                self.buf.cap - self.len,
+            )
+        }
+    }
+
+    /// Sets the length without touching the values.
+    ///
+    /// # Safety
+    ///
+    /// `len` is at most the capacity, and the first `len` slots hold
+    /// initialized values. Values past `len` that were part of the vector
+    /// are forgotten, not dropped.
+    pub unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity());
+        self.len = len;
+    }
+
+    /// Counters of the reallocations so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> VectorStats {
+        self.buf.stats
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
 
-    /// Creates and returns a new `Vec` with zero length.
-    pub fn new() -> Self {
-        Self {
-            buf: Buffer::new(),
-            len: 0,
-        }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn push(&mut self, value: T) {
-        if self.len == self.cap() {
+        if self.len == self.capacity() {
             self.buf.grow();
         }
 
@@ -123,6 +321,62 @@ impl<T> Vector<T> {
         self.len += 1;
     }
 
+    /// Like [`Vector::push`], but gives `value` back if the buffer can't
+    /// grow.
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.len == self.capacity() && self.buf.try_grow().is_err() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.push(value);
+        Ok(())
+    }
+
+    /// Pushes `value` only if there's a free slot, never allocating. Gives
+    /// `value` back when the buffer is full, like `Vec::push_within_capacity`.
+    pub fn push_within_capacity(&mut self, value: T) -> Result<(), T> {
+        if self.len == self.capacity() {
+            return Err(value);
+        }
+
+        self.push(value);
+        Ok(())
+    }
+
+    /// Makes room for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        error::handle_reserve(self.try_reserve(additional));
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
+        if needed <= self.capacity() {
+            return Ok(());
+        }
+
+        // Grown like a push would, so repeated reserves stay amortized O(1).
+        self.buf.try_grow_for(needed)
+    }
+
+    /// Frees the capacity past the length.
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to(self.len);
+    }
+
+    /// Hands the values over for the rest of the program, or as long as the
+    /// allocator lives. The buffer is never freed, nor the values dropped.
+    pub fn leak<'a>(self) -> &'a mut [T]
+    where
+        A: 'a,
+    {
+        let vec = mem::ManuallyDrop::new(self);
+        unsafe { std::slice::from_raw_parts_mut(vec.ptr(), vec.len) }
+    }
+
     pub fn pop(&mut self) -> Option<T> {
         if self.len == 0 {
             None
@@ -135,7 +389,7 @@ impl<T> Vector<T> {
     pub fn insert(&mut self, index: usize, value: T) {
         assert!(index <= self.len, "Index out of bounds");
 
-        if self.cap() == self.len {
+        if self.capacity() == self.len {
             self.buf.grow();
         }
 
@@ -152,10 +406,100 @@ impl<T> Vector<T> {
         }
     }
 
+    /// Appends copies of `values`, reserving once and copying them all at
+    /// once instead of one by one.
+    pub fn extend_from_copy_slice(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        self.reserve(values.len());
+
+        unsafe {
+            ptr::copy_nonoverlapping(values.as_ptr(), self.ptr().add(self.len), values.len());
+        }
+        self.len += values.len();
+    }
+
+    /// Inserts clones of `values` at `index`, moving the values after it
+    /// only once.
+    pub fn insert_slice(&mut self, index: usize, values: &[T])
+    where
+        T: Clone,
+    {
+        let mut values = values.iter();
+        self.insert_with(index, values.len(), || values.next().unwrap().clone());
+    }
+
+    /// Inserts the values of `iter` at `index`, moving the values after it
+    /// only once. They're collected first, the iterator's length isn't
+    /// known up front.
+    pub fn insert_from_iter<I: IntoIterator<Item = T>>(&mut self, index: usize, iter: I) {
+        assert!(index <= self.len, "Index out of bounds");
+
+        let mut values = iter.into_iter().collect::<Vector<T>>().into_iter();
+        self.insert_with(index, values.len(), || values.next().unwrap());
+    }
+
+    /// Moves the values from `index` on up by `count` and fills the gap with
+    /// `fill`. If `fill` panics the values written so far stay and the rest
+    /// are moved back down.
+    fn insert_with<F: FnMut() -> T>(&mut self, index: usize, count: usize, mut fill: F) {
+        assert!(index <= self.len, "Index out of bounds");
+        self.reserve(count);
+
+        let ptr = self.ptr();
+        let tail_len = self.len - index;
+        unsafe { ptr::copy(ptr.add(index), ptr.add(index + count), tail_len) };
+
+        let mut written = Guard::new(0, |&mut written| unsafe {
+            if written != count {
+                ptr::copy(ptr.add(index + count), ptr.add(index + written), tail_len);
+            }
+            self.len = index + written + tail_len;
+        });
+
+        while *written < count {
+            unsafe { ptr::write(ptr.add(index + *written), fill()) };
+            *written += 1;
+        }
+    }
+
+    /// Moves all the values of `other` to the end with one copy. `other` is
+    /// left empty but keeps its buffer.
+    pub fn append(&mut self, other: &mut Self) {
+        let count = other.len;
+        self.reserve(count);
+
+        unsafe {
+            ptr::copy_nonoverlapping(other.ptr(), self.ptr().add(self.len), count);
+        }
+        other.len = 0;
+        self.len += count;
+    }
+
+    /// Like [`Vector::insert`], but gives `value` back if the buffer can't
+    /// grow.
+    ///
+    /// # Panics
+    ///
+    /// If `index > len`, just like [`Vector::insert`].
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), CapacityError<T>> {
+        assert!(index <= self.len, "Index out of bounds");
+
+        if self.len == self.capacity() && self.buf.try_grow().is_err() {
+            return Err(CapacityError::new(value));
+        }
+
+        self.insert(index, value);
+        Ok(())
+    }
+
     pub fn remove(&mut self, index: usize) -> T {
         assert!(index < self.len, "Index out of bounds");
 
         unsafe {
+            self.len -= 1;
+
             let value = ptr::read(self.ptr().add(index));
 
             ptr::copy(
@@ -168,185 +512,1259 @@ impl<T> Vector<T> {
         }
     }
 
-    pub fn drain(&mut self) -> Drain<T> {
-        let iter = RawIter::new(&self);
+    /// Like [`Vector::remove`], but returns an error for an out of bounds
+    /// `index` instead of panicking.
+    pub fn try_remove(&mut self, index: usize) -> Result<T, IndexError> {
+        if index >= self.len {
+            return Err(IndexError { index, len: self.len });
+        }
+
+        Ok(self.remove(index))
+    }
+
+    /// Removes the element at `index` and moves the last element into its
+    /// place, O(1) but doesn't preserve the order.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Index out of bounds");
+
+        let last = self.len - 1;
+        self.swap(index, last);
+        self.pop().unwrap()
+    }
+
+    /// Mutable references to the values at every index at once. Returns
+    /// `None` if an index is out of bounds or appears twice.
+    pub fn get_many_mut<const N: usize>(&mut self, indices: [usize; N]) -> Option<[&mut T; N]> {
+        for (i, &index) in indices.iter().enumerate() {
+            if index >= self.len || indices[..i].contains(&index) {
+                return None;
+            }
+        }
+
+        // In bounds and disjoint, so the references don't alias.
+        let ptr = self.ptr();
+        Some(indices.map(|index| unsafe { &mut *ptr.add(index) }))
+    }
+
+    /// Drops every value, keeping the capacity. If a drop panics the others
+    /// are still dropped and the vector is left empty.
+    pub fn clear(&mut self) {
+        let len = mem::replace(&mut self.len, 0);
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr(), len)) };
+    }
+
+    /// Removes and yields the values in `range`. Once the iterator is
+    /// dropped the values it didn't yield are dropped and the ones after
+    /// `range` moved down. Panics if `range` is out of bounds.
+    ///
+    /// Leaking the iterator leaks the values from `range.start` on, the
+    /// vector is left with the ones before.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A, G> {
+        let Range { start, end } = error::check_range(range, self.len);
+        let tail_len = self.len - end;
 
-        self.len = 0;
+        self.len = start;
+        let iter = unsafe { RawIter::new(self.ptr().add(start), end - start) };
 
         Drain {
+            vec: self,
             iter,
-            vec: marker::PhantomData,
+            tail_start: end,
+            tail_len,
         }
     }
-}
 
-impl<T> Drop for Vector<T> {
-    fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+    /// Removes and yields the first `n` values, the rest are moved to the
+    /// front once when the iterator is dropped. Panics if `n > len`.
+    pub fn drain_front(&mut self, n: usize) -> Drain<'_, T, A, G> {
+        self.drain(..n)
     }
-}
 
-impl<T> Deref for Vector<T> {
-    type Target = [T];
-    fn deref(&self) -> &Self::Target {
-        unsafe { std::slice::from_raw_parts(self.ptr(), self.len) }
+    /// Drops the first `n` values, or all of them if there are fewer, and
+    /// moves the rest to the front at once.
+    pub fn truncate_front(&mut self, n: usize) {
+        self.drain(..n.min(self.len));
     }
-}
 
-impl<T> DerefMut for Vector<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { std::slice::from_raw_parts_mut(self.ptr(), self.len) }
+    /// Keeps only the values for which `keep` returns `true`, in order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        self.retain_mut(|value| keep(value));
     }
-}
 
-impl<T> IntoIterator for Vector<T> {
-    type Item = T;
-    type IntoIter = IntoIter<T>;
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut keep: F) {
+        self.extract_if(|value| !keep(value)).for_each(drop);
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        unsafe {
-            let iter = RawIter::new(&self);
+    /// Removes consecutive equal values, keeping the first of each run.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
 
-            let buf = ptr::read(&self.buf);
+    /// Removes consecutive values with the same key.
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
 
-            mem::forget(self);
+    /// Removes the values for which `same(value, kept)` returns `true`,
+    /// `kept` being the last value kept before it, in one pass.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same: F) {
+        let len = self.len;
+        if len <= 1 {
+            return;
+        }
+        let ptr = self.ptr();
+
+        // Values are read from `read` on and the kept ones moved down to
+        // `write`. If `same` or a drop panics, the unread ones are moved
+        // down too.
+        let mut cursors = Guard::new((1, 1), |&mut (read, write)| unsafe {
+            ptr::copy(ptr.add(read), ptr.add(write), len - read);
+            self.len = write + len - read;
+        });
+
+        while cursors.0 < len {
+            let (read, write) = *cursors;
+            unsafe {
+                let value = &mut *ptr.add(read);
+                if same(value, &mut *ptr.add(write - 1)) {
+                    cursors.0 += 1;
+                    ptr::drop_in_place(value);
+                } else {
+                    ptr::copy(value, ptr.add(write), 1);
+                    *cursors = (read + 1, write + 1);
+                }
+            }
+        }
+    }
 
-            IntoIter { iter, _buf: buf }
+    /// Mutable windows of `size` values, from the start of the vector to its
+    /// end one value at a time. They overlap, so they're lent one at a time:
+    ///
+    /// ```
+    /// let mut v = ds_rust::Vector::from([1, 2, 3, 4]);
+    /// let mut windows = v.windows_mut(2);
+    /// while let Some([prev, next]) = windows.next() {
+    ///     *next += *prev;
+    /// }
+    /// assert_eq!(*v, [1, 3, 6, 10]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    pub fn windows_mut(&mut self, size: usize) -> WindowsMut<'_, T> {
+        assert!(size != 0, "Window size of zero");
+        WindowsMut {
+            slice: self,
+            size,
+            start: 0,
         }
     }
-}
 
-struct RawIter<T> {
-    start: *const T,
-    end: *const T,
-}
+    /// Removes the values equal to an earlier one, sorted or not, keeping the
+    /// first of each. Each value is hashed once.
+    pub fn unique(&mut self)
+    where
+        T: Hash + Eq,
+    {
+        // The set borrows the values, so it marks the ones to keep first.
+        let mut seen = HashSet::with_capacity(self.len);
+        let keep: Vector<bool> = self.iter().map(|value| seen.insert(value)).collect();
+        drop(seen);
+
+        let mut keep = keep.into_iter();
+        self.retain(|_| keep.next().unwrap());
+    }
 
-impl<T> RawIter<T> {
-    fn new(slice: &[T]) -> Self {
-        RawIter {
-            start: slice.as_ptr(),
-            end: if mem::size_of::<T>() == 0 {
-                (slice.as_ptr() as usize + slice.len()) as *const _
-            } else if slice.len() == 0 {
-                slice.as_ptr()
-            } else {
-                unsafe { slice.as_ptr().add(slice.len()) }
-            },
+    /// Removes the values whose key equals an earlier value's, keeping the
+    /// first of each, in one pass.
+    pub fn unique_by_key<K: Hash + Eq, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        let mut seen = HashSet::new();
+        self.retain(|value| seen.insert(key(value)));
+    }
+
+    /// Removes and yields the values for which `pred` returns `true`, as the
+    /// iterator is advanced. See [`extract`](crate::extract) for what happens
+    /// when it's dropped early or something panics.
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> ExtractIf<ExtractSource<'_, T, A, G>, F> {
+        // Until the source is finished the values past the kept ones are
+        // either moved out or moved down, so leaking it must not let `vec`
+        // see them.
+        let old_len = mem::replace(&mut self.len, 0);
+
+        let source = ExtractSource {
+            vec: self,
+            index: 0,
+            removed: 0,
+            old_len,
+        };
+        ExtractIf::new(source, pred)
+    }
+
+    /// Sorts the values with one of the [`sort`](crate::sort) algorithms.
+    pub fn sort_with(&mut self, algorithm: Algorithm)
+    where
+        T: Ord,
+    {
+        algorithm.sort_by(self, T::cmp);
+    }
+
+    pub fn sort_with_by<F>(&mut self, algorithm: Algorithm, compare: F)
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering,
+    {
+        algorithm.sort_by(self, compare);
+    }
+
+    /// Merges two sorted vectors into one that's sorted, in `self`'s buffer.
+    /// Equal values from `self` come first.
+    ///
+    /// Runs are found by galloping, so merging a few values into a long
+    /// vector takes O(log n) comparisons per value.
+    pub fn merge_sorted(self, other: Self) -> Self
+    where
+        T: Ord,
+    {
+        self.merge_sorted_by(other, T::cmp)
+    }
+
+    /// [`merge_sorted`](Vector::merge_sorted) for vectors sorted by
+    /// `compare`.
+    pub fn merge_sorted_by<F>(mut self, mut other: Self, mut compare: F) -> Self
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering,
+    {
+        let (a_len, b_len) = (self.len, other.len);
+        let total = a_len + b_len;
+        self.reserve(b_len);
+
+        // `self`'s values go to the end of the buffer and the merge fills it
+        // from the start. Both buffers own their values through the cursors.
+        let dst = self.ptr();
+        let src = other.ptr();
+        unsafe { ptr::copy(dst, dst.add(b_len), a_len) };
+        other.len = 0;
+
+        // Next value of `self`, next of `other`, next slot. The slot never
+        // catches up with the first, and whatever is left is moved down
+        // even if `compare` panics.
+        let mut cursors = Guard::new((b_len, 0, 0), |&mut (a, b, slot)| unsafe {
+            ptr::copy(dst.add(a), dst.add(slot), total - a);
+            ptr::copy_nonoverlapping(src.add(b), dst.add(slot + total - a), b_len - b);
+            self.len = total;
+        });
+
+        while cursors.0 < total && cursors.1 < b_len {
+            let (a, b, slot) = *cursors;
+            let next_a = unsafe { &*dst.add(a) };
+            let run = unsafe { std::slice::from_raw_parts(src.add(b), b_len - b) };
+            let taken = sort::gallop(run, |value| compare(value, next_a).is_lt());
+            unsafe { ptr::copy_nonoverlapping(src.add(b), dst.add(slot), taken) };
+            *cursors = (a, b + taken, slot + taken);
+
+            let (a, b, slot) = *cursors;
+            if b == b_len {
+                break;
+            }
+            let next_b = unsafe { &*src.add(b) };
+            let run = unsafe { std::slice::from_raw_parts(dst.add(a), total - a) };
+            let taken = sort::gallop(run, |value| compare(next_b, value).is_ge());
+            unsafe { ptr::copy(dst.add(a), dst.add(slot), taken) };
+            *cursors = (a + taken, b, slot + taken);
         }
+
+        drop(cursors);
+        self
     }
-}
 
-impl<T> Iterator for RawIter<T> {
-    type Item = T;
+    /// Inserts `value` into a sorted vector, after the values equal to it,
+    /// and returns its index. One binary search and one shift.
+    pub fn insert_sorted(&mut self, value: T) -> usize
+    where
+        T: Ord,
+    {
+        self.insert_sorted_by(value, T::cmp)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.start == self.end {
-            return None;
+    /// [`insert_sorted`](Vector::insert_sorted) for a vector sorted by
+    /// `compare`.
+    pub fn insert_sorted_by<F>(&mut self, value: T, mut compare: F) -> usize
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering,
+    {
+        let index = self.partition_point(|x| compare(x, &value) != std::cmp::Ordering::Greater);
+        self.insert(index, value);
+        index
+    }
+
+    /// Sorts the values with [`sort::bottom_up_merge_sort`], keeping equal
+    /// values in order.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        sort::bottom_up_merge_sort(self);
+    }
+
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering,
+    {
+        sort::bottom_up_merge_sort_by(self, compare);
+    }
+
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        sort::bottom_up_merge_sort_by(self, |a, b| key(a).cmp(&key(b)));
+    }
+
+    /// Stable sort calling `key` once per value, for keys that are costly
+    /// to compute. The keys are collected into a scratch vector with the
+    /// index of their value, sorted, and the values then swapped into
+    /// place.
+    pub fn sort_by_cached_key<K: Ord, F: FnMut(&T) -> K>(&mut self, key: F) {
+        if self.len <= 1 {
+            return;
         }
 
-        unsafe {
-            if mem::size_of::<T>() == 0 {
-                self.start = (self.start as usize + 1) as *const _;
-                Some(ptr::read(ptr::NonNull::<T>::dangling().as_ptr()))
-            } else {
-                let old_ptr = self.start;
-                self.start = self.start.offset(1);
-                Some(ptr::read(old_ptr))
+        // The indices are distinct, so an unstable sort keeps ties in order.
+        let mut keys: Vector<(K, usize)> =
+            self.iter().map(key).enumerate().map(|(i, key)| (key, i)).collect();
+        keys.sort_unstable();
+
+        // `keys[i].1` is where the value that belongs at `i` was, unless it
+        // was swapped away earlier, in which case that slot tells where it
+        // went.
+        for i in 0..self.len {
+            let mut index = keys[i].1;
+            while index < i {
+                index = keys[index].1;
             }
+            keys[i].1 = index;
+            self.swap(i, index);
         }
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let type_size = mem::size_of::<T>();
+    /// Sorts the values in place with [`sort::intro_sort`], equal values
+    /// may end up in any order.
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        sort::intro_sort(self);
+    }
 
-        let mut len = self.end as usize - self.start as usize;
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering,
+    {
+        sort::intro_sort_by(self, compare);
+    }
 
-        if type_size != 0 {
-            len /= type_size;
-        }
+    pub fn sort_unstable_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        sort::intro_sort_by(self, |a, b| key(a).cmp(&key(b)));
+    }
 
-        (len, Some(len))
+    /// Turns the vector into a read-only [`Frozen`] snapshot that can be
+    /// cloned and shared between threads without copying the values.
+    pub fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self)
     }
 }
 
-impl<T> DoubleEndedIterator for RawIter<T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
-        if self.start == self.end {
-            return None;
+impl<T, A: Allocator, G: GrowthStrategy> Drop for Vector<T, A, G> {
+    fn drop(&mut self) {
+        // The buffer is freed afterwards, even if a drop panics.
+        self.clear();
+    }
+}
+
+impl<T> Default for Vector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone, G: GrowthStrategy + Clone> Clone for Vector<T, A, G> {
+    fn clone(&self) -> Self {
+        let mut vec = Self::with_growth_in(self.buf.growth.clone(), self.allocator().clone());
+        vec.reserve(self.len);
+
+        for value in self.iter() {
+            vec.push(value.clone());
         }
 
-        unsafe {
-            if mem::size_of::<T>() == 0 {
-                self.end = (self.end as usize - 1) as *const _;
-                Some(ptr::read(ptr::NonNull::<T>::dangling().as_ptr()))
-            } else {
-                self.end = self.end.offset(-1);
-                Some(ptr::read(self.end))
-            }
+        vec
+    }
+
+    /// Clones into the existing buffer, which only grows if `source` is
+    /// longer than its capacity. The values both have in common are cloned
+    /// with `clone_from` as well, so they may reuse their own allocations.
+    fn clone_from(&mut self, source: &Self) {
+        while self.len > source.len {
+            self.pop();
         }
+
+        let (common, rest) = source.split_at(self.len);
+        self.clone_from_slice(common);
+        self.reserve(rest.len());
+        self.extend(rest.iter().cloned());
     }
 }
 
-pub struct IntoIter<T> {
-    _buf: Buffer<T>,
-    iter: RawIter<T>,
+impl<T: std::fmt::Debug, A: Allocator, G: GrowthStrategy> std::fmt::Debug for Vector<T, A, G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
 }
 
-impl<T> Iterator for IntoIter<T> {
-    type Item = T;
+impl<T, U, A, B, G, H> PartialEq<Vector<U, B, H>> for Vector<T, A, G>
+where
+    T: PartialEq<U>,
+    A: Allocator,
+    B: Allocator,
+    G: GrowthStrategy,
+    H: GrowthStrategy,
+{
+    fn eq(&self, other: &Vector<U, B, H>) -> bool {
+        **self == **other
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
+impl<T: PartialEq<U>, U, A: Allocator, G: GrowthStrategy> PartialEq<[U]> for Vector<T, A, G> {
+    fn eq(&self, other: &[U]) -> bool {
+        **self == *other
     }
+}
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
+impl<T: PartialEq<U>, U, A: Allocator, G: GrowthStrategy> PartialEq<&[U]> for Vector<T, A, G> {
+    fn eq(&self, other: &&[U]) -> bool {
+        **self == **other
     }
 }
-impl<T> DoubleEndedIterator for IntoIter<T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter.next_back()
+
+impl<T: PartialEq<U>, U, A: Allocator, G: GrowthStrategy, const N: usize> PartialEq<[U; N]>
+    for Vector<T, A, G>
+{
+    fn eq(&self, other: &[U; N]) -> bool {
+        **self == *other
     }
 }
 
-impl<T> Drop for IntoIter<T> {
-    fn drop(&mut self) {
-        for _ in &mut *self {}
+impl<T: PartialEq<U>, U, A: Allocator, G: GrowthStrategy, const N: usize> PartialEq<&[U; N]>
+    for Vector<T, A, G>
+{
+    fn eq(&self, other: &&[U; N]) -> bool {
+        **self == **other
     }
 }
 
-pub struct Drain<'a, T: 'a> {
-    vec: marker::PhantomData<&'a mut Vector<T>>,
-    iter: RawIter<T>,
+impl<T: Eq, A: Allocator, G: GrowthStrategy> Eq for Vector<T, A, G> {}
+
+impl<T: PartialOrd, A: Allocator, G: GrowthStrategy> PartialOrd for Vector<T, A, G> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
 }
 
-impl<'a, T> Iterator for Drain<'a, T> {
-    type Item = T;
+impl<T: Ord, A: Allocator, G: GrowthStrategy> Ord for Vector<T, A, G> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
+// Hashes like the slice, as `Borrow<[T]>` requires, so a map keyed by
+// vectors can be queried with slices.
+impl<T: Hash, A: Allocator, G: GrowthStrategy> Hash for Vector<T, A, G> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
     }
+}
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
+impl<T, A: Allocator, G: GrowthStrategy> Deref for Vector<T, A, G> {
+    type Target = [T];
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr(), self.len) }
     }
 }
 
-impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
-    fn next_back(&mut self) -> Option<T> {
-        self.iter.next_back()
+impl<T, A: Allocator, G: GrowthStrategy> DerefMut for Vector<T, A, G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr(), self.len) }
     }
 }
 
-impl<'a, T> Drop for Drain<'a, T> {
-    fn drop(&mut self) {
-        for _ in &mut *self {}
+impl<T, A: Allocator, G: GrowthStrategy> AsRef<[T]> for Vector<T, A, G> {
+    fn as_ref(&self) -> &[T] {
+        self
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Vector;
+impl<T, A: Allocator, G: GrowthStrategy> AsMut<[T]> for Vector<T, A, G> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Borrow<[T]> for Vector<T, A, G> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> BorrowMut<[T]> for Vector<T, A, G> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Extend<T> for Vector<T, A, G> {
+    /// Reserves room for the values the iterator promises whenever the
+    /// buffer is full, so an exact size iterator allocates once.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+        while let Some(value) = iter.next() {
+            if self.len == self.capacity() {
+                let (lower, _) = iter.size_hint();
+                self.reserve(lower.saturating_add(1));
+            }
+
+            unsafe { ptr::write(self.ptr().add(self.len), value) };
+            self.len += 1;
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T> {
+    /// Moves the array into a buffer of exactly `N` slots.
+    fn from(array: [T; N]) -> Self {
+        let mut vec = Self::new();
+        vec.reserve(N);
+
+        unsafe {
+            ptr::write(vec.ptr().cast::<[T; N]>(), array);
+        }
+        vec.len = N;
+
+        vec
+    }
+}
+
+impl<T: Clone> From<&[T]> for Vector<T> {
+    fn from(slice: &[T]) -> Self {
+        let mut vec = Self::new();
+        vec.reserve(slice.len());
+        vec.extend(slice.iter().cloned());
+
+        vec
+    }
+}
+
+/// Takes over the `Vec`'s buffer, the values aren't moved.
+impl<T> From<Vec<T>> for Vector<T> {
+    fn from(vec: Vec<T>) -> Self {
+        let mut vec = mem::ManuallyDrop::new(vec);
+        unsafe { Vector::from_raw_parts(vec.as_mut_ptr(), vec.len(), vec.capacity()) }
+    }
+}
+
+/// Hands the buffer over to a `Vec`, the values aren't moved.
+impl<T> From<Vector<T>> for Vec<T> {
+    fn from(vec: Vector<T>) -> Self {
+        let (ptr, len, cap) = vec.into_raw_parts();
+        unsafe { Vec::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> IntoIterator for Vector<T, A, G> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A, G>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        unsafe {
+            let iter = RawIter::new(self.ptr(), self.len);
+
+            let buf = ptr::read(&self.buf);
+
+            mem::forget(self);
+
+            IntoIter { iter, buf }
+        }
+    }
+}
+
+/// Moves values out of a range of slots, front to back or back to front.
+///
+/// It counts the values left instead of comparing pointers, so zero sized
+/// types need no special casing: offsetting their pointer is a no-op and
+/// reading from a dangling, aligned pointer is fine.
+struct RawIter<T> {
+    ptr: NonNull<T>,
+    len: usize,
+}
+
+// Yields owned values, like `vec::IntoIter`.
+unsafe impl<T: Send> Send for RawIter<T> {}
+unsafe impl<T: Sync> Sync for RawIter<T> {}
+
+impl<T> RawIter<T> {
+    /// # Safety
+    ///
+    /// `ptr` has to point to `len` initialized values that the iterator can
+    /// move out, and stay valid while it's alive.
+    unsafe fn new(ptr: *mut T, len: usize) -> Self {
+        RawIter {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            len,
+        }
+    }
+
+    fn empty() -> Self {
+        RawIter {
+            ptr: NonNull::dangling(),
+            len: 0,
+        }
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Iterator for RawIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        unsafe {
+            let value = self.ptr.read();
+            self.ptr = self.ptr.add(1);
+            self.len -= 1;
+
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for RawIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        unsafe { Some(self.ptr.add(self.len).read()) }
+    }
+}
+
+pub struct IntoIter<T, A: Allocator = Global, G: GrowthStrategy = Doubling> {
+    buf: Buffer<T, A, G>,
+    iter: RawIter<T>,
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Iterator for IntoIter<T, A, G> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T, A: Allocator, G: GrowthStrategy> DoubleEndedIterator for IntoIter<T, A, G> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> ExactSizeIterator for IntoIter<T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthStrategy> FusedIterator for IntoIter<T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthStrategy> IntoIter<T, A, G> {
+    /// The values not yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.iter.as_mut_slice()
+    }
+
+    /// Gives back a vector of the values not yielded yet, in the original
+    /// buffer. They're moved to its start if some were yielded from the
+    /// front.
+    pub fn into_vector(self) -> Vector<T, A, G> {
+        let this = mem::ManuallyDrop::new(self);
+        let (ptr, len) = (this.iter.ptr.as_ptr(), this.iter.len);
+        let start = this.buf.as_ptr();
+
+        unsafe {
+            if ptr != start {
+                ptr::copy(ptr, start, len);
+            }
+            Vector {
+                buf: ptr::read(&this.buf),
+                len,
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Drop for IntoIter<T, A, G> {
+    fn drop(&mut self) {
+        // `drop_in_place` keeps dropping the others if one of them panics.
+        let rest = mem::replace(&mut self.iter, RawIter::empty());
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(rest.ptr.as_ptr(), rest.len));
+        }
+    }
+}
+
+/// Lending iterator of [`Vector::windows_mut`]. Each window borrows the
+/// iterator, so it has to be dropped before asking for the next one.
+pub struct WindowsMut<'a, T> {
+    slice: &'a mut [T],
+    size: usize,
+    /// Start of the next window.
+    start: usize,
+}
+
+impl<T> WindowsMut<'_, T> {
+    // Not `Iterator::next`, the windows it lends overlap.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&mut [T]> {
+        let start = self.start;
+        let window = self.slice.get_mut(start..start + self.size)?;
+        self.start += 1;
+        Some(window)
+    }
+
+    /// Number of windows left.
+    pub fn len(&self) -> usize {
+        (self.slice.len() + 1).saturating_sub(self.start + self.size)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// [`Source`] of [`Vector::extract_if`]. The kept values are moved down
+/// over the removed ones as it goes, and the values it didn't visit when it
+/// finishes.
+pub struct ExtractSource<'a, T, A: Allocator = Global, G: GrowthStrategy = Doubling> {
+    vec: &'a mut Vector<T, A, G>,
+    /// Next value to visit.
+    index: usize,
+    /// Values removed so far, the size of the gap before `index`.
+    removed: usize,
+    old_len: usize,
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Sealed for ExtractSource<'_, T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthStrategy> Source for ExtractSource<'_, T, A, G> {
+    type Item = T;
+
+    fn current(&mut self) -> Option<&mut T> {
+        if self.index == self.old_len {
+            return None;
+        }
+
+        unsafe { Some(&mut *self.vec.ptr().add(self.index)) }
+    }
+
+    fn keep(&mut self) {
+        unsafe {
+            let current = self.vec.ptr().add(self.index);
+            if self.removed > 0 {
+                ptr::copy_nonoverlapping(current, current.sub(self.removed), 1);
+            }
+        }
+        self.index += 1;
+    }
+
+    fn remove(&mut self) -> T {
+        let value = unsafe { ptr::read(self.vec.ptr().add(self.index)) };
+        self.index += 1;
+        self.removed += 1;
+        value
+    }
+
+    fn finish(&mut self) {
+        unsafe {
+            let ptr = self.vec.ptr();
+            ptr::copy(
+                ptr.add(self.index),
+                ptr.add(self.index - self.removed),
+                self.old_len - self.index,
+            );
+        }
+        self.vec.len = self.old_len - self.removed;
+    }
+
+    fn remaining(&self) -> usize {
+        self.old_len - self.index
+    }
+}
+
+/// Iterator of [`Vector::drain`]. The vector's length stops at the start of
+/// the range until it's dropped.
+pub struct Drain<'a, T: 'a, A: Allocator = Global, G: GrowthStrategy = Doubling> {
+    vec: &'a mut Vector<T, A, G>,
+    /// Values of the range not yielded yet.
+    iter: RawIter<T>,
+    /// Values after the range, to move down to the vector's length.
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Iterator for Drain<'_, T, A, G> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> DoubleEndedIterator for Drain<'_, T, A, G> {
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> ExactSizeIterator for Drain<'_, T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthStrategy> FusedIterator for Drain<'_, T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthStrategy> Drain<'_, T, A, G> {
+    /// The values of the range not yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.iter.as_mut_slice()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> Drop for Drain<'_, T, A, G> {
+    fn drop(&mut self) {
+        // The tail is moved down even if dropping a value panics.
+        let mut this = Guard::new(self, |drain| unsafe {
+            let vec = &mut *drain.vec;
+            let start = vec.len;
+            if drain.tail_start != start {
+                let ptr = vec.ptr();
+                ptr::copy(ptr.add(drain.tail_start), ptr.add(start), drain.tail_len);
+            }
+            vec.len = start + drain.tail_len;
+        });
+
+        // `drop_in_place` keeps dropping the others if one of them panics.
+        let rest = mem::replace(&mut this.iter, RawIter::empty());
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(rest.ptr.as_ptr(), rest.len));
+        }
+    }
+}
+
+/// Appends the bytes, so a `Vector` can collect any writer's output.
+impl<A: Allocator, G: GrowthStrategy> io::Write for Vector<u8, A, G> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.extend_from_copy_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// Writes every buffer, reserving for all of them at once.
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        self.reserve(len);
+        for buf in bufs {
+            self.extend_from_copy_slice(buf);
+        }
+        Ok(len)
+    }
+
+    /// Never fails, the whole of `buf` is written by one call.
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.extend_from_copy_slice(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T, A: Allocator, G: GrowthStrategy> MemoryUsage for Vector<T, A, G> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+    }
+
+    fn node_count(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: Allocator, G: GrowthStrategy> serde::Serialize for Vector<T, A, G> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Vector<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = crate::serde_impls::SeqVisitor::new(Vector::new(), Vector::push);
+        deserializer.deserialize_seq(visitor)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a, A: Allocator, G: GrowthStrategy> rayon::iter::IntoParallelIterator
+    for &'a Vector<T, A, G>
+{
+    type Item = &'a T;
+    type Iter = rayon::slice::Iter<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        use rayon::iter::IntoParallelRefIterator;
+
+        self[..].par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Send + 'a, A: Allocator, G: GrowthStrategy> rayon::iter::IntoParallelIterator
+    for &'a mut Vector<T, A, G>
+{
+    type Item = &'a mut T;
+    type Iter = rayon::slice::IterMut<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        use rayon::iter::IntoParallelRefMutIterator;
+
+        self[..].par_iter_mut()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator + Send, G: GrowthStrategy + Send> rayon::iter::IntoParallelIterator
+    for Vector<T, A, G>
+{
+    type Item = T;
+    type Iter = ParIntoIter<T, A, G>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIntoIter { vec: self }
+    }
+}
+
+/// Parallel iterator that moves the values out of a `Vec`, splitting the
+/// buffer in halves.
+#[cfg(feature = "rayon")]
+pub struct ParIntoIter<T, A: Allocator = Global, G: GrowthStrategy = Doubling> {
+    vec: Vector<T, A, G>,
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator + Send, G: GrowthStrategy + Send> rayon::iter::ParallelIterator
+    for ParIntoIter<T, A, G>
+{
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: rayon::iter::plumbing::UnindexedConsumer<T>,
+    {
+        rayon::iter::plumbing::bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.vec.len)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator + Send, G: GrowthStrategy + Send> rayon::iter::IndexedParallelIterator
+    for ParIntoIter<T, A, G>
+{
+    fn drive<C: rayon::iter::plumbing::Consumer<T>>(self, consumer: C) -> C::Result {
+        rayon::iter::plumbing::bridge(self, consumer)
+    }
+
+    fn len(&self) -> usize {
+        self.vec.len
+    }
+
+    fn with_producer<CB>(mut self, callback: CB) -> CB::Output
+    where
+        CB: rayon::iter::plumbing::ProducerCallback<T>,
+    {
+        unsafe {
+            // The producer owns the values now, the buffer only gets freed.
+            let len = mem::replace(&mut self.vec.len, 0);
+            let slice = std::slice::from_raw_parts_mut(self.vec.ptr(), len);
+
+            callback.callback(crate::rayon_impls::DrainProducer::new(slice))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Vector<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.try_fold(Vector::new(), |mut v, elem| {
+            v.push(elem?);
+            Ok(v)
+        })
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.try_fold(Vector::new(), |mut v, elem| {
+            v.push(elem?);
+            Ok(v)
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for Vector<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = crate::strategy::VectorStrategy<T::Strategy>;
+
+    fn arbitrary_with((size, params): Self::Parameters) -> Self::Strategy {
+        crate::strategy::vector(proptest::arbitrary::any_with::<T>(params), size)
+    }
+}
+
+/// Booleans packed 64 to a word, the `Vector<bool>` that uses a bit per
+/// value instead of a byte. See [`BitVec`](crate::BitVec).
+pub type BoolVector = crate::BitVec;
+
+/// [`Vector`] that keeps up to `N` values inline and only allocates once it
+/// outgrows them. After spilling it stays on the heap, like [`Vector`] it
+/// never gives capacity back on its own.
+pub struct SmallVector<T, const N: usize> {
+    inline: [MaybeUninit<T>; N],
+    /// Holds the values once they've spilled, `None` while they're inline.
+    heap: Option<Buffer<T>>,
+    len: usize,
+}
+
+impl<T, const N: usize> SmallVector<T, N> {
+    pub const fn new() -> Self {
+        SmallVector {
+            inline: [const { MaybeUninit::uninit() }; N],
+            heap: None,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.heap.as_ref().map_or(N, |buf| buf.cap)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` once the values moved to the heap.
+    pub fn spilled(&self) -> bool {
+        self.heap.is_some()
+    }
+
+    fn ptr(&self) -> *const T {
+        match &self.heap {
+            Some(buf) => buf.as_ptr(),
+            None => self.inline.as_ptr().cast(),
+        }
+    }
+
+    fn ptr_mut(&mut self) -> *mut T {
+        match &self.heap {
+            Some(buf) => buf.as_ptr(),
+            None => self.inline.as_mut_ptr().cast(),
+        }
+    }
+
+    /// Doubles the heap buffer, or moves the inline values to a new one of
+    /// twice the inline capacity.
+    fn grow(&mut self) {
+        if let Some(buf) = &mut self.heap {
+            buf.grow();
+            return;
+        }
+
+        let mut buf = Buffer::new();
+        error::handle_reserve(buf.try_grow_to(N.saturating_mul(2).max(1)));
+        unsafe {
+            ptr::copy_nonoverlapping(self.inline.as_ptr().cast(), buf.as_ptr(), self.len);
+        }
+        self.heap = Some(buf);
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.grow();
+        }
+
+        unsafe {
+            ptr::write(self.ptr_mut().add(self.len), value);
+        }
+
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            unsafe { Some(ptr::read(self.ptr().add(self.len))) }
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "Index out of bounds");
+
+        if self.len == self.capacity() {
+            self.grow();
+        }
+
+        unsafe {
+            let ptr = self.ptr_mut();
+            ptr::copy(ptr.add(index), ptr.add(index + 1), self.len - index);
+            ptr::write(ptr.add(index), value);
+        }
+
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Index out of bounds");
+
+        unsafe {
+            self.len -= 1;
+
+            let ptr = self.ptr_mut();
+            let value = ptr::read(ptr.add(index));
+            ptr::copy(ptr.add(index + 1), ptr.add(index), self.len - index);
+
+            value
+        }
+    }
+
+    /// Drops every value, keeping the capacity. If a drop panics the others
+    /// are still dropped and the vector is left empty.
+    pub fn clear(&mut self) {
+        let len = mem::replace(&mut self.len, 0);
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr_mut(), len)) };
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVector<T, N> {
+    fn drop(&mut self) {
+        // The heap buffer, if any, frees itself afterwards.
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for SmallVector<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVector<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: std::fmt::Debug, const N: usize> std::fmt::Debug for SmallVector<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVector<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVector<T, N> {}
+
+impl<T, const N: usize> Deref for SmallVector<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVector<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr_mut(), self.len) }
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVector<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVector<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SmallVector::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoolVector, SmallVector, Vector};
 
     #[test]
     fn test_basics() {
@@ -360,4 +1778,1065 @@ mod tests {
         assert_eq!(v.pop(), Some(3));
         assert_eq!(v.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_remove() {
+        let mut v = vector![0, 1, 2, 3, 4];
+
+        assert_eq!(v.remove(1), 1);
+        assert_eq!(*v, [0, 2, 3, 4]);
+        assert_eq!(v.swap_remove(0), 0);
+        assert_eq!(*v, [4, 2, 3]);
+        assert_eq!(v.remove(2), 3);
+        assert_eq!(*v, [4, 2]);
+    }
+
+    #[test]
+    fn test_clone() {
+        let vec = vector![String::from("a"), String::from("b")];
+        let mut copy = vec.clone();
+        copy.push(String::from("c"));
+
+        assert_eq!(*vec, ["a", "b"]);
+        assert_eq!(*copy, ["a", "b", "c"]);
+        assert_eq!(Vector::<String>::new().clone().capacity(), 0);
+
+        let mut target = vector![String::from("x"); 5];
+        let (ptr, first) = (target.as_ptr(), target[0].as_ptr());
+        target.clone_from(&copy);
+        assert_eq!(*target, ["a", "b", "c"]);
+        assert_eq!(target.as_ptr(), ptr);
+        // Cloned into, so the string kept its allocation.
+        assert_eq!(target[0].as_ptr(), first);
+
+        target.clone_from(&vector![String::new(); 8]);
+        assert_eq!(target.len(), 8);
+        assert!(target.iter().all(String::is_empty));
+    }
+
+    #[test]
+    fn test_slice_conversions() {
+        fn sum(values: impl AsRef<[i32]>) -> i32 {
+            values.as_ref().iter().sum()
+        }
+
+        let mut vec = vector![3, 1, 2];
+        assert_eq!(sum(&vec), 6);
+
+        vec.as_mut().sort();
+        assert_eq!(vec.as_ref(), [1, 2, 3]);
+
+        fn first<B: std::borrow::Borrow<[i32]>>(values: B) -> Option<i32> {
+            values.borrow().first().copied()
+        }
+        assert_eq!(first(vec), Some(1));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let squares: Vector<u64> = (1..=4).map(|n| n * n).collect();
+        assert_eq!(*squares, [1, 4, 9, 16]);
+
+        let mut words = Vector::from(["a".to_string(), "b".to_string()]);
+        assert_eq!(words.capacity(), 2);
+        words.extend(["c", "d"].map(String::from));
+        assert_eq!(*words, ["a", "b", "c", "d"]);
+
+        let copy = Vector::from(&words[1..3]);
+        assert_eq!(*copy, ["b", "c"]);
+        assert_eq!(Vector::<String>::from([]).capacity(), 0);
+        assert_eq!(Vector::from([(); 3]).len(), 3);
+    }
+
+    #[test]
+    fn test_into_boxed_slice() {
+        use crate::alloc::{Bump, Counting};
+
+        let mut v = Vector::new();
+        v.reserve(8);
+        v.extend((0..5).map(|i| i.to_string()));
+        assert_eq!(v.capacity(), 8);
+        let boxed = v.into_boxed_slice();
+        assert_eq!(&*boxed, ["0", "1", "2", "3", "4"]);
+        assert_eq!(Vector::<u8>::new().into_boxed_slice().len(), 0);
+        assert_eq!(vector![(); 3].into_boxed_slice().len(), 3);
+
+        let counting = Counting::new();
+        let mut v = Vector::new_in(&counting);
+        v.extend(0..9u32);
+        v.drain(2..);
+        v.shrink_to_fit();
+        assert_eq!((v.capacity(), counting.bytes_in_use()), (2, 8));
+        v.clear();
+        v.shrink_to_fit();
+        assert_eq!((v.capacity(), counting.bytes_in_use()), (0, 0));
+
+        let leaked: &'static mut [i32] = vector![1, 2].leak();
+        leaked[0] = 10;
+        assert_eq!(leaked, [10, 2]);
+
+        // The arena takes back the end of its last block.
+        let bump = Bump::with_capacity(64);
+        let mut v = Vector::new_in(&bump);
+        v.reserve(8);
+        v.extend(0..5u8);
+        assert_eq!(bump.used(), 8);
+        v.shrink_to_fit();
+        assert_eq!(bump.used(), 5);
+        assert_eq!(*v.leak(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_dedup() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mut v = vector![1, 1, 2, 3, 3, 3, 1, 4, 4];
+        v.dedup();
+        assert_eq!(v, [1, 2, 3, 1, 4]);
+
+        let mut v = vector![10, 11, 25, 20, 31, 12];
+        v.dedup_by_key(|n| *n / 10);
+        assert_eq!(v, [10, 25, 31, 12]);
+
+        let mut words = vector![String::from("a"), "A".into(), "b".into(), "B".into(), "c".into()];
+        words.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(words, ["a", "b", "c"]);
+
+        let mut single = vector![String::from("x")];
+        single.dedup();
+        assert_eq!(single, ["x"]);
+
+        // Panics on the fourth comparison, after removing one 2: the values
+        // not compared yet stay.
+        let mut v = vector![1, 2, 2, 3, 3, 4];
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            v.dedup_by(|a, b| {
+                calls += 1;
+                assert!(calls < 4);
+                a == b
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(v, [1, 2, 3, 3, 4]);
+    }
+
+    #[test]
+    fn test_insert_slice() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        use std::rc::Rc;
+
+        let mut v = vector![1, 5];
+        v.insert_slice(1, &[2, 3, 4]);
+        v.insert_slice(5, &[6]);
+        v.insert_slice(0, &[]);
+        assert_eq!(v, [1, 2, 3, 4, 5, 6]);
+
+        v.insert_from_iter(0, (-2..0).rev());
+        v.insert_from_iter(2, [0].into_iter().filter(|_| true));
+        assert_eq!(v, [-1, -2, 0, 1, 2, 3, 4, 5, 6]);
+
+        // Cloning the third value panics: the first two stay and the tail is
+        // moved back down next to them.
+        struct Bomb(i32, Rc<()>);
+
+        impl Clone for Bomb {
+            fn clone(&self) -> Self {
+                assert_ne!(self.0, 3);
+                Bomb(self.0, Rc::clone(&self.1))
+            }
+        }
+
+        let counter = Rc::new(());
+        let bombs: Vec<_> = (1..=4).map(|n| Bomb(n, Rc::clone(&counter))).collect();
+        let mut v = vector![Bomb(0, Rc::clone(&counter)), Bomb(9, Rc::clone(&counter))];
+        let result = catch_unwind(AssertUnwindSafe(|| v.insert_slice(1, &bombs)));
+        assert!(result.is_err());
+        assert_eq!(v.iter().map(|bomb| bomb.0).collect::<Vec<_>>(), [0, 1, 2, 9]);
+        assert_eq!(Rc::strong_count(&counter), 9);
+        drop((v, bombs));
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        use std::rc::Rc;
+
+        let merged = vector![1, 3, 5, 7].merge_sorted(vector![2, 3, 4, 8, 9]);
+        assert_eq!(merged, [1, 2, 3, 3, 4, 5, 7, 8, 9]);
+        assert_eq!(Vector::new().merge_sorted(vector![1]), [1]);
+        assert_eq!(vector![1].merge_sorted(Vector::new()), [1]);
+
+        // Ties keep `self`'s values first.
+        let left = vector![(1, 'a'), (2, 'a'), (2, 'b')];
+        let right = vector![(0, 'c'), (2, 'c'), (3, 'c')];
+        let merged = left.merge_sorted_by(right, |x, y| x.0.cmp(&y.0));
+        assert_eq!(merged, [(0, 'c'), (1, 'a'), (2, 'a'), (2, 'b'), (2, 'c'), (3, 'c')]);
+
+        // A long run costs logarithmically many comparisons.
+        let mut comparisons = 0;
+        let long: Vector<_> = (0..10_000).collect();
+        let merged = long.merge_sorted_by(vector![5_000, 20_000], |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(merged.len(), 10_002);
+        assert!(merged.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(comparisons < 100, "{comparisons}");
+
+        // A panicking comparison leaves every value in the result.
+        let counter = Rc::new(());
+        let make = |range: std::ops::Range<i32>| -> Vector<_> {
+            range.map(|n| (n, Rc::clone(&counter))).collect()
+        };
+        let (left, right) = (make(0..10), make(5..15));
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            left.merge_sorted_by(right, |a, b| {
+                calls += 1;
+                assert!(calls < 6);
+                a.0.cmp(&b.0)
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_unique() {
+        let mut v = vector![3, 1, 3, 2, 1, 4, 2];
+        v.unique();
+        assert_eq!(v, [3, 1, 2, 4]);
+
+        let mut words = vector!["b", "A", "a", "c", "B"];
+        words.unique_by_key(|word| word.to_ascii_lowercase());
+        assert_eq!(words, ["b", "A", "c"]);
+
+        let mut empty: Vector<String> = Vector::new();
+        empty.unique();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_windows_mut() {
+        // Smooths in place, each window sees the values already smoothed.
+        let mut v = vector![0, 9, 0, 9, 0];
+        let mut windows = v.windows_mut(3);
+        assert_eq!(windows.len(), 3);
+        while let Some(window) = windows.next() {
+            window[1] = (window[0] + window[1] + window[2]) / 3;
+        }
+        assert!(windows.is_empty());
+        assert_eq!(*v, [0, 3, 4, 4, 0]);
+
+        assert!(v.windows_mut(6).next().is_none());
+        assert_eq!(v.windows_mut(6).len(), 0);
+        assert_eq!(v.windows_mut(5).next().unwrap().len(), 5);
+
+        let result = std::panic::catch_unwind(move || v.windows_mut(0).is_empty());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_comparisons() {
+        use std::collections::HashMap;
+
+        let a = vector![1, 2, 3];
+        assert_eq!(a, vector![1, 2, 3]);
+        assert_eq!(a, [1, 2, 3]);
+        assert_eq!(a, &[1, 2, 3]);
+        assert_eq!(a, [1, 2, 3][..]);
+        assert_eq!(a, &[1, 2, 3][..]);
+        assert_ne!(a, [1, 2]);
+        assert_eq!(vector![String::from("a")], ["a"]);
+
+        assert!(a < vector![1, 3] && a > vector![1, 2]);
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+        assert_eq!(vector![0.5].partial_cmp(&vector![f64::NAN]), None);
+
+        let mut map = HashMap::new();
+        map.insert(a, "a");
+        map.insert(vector![4], "b");
+        assert_eq!(map.get(&vector![1, 2, 3]), Some(&"a"));
+        assert_eq!(map.get(&[4][..]), Some(&"b"));
+    }
+
+    #[test]
+    fn test_write() {
+        use std::io::{IoSlice, Write};
+
+        let mut out = Vector::new();
+        let name = "ab";
+        write!(out, "{}-{name}", 12).unwrap();
+        out.write_all(&[b'!'; 3]).unwrap();
+        assert_eq!(*out, *b"12-ab!!!");
+
+        let mut input: &[u8] = b" copied";
+        std::io::copy(&mut input, &mut out).unwrap();
+        assert_eq!(std::str::from_utf8(&out).unwrap(), "12-ab!!! copied");
+        out.flush().unwrap();
+
+        let mut out = Vector::new();
+        let bufs = [IoSlice::new(b"ab"), IoSlice::new(b""), IoSlice::new(b"cde")];
+        assert_eq!(out.write_vectored(&bufs).unwrap(), 5);
+        assert_eq!((&*out, out.capacity()), (&b"abcde"[..], 5));
+
+        // Works behind a buffered writer, like any other sink.
+        let mut writer = std::io::BufWriter::new(out);
+        writeln!(writer, "{:?}", [1, 2]).unwrap();
+        assert_eq!(*writer.into_inner().unwrap(), *b"abcde[1, 2]\n");
+    }
+
+    #[test]
+    fn test_zero_sized_iter() {
+        let mut v = vector![(); 5];
+        assert_eq!(v.capacity(), usize::MAX);
+
+        let mut drain = v.drain(..);
+        assert_eq!(drain.size_hint(), (5, Some(5)));
+        assert_eq!(drain.next_back(), Some(()));
+        assert_eq!(drain.next(), Some(()));
+        assert_eq!(drain.count(), 3);
+        assert!(v.is_empty());
+
+        v.push(());
+        v.push(());
+        let mut iter = v.into_iter();
+        assert_eq!(iter.next_back(), Some(()));
+        assert_eq!(iter.size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn test_drain_range() {
+        let mut v: Vector<String> = Vector::new();
+        for i in 0..8 {
+            v.push(i.to_string());
+        }
+
+        let mut drain = v.drain(2..6);
+        assert_eq!(drain.len(), 4);
+        assert_eq!(drain.next().as_deref(), Some("2"));
+        assert_eq!(drain.next_back().as_deref(), Some("5"));
+        drop(drain);
+        assert_eq!(*v, ["0", "1", "6", "7"]);
+
+        assert_eq!(v.drain(..=1).collect::<Vec<_>>(), ["0", "1"]);
+        assert_eq!(v.drain(2..).count(), 0);
+        assert_eq!(v.drain(1..1).count(), 0);
+        assert_eq!(*v, ["6", "7"]);
+
+        // Leaking it leaves the values before the range.
+        std::mem::forget(v.drain(1..));
+        assert_eq!(*v, ["6"]);
+
+        let result = std::panic::catch_unwind(move || v.drain(1..3).count());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_iter_as_slice() {
+        let mut iter = vector![1, 2, 3, 4].into_iter();
+        assert_eq!(iter.next(), Some(1));
+        iter.as_mut_slice()[0] = 20;
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.as_slice(), [20, 3]);
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.by_ref().count(), 2);
+        assert!(iter.as_slice().is_empty());
+        assert_eq!(iter.next(), None);
+
+        let mut v = vector![1, 2, 3, 4, 5];
+        let mut drain = v.drain(1..4);
+        assert_eq!(drain.next_back(), Some(4));
+        drain.as_mut_slice()[1] = 30;
+        assert_eq!(drain.as_slice(), [2, 30]);
+        assert_eq!(drain.len(), 2);
+        drop(drain);
+        assert_eq!(*v, [1, 5]);
+    }
+
+    #[test]
+    fn test_drain_front() {
+        let mut batch: Vector<String> = (0..6).map(|i| i.to_string()).collect();
+        let ptr = batch.as_ptr();
+
+        assert_eq!(batch.drain_front(2).collect::<Vec<_>>(), ["0", "1"]);
+        batch.truncate_front(1);
+        assert_eq!(batch, ["3", "4", "5"]);
+        assert_eq!(batch.as_ptr(), ptr);
+
+        assert_eq!(batch.drain_front(0).count(), 0);
+        batch.truncate_front(10);
+        assert!(batch.is_empty());
+
+        let result = std::panic::catch_unwind(move || batch.drain_front(1).count());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_into_vector() {
+        let v: Vector<String> = (0..5).map(|i| i.to_string()).collect();
+        let ptr = v.as_ptr();
+
+        let mut iter = v.into_iter();
+        assert_eq!(iter.next().as_deref(), Some("0"));
+        assert_eq!(iter.next_back().as_deref(), Some("4"));
+        let mut v = iter.into_vector();
+        assert_eq!(v, ["1", "2", "3"]);
+        assert_eq!((v.as_ptr(), v.capacity()), (ptr, 5));
+        v.push(String::from("5"));
+
+        let untouched = v.into_iter().into_vector();
+        assert_eq!(untouched, ["1", "2", "3", "5"]);
+
+        let mut iter = untouched.into_iter();
+        iter.by_ref().for_each(drop);
+        assert!(iter.into_vector().is_empty());
+        assert_eq!(vector![(), ()].into_iter().into_vector().len(), 2);
+    }
+
+    #[test]
+    fn test_try_methods() {
+        use crate::alloc::Bump;
+        use crate::error::{IndexError, TryReserveError};
+
+        let arena = Bump::with_capacity(64);
+        let mut v = Vector::new_in(&arena);
+
+        // The buffer grows in place up to 8 values, which fill the arena.
+        for i in 0..4u64 {
+            assert!(v.try_push(i).is_ok());
+        }
+        assert!(v.try_insert(0, 10).is_ok());
+        assert_eq!(v.capacity(), 8);
+        for i in 5..8 {
+            v.push(i);
+        }
+
+        let err = v.try_push(8).unwrap_err();
+        assert_eq!(*err.element(), 8);
+        assert_eq!(v.try_insert(3, 9).unwrap_err().into_inner(), 9);
+        assert!(matches!(v.try_reserve(1), Err(TryReserveError::AllocError { .. })));
+        assert_eq!(*v, [10, 0, 1, 2, 3, 5, 6, 7]);
+
+        assert_eq!(v.try_remove(8), Err(IndexError { index: 8, len: 8 }));
+        assert_eq!(v.try_remove(0), Ok(10));
+
+        let mut v: Vector<u8> = Vector::new();
+        assert_eq!(v.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+        v.reserve(10);
+        assert!(v.capacity() >= 10);
+        let cap = v.capacity();
+        v.reserve(cap + 1);
+        assert!(v.capacity() >= 2 * cap);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut v = vector![1, 2, 3, 4, 5, 6];
+        v.retain(|value| value % 3 != 0);
+        assert_eq!(*v, [1, 2, 4, 5]);
+
+        v.retain_mut(|value| {
+            *value *= 10;
+            *value > 10
+        });
+        assert_eq!(*v, [20, 40, 50]);
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut v: Vector<i32> = vector![1, 2, 3, 4, 5, 6, 7, 8];
+        let mut evens = v.extract_if(|value| *value % 2 == 0);
+        assert_eq!(evens.next(), Some(2));
+        assert_eq!(evens.next(), Some(4));
+        // Dropped early, 6 and 8 weren't visited and stay.
+        drop(evens);
+        assert_eq!(*v, [1, 3, 5, 6, 7, 8]);
+
+        let big: Vec<_> = v.extract_if(|value| *value > 5).collect();
+        assert_eq!(big, [6, 7, 8]);
+        assert_eq!(*v, [1, 3, 5]);
+
+        // Leaking it leaves the vector empty but valid.
+        std::mem::forget(v.extract_if(|_| true));
+        assert!(v.is_empty());
+        v.push(1);
+        assert_eq!(*v, [1]);
+    }
+
+    #[test]
+    fn test_panic_safety() {
+        use crate::sort::Algorithm;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        use std::rc::Rc;
+
+        /// Panics when dropped if `bomb` is set.
+        struct Value {
+            key: i32,
+            bomb: bool,
+            _counter: Rc<()>,
+        }
+
+        impl Drop for Value {
+            fn drop(&mut self) {
+                if self.bomb {
+                    panic!("dropping {}", self.key);
+                }
+            }
+        }
+
+        let counter = Rc::new(());
+        let mut v = Vector::new();
+        for key in 0..10 {
+            let _counter = Rc::clone(&counter);
+            v.push(Value { key, bomb: key == 7, _counter });
+        }
+        let keys = |v: &Vector<Value>| v.iter().map(|value| value.key).collect::<Vec<_>>();
+
+        // `keep` panics on the fifth value, the unchecked ones stay.
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            v.retain(|value| {
+                calls += 1;
+                assert!(calls < 5);
+                value.key % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(keys(&v), [0, 2, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(Rc::strong_count(&counter), 9);
+
+        // Dropping 7 panics, it's gone anyway and 8 and 9 are kept.
+        let result = catch_unwind(AssertUnwindSafe(|| v.retain(|value| value.key != 7)));
+        assert!(result.is_err());
+        assert_eq!(keys(&v), [0, 2, 4, 5, 6, 8, 9]);
+        assert_eq!(Rc::strong_count(&counter), 8);
+
+        for algorithm in [Algorithm::Insertion, Algorithm::Merge, Algorithm::Quick, Algorithm::Heap] {
+            let mut calls = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                v.sort_with_by(algorithm, |a, b| {
+                    calls += 1;
+                    assert!(calls < 4);
+                    b.key.cmp(&a.key)
+                })
+            }));
+            assert!(result.is_err());
+
+            let mut sorted = keys(&v);
+            sorted.sort();
+            assert_eq!(sorted, [0, 2, 4, 5, 6, 8, 9]);
+            assert_eq!(Rc::strong_count(&counter), 8);
+        }
+
+        // Dropping 7 panics, the rest of the range is dropped anyway and the
+        // tail moved down.
+        v.push(Value { key: 7, bomb: true, _counter: Rc::clone(&counter) });
+        v.push(Value { key: 10, bomb: false, _counter: Rc::clone(&counter) });
+        let result = catch_unwind(AssertUnwindSafe(|| drop(v.drain(1..8))));
+        assert!(result.is_err());
+        assert_eq!(keys(&v)[1..], [10]);
+        assert_eq!(Rc::strong_count(&counter), 3);
+
+        drop(v);
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        // Clearing, dropping the vector or its iterator: the values after
+        // the one that panics are dropped anyway.
+        let bombs = || -> Vector<_> {
+            (0..4)
+                .map(|key| Value { key, bomb: key == 1, _counter: Rc::clone(&counter) })
+                .collect()
+        };
+        let mut v = bombs();
+        assert!(catch_unwind(AssertUnwindSafe(|| v.clear())).is_err());
+        assert!(v.is_empty());
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        assert!(catch_unwind(AssertUnwindSafe(|| drop(bombs()))).is_err());
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        let mut iter = bombs().into_iter();
+        iter.next();
+        assert!(catch_unwind(AssertUnwindSafe(|| drop(iter))).is_err());
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        let mut small: SmallVector<_, 2> = bombs().into_iter().collect();
+        assert!(catch_unwind(AssertUnwindSafe(|| small.clear())).is_err());
+        assert!(small.is_empty());
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_sort_with() {
+        use crate::sort::Algorithm;
+
+        for algorithm in [Algorithm::Insertion, Algorithm::Merge, Algorithm::Quick, Algorithm::Heap] {
+            let mut v = vector![5, 3, 8, 1, 9, 2];
+            v.sort_with(algorithm);
+            assert_eq!(*v, [1, 2, 3, 5, 8, 9]);
+
+            v.sort_with_by(algorithm, |a, b| b.cmp(a));
+            assert_eq!(*v, [9, 8, 5, 3, 2, 1]);
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut v: Vector<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        v.sort();
+        assert!(v.iter().copied().eq(0..1000));
+
+        v.sort_by(|a, b| b.cmp(a));
+        assert!(v.iter().copied().eq((0..1000).rev()));
+
+        // Stable, so ties keep their order.
+        let mut pairs: Vector<(u8, u32)> = (0..100).map(|i| ((i * 37 % 5) as u8, i)).collect();
+        let mut expected = pairs.to_vec();
+        expected.sort_by_key(|pair| pair.0);
+        pairs.sort_by_key(|pair| pair.0);
+        assert_eq!(pairs, expected[..]);
+
+        pairs.sort_by_key(|pair| pair.1);
+        let mut calls = 0;
+        pairs.sort_by_cached_key(|pair| {
+            calls += 1;
+            pair.0.to_string()
+        });
+        assert_eq!(calls, 100);
+        assert_eq!(pairs, expected[..]);
+
+        let mut words = vector!["b", "c", "a"];
+        words.sort_by_cached_key(|word| word.to_uppercase());
+        assert_eq!(words, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_spare_capacity() {
+        use std::io::Read;
+
+        let mut v: Vector<u8> = Vector::new();
+        v.reserve(16);
+        v.push(b'>');
+
+        let mut input: &[u8] = b"filled in place";
+        let spare = v.spare_capacity_mut();
+        assert_eq!(spare.len(), 15);
+        // Zero the slots first, `read` takes initialized bytes.
+        for slot in spare.iter_mut() {
+            slot.write(0);
+        }
+        let spare = unsafe { &mut *(spare as *mut [std::mem::MaybeUninit<u8>] as *mut [u8]) };
+        let read = input.read(spare).unwrap();
+        unsafe { v.set_len(1 + read) };
+        assert_eq!(&*v, b">filled in place");
+        assert!(v.spare_capacity_mut().is_empty());
+
+        let mut strings: Vector<String> = Vector::new();
+        strings.reserve(2);
+        strings.spare_capacity_mut()[0].write(String::from("a"));
+        unsafe { strings.set_len(1) };
+        assert_eq!(strings, ["a"]);
+    }
+
+    #[test]
+    fn test_growth_strategy() {
+        use crate::alloc::Counting;
+        use crate::growth::{Doubling, Geometric, PageAligned};
+
+        let mut v = Vector::with_growth(Geometric::ONE_AND_HALF);
+        let mut caps = Vec::new();
+        for i in 0..20 {
+            v.push(i);
+            if caps.last() != Some(&v.capacity()) {
+                caps.push(v.capacity());
+            }
+        }
+        assert_eq!(caps, [4, 6, 9, 13, 19, 28]);
+
+        // Reserving grows the same way, unless more is asked for.
+        v.reserve(9);
+        assert_eq!(v.capacity(), 42);
+        v.reserve(100);
+        assert_eq!(v.capacity(), 120);
+        assert_eq!(v.clone().capacity(), 20);
+
+        let counting = Counting::new();
+        let mut paged = Vector::with_growth_in(PageAligned(Doubling), &counting);
+        paged.push(0u64);
+        assert_eq!(paged.capacity(), 512);
+        assert_eq!(counting.bytes_in_use(), 4096);
+        paged.extend(1..513);
+        assert_eq!(paged.capacity(), 1024);
+        assert_eq!(counting.bytes_in_use(), 8192);
+
+        let mut by_eight = Vector::with_growth(|cap: usize, _, _| cap + 8);
+        for i in 0..9 {
+            by_eight.push(i);
+        }
+        assert_eq!(by_eight.capacity(), 16);
+        assert_eq!(by_eight, (0..9).collect::<Vector<_>>());
+    }
+
+    #[test]
+    fn test_get_many_mut() {
+        let mut v = vector![1, 2, 3, 4];
+
+        let [a, b] = v.get_many_mut([3, 0]).unwrap();
+        std::mem::swap(a, b);
+        let [a, b, c] = v.get_many_mut([1, 2, 3]).unwrap();
+        *c += *a + *b;
+        assert_eq!(*v, [4, 2, 3, 6]);
+
+        assert!(v.get_many_mut([0, 4]).is_none());
+        assert!(v.get_many_mut([1, 2, 1]).is_none());
+        assert_eq!(v.get_many_mut([]), Some([]));
+    }
+
+    #[test]
+    fn test_raw_parts() {
+        let mut v = vector![String::from("a"), String::from("b")];
+        v.reserve(2);
+        let (ptr, len, cap) = v.into_raw_parts();
+        assert_eq!((len, cap), (2, 4));
+
+        // Through a `Vec` and back, the buffer never moves.
+        let mut std_vec = unsafe { Vec::from_raw_parts(ptr, len, cap) };
+        std_vec.push(String::from("c"));
+        let mut std_vec = std::mem::ManuallyDrop::new(std_vec);
+        let mut v = unsafe {
+            Vector::from_raw_parts(std_vec.as_mut_ptr(), std_vec.len(), std_vec.capacity())
+        };
+        assert_eq!(v.as_ptr(), ptr);
+        v.push(String::from("d"));
+        assert_eq!(v, ["a", "b", "c", "d"]);
+        assert_eq!(v.capacity(), 4);
+
+        let dangling = std::ptr::NonNull::<u64>::dangling().as_ptr();
+        let empty = unsafe { Vector::from_raw_parts(dangling, 0, 0) };
+        assert_eq!(empty.capacity(), 0);
+        let dangling = std::ptr::NonNull::<()>::dangling().as_ptr();
+        let zst = unsafe { Vector::from_raw_parts(dangling, 3, 0) };
+        assert_eq!(zst.len(), 3);
+        assert_eq!(zst.capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn test_std_vec() {
+        let mut std_vec = Vec::with_capacity(4);
+        std_vec.extend([String::from("a"), String::from("b")]);
+        let ptr = std_vec.as_ptr();
+
+        let mut v = Vector::from(std_vec);
+        assert_eq!((v.as_ptr(), v.len(), v.capacity()), (ptr, 2, 4));
+        v.push(String::from("c"));
+
+        let std_vec = Vec::from(v);
+        assert_eq!(std_vec.as_ptr(), ptr);
+        assert_eq!(std_vec, ["a", "b", "c"]);
+
+        assert_eq!(Vec::from(Vector::<u8>::new()), Vec::<u8>::new());
+        assert_eq!(Vec::from(Vector::from(vec![(); 3])).len(), 3);
+    }
+
+    #[test]
+    fn test_extend_from_copy_slice() {
+        use crate::alloc::Counting;
+
+        let counting = Counting::new();
+        let mut bytes = Vector::new_in(&counting);
+        bytes.extend_from_copy_slice(b"hello");
+        bytes.extend_from_copy_slice(b"");
+        bytes.push(b' ');
+        bytes.extend_from_copy_slice(&[b'w'; 3]);
+        assert_eq!(bytes, *b"hello www");
+
+        // One allocation for the first slice, one growing for the push.
+        assert_eq!(counting.allocations(), 2);
+    }
+
+    #[test]
+    fn test_extend_reserves() {
+        use crate::alloc::Counting;
+
+        let counting = Counting::new();
+        let mut v = Vector::new_in(&counting);
+        v.extend(0..1000);
+        assert_eq!((v.capacity(), counting.allocations()), (1000, 1));
+
+        // No size hint: grows as it goes, by the strategy.
+        let mut v = Vector::new();
+        v.extend((0..100).filter(|n| n % 2 == 0));
+        assert_eq!((v.len(), v.capacity()), (50, 64));
+
+        // Reserves the lower bound, then grows by the strategy past it.
+        let mut v = Vector::new();
+        v.extend((0..10).chain((10..30).filter(|_| true)));
+        assert!(v.iter().copied().eq(0..30));
+        assert_eq!(v.capacity(), 40);
+    }
+
+    #[test]
+    fn test_push_within_capacity() {
+        use crate::alloc::Counting;
+
+        let counting = Counting::new();
+        let mut v = Vector::new_in(&counting);
+        assert_eq!(v.push_within_capacity(1), Err(1));
+
+        v.reserve(2);
+        let allocations = counting.allocations();
+        assert_eq!(v.push_within_capacity(1), Ok(()));
+        assert_eq!(v.push_within_capacity(2), Ok(()));
+        assert_eq!(v.push_within_capacity(3), Err(3));
+        assert_eq!(v, [1, 2]);
+        assert_eq!(counting.allocations(), allocations);
+    }
+
+    #[test]
+    fn test_bool_vector() {
+        use crate::traits::MemoryUsage;
+
+        let mut bits = BoolVector::new();
+        for i in 0..100 {
+            bits.push(i % 3 == 0);
+        }
+        bits.set(1, true);
+        assert_eq!((bits.len(), bits.get(0), bits.get(2)), (100, Some(true), Some(false)));
+        assert_eq!(bits.get(100), None);
+        assert_eq!(bits.iter().filter(|&bit| bit).count(), 35);
+        assert_eq!(bits.pop(), Some(true));
+
+        // A bit per value rather than a byte.
+        let bytes: Vector<bool> = bits.iter().collect();
+        assert!(bits.heap_bytes() * 4 < bytes.heap_bytes());
+        assert!(bits.iter().eq(bytes.iter().copied()));
+    }
+
+    #[test]
+    fn test_small_vector() {
+        use std::rc::Rc;
+
+        let mut v: SmallVector<i32, 4> = SmallVector::new();
+        v.extend([1, 2, 4]);
+        v.insert(2, 3);
+        assert!(!v.spilled());
+        assert_eq!(v.capacity(), 4);
+        assert_eq!(*v, [1, 2, 3, 4]);
+
+        // Full, so the next value moves everything to the heap.
+        v.insert(0, 0);
+        assert!(v.spilled());
+        assert_eq!(v.capacity(), 8);
+        assert_eq!(*v, [0, 1, 2, 3, 4]);
+
+        assert_eq!(v.remove(1), 1);
+        assert_eq!(v.pop(), Some(4));
+        v[0] = 10;
+        assert_eq!(*v, [10, 2, 3]);
+        assert!(v.spilled());
+        assert_eq!(v.clone(), v);
+        assert_eq!(format!("{v:?}"), "[10, 2, 3]");
+
+        let counter = Rc::new(());
+        let inline: SmallVector<_, 2> = (0..2).map(|_| Rc::clone(&counter)).collect();
+        let spilled: SmallVector<_, 2> = (0..3).map(|_| Rc::clone(&counter)).collect();
+        assert_eq!(Rc::strong_count(&counter), 6);
+        drop((inline, spilled));
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        let mut zst: SmallVector<(), 0> = SmallVector::new();
+        zst.extend([(), ()]);
+        assert_eq!(zst.len(), 2);
+        assert_eq!(zst.capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut a = vector![String::from("a")];
+        let mut b = vector![String::from("b"), String::from("c")];
+        let capacity = b.capacity();
+
+        a.append(&mut b);
+        assert_eq!(a, ["a", "b", "c"]);
+        assert!(b.is_empty() && b.capacity() == capacity);
+
+        a.append(&mut Vector::new());
+        assert_eq!(a.len(), 3);
+
+        let mut units = vector![(); 2];
+        units.append(&mut vector![(); 3]);
+        assert_eq!(units.len(), 5);
+    }
+
+    #[test]
+    fn test_insert_sorted() {
+        let mut v = Vector::new();
+        for value in [5, 1, 4, 1, 9] {
+            v.insert_sorted(value);
+        }
+        assert_eq!(v, [1, 1, 4, 5, 9]);
+        assert_eq!(v.insert_sorted(0), 0);
+        assert_eq!(v.insert_sorted(10), 6);
+        assert_eq!(v.insert_sorted(4), 4);
+
+        // Equal keys go after the ones already there.
+        let mut pairs = Vector::new();
+        for pair in [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')] {
+            pairs.insert_sorted_by(pair, |a, b| a.0.cmp(&b.0));
+        }
+        assert_eq!(pairs, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn test_sort_unstable() {
+        let mut v: Vector<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        v.sort_unstable();
+        assert!(v.iter().copied().eq(0..1000));
+
+        v.sort_unstable_by(|a, b| b.cmp(a));
+        assert!(v.iter().copied().eq((0..1000).rev()));
+
+        let mut words = vector!["pear", "fig", "banana"];
+        words.sort_unstable_by_key(|word| word.len());
+        assert_eq!(words, ["fig", "pear", "banana"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::{Deserialize, de::value::{Error, SeqDeserializer}};
+        use serde_test::{Token, assert_ser_tokens};
+
+        let v = vector!["a", "b"];
+
+        assert_ser_tokens(
+            &v,
+            &[
+                Token::Seq { len: Some(2) },
+                Token::Str("a"),
+                Token::Str("b"),
+                Token::SeqEnd,
+            ],
+        );
+
+        let input = SeqDeserializer::<_, Error>::new(["a", "b"].into_iter());
+        let back = Vector::<String>::deserialize(input).unwrap();
+        assert_eq!(*back, ["a", "b"]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_rayon() {
+        use rayon::prelude::*;
+        use std::sync::Arc;
+
+        let mut v = Vector::new();
+        for i in 0..1000 {
+            v.push(i);
+        }
+
+        assert_eq!(v.par_iter().sum::<i32>(), 499500);
+        v.par_iter_mut().for_each(|x| *x *= 2);
+        assert_eq!(v[999], 1998);
+
+        let doubled: Vec<i32> = v.into_par_iter().map(|x| x / 2).collect();
+        assert_eq!(doubled, (0..1000).collect::<Vec<_>>());
+
+        // Values that are never consumed still get dropped.
+        let mut v = Vector::new();
+        for i in 0..100 {
+            v.push(i.to_string());
+        }
+        let found = v.into_par_iter().find_any(|s| s == "42");
+        assert_eq!(found.as_deref(), Some("42"));
+
+        let counter = Arc::new(());
+        let mut v = Vector::new();
+        for _ in 0..10 {
+            v.push(Arc::clone(&counter));
+        }
+        assert_eq!(v.into_par_iter().take(3).count(), 3);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = [1, 10, 1, 20, 1, 30, 0];
+        let v = Vector::<u8>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(*v, [10, 20, 30]);
+
+        let bytes = [1, 4, 1, 5, 1, 6];
+        let v = Vector::<u8>::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+        let expected = Vec::<u8>::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+        assert_eq!(*v, *expected);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        use std::sync::Arc;
+        use std::thread;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Vector<String>>();
+        assert_send_sync::<super::IntoIter<String>>();
+        assert_send_sync::<super::Drain<'_, String>>();
+
+        let mut v = Vector::new();
+        for i in 0..100 {
+            v.push(i);
+        }
+        let v = thread::spawn(move || {
+            let mut v = v;
+            v.push(100);
+            v
+        })
+        .join()
+        .unwrap();
+        assert_eq!(v.len(), 101);
+
+        let shared = Arc::new(v);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || shared.iter().sum::<i32>())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 5050);
+        }
+
+        let items = thread::spawn(move || {
+            let mut v = Vector::new();
+            v.push("a".to_string());
+            v.push("b".to_string());
+            v.into_iter()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(items.collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut vec = Vector::new();
+        for i in 0..100u32 {
+            vec.push(i);
+        }
+
+        // Capacity 1 is allocated, then doubled up to 128.
+        let stats = vec.stats();
+        assert_eq!(stats.reallocations, 7);
+        assert!(stats.bytes_copied <= 127 * 4);
+
+        let mut reserved = Vector::<u32>::new();
+        reserved.reserve(100);
+        assert_eq!(reserved.stats().reallocations, 0);
+    }
+}
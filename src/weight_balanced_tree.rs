@@ -0,0 +1,654 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem,
+    ops::ControlFlow,
+};
+
+use crate::alloc::Global;
+use crate::display_tree::{DisplayTree, TreeNode, display_tree};
+use crate::raw::NodePtr;
+use crate::stats::TreeStats;
+use crate::traits::MemoryUsage;
+use crate::traverse::{Order, Traverse, Visit, walk_binary};
+
+/// A subtree is out of balance when one side weighs more than `DELTA` times
+/// the other.
+const DELTA: usize = 3;
+
+/// Decides between a single and a double rotation when rebalancing.
+/// `(3, 2)` is the only integer pair proven to keep the tree balanced.
+const GAMMA: usize = 2;
+
+/// WBTree node.
+struct Node<T> {
+    left: Link<T>,
+    right: Link<T>,
+    /// Number of nodes in the subtree rooted at this node.
+    size: usize,
+    elem: T,
+}
+
+type Link<T> = Option<NodePtr<Node<T>>>;
+
+/// Weight-balanced binary search tree (BB[α]).
+///
+/// Every node keeps the size of its subtree and the two sides of any node
+/// are kept within a factor of [`DELTA`] of each other, so the tree stays
+/// O(log n) deep. The sizes also make it an order-statistics tree: the k-th
+/// smallest element ([`WBTree::select`]) and the position of an element
+/// ([`WBTree::rank`]) are found in O(log n).
+pub struct WBTree<T> {
+    root: Link<T>,
+    stats: TreeStats,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for WBTree<T> {}
+unsafe impl<T: Sync> Sync for WBTree<T> {}
+
+/// In-order iterator, see [`WBTree::iter`].
+pub struct Iter<'a, T> {
+    stack: Vec<NodePtr<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+impl<T> Node<T> {
+    /// Create new leaf node.
+    fn new(elem: T) -> NodePtr<Node<T>> {
+        NodePtr::alloc(
+            &Global,
+            Node {
+                left: None,
+                right: None,
+                size: 1,
+                elem,
+            },
+        )
+    }
+}
+
+unsafe fn size<T>(link: Link<T>) -> usize {
+    unsafe { link.map_or(0, |node| node.as_ref().size) }
+}
+
+/// Weight used for the balance checks, leaves weigh 1.
+unsafe fn weight<T>(link: Link<T>) -> usize {
+    unsafe { size(link) + 1 }
+}
+
+unsafe fn update_size<T>(node: NodePtr<Node<T>>) {
+    unsafe {
+        node.as_mut().size = size(node.as_ref().left) + size(node.as_ref().right) + 1;
+    }
+}
+
+/// ```text
+///     x              y
+///    / \            / \
+///   a   y    =>    x   c
+///      / \        / \
+///     b   c      a   b
+/// ```
+unsafe fn rotate_left<T>(x: NodePtr<Node<T>>) -> NodePtr<Node<T>> {
+    unsafe {
+        let y = x.as_ref().right.unwrap();
+        x.as_mut().right = y.as_ref().left;
+        y.as_mut().left = Some(x);
+        update_size(x);
+        update_size(y);
+        y
+    }
+}
+
+/// Mirror image of [`rotate_left`].
+unsafe fn rotate_right<T>(x: NodePtr<Node<T>>) -> NodePtr<Node<T>> {
+    unsafe {
+        let y = x.as_ref().left.unwrap();
+        x.as_mut().left = y.as_ref().right;
+        y.as_mut().right = Some(x);
+        update_size(x);
+        update_size(y);
+        y
+    }
+}
+
+/// Fixes the size of `node` and rotates if one side got too heavy after a
+/// single insert or remove below it. Returns the new root of the subtree.
+unsafe fn rebalance<T>(node: NodePtr<Node<T>>, stats: &mut TreeStats) -> NodePtr<Node<T>> {
+    unsafe {
+        update_size(node);
+
+        let left = node.as_ref().left;
+        let right = node.as_ref().right;
+
+        if weight(right) > DELTA * weight(left) {
+            let right = right.unwrap();
+            // Double rotation if the inner grandchild is the heavy one.
+            if weight(right.as_ref().left) >= GAMMA * weight(right.as_ref().right) {
+                node.as_mut().right = Some(rotate_right(right));
+                stats.record_rotation();
+            }
+            stats.record_rotation();
+            rotate_left(node)
+        } else if weight(left) > DELTA * weight(right) {
+            let left = left.unwrap();
+            if weight(left.as_ref().right) >= GAMMA * weight(left.as_ref().left) {
+                node.as_mut().left = Some(rotate_left(left));
+                stats.record_rotation();
+            }
+            stats.record_rotation();
+            rotate_right(node)
+        } else {
+            node
+        }
+    }
+}
+
+/// Detaches the minimum of the subtree rooted at `node`. Returns the new
+/// root of the subtree and the detached node.
+unsafe fn remove_min<T>(
+    node: NodePtr<Node<T>>,
+    stats: &mut TreeStats,
+) -> (Link<T>, NodePtr<Node<T>>) {
+    unsafe {
+        match node.as_ref().left {
+            None => (node.as_ref().right, node),
+            Some(left) => {
+                let (new_left, min) = remove_min(left, stats);
+                node.as_mut().left = new_left;
+                (Some(rebalance(node, stats)), min)
+            }
+        }
+    }
+}
+
+impl<T> WBTree<T> {
+    /// Creates a new WBTree with no elements.
+    pub fn new() -> Self {
+        WBTree {
+            root: None,
+            stats: TreeStats::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns WBTree size.
+    pub fn size(&self) -> usize {
+        unsafe { size(self.root) }
+    }
+
+    /// Returns whether the WBTree has no values.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Counters of the rebalancing done so far.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TreeStats {
+        self.stats
+    }
+
+    /// Returns the `k`-th smallest element, starting from `0`.
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut current = self.root;
+
+        unsafe {
+            while let Some(node) = current {
+                let left_size = size(node.as_ref().left);
+
+                match k.cmp(&left_size) {
+                    Ordering::Less => current = node.as_ref().left,
+                    Ordering::Equal => return Some(&node.as_ref().elem),
+                    Ordering::Greater => {
+                        k -= left_size + 1;
+                        current = node.as_ref().right;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the smallest element.
+    pub fn min(&self) -> Option<&T> {
+        self.select(0)
+    }
+
+    /// Returns the largest element.
+    pub fn max(&self) -> Option<&T> {
+        self.select(self.size().checked_sub(1)?)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.size(),
+            marker: PhantomData,
+        };
+
+        iter.push_left(self.root);
+
+        iter
+    }
+
+    /// Removes every element from the tree.
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePtr<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            unsafe {
+                let node = node.dealloc(&Global);
+                stack.extend(node.left);
+                stack.extend(node.right);
+            }
+        }
+    }
+}
+
+impl<T: Ord> WBTree<T> {
+    /// Insert a new node. Does nothing if `elem` is already in the tree.
+    pub fn insert(&mut self, elem: T) {
+        unsafe {
+            self.root = Some(Self::insert_recursive(self.root, elem, 1, &mut self.stats));
+        }
+    }
+
+    /// Recursive function to insert a new node at `depth`, returns the new
+    /// root of the subtree.
+    unsafe fn insert_recursive(
+        current: Link<T>,
+        elem: T,
+        depth: usize,
+        stats: &mut TreeStats,
+    ) -> NodePtr<Node<T>> {
+        let Some(node) = current else {
+            stats.record_depth(depth);
+            return Node::new(elem);
+        };
+
+        unsafe {
+            match elem.cmp(&node.as_ref().elem) {
+                Ordering::Less => {
+                    let left = Self::insert_recursive(node.as_ref().left, elem, depth + 1, stats);
+                    node.as_mut().left = Some(left);
+                }
+                Ordering::Greater => {
+                    let right = Self::insert_recursive(node.as_ref().right, elem, depth + 1, stats);
+                    node.as_mut().right = Some(right);
+                }
+                Ordering::Equal => return node,
+            }
+
+            rebalance(node, stats)
+        }
+    }
+
+    /// Returns `true` if `elem` is in the tree.
+    pub fn contains(&self, elem: &T) -> bool {
+        self.get(elem).is_some()
+    }
+
+    /// Returns the element in the tree that is equal to `elem`.
+    pub fn get(&self, elem: &T) -> Option<&T> {
+        let mut current = self.root;
+
+        unsafe {
+            while let Some(node) = current {
+                current = match elem.cmp(&node.as_ref().elem) {
+                    Ordering::Less => node.as_ref().left,
+                    Ordering::Greater => node.as_ref().right,
+                    Ordering::Equal => return Some(&node.as_ref().elem),
+                };
+            }
+        }
+
+        None
+    }
+
+    /// Returns the number of elements strictly less than `elem`, which is
+    /// the position `elem` has (or would have) in sorted order.
+    pub fn rank(&self, elem: &T) -> usize {
+        let mut current = self.root;
+        let mut rank = 0;
+
+        unsafe {
+            while let Some(node) = current {
+                match elem.cmp(&node.as_ref().elem) {
+                    Ordering::Less => current = node.as_ref().left,
+                    Ordering::Equal => return rank + size(node.as_ref().left),
+                    Ordering::Greater => {
+                        rank += size(node.as_ref().left) + 1;
+                        current = node.as_ref().right;
+                    }
+                }
+            }
+        }
+
+        rank
+    }
+
+    /// Removes `elem` from the WBTree.
+    pub fn remove(&mut self, elem: &T) {
+        self.take(elem);
+    }
+
+    /// Removes `elem` from the WBTree and returns it.
+    pub fn take(&mut self, elem: &T) -> Option<T> {
+        let mut removed = None;
+
+        unsafe {
+            self.root = Self::remove_recursive(self.root, elem, &mut removed, &mut self.stats);
+        }
+
+        removed
+    }
+
+    /// WBTree remove algorithm, returns the new root of the subtree.
+    unsafe fn remove_recursive(
+        current: Link<T>,
+        elem: &T,
+        removed: &mut Option<T>,
+        stats: &mut TreeStats,
+    ) -> Link<T> {
+        let node = current?;
+
+        unsafe {
+            match elem.cmp(&node.as_ref().elem) {
+                Ordering::Less => {
+                    let left = node.as_ref().left;
+                    node.as_mut().left = Self::remove_recursive(left, elem, removed, stats);
+                }
+                Ordering::Greater => {
+                    let right = node.as_ref().right;
+                    node.as_mut().right = Self::remove_recursive(right, elem, removed, stats);
+                }
+                Ordering::Equal => {
+                    let node = node.dealloc(&Global);
+                    *removed = Some(node.elem);
+
+                    // The in-order successor replaces the removed node.
+                    return match (node.left, node.right) {
+                        (None, child) | (child, None) => child,
+                        (Some(left), Some(right)) => {
+                            let (new_right, successor) = remove_min(right, stats);
+                            successor.as_mut().left = Some(left);
+                            successor.as_mut().right = new_right;
+                            Some(rebalance(successor, stats))
+                        }
+                    };
+                }
+            }
+
+            Some(rebalance(node, stats))
+        }
+    }
+}
+
+impl<T> Drop for WBTree<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for WBTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// Pushes `link` and all its left descendants.
+    fn push_left(&mut self, mut link: Link<T>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = unsafe { node.as_ref().left };
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        unsafe {
+            self.push_left(node.as_ref().right);
+            self.len -= 1;
+
+            Some(&node.as_ref().elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a WBTree<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> Extend<T> for WBTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for WBTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+
+        tree
+    }
+}
+
+impl<T: Debug> Debug for WBTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T: fmt::Display> WBTree<T> {
+    /// Draws the shape of the tree with the size of every subtree, see
+    /// [`display_tree`].
+    pub fn display_tree(&self) -> DisplayTree<impl TreeNode + '_> {
+        display_tree(self.root.map(|root| unsafe { root.as_ref() }))
+    }
+}
+
+impl<T: fmt::Display> TreeNode for &Node<T> {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (size {})", self.elem, self.size)
+    }
+
+    fn children(&self) -> Vec<Option<Self>> {
+        unsafe {
+            vec![
+                self.left.map(|n| n.as_ref()),
+                self.right.map(|n| n.as_ref()),
+            ]
+        }
+    }
+}
+
+impl<T> Traverse for WBTree<T> {
+    type Elem = T;
+
+    fn traverse<'a, B>(
+        &'a self,
+        order: Order,
+        visit: impl FnMut(Visit<'a, T>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let split = |node: NodePtr<Node<T>>| unsafe {
+            let node = node.as_ref();
+            (&node.elem, node.left, node.right)
+        };
+
+        walk_binary(self.root, order, split, visit)
+    }
+}
+
+impl<T> MemoryUsage for WBTree<T> {
+    fn heap_bytes(&self) -> usize {
+        self.size() * mem::size_of::<Node<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DELTA, Link, WBTree, weight};
+
+    fn tree_values() -> Vec<i32> {
+        vec![40, 20, 60, 10, 30, 25, 35, 50, 45, 70, 80, 75]
+    }
+
+    /// Checks the search tree order, the stored sizes and the weight balance
+    /// of every node.
+    fn check_balanced<T: Ord>(tree: &WBTree<T>) {
+        fn check<T: Ord>(link: Link<T>) -> usize {
+            let Some(node) = link else {
+                return 0;
+            };
+
+            unsafe {
+                let node = node.as_ref();
+
+                if let Some(left) = node.left {
+                    assert!(left.as_ref().elem < node.elem, "left child out of order");
+                }
+                if let Some(right) = node.right {
+                    assert!(right.as_ref().elem > node.elem, "right child out of order");
+                }
+
+                assert!(
+                    weight(node.left) <= DELTA * weight(node.right),
+                    "left too heavy"
+                );
+                assert!(
+                    weight(node.right) <= DELTA * weight(node.left),
+                    "right too heavy"
+                );
+
+                let size = check(node.left) + check(node.right) + 1;
+                assert_eq!(node.size, size, "stale size");
+
+                size
+            }
+        }
+
+        check(tree.root);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let numbers = tree_values();
+        let mut tree = WBTree::new();
+
+        for n in &numbers {
+            tree.insert(*n);
+            check_balanced(&tree);
+        }
+
+        tree.insert(40);
+        assert_eq!(tree.size(), numbers.len());
+        assert!(numbers.iter().all(|n| tree.contains(n)));
+
+        for n in [75, 70, 50, 20, 40] {
+            tree.remove(&n);
+            assert!(!tree.contains(&n));
+            check_balanced(&tree);
+        }
+
+        assert_eq!(tree.take(&35), Some(35));
+        assert_eq!(tree.take(&35), None);
+        assert_eq!(tree.size(), numbers.len() - 6);
+    }
+
+    #[test]
+    fn test_sequential() {
+        let mut tree: WBTree<u32> = (0..1000).collect();
+        check_balanced(&tree);
+
+        for i in (0..1000).filter(|i| i % 3 != 0) {
+            tree.remove(&i);
+        }
+        check_balanced(&tree);
+        assert_eq!(tree.size(), 334);
+    }
+
+    #[test]
+    fn test_select_rank() {
+        let mut values = tree_values();
+        let tree: WBTree<i32> = values.iter().copied().collect();
+
+        values.sort();
+
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(tree.select(i), Some(value));
+            assert_eq!(tree.rank(value), i);
+        }
+
+        assert_eq!(tree.select(values.len()), None);
+        assert_eq!(tree.rank(&0), 0);
+        assert_eq!(tree.rank(&26), 3);
+        assert_eq!(tree.rank(&100), values.len());
+        assert_eq!(tree.min(), Some(&10));
+        assert_eq!(tree.max(), Some(&80));
+        assert!(tree.iter().eq(values.iter()));
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<WBTree<String>>();
+        assert_send_sync::<super::Iter<'_, String>>();
+    }
+
+    #[test]
+    fn test_display_tree() {
+        let mut tree = WBTree::new();
+        for value in 1..=5 {
+            tree.insert(value);
+        }
+
+        assert_eq!(
+            tree.display_tree().to_string(),
+            "2 (size 5)\n├── 1 (size 1)\n└── 3 (size 3)\n    ├── ·\n    └── 4 (size 2)\n        ├── ·\n        └── 5 (size 1)"
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut tree = WBTree::new();
+        for i in 1..=7 {
+            tree.insert(i);
+        }
+
+        let stats = tree.stats();
+        assert_eq!(stats.rotations, 2);
+        assert_eq!(stats.max_depth, 5);
+    }
+}
@@ -0,0 +1,397 @@
+use std::{collections::HashMap, fmt::Debug};
+
+/// Unsigned integer that can be used as a key of an [`XFastTrie`].
+pub trait UnsignedKey: Copy + Ord {
+    /// Number of bits of the type.
+    const BITS: u32;
+
+    fn to_u64(self) -> u64;
+
+    /// Only called with values that came from [`UnsignedKey::to_u64`].
+    fn from_u64(value: u64) -> Self;
+}
+
+macro_rules! impl_unsigned_key {
+    ($($t:ty),*) => {
+        $(
+            impl UnsignedKey for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                fn to_u64(self) -> u64 {
+                    self as u64
+                }
+
+                fn from_u64(value: u64) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_key!(u8, u16, u32, u64, usize);
+
+/// Subtree of the keys sharing a prefix.
+struct Prefix {
+    min: u64,
+    max: u64,
+}
+
+/// Key in the sorted doubly linked list of all the keys.
+struct Leaf<K> {
+    key: K,
+    prev: Option<u64>,
+    next: Option<u64>,
+}
+
+/// Ordered set of integers that finds successors without comparing keys.
+///
+/// Conceptually a binary trie over the bits of the keys, where level `l`
+/// holds the `l`-bit prefixes that some key starts with. Every level is a
+/// hash table, so whether a prefix exists is an O(1) lookup, and since a key
+/// shares a prefix with the set at every level up to some depth, that depth
+/// can be binary searched in O(log log U) lookups, `U` being the key range
+/// (6 lookups for `u64`). The node found there holds the minimum and maximum
+/// of its subtree, one of which is a neighbour of the key, and the keys are
+/// linked in order to reach the other neighbour.
+///
+/// [`successor`], [`predecessor`] and [`contains`] are O(log log U) while
+/// [`insert`] and [`remove`] update every level in O(log U). Uses O(n log U)
+/// memory.
+///
+/// [`successor`]: XFastTrie::successor
+/// [`predecessor`]: XFastTrie::predecessor
+/// [`contains`]: XFastTrie::contains
+/// [`insert`]: XFastTrie::insert
+/// [`remove`]: XFastTrie::remove
+pub struct XFastTrie<K> {
+    /// `levels[l]` maps the prefixes of length `l` to their subtree, from
+    /// the root at level 0 to single keys at level `K::BITS`.
+    levels: Vec<HashMap<u64, Prefix>>,
+    leaves: HashMap<u64, Leaf<K>>,
+    min: Option<u64>,
+}
+
+pub struct Iter<'a, K> {
+    leaves: &'a HashMap<u64, Leaf<K>>,
+    next: Option<u64>,
+    len: usize,
+}
+
+/// First `len` bits of the `K::BITS`-bit `key`.
+fn prefix<K: UnsignedKey>(key: u64, len: u32) -> u64 {
+    key.checked_shr(K::BITS - len).unwrap_or(0)
+}
+
+impl<K: UnsignedKey> XFastTrie<K> {
+    /// Creates a new XFastTrie with no keys.
+    pub fn new() -> Self {
+        XFastTrie {
+            levels: (0..K::BITS).map(|_| HashMap::new()).collect(),
+            leaves: HashMap::new(),
+            min: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn contains(&self, key: K) -> bool {
+        self.leaves.contains_key(&key.to_u64())
+    }
+
+    /// Length of the longest prefix of `key` shared with some key of the
+    /// set, the set must not be empty.
+    fn longest_prefix(&self, key: u64) -> u32 {
+        // The root always matches and a full match is a leaf.
+        let (mut lo, mut hi) = (0, K::BITS);
+
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            let found = if mid == K::BITS {
+                self.leaves.contains_key(&key)
+            } else {
+                self.levels[mid as usize].contains_key(&prefix::<K>(key, mid))
+            };
+
+            if found {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+
+        lo
+    }
+
+    /// Keys right before and right after `key`, which isn't in the set.
+    fn neighbours(&self, key: u64) -> (Option<u64>, Option<u64>) {
+        if self.is_empty() {
+            return (None, None);
+        }
+
+        let len = self.longest_prefix(key);
+        let node = &self.levels[len as usize][&prefix::<K>(key, len)];
+
+        // The node has no child on the side of the next bit of `key`, so its
+        // whole subtree lies on the other side.
+        if key >> (K::BITS - len - 1) & 1 == 0 {
+            (self.leaves[&node.min].prev, Some(node.min))
+        } else {
+            (Some(node.max), self.leaves[&node.max].next)
+        }
+    }
+
+    /// Smallest key greater than `key`, in O(log log U).
+    pub fn successor(&self, key: K) -> Option<K> {
+        let key = key.to_u64();
+        let next = match self.leaves.get(&key) {
+            Some(leaf) => leaf.next,
+            None => self.neighbours(key).1,
+        };
+
+        next.map(K::from_u64)
+    }
+
+    /// Largest key smaller than `key`, in O(log log U).
+    pub fn predecessor(&self, key: K) -> Option<K> {
+        let key = key.to_u64();
+        let prev = match self.leaves.get(&key) {
+            Some(leaf) => leaf.prev,
+            None => self.neighbours(key).0,
+        };
+
+        prev.map(K::from_u64)
+    }
+
+    pub fn min(&self) -> Option<K> {
+        self.min.map(K::from_u64)
+    }
+
+    pub fn max(&self) -> Option<K> {
+        self.levels[0].get(&0).map(|root| K::from_u64(root.max))
+    }
+
+    /// Adds `key` to the set, returns `false` if it was already present.
+    pub fn insert(&mut self, elem: K) -> bool {
+        let key = elem.to_u64();
+        if self.leaves.contains_key(&key) {
+            return false;
+        }
+
+        let (prev, next) = self.neighbours(key);
+        match prev {
+            Some(prev) => self.leaves.get_mut(&prev).unwrap().next = Some(key),
+            None => self.min = Some(key),
+        }
+        if let Some(next) = next {
+            self.leaves.get_mut(&next).unwrap().prev = Some(key);
+        }
+        self.leaves.insert(
+            key,
+            Leaf {
+                key: elem,
+                prev,
+                next,
+            },
+        );
+
+        for len in 0..K::BITS {
+            let node = self.levels[len as usize]
+                .entry(prefix::<K>(key, len))
+                .or_insert(Prefix { min: key, max: key });
+            node.min = node.min.min(key);
+            node.max = node.max.max(key);
+        }
+
+        true
+    }
+
+    /// Removes `key` from the set, returns `false` if it wasn't present.
+    pub fn remove(&mut self, elem: K) -> bool {
+        let key = elem.to_u64();
+        let Some(leaf) = self.leaves.remove(&key) else {
+            return false;
+        };
+
+        match leaf.prev {
+            Some(prev) => self.leaves.get_mut(&prev).unwrap().next = leaf.next,
+            None => self.min = leaf.next,
+        }
+        if let Some(next) = leaf.next {
+            self.leaves.get_mut(&next).unwrap().prev = leaf.prev;
+        }
+
+        // Bottom-up, every node is rebuilt from its children.
+        for len in (0..K::BITS).rev() {
+            let p = prefix::<K>(key, len);
+            let (left, right) = if len + 1 == K::BITS {
+                let child = |k: u64| self.leaves.contains_key(&k).then_some((k, k));
+                (child(2 * p), child(2 * p + 1))
+            } else {
+                let children = &self.levels[len as usize + 1];
+                let child = |k: u64| children.get(&k).map(|node| (node.min, node.max));
+                (child(2 * p), child(2 * p + 1))
+            };
+
+            let level = &mut self.levels[len as usize];
+            match (left, right) {
+                (None, None) => {
+                    level.remove(&p);
+                }
+                (left, right) => {
+                    let node = level.get_mut(&p).unwrap();
+                    node.min = left.or(right).unwrap().0;
+                    node.max = right.or(left).unwrap().1;
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn clear(&mut self) {
+        for level in &mut self.levels {
+            level.clear();
+        }
+        self.leaves.clear();
+        self.min = None;
+    }
+
+    /// Iterates over the keys in ascending order.
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter {
+            leaves: &self.leaves,
+            next: self.min,
+            len: self.len(),
+        }
+    }
+}
+
+impl<K: UnsignedKey> Default for XFastTrie<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K> Iterator for Iter<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let leaf = &self.leaves[&self.next?];
+        self.next = leaf.next;
+        self.len -= 1;
+
+        Some(&leaf.key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K> ExactSizeIterator for Iter<'a, K> {}
+
+impl<'a, K: UnsignedKey> IntoIterator for &'a XFastTrie<K> {
+    type IntoIter = Iter<'a, K>;
+    type Item = &'a K;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: UnsignedKey> Extend<K> for XFastTrie<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl<K: UnsignedKey> FromIterator<K> for XFastTrie<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        trie.extend(iter);
+
+        trie
+    }
+}
+
+impl<K: UnsignedKey + Debug> Debug for XFastTrie<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XFastTrie;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_successor_predecessor() {
+        let trie: XFastTrie<u8> = [10, 200, 3, 64, 65, 255, 0].into_iter().collect();
+
+        assert_eq!(trie.successor(10), Some(64));
+        assert_eq!(trie.successor(11), Some(64));
+        assert_eq!(trie.successor(64), Some(65));
+        assert_eq!(trie.successor(201), Some(255));
+        assert_eq!(trie.successor(255), None);
+        assert_eq!(trie.predecessor(3), Some(0));
+        assert_eq!(trie.predecessor(199), Some(65));
+        assert_eq!(trie.predecessor(0), None);
+
+        assert_eq!(trie.min(), Some(0));
+        assert_eq!(trie.max(), Some(255));
+        assert!(trie.iter().copied().eq([0, 3, 10, 64, 65, 200, 255]));
+    }
+
+    #[test]
+    fn test_against_btree() {
+        let mut trie = XFastTrie::new();
+        let mut set = BTreeSet::new();
+
+        let mut x = 0x2545_f491_4f6c_dd1du64;
+        for step in 0..2000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            // Few high bits so keys collide and share long prefixes.
+            let key = x & 0xf000_0000_0000_00ff;
+
+            if step % 3 == 2 {
+                assert_eq!(trie.remove(key), set.remove(&key));
+            } else {
+                assert_eq!(trie.insert(key), set.insert(key));
+            }
+
+            let probe = x.rotate_left(32) & 0xf000_0000_0000_00ff;
+            assert_eq!(
+                trie.successor(probe),
+                set.range(probe + 1..).next().copied()
+            );
+            assert_eq!(
+                trie.predecessor(probe),
+                set.range(..probe).next_back().copied()
+            );
+            assert_eq!(trie.contains(probe), set.contains(&probe));
+        }
+
+        assert!(trie.iter().eq(set.iter()));
+        assert_eq!(trie.len(), set.len());
+        assert_eq!(trie.max(), set.last().copied());
+
+        for key in set {
+            assert!(trie.remove(key));
+        }
+        assert!(trie.is_empty());
+        assert_eq!(trie.min(), None);
+        assert_eq!(trie.successor(0), None);
+    }
+}